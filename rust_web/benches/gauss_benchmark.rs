@@ -0,0 +1,74 @@
+//! Gaussian elimination, rank and nullspace of the F2 matrix backends, to pick the one
+//! detection_webs builds on
+//!
+//! The old `f2linalg` module was folded into `bitwisef2linalg`, so this compares the
+//! bit-packed `rust_web::linalg::Mat2` with quizx's byte-per-entry `Mat2` on random
+//! square matrices of 1% to 50% density. Run one operation with e.g.
+//! `cargo bench -p rust_web --bench gauss_benchmark -- nullspace`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use quizx::linalg::Mat2 as QuizxMat2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_web::linalg::Mat2;
+use std::time::Duration;
+
+const SIZES: [usize; 4] = [64, 256, 1024, 4096];
+const DENSITIES: [f64; 4] = [0.01, 0.05, 0.1, 0.5];
+/// quizx eliminates byte by byte, which takes minutes per run at 4096
+const QUIZX_MAX_SIZE: usize = 1024;
+
+/// The same random matrix in both formats, seeded by its size and density
+fn random_matrix(size: usize, density: f64) -> (Mat2, QuizxMat2) {
+    let mut rng = StdRng::seed_from_u64(size as u64 ^ density.to_bits());
+    let data: Vec<Vec<u8>> = (0..size)
+        .map(|_| (0..size).map(|_| rng.gen_bool(density) as u8).collect())
+        .collect();
+    (Mat2::from_u8(data.clone()), QuizxMat2::new(data))
+}
+
+/// Benchmarks `bitwise` and `quizx` on every size and density as group `name`
+fn bench_operation(
+    c: &mut Criterion,
+    name: &str,
+    bitwise: fn(&mut Mat2) -> usize,
+    quizx: fn(&mut QuizxMat2) -> usize,
+) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10).warm_up_time(Duration::from_secs(1));
+    for size in SIZES {
+        for density in DENSITIES {
+            let (mat, quizx_mat) = random_matrix(size, density);
+            let parameter = format!("{}x{}/{}%", size, size, density * 100.0);
+            group.bench_with_input(BenchmarkId::new("bitwise", &parameter), &mat, |b, mat| {
+                b.iter_batched_ref(|| mat.clone(), bitwise, BatchSize::LargeInput)
+            });
+            if size <= QUIZX_MAX_SIZE {
+                group.bench_with_input(BenchmarkId::new("quizx", &parameter), &quizx_mat, |b, mat| {
+                    b.iter_batched_ref(|| mat.clone(), quizx, BatchSize::LargeInput)
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+fn bench_gauss(c: &mut Criterion) {
+    bench_operation(
+        c,
+        "gauss",
+        |mat| mat.gauss(true, None, None, 0, &mut Vec::new()),
+        |mat| mat.gauss(true),
+    );
+}
+
+fn bench_rank(c: &mut Criterion) {
+    bench_operation(c, "rank", |mat| mat.rank(), |mat| mat.rank());
+}
+
+fn bench_nullspace(c: &mut Criterion) {
+    bench_operation(c, "nullspace", |mat| mat.nullspace(false).len(), |mat| mat.nullspace().len());
+}
+
+criterion_group!(benches, bench_gauss, bench_rank, bench_nullspace);
+criterion_main!(benches);
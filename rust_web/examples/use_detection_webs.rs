@@ -80,7 +80,7 @@ pub fn use_det_web(path: &str) -> Result<(), Box<dyn Error>> {
     info!("Graph loading took: {:?}", load_start.elapsed());
     
     let make_rg_start = Instant::now();
-    make_rg(&mut graph);
+    make_rg(&mut graph)?;
     info!("make_rg took: {:?}", make_rg_start.elapsed());
     
     // Create output filenames
@@ -1,4 +1,4 @@
-use rust_web::bitwisef2linalg::Mat2;
+use rust_web::linalg::Mat2;
 
 fn main(){
     let matrix = Mat2::id(2);
@@ -19,7 +19,7 @@ fn main()-> Result<(), Box<dyn std::error::Error>>{
         &(name.clone()+".png"),
         None,true)?;
     println!("Made it to after drawing first one");
-    make_rg(&mut graph);
+    make_rg(&mut graph)?;
     println!("At least got graph before saving");
     graph_to_png(
         &graph,
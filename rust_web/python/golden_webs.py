@@ -0,0 +1,57 @@
+"""
+Prints the detection webs the Python reference implementation finds for a .zxg file,
+as normalised JSON on stdout. Used by the `pyzx-compare` golden test
+(tests/pyzx_compare.rs), which compares them with the Rust port.
+
+Vertices are named by their JSON key. A vertex added by make_rg is named
+`rg(<a>,<b>)` after its two neighbours, sorted, so both sides agree on the names.
+Output: {"pyzx": <version>, "webs": [[[<name>, <name>, <pauli>], ...], ...]} with
+each edge's names sorted and each web's edges sorted.
+"""
+import json
+import sys
+
+import pyzx as zx
+
+from detection_webs import get_detection_webs
+from graph_loader import load_graph
+
+# The reference outputs were checked against this release; others may order webs differently
+PYZX_VERSION = "0.9.0"
+
+
+def rig_by_row(g):
+    """Inputs on the minimal row and outputs on the maximal one, by qubit, like the Rust loader"""
+    boundaries = [v for v in g.vertices() if g.type(v) == zx.VertexType.BOUNDARY]
+    rows = [g.row(v) for v in boundaries]
+    by_qubit = lambda vs: sorted(vs, key=g.qubit)
+    g.set_inputs(by_qubit([v for v in boundaries if g.row(v) == min(rows)]))
+    g.set_outputs(by_qubit([v for v in boundaries if g.row(v) == max(rows)]))
+
+
+def vertex_name(g, v):
+    label = g.vdata(v, 'label', None)
+    if label is not None:
+        return label
+    return "rg({},{})".format(*sorted(g.vdata(n, 'label') for n in g.neighbors(v)))
+
+
+def main(path):
+    if zx.__version__ != PYZX_VERSION:
+        sys.exit(f"golden_webs.py needs pyzx {PYZX_VERSION}, found {zx.__version__}")
+    g = load_graph(path)
+    rig_by_row(g)
+    webs = []
+    for pw in get_detection_webs(g):
+        edges = set()
+        for (s, t), pauli in pw.half_edges().items():
+            a, b = sorted((vertex_name(g, s), vertex_name(g, t)))
+            edges.add((a, b, pauli))
+        webs.append(sorted(edges))
+    json.dump({"pyzx": zx.__version__, "webs": webs}, sys.stdout)
+
+
+if __name__ == '__main__':
+    if len(sys.argv) != 2:
+        sys.exit("Usage: golden_webs.py <file.zxg>")
+    main(sys.argv[1])
@@ -0,0 +1,161 @@
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use crate::logging::LOADER;
+use std::path::{Component, Path, PathBuf};
+
+/// Archive formats that can be passed in place of a single .zxg file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+    Tar,
+}
+
+impl ArchiveKind {
+    /// Recognises an archive by its extension: `.zip`, `.tar.gz`, `.tgz` or `.tar`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// A .zxg file read from an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path inside the archive, always relative and without `..`
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Reads every .zxg file in the archive at `path` into memory, sorted by path
+///
+/// Other files and directories are skipped, as are entries whose path would escape
+/// the archive root, with a warning. The archive format is taken from the extension.
+pub fn read_zxg_entries(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let kind = ArchiveKind::from_path(path)
+        .ok_or_else(|| format!("{} is not a .zip, .tar.gz or .tar archive", path.display()))?;
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut entries = match kind {
+        ArchiveKind::Zip => read_zip(file),
+        ArchiveKind::TarGz => read_tar(GzDecoder::new(file)),
+        ArchiveKind::Tar => read_tar(file),
+    }
+    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// `path` if it is a .zxg file that stays inside the archive root
+fn zxg_path(path: &Path) -> Option<PathBuf> {
+    let safe = path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !safe {
+        log::warn!(target: LOADER, "Skipping archive entry {} outside the archive root", path.display());
+        return None;
+    }
+    let is_zxg = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zxg"));
+    is_zxg.then(|| path.components().filter(|c| matches!(c, Component::Normal(_))).collect())
+}
+
+fn read_zip(file: File) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(path) = zxg_path(Path::new(entry.name())) else { continue };
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        entries.push(ArchiveEntry { path, contents });
+    }
+    Ok(entries)
+}
+
+fn read_tar(reader: impl Read) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path().map_err(|e| e.to_string())?.into_owned();
+        let Some(path) = zxg_path(&name) else { continue };
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        entries.push(ArchiveEntry { path, contents });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::fixture;
+    use crate::graph_loader::load_graph_from_str;
+    use crate::zxg_export::to_zxg;
+    use quizx::hash_graph::GraphLike;
+    use std::io::Write;
+
+    /// Archive members and their contents: two diagrams and a file that isn't one
+    fn files() -> [(&'static str, String); 3] {
+        let zxg = |name| to_zxg(&fixture(name).unwrap()).unwrap();
+        [
+            ("round2/steane.zxg", zxg("steane_2_rounds")),
+            ("cnot.zxg", zxg("xx_stab_2_rounds")),
+            ("README.md", "# Experiments\n".to_string()),
+        ]
+    }
+
+    fn check(entries: &[ArchiveEntry]) {
+        let paths: Vec<_> = entries.iter().map(|e| e.path.to_str().unwrap()).collect();
+        assert_eq!(paths, vec!["cnot.zxg", "round2/steane.zxg"]);
+        assert_eq!(entries[1].contents, files()[0].1);
+        let graph = load_graph_from_str(&entries[1].contents).unwrap();
+        assert_eq!(graph.inputs().len(), 7);
+    }
+
+    #[test]
+    fn test_read_archives() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let zip_path = dir.path().join("experiments.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        for (name, contents) in files() {
+            zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.start_file("../escape.zxg", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.finish().unwrap();
+        check(&read_zxg_entries(&zip_path).unwrap());
+
+        let tar_path = dir.path().join("experiments.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(File::create(&tar_path).unwrap(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(encoder);
+        for (name, contents) in files() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+        check(&read_zxg_entries(&tar_path).unwrap());
+
+        assert_eq!(ArchiveKind::from_path(Path::new("a/B.TGZ")), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::from_path(Path::new("a.zxg")), None);
+        assert!(read_zxg_entries(Path::new("graph.zxg")).unwrap_err().contains("not a .zip"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Scaling curves from benchmark results
+//!
+//! `benchmark_detwebs --record` appends one [`BenchRecord`] per run to a JSON lines file;
+//! running it over code distances and plotting the file with `bench_plot` gives the time
+//! and memory curves of a performance report. Drawing needs the `plots` feature.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// One benchmark run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchRecord {
+    /// Problem size, e.g. the code distance
+    pub n: f64,
+    /// Wall time of the measured stage
+    pub seconds: f64,
+    /// Peak resident memory, where the platform reports it
+    #[serde(default)]
+    pub peak_bytes: Option<u64>,
+    /// Curve the run belongs to, e.g. the code family
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Reads records from a JSON array or from one JSON object per line
+pub fn parse_records(text: &str) -> Result<Vec<BenchRecord>, String> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text).map_err(|e| format!("Invalid benchmark results: {}", e));
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("Invalid benchmark record on line {}: {}", i + 1, e)))
+        .collect()
+}
+
+pub fn load_records(path: &Path) -> Result<Vec<BenchRecord>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_records(&text)
+}
+
+/// Appends `record` as one line to the JSON lines file at `path`, creating it if needed
+pub fn append_record(path: &Path, record: &BenchRecord) -> Result<(), String> {
+    let line = serde_json::to_string(record).expect("records always serialise");
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Quantity on the vertical axis of a scaling plot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Wall time in seconds
+    Time,
+    /// Peak memory in MiB
+    Memory,
+}
+
+impl Metric {
+    pub fn axis_label(self) -> &'static str {
+        match self {
+            Metric::Time => "time (s)",
+            Metric::Memory => "peak memory (MiB)",
+        }
+    }
+
+    fn value(self, record: &BenchRecord) -> Option<f64> {
+        match self {
+            Metric::Time => Some(record.seconds),
+            Metric::Memory => record.peak_bytes.map(|bytes| bytes as f64 / (1024.0 * 1024.0)),
+        }
+    }
+}
+
+/// A named curve of (n, value) points
+pub type Series = (String, Vec<(f64, f64)>);
+
+/// The points of `metric` against n, one curve per label, sorted by n
+///
+/// Repeated runs at the same n are averaged. Runs without a value for `metric` and
+/// non-positive values, which a log scale can't show, are left out, as are curves left
+/// empty. Unlabelled runs form the curve "".
+pub fn series(records: &[BenchRecord], metric: Metric) -> Vec<Series> {
+    let mut curves: BTreeMap<String, Vec<(f64, f64)>> = BTreeMap::new();
+    for record in records {
+        if let Some(value) = metric.value(record).filter(|&v| v > 0.0 && record.n > 0.0) {
+            curves.entry(record.label.clone().unwrap_or_default()).or_default().push((record.n, value));
+        }
+    }
+    curves.into_iter()
+        .map(|(label, mut points)| {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut averaged: Vec<(f64, f64, usize)> = Vec::new();
+            for (n, value) in points {
+                match averaged.last_mut() {
+                    Some((last, sum, count)) if *last == n => {
+                        *sum += value;
+                        *count += 1;
+                    }
+                    _ => averaged.push((n, value, 1)),
+                }
+            }
+            (label, averaged.into_iter().map(|(n, sum, count)| (n, sum / count as f64)).collect())
+        })
+        .collect()
+}
+
+/// `v` to at most three significant digits, without the float noise of the plain
+/// formatting
+#[cfg(feature = "plots")]
+fn tick_label(v: f64) -> String {
+    let decimals = (2 - v.abs().log10().floor() as i32).max(0) as usize;
+    let label = format!("{:.*}", decimals, v);
+    if label.contains('.') {
+        label.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        label
+    }
+}
+
+/// Draws the curves of `metric` on log-log axes into a PNG at `path`
+///
+/// Fails if no record has a value for `metric`.
+#[cfg(feature = "plots")]
+pub fn plot_scaling(records: &[BenchRecord], metric: Metric, path: &Path) -> Result<(), String> {
+    use plotters::prelude::*;
+
+    let curves = series(records, metric);
+    let points = || curves.iter().flat_map(|(_, points)| points.iter().copied());
+    if points().next().is_none() {
+        return Err(format!("No benchmark record has a {}", metric.axis_label()));
+    }
+    // Pad the ranges so that single points and flat curves still get an axis
+    let range = |pick: fn((f64, f64)) -> f64| {
+        let (min, max) = points().map(pick).fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        min / 1.2..max * 1.2
+    };
+    // Label the measured sizes, which a log axis over a narrow range would skip
+    let mut key_points: Vec<f64> = points().map(|p| p.0).collect();
+    key_points.sort_by(f64::total_cmp);
+    key_points.dedup();
+    let failed = |e: &dyn std::fmt::Display| format!("Failed to plot {}: {}", path.display(), e);
+
+    let root = BitMapBackend::new(path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| failed(&e))?;
+    let title = format!("{} against n", metric.axis_label());
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 24))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(range(|p| p.0).log_scale().with_key_points(key_points), range(|p| p.1).log_scale())
+        .map_err(|e| failed(&e))?;
+    chart.configure_mesh()
+        .x_label_formatter(&|&n| tick_label(n))
+        .y_label_formatter(&|&v| tick_label(v))
+        .x_desc("n")
+        .y_desc(metric.axis_label())
+        .draw()
+        .map_err(|e| failed(&e))?;
+
+    for (i, (label, points)) in curves.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let line = chart.draw_series(LineSeries::new(points.iter().copied(), color.stroke_width(2)))
+            .map_err(|e| failed(&e))?;
+        if !label.is_empty() {
+            line.label(label.as_str())
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+        }
+        chart.draw_series(points.iter().map(|&p| Circle::new(p, 4, color.filled())))
+            .map_err(|e| failed(&e))?;
+    }
+    if curves.iter().any(|(label, _)| !label.is_empty()) {
+        chart.configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(|e| failed(&e))?;
+    }
+    root.present().map_err(|e| failed(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_series() {
+        let record = |n, seconds, peak_bytes, label: &str| BenchRecord {
+            n,
+            seconds,
+            peak_bytes,
+            label: Some(label.to_string()),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+        let runs = vec![
+            record(5.0, 4.0, Some(8 << 20), "steane"),
+            record(3.0, 1.0, Some(2 << 20), "steane"),
+            record(3.0, 3.0, None, "steane"),
+            record(3.0, 0.5, None, "surface"),
+        ];
+        for run in &runs {
+            append_record(&path, run).unwrap();
+        }
+        assert_eq!(load_records(&path).unwrap(), runs);
+        assert_eq!(parse_records(&serde_json::to_string(&runs).unwrap()).unwrap(), runs);
+        assert!(parse_records("{\"n\": 3}").unwrap_err().contains("line 1"));
+
+        assert_eq!(series(&runs, Metric::Time), vec![
+            ("steane".to_string(), vec![(3.0, 2.0), (5.0, 4.0)]),
+            ("surface".to_string(), vec![(3.0, 0.5)]),
+        ]);
+        assert_eq!(series(&runs, Metric::Memory), vec![("steane".to_string(), vec![(3.0, 2.0), (5.0, 8.0)])]);
+
+        #[cfg(feature = "plots")]
+        {
+            let png = dir.path().join("time.png");
+            plot_scaling(&runs, Metric::Time, &png).unwrap();
+            let decoder = png::Decoder::new(std::fs::File::open(&png).unwrap());
+            assert_eq!(decoder.read_info().unwrap().info().width, 800);
+            assert!(plot_scaling(&runs[3..], Metric::Memory, &png).is_err());
+            assert_eq!((tick_label(2.0), tick_label(0.30000000000000004), tick_label(1234.6)), ("2".into(), "0.3".into(), "1235".into()));
+        }
+    }
+}
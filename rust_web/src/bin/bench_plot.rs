@@ -0,0 +1,34 @@
+use rust_web::{
+    bench_plot::{load_records, plot_scaling, Metric},
+    cli::{CliError, ErrorFormat, ErrorKind},
+    logging,
+};
+use std::env;
+use std::path::Path;
+
+/// Plot time and memory against n from benchmark results
+///
+/// Usage: bench_plot <results.jsonl> [out_dir] [-v|-vv|-q] [--log TARGET=LEVEL,...]
+///
+/// Writes `time_vs_n.png` and, if the results have memory figures, `memory_vs_n.png`.
+fn main() {
+    let args = logging::init(&env::args().collect::<Vec<_>>());
+    if args.len() < 2 {
+        eprintln!("Usage: {} <results.jsonl> [out_dir] [-v|-vv|-q] [--log TARGET=LEVEL,...]", args[0]);
+        std::process::exit(ErrorKind::Usage.exit_code());
+    }
+    let out_dir = Path::new(args.get(2).map(String::as_str).unwrap_or("."));
+
+    let records = load_records(Path::new(&args[1]))
+        .unwrap_or_else(|e| CliError::new(ErrorKind::BadInput, e).exit(ErrorFormat::Text));
+    let plots = [(Metric::Time, "time_vs_n.png"), (Metric::Memory, "memory_vs_n.png")];
+    for (metric, name) in plots {
+        if metric == Metric::Memory && records.iter().all(|r| r.peak_bytes.is_none()) {
+            continue;
+        }
+        let path = out_dir.join(name);
+        plot_scaling(&records, metric, &path)
+            .unwrap_or_else(|e| CliError::new(ErrorKind::Other, e).exit(ErrorFormat::Text));
+        println!("Wrote {}", path.display());
+    }
+}
@@ -1,4 +1,3 @@
-use env_logger;
 use log::{info, error};
 use std::env;
 use std::path::Path;
@@ -7,29 +6,46 @@ use rayon::prelude::*;
 
 // Import necessary functions from the library
 use rust_web::{
+    bench_plot::{append_record, BenchRecord},
+    cli::{CliError, ErrorKind},
     graph_loader::load_graph,
-    detection_webs::get_detection_webs,
+    detection_webs::{get_detection_webs_lenient, WebOptions},
     graph_visualizer,
+    logging,
     make_rg::make_rg,
+    memory::{format_bytes, log_memory, memory_usage},
+    GraphLike,
 };
 fn main() {
-    // Initialize logger
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
-    
-    // Get the input file path from command line arguments or use a default
-    let args: Vec<String> = env::args().collect();
-    let path = if args.len() > 1 {
-        args[1].clone()
-    } else {
-        // Default test file path - adjust this to your test file
-        "tests/zxgs/2_rounds_steane.zxg".to_string()
-    };
+    // Usage: benchmark_detwebs [file.zxg] [--record results.jsonl] [--n N] [--label L] [-v|-vv|-q] [--log TARGET=LEVEL,...]
+    // --record appends the web detection time and peak memory for bench_plot; n defaults
+    // to the number of vertices and the label to the file name
+    let args = logging::init(&env::args().collect::<Vec<_>>());
+    let mut path = None;
+    let mut record_path = None;
+    let mut n = None;
+    let mut label = None;
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        let mut value = |flag: &str| rest.next().cloned().unwrap_or_else(|| {
+            CliError::new(ErrorKind::Usage, format!("{} needs a value", flag)).exit(Default::default())
+        });
+        match arg.as_str() {
+            "--record" => record_path = Some(value(arg)),
+            "--n" => n = Some(value(arg).parse::<f64>().unwrap_or_else(|e| {
+                CliError::new(ErrorKind::Usage, format!("Invalid --n: {}", e)).exit(Default::default())
+            })),
+            "--label" => label = Some(value(arg)),
+            _ => path = Some(arg.clone()),
+        }
+    }
+    // Default test file path - adjust this to your test file
+    let path = path.unwrap_or_else(|| "tests/zxgs/2_rounds_steane.zxg".to_string());
     
     // Check if file exists
     if !Path::new(&path).exists() {
-        error!("Input file not found: {}", path);
         error!("Current working directory: {:?}", std::env::current_dir().unwrap_or_default());
-        std::process::exit(1);
+        CliError::new(ErrorKind::Io, format!("Input file not found: {}", path)).exit(Default::default());
     }
     
     info!("Starting benchmark for: {}", path);
@@ -39,17 +55,18 @@ fn main() {
     let load_start = Instant::now();
     let mut graph = match load_graph(&path) {
         Ok(g) => g,
-        Err(e) => {
-            error!("Failed to load graph: {}", e);
-            std::process::exit(1);
-        }
+        Err(e) => CliError::new(ErrorKind::BadInput, format!("Failed to load graph: {}", e)).exit(Default::default()),
     };
     info!("Graph loaded in: {:?}", load_start.elapsed());
+    log_memory("loading");
     
     // 2. Process the graph with make_rg
     let make_rg_start = Instant::now();
-    make_rg(&mut graph);
+    if let Err(e) = make_rg(&mut graph) {
+        CliError::new(ErrorKind::BadInput, e).exit(Default::default());
+    }
     info!("make_rg completed in: {:?}", make_rg_start.elapsed());
+    log_memory("make_rg");
     
     // 3. Set inputs and outputs
     // graph.set_outputs(vec![132, 131, 94, 125, 169, 97, 170]);
@@ -57,8 +74,11 @@ fn main() {
     
     // 4. Generate detection webs
     let detection_start = Instant::now();
-    let webs = get_detection_webs(&mut graph);
-    info!("Generated {} detection webs in: {:?}", webs.len(), detection_start.elapsed());
+    let webs = get_detection_webs_lenient(&mut graph, &WebOptions::default())
+        .unwrap_or_else(|e| CliError::new(ErrorKind::Solver, e).exit(Default::default()));
+    let detection_time = detection_start.elapsed();
+    info!("Generated {} detection webs in: {:?}", webs.len(), detection_time);
+    log_memory("web detection");
     
     // 5. Visualize the main graph (just for timing, discard the result)
     let vis_start = Instant::now();
@@ -86,4 +106,20 @@ fn main() {
     info!("Processed {} webs", webs_processed.len());
     
     info!("Total execution time: {:?}", total_start.elapsed());
+    let usage = memory_usage();
+    if let Some(usage) = usage {
+        info!("Peak memory: {}", format_bytes(usage.peak));
+    }
+
+    if let Some(record_path) = record_path {
+        let record = BenchRecord {
+            n: n.unwrap_or(graph.num_vertices() as f64),
+            seconds: detection_time.as_secs_f64(),
+            peak_bytes: usage.map(|usage| usage.peak),
+            label: label.or_else(|| Path::new(&path).file_stem().map(|stem| stem.to_string_lossy().into_owned())),
+        };
+        append_record(Path::new(&record_path), &record)
+            .unwrap_or_else(|e| CliError::new(ErrorKind::Io, e).exit(Default::default()));
+        info!("Recorded run in {}", record_path);
+    }
 }
@@ -0,0 +1,71 @@
+use rust_web::{
+    cli::{CliError, ErrorFormat, ErrorKind},
+    detection_webs::{get_detection_webs_lenient, WebOptions},
+    graph_loader::{load_graph_with_keys, LoadOptions},
+    graph_visualizer::{RenderOptions, RetryPolicy},
+    logging,
+    reference_webs::{load_reference_webs, render_comparison, vertex_names},
+    web_set::WebSet,
+};
+use std::env;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage: diff_reference <file.zxg> <reference.json> [OUT_DIR] [--error-format text|json] [-v|-vv|-q] [--log TARGET=LEVEL,...]";
+
+/// Compares the detection webs of a .zxg file with reference webs, e.g. the output of
+/// `python/golden_webs.py`, rendering every web coloured by whether it matches, is
+/// missing or is extra, and writing the verdicts to `comparison.json`
+///
+/// Output goes to OUT_DIR, by default `detection_web_visualizations/<stem>_reference`
+/// next to the input. Exits with the solver code if the spans differ.
+fn main() {
+    let args = logging::init(&env::args().collect::<Vec<_>>());
+    let error_format = ErrorFormat::from_args(&args);
+    // Everything but `--error-format` and its value, which `ErrorFormat::from_args` read
+    let mut positional = Vec::new();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--error-format" => {
+                rest.next();
+            }
+            flag if flag.starts_with("--") => CliError::new(ErrorKind::Usage, format!("Unknown option {}\n{}", flag, USAGE)).exit(error_format),
+            _ => positional.push(arg),
+        }
+    }
+    let (zxg, reference) = match positional[..] {
+        [zxg, reference] | [zxg, reference, _] => (Path::new(zxg), Path::new(reference)),
+        _ => CliError::new(ErrorKind::Usage, USAGE).exit(error_format),
+    };
+    let out_dir = positional.get(2).map(PathBuf::from).unwrap_or_else(|| {
+        let stem = zxg.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+        zxg.parent().unwrap_or(Path::new(".")).join("detection_web_visualizations").join(format!("{}_reference", stem))
+    });
+    if let Err(e) = run(zxg, reference, &out_dir) {
+        e.exit(error_format);
+    }
+}
+
+fn run(zxg: &Path, reference: &Path, out_dir: &Path) -> Result<(), CliError> {
+    let path = zxg.to_str().ok_or_else(|| CliError::new(ErrorKind::Io, "Invalid graph path encoding"))?;
+    let (mut g, keys) = load_graph_with_keys(path, &LoadOptions::default()).map_err(CliError::wrap(ErrorKind::BadInput))?;
+    let computed = get_detection_webs_lenient(&mut g, &WebOptions::default()).map_err(CliError::wrap(ErrorKind::Solver))?;
+    let reference = load_reference_webs(reference, &vertex_names(&g, &keys)).map_err(CliError::wrap(ErrorKind::BadInput))?;
+
+    std::fs::create_dir_all(out_dir)
+        .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to create {}: {}", out_dir.display(), e)))?;
+    let (comparison, paths) = render_comparison(&g, &reference, &WebSet::new(computed), out_dir, &RenderOptions::default(), &RetryPolicy::default())
+        .map_err(CliError::wrap(ErrorKind::Io))?;
+    let json = serde_json::to_string_pretty(&comparison).map_err(|e| CliError::new(ErrorKind::Other, e))?;
+    let json_path = out_dir.join("comparison.json");
+    std::fs::write(&json_path, json)
+        .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to write {}: {}", json_path.display(), e)))?;
+    log::info!("Wrote {} images and {}", paths.len(), json_path.display());
+
+    println!("{}", comparison);
+    if comparison.agrees() {
+        Ok(())
+    } else {
+        Err(CliError::new(ErrorKind::Solver, "Detection webs differ from the reference"))
+    }
+}
@@ -0,0 +1,29 @@
+use rust_web::{
+    cli::{CliError, ErrorFormat, ErrorKind},
+    fixtures::write_fixtures,
+    logging,
+};
+use std::env;
+use std::path::PathBuf;
+
+/// Writes the canonical fixture graphs as .zxg files
+///
+/// Usage: gen_fixtures [DIR] [-v|-vv|-q] [--log TARGET=LEVEL,...], by default to
+/// tests/zxgs/generated
+fn main() {
+    let args = logging::init(&env::args().collect::<Vec<_>>());
+    if args.len() > 2 {
+        eprintln!("Usage: {} [DIR] [-v|-vv|-q] [--log TARGET=LEVEL,...]", args[0]);
+        std::process::exit(ErrorKind::Usage.exit_code());
+    }
+    let dir = args.get(1).map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("tests").join("zxgs").join("generated"));
+    match write_fixtures(&dir) {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+        }
+        Err(e) => CliError::new(ErrorKind::Io, e).exit(ErrorFormat::Text),
+    }
+}
@@ -1,65 +1,312 @@
 use rust_web::{
-    graph_loader::load_graph,
-    detection_webs::get_detection_webs,
-    graph_visualizer,
-    make_rg::make_rg
+    archive::{read_zxg_entries, ArchiveKind},
+    checkpoint::Checkpoints,
+    cli::{BatchPolicy, CliError, ErrorFormat, ErrorKind, Failures},
+    coarsen::CoarseView,
+    degree_split::split_high_degree,
+    compare::compare_variants,
+    connectivity::{check_components, get_detection_webs_per_component, ComponentPolicy},
+    graph_loader::{load_graph_from_str_tagged, load_graph_lenient, load_graph_tagged, LoadOptions},
+    detection_webs::{get_detection_webs_lenient_traced, plan_detection_webs, BoundaryRoles, NormalForm, WebOptions, WebProvenance},
+    limits::Limits,
+    logging,
+    graph_visualizer::{self, RenderFallback, RenderOptions, RetryPolicy},
+    memory::{format_bytes, log_memory, memory_usage},
+    pauliweb::{web_names, PauliWeb},
+    shared::SharedDiagram,
+    tags::Tags,
+    tikz_export::tikz_to_string_with_options,
+    web_stats::write_distributions,
+    Graph, GraphLike,
 };
-use std::error::Error;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::fs::create_dir_all;
-use std::process::{Command, Stdio};
 use std::env;
-use std::io::Write;
-use std::time::Instant;
-use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use log::{info, error, debug};
 use rayon::prelude::*;
+use indicatif::{ProgressBar, ProgressStyle};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize logger
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
-    
-    // Get the input file path from command line arguments
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        error!("Please provide a path to a .zxg file");
-        std::process::exit(1);
+/// Which webs to render, by their 1-based number as listed in `webs.json`
+#[derive(Debug, Default)]
+struct Selection {
+    /// Render at most this many webs
+    limit: Option<usize>,
+    /// Render only these webs
+    only: Option<Vec<usize>>,
+}
+
+impl Selection {
+    fn contains(&self, number: usize) -> bool {
+        self.only.as_ref().is_none_or(|only| only.contains(&number))
+    }
+}
+
+/// How a run treats disconnected diagrams and failing items, and whether it only plans
+#[derive(Debug, Default)]
+struct RunOptions {
+    components: ComponentPolicy,
+    /// Whether to go on past a failing archive entry or web render
+    policy: BatchPolicy,
+    /// Only load and validate each graph and print its plan
+    dry_run: bool,
+    /// Write the DOT source of each render next to its image
+    keep_dot: bool,
+    /// Also write the graph and each selected web as TikZ
+    tikz: bool,
+}
+
+const COMPARE_USAGE: &str = "Usage: use_detection_webs compare <a.zxg> <b.zxg>... [--ordering natural|by-row|min-degree] [--normal-form rg|graph-like|none] [--allow-huge] [--json] [--error-format text|json] [-v|-vv|-q] [--log TARGET=LEVEL,...]";
+
+const USAGE: &str = "Usage: use_detection_webs <file.zxg|archive.zip|archive.tar.gz> [--limit N] [--only i,j,k] [--ordering natural|by-row|min-degree] [--normal-form rg|graph-like|none] [--components warn|error|split] [--dump-matrices DIR] [--checkpoint DIR] [--checkpoint-every SECS] [--max-degree N] [--dry-run] [--keep-going|--fail-fast] [--keep-dot] [--tikz] [--allow-huge] [--require-graphviz] [--error-format text|json] [-v|-vv|-q] [--log TARGET=LEVEL,...]";
+
+/// Parses the graph path, web selection, web options and run options from the command
+/// line arguments
+fn parse_args(args: &[String]) -> Result<(String, Selection, WebOptions, RunOptions), String> {
+    let mut path = None;
+    let mut selection = Selection::default();
+    let mut web_options = WebOptions::default();
+    let mut run = RunOptions::default();
+    let mut checkpoint_every = None;
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--limit" => {
+                let value = args.next().ok_or("--limit needs a value")?;
+                let limit = value.parse().map_err(|e| format!("Invalid --limit '{}': {}", value, e))?;
+                selection.limit = Some(limit);
+            }
+            "--only" => {
+                let value = args.next().ok_or("--only needs a value")?;
+                let numbers = value.split(',')
+                    .map(|n| match n.trim().parse::<usize>() {
+                        Ok(0) => Err("Web numbers start at 1".to_string()),
+                        Ok(n) => Ok(n),
+                        Err(e) => Err(format!("Invalid web number '{}': {}", n, e)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                selection.only = Some(numbers);
+            }
+            "--ordering" => {
+                let value = args.next().ok_or("--ordering needs a value")?;
+                web_options.ordering = value.parse()?;
+            }
+            "--normal-form" => {
+                let value = args.next().ok_or("--normal-form needs a value")?;
+                web_options.normal_form = value.parse()?;
+            }
+            "--components" => {
+                let value = args.next().ok_or("--components needs a value")?;
+                run.components = value.parse()?;
+            }
+            "--dump-matrices" => {
+                let value = args.next().ok_or("--dump-matrices needs a directory")?;
+                web_options.dump_matrices = Some(PathBuf::from(value));
+            }
+            "--checkpoint" => {
+                let value = args.next().ok_or("--checkpoint needs a directory")?;
+                web_options.checkpoints = Some(Checkpoints::new(value));
+            }
+            "--checkpoint-every" => {
+                let value = args.next().ok_or("--checkpoint-every needs a number of seconds")?;
+                let secs: u64 = value.parse().map_err(|e| format!("Invalid --checkpoint-every '{}': {}", value, e))?;
+                checkpoint_every = Some(Duration::from_secs(secs));
+            }
+            "--max-degree" => {
+                let value = args.next().ok_or("--max-degree needs a value")?;
+                let max_degree = value.parse().map_err(|e| format!("Invalid --max-degree '{}': {}", value, e))?;
+                web_options.max_degree = Some(max_degree);
+            }
+            "--allow-huge" => web_options.limits = Limits::unlimited(),
+            "--dry-run" => run.dry_run = true,
+            "--keep-going" => run.policy = BatchPolicy::KeepGoing,
+            "--fail-fast" => run.policy = BatchPolicy::FailFast,
+            "--keep-dot" => run.keep_dot = true,
+            "--tikz" => run.tikz = true,
+            // Checked in `main` before anything else runs
+            "--require-graphviz" => {}
+            "--error-format" => {
+                // Already picked up by `ErrorFormat::from_args`, just validate it here
+                let value = args.next().ok_or("--error-format needs a value")?;
+                value.parse::<ErrorFormat>()?;
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    if let Some(interval) = checkpoint_every {
+        let checkpoints = web_options.checkpoints.as_mut().ok_or("--checkpoint-every needs --checkpoint")?;
+        checkpoints.interval = interval;
+    }
+    Ok((path.ok_or("Please provide a path to a .zxg file")?, selection, web_options, run))
+}
+
+/// Options of the `compare` subcommand: the graphs, web options and whether to print JSON
+fn parse_compare_args(args: &[String]) -> Result<(Vec<String>, WebOptions, bool), String> {
+    let mut paths = Vec::new();
+    let mut web_options = WebOptions::default();
+    let mut json = false;
+    let mut args = args.iter().skip(2);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ordering" => {
+                let value = args.next().ok_or("--ordering needs a value")?;
+                web_options.ordering = value.parse()?;
+            }
+            "--normal-form" => {
+                let value = args.next().ok_or("--normal-form needs a value")?;
+                web_options.normal_form = value.parse()?;
+            }
+            "--allow-huge" => web_options.limits = Limits::unlimited(),
+            "--json" => json = true,
+            "--error-format" => {
+                let value = args.next().ok_or("--error-format needs a value")?;
+                value.parse::<ErrorFormat>()?;
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ => paths.push(arg.clone()),
+        }
+    }
+    if paths.len() < 2 {
+        return Err("compare needs at least two .zxg files".to_string());
+    }
+    Ok((paths, web_options, json))
+}
+
+/// Runs the web detection on each graph and prints one table comparing them, the first
+/// graph being the baseline
+fn compare(paths: &[String], web_options: &WebOptions, json: bool) -> Result<(), CliError> {
+    let load_options = LoadOptions { limits: web_options.limits, ..LoadOptions::default() };
+    let stem = |path: &str| Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or(path).to_string();
+    let stems: Vec<String> = paths.iter().map(|path| stem(path)).collect();
+    let mut graphs = Vec::new();
+    for (path, name) in paths.iter().zip(&stems) {
+        let graph = load_graph_lenient(path, &load_options)
+            .map_err(|e| CliError::new(ErrorKind::BadInput, format!("{}: {}", path, e)))?;
+        // Files of the same name in different directories keep their paths apart
+        let unique = stems.iter().filter(|other| *other == name).count() == 1;
+        graphs.push((if unique { name.clone() } else { path.clone() }, graph));
+    }
+    let comparison = compare_variants(&graphs, web_options).map_err(CliError::wrap(ErrorKind::Solver))?;
+    if json {
+        let report = serde_json::json!({
+            "normal_form": web_options.normal_form,
+            "variants": comparison.variants,
+            "highlights": comparison.highlights(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).map_err(|e| CliError::new(ErrorKind::Other, e))?);
+    } else {
+        println!("Normal form: {}", web_options.normal_form);
+        print!("{}", comparison);
+    }
+    Ok(())
+}
+
+fn main() {
+    // Set up logging, leaving the other arguments
+    let args = logging::init(&env::args().collect::<Vec<_>>());
+    let error_format = ErrorFormat::from_args(&args);
+
+    if args.get(1).map(String::as_str) == Some("compare") {
+        let (paths, web_options, json) = parse_compare_args(&args).unwrap_or_else(|e| {
+            if error_format == ErrorFormat::Text {
+                error!("{}", COMPARE_USAGE);
+            }
+            CliError::new(ErrorKind::Usage, e).exit(error_format)
+        });
+        if let Err(e) = compare(&paths, &web_options, json) {
+            e.exit(error_format);
+        }
+        return;
     }
-    let path = &args[1];
+    let (path, selection, web_options, run) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            if error_format == ErrorFormat::Text {
+                error!("{}", USAGE);
+            }
+            CliError::new(ErrorKind::Usage, e).exit(error_format);
+        }
+    };
     
+    if args.iter().any(|arg| arg == "--require-graphviz") && !graph_visualizer::graphviz_available("neato") {
+        CliError::new(ErrorKind::MissingGraphviz, "neato not found; install Graphviz or drop --require-graphviz")
+            .exit(error_format);
+    }
+
     info!("Processing file: {}", path);
     
     // Run the detection web generation
-    if let Err(e) = use_det_web(path) {
-        error!("Error: {}", e);
-        std::process::exit(1);
+    if let Err(e) = use_det_web(&path, &selection, &web_options, &run) {
+        e.exit(error_format);
     }
-    
-    Ok(())
 }
 
-/// Main function to generate and visualize detection webs for a given ZXG file
-
-pub fn use_det_web(path: &str) -> Result<(), Box<dyn Error>> {
+/// Main function to generate and visualize detection webs for a given ZXG file, or for
+/// every .zxg file in a .zip, .tar.gz or .tar archive
+///
+/// With `dry_run`, each graph is only loaded and validated and its plan printed. Failing
+/// archive entries are listed in `failures.json` in the archive's output directory, and
+/// fail the run once all entries are done, or right away with [`BatchPolicy::FailFast`].
+fn use_det_web(
+    path: &str,
+    selection: &Selection,
+    web_options: &WebOptions,
+    run: &RunOptions,
+) -> Result<(), CliError> {
     let total_start = Instant::now();
     info!("Starting detection web generation for: {}", path);
+    let load_options = LoadOptions { limits: web_options.limits, ..LoadOptions::default() };
 
     // Set up output directory structure
     let input_path = std::path::Path::new(path);
     let base_output_dir = input_path.parent()
         .unwrap_or_else(|| std::path::Path::new("."))
         .join("detection_web_visualizations");
-    
+
+    // Archives are read in memory, each graph getting the output directory of its path inside
+    if ArchiveKind::from_path(input_path).is_some() {
+        let archive_dir = base_output_dir.join(archive_stem(input_path));
+        let entries = read_zxg_entries(input_path).map_err(CliError::wrap(ErrorKind::Io))?;
+        info!("Found {} .zxg files in {}", entries.len(), path);
+        let total = entries.len();
+        let mut failures = Failures::default();
+        for entry in entries {
+            info!("Processing archive entry: {}", entry.path.display());
+            let result = load_graph_from_str_tagged(&entry.contents, &load_options)
+                .map_err(CliError::wrap(ErrorKind::BadInput))
+                .and_then(|(graph, tags)| process_graph(graph, &tags, &archive_dir.join(entry.path.with_extension("")), selection, web_options, run));
+            if let Err(e) = result {
+                error!("Failed to process {}: {}", entry.path.display(), e);
+                failures.push(entry.path.display(), e);
+                if run.policy == BatchPolicy::FailFast {
+                    info!("Skipping the remaining entries (--fail-fast)");
+                    break;
+                }
+            }
+        }
+        info!("Total execution time: {:?}", total_start.elapsed());
+        if let Some(usage) = memory_usage() {
+            info!("Peak memory: {}", format_bytes(usage.peak));
+        }
+        if !run.dry_run {
+            create_dir_all(&archive_dir)
+                .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to create output directory: {}", e)))?;
+            failures.write(&archive_dir.join("failures.json"))?;
+        }
+        // The exit code is that of the first failing entry
+        return failures.into_result(total, "archive entries");
+    }
+
     // Create a subdirectory based on the input filename (without extension)
     let output_dir = base_output_dir.join(
         input_path.file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("output")
     );
-    
-    debug!("Output directory: {:?}", output_dir);
-    create_dir_all(&output_dir)
-        .map_err(|e| format!("Failed to create output directory: {}", e))?;
 
     // Try to find the input file in multiple possible locations
     let find_start = Instant::now();
@@ -73,48 +320,104 @@ pub fn use_det_web(path: &str) -> Result<(), Box<dyn Error>> {
     // Find the first path that exists and is a file
     let graph_path = possible_paths.iter()
         .find(|p| p.exists() && p.is_file())
-        .ok_or_else(|| format!("Could not find input file: {}", path))?;
+        .ok_or_else(|| CliError::new(ErrorKind::Io, format!("Could not find input file: {}", path)))?;
     debug!("Found graph at: {:?}", graph_path);
     info!("File search took: {:?}", find_start.elapsed());
     
     let load_start = Instant::now();
-    let mut graph = load_graph(graph_path.to_str().ok_or("Invalid graph path encoding")?)?;
+    let graph_path = graph_path.to_str()
+        .ok_or_else(|| CliError::new(ErrorKind::Io, "Invalid graph path encoding"))?;
+    let (graph, tags) = load_graph_tagged(graph_path, &load_options).map_err(CliError::wrap(ErrorKind::BadInput))?;
     info!("Graph loading took: {:?}", load_start.elapsed());
+
+    process_graph(graph, &tags, &output_dir, selection, web_options, run)?;
+    info!("Total execution time: {:?}", total_start.elapsed());
+    if let Some(usage) = memory_usage() {
+        info!("Peak memory: {}", format_bytes(usage.peak));
+    }
+    Ok(())
+}
+
+/// The archive file name without its archive extension, e.g. `runs` for `runs.tar.gz`
+fn archive_stem(path: &Path) -> String {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("output");
+    [".tar.gz", ".tgz", ".tar", ".zip"].iter()
+        .find_map(|ext| name.len().checked_sub(ext.len())
+            .filter(|&cut| name[cut..].eq_ignore_ascii_case(ext))
+            .map(|cut| name[..cut].to_string()))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Computes the detection webs of one graph and renders the graph and its webs to `output_dir`
+///
+/// The input and output indices among the `tags` of the file take the place of
+/// boundary roles guessed by row. Webs that fail to render are listed in
+/// `failures.json` and fail the graph once the others are drawn, or skip the remaining
+/// webs with [`BatchPolicy::FailFast`].
+fn process_graph(
+    mut graph: Graph,
+    tags: &Tags,
+    output_dir: &Path,
+    selection: &Selection,
+    web_options: &WebOptions,
+    run: &RunOptions,
+) -> Result<(), CliError> {
+    debug!("Output directory: {:?}", output_dir);
+    let tagged_options;
+    let web_options = match tags.boundary_roles(&graph).map_err(CliError::wrap(ErrorKind::BadInput))? {
+        Some(roles) if web_options.roles == BoundaryRoles::ByRow => {
+            info!("Using the input and output indices of the file as boundary roles");
+            tagged_options = WebOptions { roles, ..web_options.clone() };
+            &tagged_options
+        }
+        _ => web_options,
+    };
+    // Split before rendering too, since large spiders slow down the layout as much
+    if let Some(max_degree) = web_options.max_degree {
+        let added = split_high_degree(&mut graph, max_degree).map_err(CliError::wrap(ErrorKind::BadInput))?;
+        info!("Split spiders of more than {} legs, adding {} spiders", max_degree, added);
+    }
+    let split = check_components(&graph, run.components).map_err(CliError::wrap(ErrorKind::BadInput))?.len() > 1
+        && run.components == ComponentPolicy::Split;
+    if run.dry_run {
+        return print_plan(&graph, output_dir, selection, web_options, split, run.tikz);
+    }
+    create_dir_all(output_dir)
+        .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to create output directory: {}", e)))?;
+    let write_error = |e: std::io::Error| CliError::new(ErrorKind::Io, format!("Failed to write image: {}", e));
+
+    let normal_form_start = Instant::now();
+    web_options.normal_form.apply(&mut graph).map_err(|e| CliError::new(ErrorKind::BadInput, e))?;
+    info!("Conversion to {} form took: {:?}", web_options.normal_form, normal_form_start.elapsed());
     
-    let make_rg_start = Instant::now();
-    make_rg(&mut graph);
-    info!("make_rg took: {:?}", make_rg_start.elapsed());
-    
-    // Create output filenames
-    let output_filename = "graph";
-    let output_path = output_dir.join(output_filename).with_extension("png");
-    
-    // Generate and save the main graph visualization using piped I/O
+    // Render the main graph; Graphviz failures fall back to a native SVG
     let vis_start = Instant::now();
-    let dot_content = graph_visualizer::to_dot_with_positions(&graph, None, false);
-    info!("Graph visualization generation took: {:?}", vis_start.elapsed());
-    
-    // Start neato process once
-    let neato_start = Instant::now();
-    let mut neato = Command::new("neato")
-        .args(["-n2", "-Tpng"])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()?;
-    
-    // Write dot content to neato's stdin
-    if let Some(stdin) = neato.stdin.as_mut() {
-        stdin.write_all(dot_content.as_bytes())?;
+    let options = RenderOptions::default();
+    let policy = RetryPolicy::default();
+    // Renders where Graphviz failed or timed out, by output name
+    let mut skipped: Vec<(String, RenderFallback)> = Vec::new();
+    let (image, fallback) = graph_visualizer::render_image_reporting(&graph, None, &options, &policy);
+    skipped.extend(fallback.map(|f| ("graph".to_string(), f)));
+    let output_path = image.save(&output_dir.join("graph")).map_err(write_error)?;
+    debug!("Wrote {:?}", output_path);
+    if run.keep_dot {
+        let dot = graph_visualizer::to_dot_with_options(&graph, None, &options);
+        std::fs::write(output_dir.join("graph.dot"), dot)
+            .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to write graph.dot: {}", e)))?;
     }
-    
-    // Get the output and write to file
-    let output = neato.wait_with_output()?;
-    if !output.status.success() {
-        return Err(format!("neato command failed with exit code: {}", 
-            output.status.code().unwrap_or(-1)).into());
-    }
-    std::fs::write(&output_path, output.stdout)?;
-    info!("Neato processing took: {:?}", neato_start.elapsed());
+    if run.tikz {
+        std::fs::write(output_dir.join("graph.tex"), tikz_to_string_with_options(&graph, &PauliWeb::new(), &options))
+            .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to write graph.tex: {}", e)))?;
+    }
+    let schematic = CoarseView::new(&graph);
+    if !schematic.super_nodes.is_empty() {
+        let schematic_options = schematic.render_options(&options);
+        let (image, fallback) = graph_visualizer::render_image_reporting(&schematic.graph, None, &schematic_options, &policy);
+        skipped.extend(fallback.map(|f| ("schematic".to_string(), f)));
+        let schematic_path = image.save(&output_dir.join("schematic")).map_err(write_error)?;
+        debug!("Wrote {:?} with {} collapsed gadgets", schematic_path, schematic.super_nodes.len());
+    }
+    info!("Graph rendering took: {:?}", vis_start.elapsed());
     
     // Process detection webs
     // graph.set_outputs(vec![132, 131, 94, 125, 169, 97, 170]);
@@ -122,86 +425,220 @@ pub fn use_det_web(path: &str) -> Result<(), Box<dyn Error>> {
     // This should no longer be needed
     
     let web_detection_start = Instant::now();
-    let webs = get_detection_webs(&mut graph);
-    info!("get_detection_webs took: {:?}", web_detection_start.elapsed());
+    let traced = if split {
+        get_detection_webs_per_component(&graph, web_options)
+    } else {
+        get_detection_webs_lenient_traced(&mut graph, web_options)
+    };
+    let (webs, provenance): (Vec<PauliWeb>, Vec<WebProvenance>) = traced
+        .map_err(CliError::wrap(ErrorKind::Solver))?
+        .into_iter()
+        .unzip();
+    let graph = SharedDiagram::new(graph);
+    info!("get_detection_webs ({} ordering) took: {:?}", web_options.ordering, web_detection_start.elapsed());
     info!("Found {} detection webs", webs.len());
+    log_memory("web detection");
+
+    if let Some(only) = &selection.only {
+        for &n in only.iter().filter(|&&n| n > webs.len()) {
+            log::warn!("Web {} does not exist, there are {} webs", n, webs.len());
+        }
+    }
+    let names = web_names(graph.graph(), &webs);
+    write_web_index(graph.graph(), &webs, &names, &provenance, tags, output_dir)?;
+    let distributions = write_distributions(graph.graph(), &webs, output_dir).map_err(CliError::wrap(ErrorKind::Io))?;
+    debug!("Wrote {:?}", distributions);
+    let selected: Vec<(String, PauliWeb)> = names.into_iter()
+        .zip(webs)
+        .enumerate()
+        .filter(|(i, _)| selection.contains(i + 1))
+        .take(selection.limit.unwrap_or(usize::MAX))
+        .map(|(_, named)| named)
+        .collect();
+    info!("Rendering {} detection webs", selected.len());
     
     let web_vis_start = Instant::now();
-    let temp_dot_files = Arc::new(Mutex::new(Vec::new()));
-    let graph = Arc::new(graph); // Share the graph between threads
+    let progress = ProgressBar::new(selected.len() as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} webs [{elapsed_precise}, ETA {eta}]")
+            .map_err(|e| CliError::new(ErrorKind::Other, e))?
+    );
     
-    // Process webs in parallel
-    let results: Vec<anyhow::Result<()>> = webs.into_par_iter().enumerate().map(|(i, web)| {
-        let web_start = Instant::now();
-        let web_output_path = output_dir.join(format!("web_{}.png", i + 1));
-        let dot_path = output_dir.join(format!("temp_web_{}.dot", i + 1));
-        
-        // Add to temp files list
-        temp_dot_files.lock().unwrap().push(dot_path.clone());
-        
-        // Generate DOT content for this specific web
-        let web_dot_content = graph_visualizer::to_dot_with_positions(&*graph, Some(&web), false);
-        
-        // Write the DOT file
-        if let Err(e) = std::fs::write(&dot_path, &web_dot_content) {
-            return Err(anyhow::anyhow!("Failed to write DOT file for web {}: {}", i + 1, e));
+    // Process webs in parallel, each returning its name and result; with --fail-fast,
+    // webs not started by the first failure are skipped
+    let total = selected.len();
+    let failed = AtomicBool::new(false);
+    let results: Vec<(String, anyhow::Result<Option<RenderFallback>>)> = selected.into_par_iter().filter_map(|(name, web)| {
+        if run.policy == BatchPolicy::FailFast && failed.load(Ordering::Relaxed) {
+            return None;
         }
-        debug!("  Web {} dot generation took: {:?}", i + 1, web_start.elapsed());
-        
-        // Process with neato
-        let neato_start = Instant::now();
-        let output = Command::new("neato")
-            .args(["-n2", "-Tpng"])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .and_then(|mut child| {
-                if let Some(stdin) = child.stdin.as_mut() {
-                    stdin.write_all(web_dot_content.as_bytes())?;
-                }
-                child.wait_with_output()
-            });
-            
-        match output {
-            Ok(output) if output.status.success() => {
-                if let Err(e) = std::fs::write(&web_output_path, output.stdout) {
-                    return Err(anyhow::anyhow!("Failed to write PNG for web {}: {}", i + 1, e));
-                }
-                debug!("  Web {} processing took: {:?}", i + 1, neato_start.elapsed());
-                info!("  Web {} completed in: {:?}", i + 1, web_start.elapsed());
-                Ok(())
-            },
-            Ok(output) => {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(anyhow::anyhow!("neato failed for web {} with status {}: {}", 
-                    i + 1, output.status, stderr))
-            },
-            Err(e) => Err(anyhow::anyhow!("Failed to execute neato for web {}: {}", i + 1, e)),
+        let result = draw_web(&graph, &web, &name, output_dir, run, &options, &policy);
+        if result.is_err() {
+            failed.store(true, Ordering::Relaxed);
         }
+        progress.inc(1);
+        Some((name, result))
     }).collect();
+    progress.finish();
+    if results.len() < total {
+        info!("Skipped {} webs after a failed render (--fail-fast)", total - results.len());
+    }
     
     // Check for any errors in the parallel processing
-    for result in results {
-        if let Err(e) = result {
-            error!("Error processing web: {}", e);
-            // Continue processing other webs but return an error at the end
+    let mut failures = Failures::default();
+    for (name, result) in &results {
+        match result {
+            Err(e) => {
+                error!("Error processing web: {}", e);
+                failures.push(format!("web_{}", name), CliError::new(ErrorKind::Io, e));
+            }
+            Ok(Some(fallback)) => skipped.push((format!("web_{}", name), fallback.clone())),
+            Ok(None) => {}
         }
     }
     info!("All webs visualization took: {:?}", web_vis_start.elapsed());
     
-    // Clean up temporary DOT files
-    let cleanup_start = Instant::now();
-    let temp_files = temp_dot_files.lock().unwrap();
-    let cleanup_errors = temp_files.par_iter()
-        .filter(|dot_file| std::fs::remove_file(dot_file).is_err())
-        .count();
+    log_skipped_renders(&skipped);
+    failures.write(&output_dir.join("failures.json"))?;
+    failures.into_result(total, "web renders")
+}
+
+/// Prints the stages a run would go through on `graph`, with the sizes of the matrices
+/// and the memory they need, without computing anything
+fn print_plan(
+    graph: &Graph,
+    output_dir: &Path,
+    selection: &Selection,
+    web_options: &WebOptions,
+    split: bool,
+    tikz: bool,
+) -> Result<(), CliError> {
+    let plan = plan_detection_webs(graph, web_options).map_err(CliError::wrap(ErrorKind::BadInput))?;
+    let webs = match (&selection.only, selection.limit) {
+        (Some(only), Some(limit)) => format!("webs {:?}, at most {}", only, limit),
+        (Some(only), None) => format!("webs {:?}", only),
+        (None, Some(limit)) => format!("the first {} webs", limit),
+        (None, None) => "every web".to_string(),
+    };
+    println!("Dry run: {} vertices, {} edges", graph.num_vertices(), graph.num_edges());
+    println!("Stages:");
+    match web_options.normal_form {
+        NormalForm::None => println!("  1. check the diagram is in rg form ({} vertices)", plan.vertices),
+        form => println!("  1. convert to {} form ({} vertices)", form, plan.vertices),
+    }
+    println!("  2. render the graph to {}", output_dir.join("graph").display());
+    if split {
+        println!("  3. detection webs of each component on its own; sizes below are for the whole diagram");
+    } else {
+        println!("  3. detection webs");
+    }
+    let outputs = if tikz { "render and write as TikZ" } else { "render" };
+    println!("  4. write {} and {} {}", output_dir.join("webs.json").display(), outputs, webs);
+    println!("{}", plan);
+    if let Some(dir) = &web_options.dump_matrices {
+        println!("Matrices would be dumped to {}", dir.display());
+    }
+    if let Some(checkpoints) = &web_options.checkpoints {
+        println!("Elimination would be checkpointed to {} every {:?}", checkpoints.dir.display(), checkpoints.interval);
+    }
+    Ok(())
+}
+
+/// Lists the renders that fell back to the native SVG renderer, timeouts first
+fn log_skipped_renders(skipped: &[(String, RenderFallback)]) {
+    if skipped.is_empty() {
+        return;
+    }
+    let timed_out = skipped.iter().filter(|(_, f)| f.timed_out).count();
+    log::warn!(
+        "Graphviz was skipped for {} renders ({} timed out); they were drawn as native SVG:",
+        skipped.len(), timed_out
+    );
+    let mut sorted: Vec<_> = skipped.iter().collect();
+    sorted.sort_by_key(|(name, f)| (!f.timed_out, name.clone()));
+    for (name, fallback) in sorted {
+        log::warn!("  {}: {}", name, fallback.reason.trim_end());
+    }
+}
+
+/// Writes `webs.json` to `output_dir`, listing each web's number, name, boundary
+/// support, weight and provenance, so that numbers from `--only` can be matched to file
+/// names and webs traced back to the solver
+///
+/// If the file had vertex tags, each web also gets how often each tag value occurs on
+/// the vertices it touches, e.g. which detector rounds it spans.
+fn write_web_index(
+    graph: &Graph,
+    webs: &[PauliWeb],
+    names: &[String],
+    provenance: &[WebProvenance],
+    tags: &Tags,
+    output_dir: &Path,
+) -> Result<(), CliError> {
+    let entries: Vec<serde_json::Value> = webs.iter().zip(names).zip(provenance).enumerate()
+        .map(|(i, ((web, name), provenance))| {
+            let boundary: String = web.project_to_boundary(graph).iter()
+                .map(|pauli| pauli.map_or("I".to_string(), |p| p.to_string()))
+                .collect();
+            let mut entry = serde_json::json!({
+                "number": i + 1,
+                "name": name,
+                "boundary": boundary,
+                "weight": web.edge_operators.len(),
+                "provenance": provenance,
+            });
+            let tag_names = tags.vertex_tag_names();
+            if !tag_names.is_empty() {
+                let touched: BTreeSet<usize> = web.edge_operators.keys().flat_map(|&(a, b)| [a, b]).collect();
+                let summary: BTreeMap<&str, BTreeMap<String, usize>> = tag_names.into_iter()
+                    .map(|name| (name, tags.value_counts(touched.iter().copied(), name)))
+                    .collect();
+                entry["tags"] = serde_json::json!(summary);
+            }
+            entry
+        })
+        .collect();
+    let path = output_dir.join("webs.json");
+    let json = serde_json::to_string_pretty(&entries).map_err(|e| CliError::new(ErrorKind::Other, e))?;
+    std::fs::write(&path, json)
+        .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Renders one web on the shared graph to `web_<name>.png` in `output_dir`, or
+/// `web_<name>.svg` if Graphviz isn't usable, returning why Graphviz was skipped if it was
+///
+/// The DOT source is piped into Graphviz; with `--keep-dot` it is also written to
+/// `web_<name>.dot`. With `--tikz` the web is also written as `web_<name>.tex`.
+fn draw_web(
+    graph: &SharedDiagram,
+    web: &PauliWeb,
+    name: &str,
+    output_dir: &Path,
+    run: &RunOptions,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> anyhow::Result<Option<RenderFallback>> {
+    let web_start = Instant::now();
     
-    if cleanup_errors > 0 {
-        log::warn!("Failed to remove {} temporary files", cleanup_errors);
-    } else if !temp_files.is_empty() {
-        debug!("Cleaned up {} temporary DOT files in {:?}", temp_files.len(), cleanup_start.elapsed());
+    if run.keep_dot {
+        let web_dot_content = graph_visualizer::to_dot_with_options(graph.graph(), Some(web), options);
+        std::fs::write(output_dir.join(format!("web_{}.dot", name)), &web_dot_content)
+            .map_err(|e| anyhow::anyhow!("Failed to write DOT file for web {}: {}", name, e))?;
+        debug!("  Web {} dot generation took: {:?}", name, web_start.elapsed());
     }
     
-    info!("Total execution time: {:?}", total_start.elapsed());
-    Ok(())
+    // Render, retrying Graphviz and falling back to a native SVG
+    let render_start = Instant::now();
+    let (image, fallback) = graph_visualizer::render_image_reporting(graph.graph(), Some(web), options, policy);
+    let web_output_path = image.save(&output_dir.join(format!("web_{}", name)))
+        .map_err(|e| anyhow::anyhow!("Failed to write image for web {}: {}", name, e))?;
+    debug!("  Web {} rendering took: {:?}", name, render_start.elapsed());
+    if run.tikz {
+        let tikz = tikz_to_string_with_options(graph.graph(), web, options);
+        std::fs::write(output_dir.join(format!("web_{}.tex", name)), tikz)
+            .map_err(|e| anyhow::anyhow!("Failed to write TikZ for web {}: {}", name, e))?;
+    }
+    // Per-web timings at info level would break up the progress bar
+    debug!("  Web {} completed in {:?}: {:?}", name, web_start.elapsed(), web_output_path);
+    Ok(fallback)
 }
@@ -0,0 +1,30 @@
+use rust_web::{
+    cli::{CliError, ErrorFormat, ErrorKind},
+    detection_webs::{get_detection_webs_lenient, WebOptions},
+    graph_loader::load_graph,
+    tui,
+    logging,
+};
+use std::env;
+use std::error::Error;
+
+/// Browse a .zxg diagram and its detection webs in the terminal
+///
+/// Usage: zx_tui <file.zxg> [render.svg] [-v|-vv|-q] [--log TARGET=LEVEL,...]
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = logging::init(&env::args().collect::<Vec<_>>());
+    if args.len() < 2 {
+        eprintln!("Usage: {} <file.zxg> [render.svg] [-v|-vv|-q] [--log TARGET=LEVEL,...]", args[0]);
+        std::process::exit(ErrorKind::Usage.exit_code());
+    }
+    let render_path = args.get(2).map(String::as_str).unwrap_or("tui_render.svg");
+
+    let mut graph = load_graph(&args[1])
+        .unwrap_or_else(|e| CliError::new(ErrorKind::BadInput, e).exit(ErrorFormat::Text));
+    // Webs refer to the red-green form, so browse that graph
+    let webs = get_detection_webs_lenient(&mut graph, &WebOptions::default())
+        .unwrap_or_else(|e| CliError::new(ErrorKind::Solver, e).exit(ErrorFormat::Text));
+
+    tui::run(graph, webs, render_path)?;
+    Ok(())
+}
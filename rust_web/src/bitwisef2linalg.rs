@@ -1,6 +1,7 @@
 use bitvec::prelude::*;
 use std::ops::{Add, Mul};
 use std::fmt;
+use std::io::{self, Read, Write};
 
 // Type aliases for better readability
 type BitVecType = BitVec<usize, Lsb0>;
@@ -13,6 +14,20 @@ pub struct Mat2 {
     data: Vec<BitVecType>, // Each BitVec represents a row
 }
 
+/// Callback of [`Mat2::gauss_resumable`], given the matrix, the tracked row operations
+/// and the state after each column
+pub type AfterColumn<'a> = dyn FnMut(&Mat2, Option<&Mat2>, &GaussState) -> Result<(), String> + 'a;
+
+/// How far [`Mat2::gauss_resumable`] got, enough to pick the elimination up again
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GaussState {
+    /// First column not eliminated yet
+    pub next_col: usize,
+    pub rank: usize,
+    /// Pivot columns so far, in row order
+    pub pivot_cols: Vec<usize>,
+}
+
 impl Mat2 {
     /// Create a new matrix from raw data (vector of rows, each as a BitVec)
     pub fn new(rows: usize, cols: usize) -> Self {
@@ -80,6 +95,21 @@ impl Mat2 {
         self.data[row].set(col, value);
     }
 
+    /// Columns of the ones in a row, in increasing order
+    pub fn row_ones(&self, row: usize) -> impl Iterator<Item = usize> + '_ {
+        self.data[row].iter_ones()
+    }
+
+    /// One row as a bit slice
+    pub fn row(&self, row: usize) -> &BitSlice<usize, Lsb0> {
+        &self.data[row]
+    }
+
+    /// The rows as bit vectors, without copying them
+    pub fn into_rows(self) -> Vec<BitVec<usize, Lsb0>> {
+        self.data
+    }
+
     /// Vertically stack this matrix with another matrix
     pub fn vstack(&self, other: &Self) -> Self {
         assert_eq!(self.cols, other.cols, "Matrices must have same number of columns for vstack");
@@ -164,23 +194,41 @@ impl Mat2 {
     pub fn gauss(
         &mut self,
         full_reduce: bool,
-        mut x: Option<&mut Self>,
+        x: Option<&mut Self>,
         mut _y: Option<&mut Self>, // Not used in this implementation
         _blocksize: usize,         // For future optimization
         pivot_cols: &mut Vec<usize>,
     ) -> usize {
+        let mut state = GaussState::default();
+        self.gauss_resumable(full_reduce, x, &mut state, &mut |_, _, _| Ok(()))
+            .expect("elimination without callback can't fail");
+        *pivot_cols = state.pivot_cols;
+        state.rank
+    }
+
+    /// Same as [`Mat2::gauss`], starting from `state` and calling `after_column` with the
+    /// matrix, `x` and the new state after each column
+    ///
+    /// Stops with the error of `after_column` if it fails. Restarting from the state passed
+    /// to `after_column` with the matrices it saw gives the same result as an
+    /// uninterrupted run, which is what [`crate::checkpoint`] builds on.
+    pub fn gauss_resumable(
+        &mut self,
+        full_reduce: bool,
+        mut x: Option<&mut Self>,
+        state: &mut GaussState,
+        after_column: &mut AfterColumn,
+    ) -> Result<usize, String> {
         let m = self.rows();
         let n = self.cols();
-        let mut rank = 0;
-
-        pivot_cols.clear();
-        pivot_cols.reserve(m.min(n));
+        state.pivot_cols.reserve(m.min(n).saturating_sub(state.pivot_cols.len()));
 
-
-        for col in 0..n {
+        while state.next_col < n && state.rank < m {
+            let col = state.next_col;
+            let rank = state.rank;
             // Find pivot row using iterator for better performance
             if let Some(pivot_row) = (rank..m).find(|&row| self.get(row, col)) {
-                pivot_cols.push(col);
+                state.pivot_cols.push(col);
 
                 // Swap rows if needed
                 if pivot_row != rank {
@@ -205,12 +253,7 @@ impl Mat2 {
                     }
                 }
 
-                rank += 1;
-
-
-                if rank == m {
-                    break;
-                }
+                state.rank += 1;
             } else if full_reduce {
                 // Full reduction: clear above the pivot
                 // This is the hot path, optimized for performance
@@ -229,24 +272,63 @@ impl Mat2 {
                     }
                 }
             }
+            state.next_col += 1;
+            after_column(self, x.as_deref(), state)?;
         }
 
-        rank
+        Ok(state.rank)
+    }
+
+    /// Writes the rows as raw words, little-endian and `cols.div_ceil(usize::BITS)` per row
+    ///
+    /// Only [`Mat2::read_words`] on the same platform reads them back.
+    pub fn write_words(&self, w: &mut impl Write) -> io::Result<()> {
+        for row in &self.data {
+            for word in row.as_raw_slice() {
+                w.write_all(&word.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a `rows`×`cols` matrix written by [`Mat2::write_words`]
+    pub fn read_words(rows: usize, cols: usize, r: &mut impl Read) -> io::Result<Self> {
+        let words = cols.div_ceil(usize::BITS as usize);
+        let mut buf = [0u8; std::mem::size_of::<usize>()];
+        let mut data = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            let mut row = Vec::with_capacity(words);
+            for _ in 0..words {
+                r.read_exact(&mut buf)?;
+                row.push(usize::from_le_bytes(buf));
+            }
+            let mut row = BitVecType::from_vec(row);
+            row.truncate(cols);
+            data.push(row);
+        }
+        Ok(Self { rows, cols, data })
     }
 
-    /// Compute a basis for the nullspace of the matrix
     pub fn nullspace(&self, _should_copy: bool) -> Vec<Self> {
         let mut mat = self.clone();
         let mut pivot_cols = Vec::new();
-        let rank = mat.gauss(true, None, None, 0, &mut pivot_cols);
-        let n = self.cols();
+        mat.gauss(true, None, None, 0, &mut pivot_cols);
+        mat.reduced_nullspace(&pivot_cols)
+    }
 
-        if rank == n {
-            return Vec::new();
-        }
+    /// Nullspace basis of a matrix brought to reduced form by [`Mat2::gauss`] with
+    /// `full_reduce`, given the pivot columns it found
+    pub fn reduced_nullspace(&self, pivot_cols: &[usize]) -> Vec<Self> {
+        self.free_columns(pivot_cols)
+            .into_iter()
+            .map(|free_var| self.reduced_nullspace_vector(pivot_cols, free_var))
+            .collect()
+    }
 
-        // Find free variables (columns without pivots)
-        let mut free_vars = Vec::with_capacity(n - rank);
+    /// Columns without a pivot, one per vector of [`Mat2::reduced_nullspace`]
+    pub fn free_columns(&self, pivot_cols: &[usize]) -> Vec<usize> {
+        let n = self.cols();
+        let mut free_vars = Vec::with_capacity(n.saturating_sub(pivot_cols.len()));
         let mut pivot_iter = pivot_cols.iter().peekable();
         
         for col in 0..n {
@@ -258,25 +340,73 @@ impl Mat2 {
             }
             free_vars.push(col);
         }
+        free_vars
+    }
 
-        // Generate basis vectors for the nullspace
-        let mut basis = Vec::with_capacity(free_vars.len());
+    /// The vector of [`Mat2::reduced_nullspace`] for the free column `free_var`, as a
+    /// one-row matrix
+    pub fn reduced_nullspace_vector(&self, pivot_cols: &[usize], free_var: usize) -> Self {
+        let mut vec = Self::zeros(1, self.cols());
+        vec.set(0, free_var, true);
         
-        for &free_var in &free_vars {
-            let mut vec = Self::zeros(1, n);
-            vec.set(0, free_var, true);
-            
-            // Back substitution
-            for (row, &pivot_col) in pivot_cols.iter().enumerate().rev() {
-                if free_var > pivot_col && mat.get(row, free_var) {
-                    vec.set(0, pivot_col, true);
-                }
+        // Back substitution
+        for (row, &pivot_col) in pivot_cols.iter().enumerate().rev() {
+            if free_var > pivot_col && self.get(row, free_var) {
+                vec.set(0, pivot_col, true);
             }
-            
-            basis.push(vec);
         }
-        
-        basis
+        vec
+    }
+
+    /// Solve `self * x = b` for `x`, one column of `b` at a time
+    ///
+    /// Returns one solution (free variables set to 0), or None if some column of `b`
+    /// is not in the column space.
+    pub fn solve(&self, b: &Self) -> Option<Self> {
+        assert_eq!(self.rows, b.rows, "Right-hand side must have as many rows as the matrix");
+        let mut augmented = self.hstack(b);
+        let mut pivot_cols = Vec::new();
+        augmented.gauss(false, None, None, 0, &mut pivot_cols);
+        if pivot_cols.iter().any(|&col| col >= self.cols) {
+            return None;
+        }
+
+        let mut x = Self::zeros(self.cols, b.cols);
+        for (row, &col) in pivot_cols.iter().enumerate() {
+            for j in 0..b.cols {
+                x.set(col, j, augmented.get(row, self.cols + j));
+            }
+        }
+        Some(x)
+    }
+
+    /// Compute the inverse of a square matrix, or None if it is singular
+    pub fn inverse(&self) -> Option<Self> {
+        assert_eq!(self.rows, self.cols, "Only square matrices have an inverse");
+        let mut mat = self.clone();
+        let mut inv = Self::id(self.rows);
+        let rank = mat.gauss(false, Some(&mut inv), None, 0, &mut Vec::new());
+        (rank == self.rows).then_some(inv)
+    }
+
+    /// Factor the matrix as `c * r` with `c` of full column rank and `r` of full row rank
+    ///
+    /// `r` is the non-zero part of the reduced row echelon form and `c` holds the
+    /// pivot columns of the matrix.
+    pub fn factor(&self) -> (Self, Self) {
+        let mut reduced = self.clone();
+        let mut pivot_cols = Vec::new();
+        let rank = reduced.gauss(false, None, None, 0, &mut pivot_cols);
+
+        let mut c = Self::zeros(self.rows, rank);
+        for (j, &col) in pivot_cols.iter().enumerate() {
+            for i in 0..self.rows {
+                c.set(i, j, self.get(i, col));
+            }
+        }
+        reduced.data.truncate(rank);
+        reduced.rows = rank;
+        (c, reduced)
     }
 
     /// Convert matrix to a vector of vectors of u8 (0 or 1)
@@ -445,4 +575,54 @@ mod tests {
         assert_eq!(vec.get(0, 1), true);
         assert_eq!(vec.get(0, 2), true);
     }
+
+    #[test]
+    fn test_solve() {
+        let mat = Mat2::from_u8(vec![
+            vec![1, 1, 0],
+            vec![0, 1, 1],
+            vec![1, 0, 1],
+        ]);
+
+        let b = Mat2::from_u8(vec![vec![1], vec![1], vec![0]]);
+        let x = mat.solve(&b).unwrap();
+        assert_eq!(mat.clone() * x, b);
+
+        // Rows sum to zero, so the right-hand side must too
+        let b = Mat2::from_u8(vec![vec![1], vec![0], vec![0]]);
+        assert!(mat.solve(&b).is_none());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let mat = Mat2::from_u8(vec![
+            vec![1, 1, 0],
+            vec![0, 1, 1],
+            vec![0, 0, 1],
+        ]);
+        let inv = mat.inverse().unwrap();
+        assert_eq!(mat.clone() * inv.clone(), Mat2::id(3));
+        assert_eq!(inv * mat, Mat2::id(3));
+
+        let singular = Mat2::from_u8(vec![
+            vec![1, 1],
+            vec![1, 1],
+        ]);
+        assert!(singular.inverse().is_none());
+    }
+
+    #[test]
+    fn test_factor() {
+        let mat = Mat2::from_u8(vec![
+            vec![1, 0, 1, 1],
+            vec![0, 1, 1, 0],
+            vec![1, 1, 0, 1],
+        ]);
+        let (c, r) = mat.factor();
+        assert_eq!((c.rows(), c.cols()), (3, 2));
+        assert_eq!((r.rows(), r.cols()), (2, 4));
+        assert_eq!(c.rank(), 2);
+        assert_eq!(r.rank(), 2);
+        assert_eq!(c * r, mat);
+    }
 }
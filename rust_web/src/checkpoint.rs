@@ -0,0 +1,257 @@
+//! Checkpoints of long Gaussian eliminations
+//!
+//! Eliminating the matrix of a large diagram can take tens of minutes. With
+//! [`Checkpoints`] the partially reduced matrix and its pivots are written to disk every
+//! so often, and a later run on the same matrix picks up from there, so a crash or OOM
+//! kill only loses the work since the last checkpoint.
+
+use crate::linalg::{GaussState, Mat2};
+use crate::logging::WEBS;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// First line of a checkpoint file
+const MAGIC: &str = "rust_web elimination checkpoint v1";
+
+/// Where and how often eliminations are checkpointed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoints {
+    pub dir: PathBuf,
+    /// Least time between two checkpoints
+    pub interval: Duration,
+}
+
+impl Checkpoints {
+    /// Checkpoints in `dir` every five minutes
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Checkpoints { dir: dir.into(), interval: Duration::from_secs(300) }
+    }
+
+    /// File of the checkpoints of elimination `name` on the matrix with `fingerprint`
+    ///
+    /// The fingerprint in the name keeps checkpoints of different graphs apart, e.g.
+    /// when the entries of an archive share a directory.
+    pub fn path(&self, name: &str, fingerprint: u64) -> PathBuf {
+        self.dir.join(format!("{}-{:016x}.ckpt", name, fingerprint))
+    }
+}
+
+/// FNV-1a hash of the elimination input: the mode, `mat` and `x`
+pub fn fingerprint(mat: &Mat2, x: Option<&Mat2>, full_reduce: bool) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |value: usize| {
+        for byte in (value as u64).to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    feed(full_reduce as usize);
+    for m in std::iter::once(mat).chain(x) {
+        feed(m.rows());
+        feed(m.cols());
+        for r in 0..m.rows() {
+            // Row separator, since the ones alone don't say where a row ends
+            feed(usize::MAX);
+            m.row_ones(r).for_each(&mut feed);
+        }
+    }
+    hash
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    fingerprint: u64,
+    next_col: usize,
+    rank: usize,
+    pivot_cols: Vec<usize>,
+    rows: usize,
+    cols: usize,
+    /// Size of `x`, if the elimination tracks one
+    x: Option<(usize, usize)>,
+}
+
+/// An elimination as saved by [`write_checkpoint`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub fingerprint: u64,
+    pub state: GaussState,
+    pub mat: Mat2,
+    pub x: Option<Mat2>,
+}
+
+/// Saves an elimination to `path`, replacing any previous checkpoint only once the new
+/// one is complete
+pub fn write_checkpoint(
+    path: &Path,
+    fingerprint: u64,
+    state: &GaussState,
+    mat: &Mat2,
+    x: Option<&Mat2>,
+) -> Result<(), String> {
+    let header = Header {
+        fingerprint,
+        next_col: state.next_col,
+        rank: state.rank,
+        pivot_cols: state.pivot_cols.clone(),
+        rows: mat.rows(),
+        cols: mat.cols(),
+        x: x.map(|x| (x.rows(), x.cols())),
+    };
+    let partial = path.with_extension("ckpt.partial");
+    let failed = |e: std::io::Error| format!("Failed to write checkpoint {}: {}", partial.display(), e);
+    let mut w = BufWriter::new(File::create(&partial).map_err(failed)?);
+    writeln!(w, "{}", MAGIC).map_err(failed)?;
+    serde_json::to_writer(&mut w, &header).map_err(|e| failed(e.into()))?;
+    writeln!(w).map_err(failed)?;
+    mat.write_words(&mut w).map_err(failed)?;
+    if let Some(x) = x {
+        x.write_words(&mut w).map_err(failed)?;
+    }
+    w.flush().map_err(failed)?;
+    drop(w);
+    std::fs::rename(&partial, path)
+        .map_err(|e| format!("Failed to move checkpoint to {}: {}", path.display(), e))
+}
+
+/// Loads a checkpoint written by [`write_checkpoint`]
+pub fn read_checkpoint(path: &Path) -> Result<Checkpoint, String> {
+    let failed = |e: String| format!("Failed to read checkpoint {}: {}", path.display(), e);
+    let mut r = BufReader::new(File::open(path).map_err(|e| failed(e.to_string()))?);
+    let mut line = String::new();
+    r.read_line(&mut line).map_err(|e| failed(e.to_string()))?;
+    if line.trim_end() != MAGIC {
+        return Err(failed("not a checkpoint file".to_string()));
+    }
+    line.clear();
+    r.read_line(&mut line).map_err(|e| failed(e.to_string()))?;
+    let header: Header = serde_json::from_str(&line).map_err(|e| failed(e.to_string()))?;
+    let mat = Mat2::read_words(header.rows, header.cols, &mut r).map_err(|e| failed(e.to_string()))?;
+    let x = header.x
+        .map(|(rows, cols)| Mat2::read_words(rows, cols, &mut r))
+        .transpose()
+        .map_err(|e| failed(e.to_string()))?;
+    Ok(Checkpoint {
+        fingerprint: header.fingerprint,
+        state: GaussState { next_col: header.next_col, rank: header.rank, pivot_cols: header.pivot_cols },
+        mat,
+        x,
+    })
+}
+
+/// [`Mat2::gauss`] of `mat`, tracking the row operations in `x` if given, checkpointed
+/// as `name` in `checkpoints`
+///
+/// Resumes from an existing checkpoint of the same input and removes the checkpoint once
+/// the elimination is done. Returns the rank and the pivot columns.
+pub fn gauss_checkpointed(
+    mat: &mut Mat2,
+    full_reduce: bool,
+    mut x: Option<&mut Mat2>,
+    checkpoints: &Checkpoints,
+    name: &str,
+) -> Result<(usize, Vec<usize>), String> {
+    std::fs::create_dir_all(&checkpoints.dir)
+        .map_err(|e| format!("Failed to create checkpoint directory {}: {}", checkpoints.dir.display(), e))?;
+    let fingerprint = fingerprint(mat, x.as_deref(), full_reduce);
+    let path = checkpoints.path(name, fingerprint);
+
+    let mut state = GaussState::default();
+    if path.exists() {
+        let checkpoint = read_checkpoint(&path)?;
+        if checkpoint.fingerprint != fingerprint || checkpoint.x.is_some() != x.is_some() {
+            return Err(format!("Checkpoint {} doesn't match the matrix; delete it to start over", path.display()));
+        }
+        log::info!(
+            target: WEBS,
+            "Resuming {} elimination from column {} of {}, {}",
+            name, checkpoint.state.next_col, mat.cols(), path.display()
+        );
+        *mat = checkpoint.mat;
+        if let (Some(x), Some(saved)) = (x.as_deref_mut(), checkpoint.x) {
+            *x = saved;
+        }
+        state = checkpoint.state;
+    }
+
+    let mut last = Instant::now();
+    let rank = mat.gauss_resumable(full_reduce, x, &mut state, &mut |mat, x, state| {
+        if last.elapsed() < checkpoints.interval {
+            return Ok(());
+        }
+        let start = Instant::now();
+        write_checkpoint(&path, fingerprint, state, mat, x)?;
+        log::info!(
+            target: WEBS,
+            "Checkpointed {} elimination at column {} of {} in {:?}",
+            name, state.next_col, mat.cols(), start.elapsed()
+        );
+        last = Instant::now();
+        Ok(())
+    })?;
+
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove finished checkpoint {}: {}", path.display(), e))?;
+    }
+    Ok((rank, state.pivot_cols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        // A dense-ish pseudo-random matrix with a few dependent rows
+        let mut mat = Mat2::zeros(12, 16);
+        for r in 0..9 {
+            for c in 0..16 {
+                mat.set(r, c, (r * 7 + c * 13 + r * c) % 5 < 2);
+            }
+        }
+        for r in 9..12 {
+            for c in 0..16 {
+                mat.set(r, c, mat.get(r - 9, c) ^ mat.get(r - 8, c));
+            }
+        }
+        let mut expected = mat.clone();
+        let mut expected_x = Mat2::id(12);
+        let mut expected_pivots = Vec::new();
+        let expected_rank = expected.gauss(true, Some(&mut expected_x), None, 0, &mut expected_pivots);
+
+        // An elimination killed after six columns, with a checkpoint after the fifth
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = Checkpoints { dir: dir.path().to_path_buf(), interval: Duration::ZERO };
+        let fingerprint = fingerprint(&mat, Some(&Mat2::id(12)), true);
+        let path = checkpoints.path("test", fingerprint);
+        let (mut crashed, mut crashed_x) = (mat.clone(), Mat2::id(12));
+        let interrupted = crashed.gauss_resumable(true, Some(&mut crashed_x), &mut GaussState::default(), &mut |m, x, state| {
+            match state.next_col {
+                5 => write_checkpoint(&path, fingerprint, state, m, x),
+                6 => Err("killed".to_string()),
+                _ => Ok(()),
+            }
+        });
+        assert_eq!(interrupted, Err("killed".to_string()));
+        let saved = read_checkpoint(&path).unwrap();
+        assert_eq!(saved.state.next_col, 5);
+        assert_eq!(saved.x.as_ref().map(Mat2::rows), Some(12));
+
+        // Resuming finishes the same elimination and cleans up
+        let (mut resumed, mut resumed_x) = (mat.clone(), Mat2::id(12));
+        let (rank, pivots) = gauss_checkpointed(&mut resumed, true, Some(&mut resumed_x), &checkpoints, "test").unwrap();
+        assert_eq!((rank, &pivots), (expected_rank, &expected_pivots));
+        assert_eq!(resumed, expected);
+        assert_eq!(resumed_x, expected_x);
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        // A different matrix doesn't pick up the checkpoint
+        let mut other = Mat2::id(3);
+        assert_ne!(checkpoints.path("test", super::fingerprint(&other, None, true)), path);
+        assert_eq!(gauss_checkpointed(&mut other, true, None, &checkpoints, "test").unwrap().0, 3);
+    }
+}
@@ -0,0 +1,221 @@
+//! Exit codes and error reporting shared by the binaries
+//!
+//! Every failure exits with the code of its [`ErrorKind`], so wrapper scripts can tell
+//! a broken input from a missing Graphviz without parsing messages. With
+//! [`ErrorFormat::Json`] the error is printed to stderr as one JSON object instead of text.
+//! Batches, like the entries of an archive or the webs of a diagram, collect the
+//! [`Failures`] of their items under a [`BatchPolicy`] and fail as a whole if any item did.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What went wrong, deciding the exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Anything not covered below
+    Other,
+    /// Invalid command line
+    Usage,
+    /// The input graph is malformed or over the size limits
+    BadInput,
+    /// Graphviz is required but not installed
+    MissingGraphviz,
+    /// Computing the detection webs failed
+    Solver,
+    /// Reading or writing a file failed
+    Io,
+}
+
+impl ErrorKind {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::BadInput => 3,
+            ErrorKind::MissingGraphviz => 4,
+            ErrorKind::Solver => 5,
+            ErrorKind::Io => 6,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorKind::Other => "other",
+            ErrorKind::Usage => "usage",
+            ErrorKind::BadInput => "bad-input",
+            ErrorKind::MissingGraphviz => "missing-graphviz",
+            ErrorKind::Solver => "solver",
+            ErrorKind::Io => "io",
+        })
+    }
+}
+
+/// How a fatal error is reported on stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// As `Error: <message>`
+    #[default]
+    Text,
+    /// As `{"error": <kind>, "code": <exit code>, "message": <message>}` on one line
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("Unknown error format '{}', expected text or json", s)),
+        }
+    }
+}
+
+impl ErrorFormat {
+    /// Finds `--error-format <format>` in the command line arguments
+    ///
+    /// Looked up separately from the other options, so that errors parsing those are
+    /// already reported in the requested format. Unknown formats fall back to text.
+    pub fn from_args(args: &[String]) -> Self {
+        args.iter()
+            .position(|arg| arg == "--error-format")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|format| format.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// A fatal error of a binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn new(kind: ErrorKind, message: impl fmt::Display) -> Self {
+        CliError { kind, message: message.to_string() }
+    }
+
+    /// Wraps an error, keeping its message
+    pub fn wrap<E: fmt::Display>(kind: ErrorKind) -> impl Fn(E) -> CliError {
+        move |message| CliError { kind, message: message.to_string() }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "error": self.kind.to_string(),
+            "code": self.kind.exit_code(),
+            "message": self.message,
+        }).to_string()
+    }
+
+    /// Reports the error in `format` and exits with its code
+    pub fn exit(&self, format: ErrorFormat) -> ! {
+        match format {
+            ErrorFormat::Text => eprintln!("Error: {}", self.message),
+            ErrorFormat::Json => eprintln!("{}", self.to_json()),
+        }
+        std::process::exit(self.kind.exit_code())
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Whether a batch goes on past a failing item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchPolicy {
+    /// Process every item, then fail if any did (`--keep-going`)
+    #[default]
+    KeepGoing,
+    /// Stop at the first failing item (`--fail-fast`)
+    FailFast,
+}
+
+/// The items of a batch that failed, by name, in the order they were found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Failures(pub Vec<(String, CliError)>);
+
+impl Failures {
+    pub fn push(&mut self, item: impl fmt::Display, error: CliError) {
+        self.0.push((item.to_string(), error));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The failures as a JSON array of `{"item", "error", "code", "message"}` objects,
+    /// the last three as in [`CliError::to_json`]
+    pub fn to_json(&self) -> String {
+        let items: Vec<serde_json::Value> = self.0.iter()
+            .map(|(item, error)| serde_json::json!({
+                "item": item,
+                "error": error.kind.to_string(),
+                "code": error.kind.exit_code(),
+                "message": error.message,
+            }))
+            .collect();
+        serde_json::to_string_pretty(&items).expect("JSON values always serialise")
+    }
+
+    /// Writes [`Failures::to_json`] to `path`, an empty array if nothing failed
+    pub fn write(&self, path: &Path) -> Result<(), CliError> {
+        std::fs::write(path, self.to_json())
+            .map_err(|e| CliError::new(ErrorKind::Io, format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    /// Fails with the kind of the first failure if there was any, saying how many of the
+    /// `total` `items` failed
+    pub fn into_result(self, total: usize, items: &str) -> Result<(), CliError> {
+        match self.0.first() {
+            None => Ok(()),
+            Some((_, first)) => Err(CliError::new(first.kind, format!("{} of {} {} failed", self.0.len(), total, items))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_output() {
+        let err = CliError::new(ErrorKind::BadInput, "Edge e1 refers to unknown vertex \"gone\"");
+        assert_eq!(err.kind.exit_code(), 3);
+        let json: serde_json::Value = serde_json::from_str(&err.to_json()).unwrap();
+        assert_eq!(json["error"], "bad-input");
+        assert_eq!(json["code"], 3);
+        assert_eq!(json["message"], "Edge e1 refers to unknown vertex \"gone\"");
+
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(ErrorFormat::from_args(&args("bin g.zxg --error-format json")), ErrorFormat::Json);
+        assert_eq!(ErrorFormat::from_args(&args("bin g.zxg --error-format")), ErrorFormat::Text);
+        assert_eq!(ErrorFormat::from_args(&args("bin g.zxg")), ErrorFormat::Text);
+    }
+
+    #[test]
+    fn test_failures() {
+        let mut failures = Failures::default();
+        assert_eq!(failures.clone().into_result(3, "webs"), Ok(()));
+        assert_eq!(failures.to_json(), "[]");
+        failures.push("web_2", CliError::new(ErrorKind::Io, "Failed to write image"));
+        failures.push("web_3", CliError::new(ErrorKind::Other, "oops"));
+        let json: serde_json::Value = serde_json::from_str(&failures.to_json()).unwrap();
+        assert_eq!(json[0]["item"], "web_2");
+        assert_eq!(json[0]["code"], 6);
+        assert_eq!(json[1]["error"], "other");
+        let err = failures.into_result(3, "webs").unwrap_err();
+        assert_eq!((err.kind, err.message.as_str()), (ErrorKind::Io, "2 of 3 webs failed"));
+    }
+}
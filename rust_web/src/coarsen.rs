@@ -0,0 +1,207 @@
+use crate::graph_visualizer::{to_dot_with_options, RenderOptions};
+use quizx::graph::{GraphLike, VData, VType, V};
+use quizx::hash_graph::Graph;
+use std::collections::{BTreeSet, HashMap};
+
+/// An ancilla or detector gadget: a segment of a qubit line that neither starts
+/// nor ends at a boundary
+///
+/// The segment is a set of spiders with the same qubit coordinate, connected along
+/// that line. Covers both a prepare–interact–measure ancilla and a stabiliser drawn
+/// as a single spider on its own line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AncillaGadget {
+    pub qubit: f64,
+    /// Spiders of the segment, sorted by row
+    pub members: Vec<V>,
+    /// Neighbours outside the segment, sorted
+    pub targets: Vec<V>,
+}
+
+/// Finds all ancilla gadgets, sorted by their first member
+///
+/// A segment only counts if it touches no boundary and interacts with at least one
+/// spider off its line, so isolated pieces of diagram are left alone.
+pub fn find_ancilla_gadgets<G: GraphLike>(g: &G) -> Vec<AncillaGadget> {
+    let is_spider = |v: V| matches!(g.vertex_type(v), VType::Z | VType::X);
+    let mut vertices: Vec<V> = g.vertices().filter(|&v| is_spider(v)).collect();
+    vertices.sort();
+
+    let mut seen = BTreeSet::new();
+    let mut gadgets = Vec::new();
+    for start in vertices {
+        if !seen.insert(start) {
+            continue;
+        }
+        let qubit = g.qubit(start);
+        let mut members = vec![start];
+        let mut stack = vec![start];
+        while let Some(v) = stack.pop() {
+            for n in g.neighbors(v) {
+                if is_spider(n) && g.qubit(n) == qubit && seen.insert(n) {
+                    members.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+
+        let mut targets = BTreeSet::new();
+        let mut on_boundary = false;
+        for &v in &members {
+            for n in g.neighbors(v).filter(|n| !members.contains(n)) {
+                on_boundary |= g.vertex_type(n) == VType::B;
+                targets.insert(n);
+            }
+        }
+        if on_boundary || targets.is_empty() {
+            continue;
+        }
+        members.sort_by(|&a, &b| g.row(a).total_cmp(&g.row(b)).then(a.cmp(&b)));
+        gadgets.push(AncillaGadget { qubit, members, targets: targets.into_iter().collect() });
+    }
+    gadgets
+}
+
+/// One gadget collapsed into a single vertex of a [`CoarseView`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuperNode {
+    /// The vertex standing in for the gadget in the coarse graph
+    pub vertex: V,
+    pub label: String,
+    pub gadget: AncillaGadget,
+}
+
+/// A schematic of a diagram with every ancilla gadget collapsed into a labelled super-node
+///
+/// Vertices outside the gadgets keep their ids, so webs and highlights on them carry over.
+#[derive(Debug, Clone)]
+pub struct CoarseView {
+    pub graph: Graph,
+    pub super_nodes: Vec<SuperNode>,
+}
+
+impl CoarseView {
+    /// Collapses the gadgets of `g` found by [`find_ancilla_gadgets`]
+    pub fn new(g: &Graph) -> Self {
+        Self::from_gadgets(g, find_ancilla_gadgets(g))
+    }
+
+    /// Collapses the given disjoint gadgets of `g`
+    ///
+    /// Each super-node sits at the mean row of its gadget on the gadget's qubit line,
+    /// is an X spider if all members are and a Z spider otherwise, and is labelled
+    /// `A<i> (<members>)`. Edges to a target keep the type of the first member edge.
+    pub fn from_gadgets(g: &Graph, gadgets: Vec<AncillaGadget>) -> Self {
+        let mut graph = g.clone();
+        let mut owner: HashMap<V, V> = HashMap::new();
+        let mut super_nodes = Vec::new();
+
+        for (i, gadget) in gadgets.into_iter().enumerate() {
+            let all_x = gadget.members.iter().all(|&v| g.vertex_type(v) == VType::X);
+            let row = gadget.members.iter().map(|&v| g.row(v)).sum::<f64>() / gadget.members.len() as f64;
+            let vertex = graph.add_vertex_with_data(VData {
+                ty: if all_x { VType::X } else { VType::Z },
+                phase: 0.into(),
+                row,
+                qubit: gadget.qubit,
+            });
+            for &v in &gadget.members {
+                graph.remove_vertex(v);
+                owner.insert(v, vertex);
+            }
+            let label = format!("A{} ({})", i + 1, gadget.members.len());
+            super_nodes.push(SuperNode { vertex, label, gadget });
+        }
+
+        let mapped = |v: V| owner.get(&v).copied().unwrap_or(v);
+        for node in &super_nodes {
+            for &v in &node.gadget.members {
+                let mut neighbours: Vec<V> = g.neighbors(v).collect();
+                neighbours.sort();
+                for n in neighbours.into_iter().filter(|n| !node.gadget.members.contains(n)) {
+                    let target = mapped(n);
+                    if !graph.connected(node.vertex, target) {
+                        graph.add_edge_with_type(node.vertex, target, g.edge_type(v, n));
+                    }
+                }
+            }
+        }
+        CoarseView { graph, super_nodes }
+    }
+
+    /// The original vertices behind a vertex of the coarse graph
+    pub fn expand(&self, v: V) -> Vec<V> {
+        match self.super_nodes.iter().find(|node| node.vertex == v) {
+            Some(node) => node.gadget.members.clone(),
+            None => vec![v],
+        }
+    }
+
+    /// `options` with the super-node labels added as phase labels
+    pub fn render_options(&self, options: &RenderOptions) -> RenderOptions {
+        let mut options = options.clone();
+        for node in &self.super_nodes {
+            options.phase_labels.insert(node.vertex, node.label.clone());
+        }
+        options
+    }
+
+    /// DOT for the schematic, see [`to_dot_with_options`]
+    pub fn to_dot(&self, options: &RenderOptions) -> String {
+        to_dot_with_options(&self.graph, None, &self.render_options(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::vertex_at;
+    use crate::fixtures::fixture;
+
+    #[test]
+    fn test_collapse_ancilla() {
+        // Two data wires measured in ZZ by an ancilla on qubit 1
+        let mut g = Graph::new();
+        let b: Vec<V> = [(0.0, 0.0), (2.0, 0.0), (0.0, 4.0), (2.0, 4.0)].iter()
+            .map(|&(q, r)| vertex_at(&mut g, VType::B, q, r))
+            .collect();
+        let d0 = vertex_at(&mut g, VType::Z, 0.0, 2.0);
+        let d2 = vertex_at(&mut g, VType::Z, 2.0, 2.0);
+        let prep = vertex_at(&mut g, VType::X, 1.0, 1.0);
+        let c0 = vertex_at(&mut g, VType::X, 1.0, 2.0);
+        let c2 = vertex_at(&mut g, VType::X, 1.0, 3.0);
+        let meas = vertex_at(&mut g, VType::X, 1.0, 4.0);
+        for (a, c) in [(b[0], d0), (d0, b[2]), (b[1], d2), (d2, b[3]), (prep, c0), (c0, c2), (c2, meas), (c0, d0), (c2, d2)] {
+            g.add_edge(a, c);
+        }
+
+        let gadgets = find_ancilla_gadgets(&g);
+        assert_eq!(gadgets, vec![AncillaGadget { qubit: 1.0, members: vec![prep, c0, c2, meas], targets: vec![d0, d2] }]);
+
+        let view = CoarseView::new(&g);
+        let node = &view.super_nodes[0];
+        assert_eq!(node.label, "A1 (4)");
+        assert_eq!(view.graph.num_vertices(), 7);
+        assert_eq!(view.graph.vertex_type(node.vertex), VType::X);
+        assert_eq!((view.graph.qubit(node.vertex), view.graph.row(node.vertex)), (1.0, 2.5));
+        let mut neighbours: Vec<V> = view.graph.neighbors(node.vertex).collect();
+        neighbours.sort();
+        assert_eq!(neighbours, vec![d0, d2]);
+        assert_eq!(view.expand(node.vertex), vec![prep, c0, c2, meas]);
+        assert_eq!(view.expand(d0), vec![d0]);
+        assert!(view.to_dot(&RenderOptions::default()).contains("A1 (4)"));
+    }
+
+    #[test]
+    fn test_steane_stabilisers() {
+        let g = fixture("steane_2_rounds").unwrap();
+        let gadgets = find_ancilla_gadgets(&g);
+        // Two rounds of six stabilisers, each drawn as one spider on the ancilla line
+        assert_eq!(gadgets.len(), 12);
+        assert!(gadgets.iter().all(|gadget| gadget.members.len() == 1 && gadget.targets.len() == 4));
+
+        let view = CoarseView::new(&g);
+        assert_eq!(view.graph.num_vertices(), g.num_vertices());
+        assert_eq!(view.graph.num_edges(), g.num_edges());
+    }
+}
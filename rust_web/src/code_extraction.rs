@@ -0,0 +1,380 @@
+use crate::linalg::Mat2;
+use crate::detection_webs::classify_boundaries;
+use crate::pauliweb::{Pauli, PauliWeb};
+use quizx::graph::{EType, VType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use std::collections::HashMap;
+
+/// A stabilizer code read off an encoder diagram
+///
+/// All operators are symplectic row vectors of length 2n: X part first, then Z part.
+#[derive(Debug, Clone)]
+pub struct StabilizerCode {
+    /// Number of physical qubits (outputs of the encoder)
+    pub n: usize,
+    /// Number of logical qubits (inputs of the encoder)
+    pub k: usize,
+    /// One stabilizer generator per row
+    pub stabilizers: Mat2,
+    /// Row i is the image of X on input i
+    pub logical_x: Mat2,
+    /// Row i is the image of Z on input i
+    pub logical_z: Mat2,
+    /// The Pauli web in the diagram realising each stabilizer generator
+    pub stabilizer_webs: Vec<PauliWeb>,
+}
+
+/// Formats a symplectic row vector (X part then Z part) as a Pauli string like "XIZY"
+pub fn pauli_string(mat: &Mat2, row: usize) -> String {
+    let n = mat.cols() / 2;
+    (0..n)
+        .map(|q| match (mat.get(row, q), mat.get(row, n + q)) {
+            (false, false) => 'I',
+            (true, false) => 'X',
+            (false, true) => 'Z',
+            (true, true) => 'Y',
+        })
+        .collect()
+}
+
+/// Extracts the stabilizer code encoded by a measurement-free encoder diagram
+///
+/// Inputs are the logical qubits and outputs the physical ones. If the graph has no
+/// inputs/outputs set, they are guessed with [`classify_boundaries`].
+///
+/// Every Pauli web of the diagram is a solution of the local spider conditions (on
+/// phase-free or π spiders, H-boxes of arity 2 and Hadamard edges). Webs without
+/// input support give the stabilizers, webs with input support X_i/Z_i give the
+/// logical operators. Fails if some spider has a non-Pauli phase or if the diagram
+/// is not an isometry (some input Pauli can't be pushed through).
+pub fn extract_code(g: &Graph) -> Result<StabilizerCode, String> {
+    let (inputs, outputs) = encoder_boundaries(g)?;
+    let k = inputs.len();
+    let n = outputs.len();
+
+    // Two variables (x, z) per edge, as seen from the lower-indexed endpoint
+    let mut edges: Vec<(V, V, EType)> = g.edges()
+        .map(|(a, b, ety)| (a.min(b), a.max(b), ety))
+        .collect();
+    edges.sort();
+    let edge_index: HashMap<(V, V), usize> = edges.iter()
+        .enumerate()
+        .map(|(i, &(a, b, _))| ((a, b), i))
+        .collect();
+
+    // Column of the (x, z) variables of edge (v, n) as seen from v.
+    // Crossing a Hadamard edge swaps the roles of X and Z.
+    let leg = |v: V, n: V| -> (usize, usize) {
+        let i = edge_index[&(v.min(n), v.max(n))];
+        let (x, z) = (2 * i, 2 * i + 1);
+        if v > n && g.edge_type(v, n) == EType::H { (z, x) } else { (x, z) }
+    };
+
+    // One row per local web condition
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    for &v in &vertices {
+        let mut neighbors: Vec<V> = g.neighbors(v).collect();
+        neighbors.sort();
+        let legs: Vec<(usize, usize)> = neighbors.iter().map(|&n| leg(v, n)).collect();
+        match g.vertex_type(v) {
+            VType::Z | VType::X => {
+                if g.phase(v).to_f64().fract() != 0.0 {
+                    return Err(format!("Spider {} has a non-Pauli phase", v));
+                }
+                // Z spiders copy X and have even Z parity, X spiders the other way around
+                let (copied, parity): (Vec<usize>, Vec<usize>) = if g.vertex_type(v) == VType::Z {
+                    legs.iter().map(|&(x, z)| (x, z)).unzip()
+                } else {
+                    legs.iter().map(|&(x, z)| (z, x)).unzip()
+                };
+                for pair in copied.windows(2) {
+                    rows.push(vec![pair[0], pair[1]]);
+                }
+                if !parity.is_empty() {
+                    rows.push(parity);
+                }
+            }
+            VType::H => {
+                if legs.len() != 2 {
+                    return Err(format!("H-box {} has arity {}, only arity 2 is supported", v, legs.len()));
+                }
+                rows.push(vec![legs[0].0, legs[1].1]);
+                rows.push(vec![legs[0].1, legs[1].0]);
+            }
+            VType::B => {}
+            other => return Err(format!("Vertex {} has unsupported type {:?}", v, other)),
+        }
+    }
+
+    let num_vars = 2 * edges.len();
+    let mut constraints = Mat2::new(rows.len(), num_vars);
+    for (r, cols) in rows.iter().enumerate() {
+        for &c in cols {
+            constraints.set(r, c, !constraints.get(r, c));
+        }
+    }
+    let solutions = if rows.is_empty() {
+        (0..num_vars).map(|i| Mat2::unit_vector(num_vars, i)).collect()
+    } else {
+        constraints.nullspace(false)
+    };
+
+    // Boundary columns of each solution: [inputs (x | z) | outputs (x | z) | all edge variables]
+    let boundary_leg = |b: V| -> Result<(usize, usize), String> {
+        let mut neighbors = g.neighbors(b);
+        match (neighbors.next(), neighbors.next()) {
+            (Some(n), None) => Ok(leg(b, n)),
+            _ => Err(format!("Boundary {} must have exactly one edge", b)),
+        }
+    };
+    let input_legs = inputs.iter().map(|&b| boundary_leg(b)).collect::<Result<Vec<_>, _>>()?;
+    let output_legs = outputs.iter().map(|&b| boundary_leg(b)).collect::<Result<Vec<_>, _>>()?;
+
+    let width = 2 * k + 2 * n + num_vars;
+    let mut system = Mat2::new(solutions.len(), width);
+    for (r, sol) in solutions.iter().enumerate() {
+        for (i, &(x, z)) in input_legs.iter().enumerate() {
+            system.set(r, i, sol.get(0, x));
+            system.set(r, k + i, sol.get(0, z));
+        }
+        for (i, &(x, z)) in output_legs.iter().enumerate() {
+            system.set(r, 2 * k + i, sol.get(0, x));
+            system.set(r, 2 * k + n + i, sol.get(0, z));
+        }
+        for c in 0..num_vars {
+            system.set(r, 2 * k + 2 * n + c, sol.get(0, c));
+        }
+    }
+
+    // Reduced row echelon form: rows pivoting on input columns are logicals, rows
+    // pivoting on output columns have no input support and are stabilizers
+    let mut pivots = Vec::new();
+    system.gauss(true, None, None, 0, &mut pivots);
+    if (0..2 * k).any(|c| !pivots.contains(&c)) {
+        return Err("Diagram is not an isometry: not every input Pauli reaches the outputs".to_string());
+    }
+
+    let output_part = |row: usize, target: &mut Mat2, target_row: usize| {
+        for c in 0..2 * n {
+            target.set(target_row, c, system.get(row, 2 * k + c));
+        }
+    };
+
+    let mut logical_x = Mat2::new(k, 2 * n);
+    let mut logical_z = Mat2::new(k, 2 * n);
+    let stabilizer_rows: Vec<usize> = pivots.iter()
+        .enumerate()
+        .filter(|&(_, &p)| p >= 2 * k && p < 2 * k + 2 * n)
+        .map(|(row, _)| row)
+        .collect();
+    let mut stabilizers = Mat2::new(stabilizer_rows.len(), 2 * n);
+
+    for (row, &pivot) in pivots.iter().enumerate() {
+        if pivot < k {
+            output_part(row, &mut logical_x, pivot);
+        } else if pivot < 2 * k {
+            output_part(row, &mut logical_z, pivot - k);
+        }
+    }
+    let mut stabilizer_webs = Vec::with_capacity(stabilizer_rows.len());
+    for (i, &row) in stabilizer_rows.iter().enumerate() {
+        output_part(row, &mut stabilizers, i);
+
+        let mut web = PauliWeb::new();
+        for (e, &(a, b, _)) in edges.iter().enumerate() {
+            let offset = 2 * k + 2 * n + 2 * e;
+            let pauli = match (system.get(row, offset), system.get(row, offset + 1)) {
+                (true, false) => Pauli::X,
+                (false, true) => Pauli::Z,
+                (true, true) => Pauli::Y,
+                (false, false) => continue,
+            };
+            web.set_edge(a, b, pauli);
+        }
+        stabilizer_webs.push(web);
+    }
+
+    Ok(StabilizerCode { n, k, stabilizers, logical_x, logical_z, stabilizer_webs })
+}
+
+/// Inputs and outputs of one logical qubit, or of several entangled ones
+pub type LogicalPair = (Vec<V>, Vec<V>);
+
+/// Groups the boundaries of an encoder diagram by the logical qubit they belong to
+///
+/// Each input is joined to the outputs its logical X and Z act on, as found by
+/// [`extract_code`]; inputs whose logicals share an output form one group, as after an
+/// entangling gate. Returns (inputs, outputs) per group, each in boundary order, with
+/// the groups sorted by first input. Outputs no logical acts on are left out.
+pub fn logical_pairs(g: &Graph) -> Result<Vec<LogicalPair>, String> {
+    let code = extract_code(g)?;
+    let (inputs, outputs) = encoder_boundaries(g)?;
+    let n = code.n;
+    let support = |i: usize| -> Vec<usize> {
+        (0..n)
+            .filter(|&q| [&code.logical_x, &code.logical_z].iter().any(|m| m.get(i, q) || m.get(i, n + q)))
+            .collect()
+    };
+    let supports: Vec<Vec<usize>> = (0..code.k).map(support).collect();
+
+    let mut group_of: Vec<Option<usize>> = vec![None; code.k];
+    let mut groups = Vec::new();
+    for start in 0..code.k {
+        if group_of[start].is_some() {
+            continue;
+        }
+        let mut members = vec![start];
+        group_of[start] = Some(groups.len());
+        let mut next = 0;
+        while next < members.len() {
+            let i = members[next];
+            next += 1;
+            for j in 0..code.k {
+                if group_of[j].is_none() && supports[j].iter().any(|q| supports[i].contains(q)) {
+                    group_of[j] = Some(groups.len());
+                    members.push(j);
+                }
+            }
+        }
+        members.sort();
+        let mut qubits: Vec<usize> = members.iter().flat_map(|&i| supports[i].iter().copied()).collect();
+        qubits.sort();
+        qubits.dedup();
+        groups.push((
+            members.iter().map(|&i| inputs[i]).collect(),
+            qubits.iter().map(|&q| outputs[q]).collect(),
+        ));
+    }
+    Ok(groups)
+}
+
+/// The inputs and outputs set on `g`, or guessed with [`classify_boundaries`] if none are
+fn encoder_boundaries(g: &Graph) -> Result<(Vec<V>, Vec<V>), String> {
+    if g.inputs().is_empty() && g.outputs().is_empty() {
+        classify_boundaries(g)
+    } else {
+        Ok((g.inputs().clone(), g.outputs().clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::vertex_at;
+
+    /// Symplectic inner product of two rows, zero iff the Paulis commute
+    fn commute(a: &Mat2, i: usize, b: &Mat2, j: usize) -> bool {
+        let n = a.cols() / 2;
+        (0..n).filter(|&q| (a.get(i, q) && b.get(j, n + q)) ^ (a.get(i, n + q) && b.get(j, q))).count() % 2 == 0
+    }
+
+    #[test]
+    fn test_repetition_code() {
+        // One input fanned out to three outputs by a Z spider
+        let mut g = Graph::new();
+        let input = vertex_at(&mut g, VType::B, 1.0, 0.0);
+        let z = vertex_at(&mut g, VType::Z, 1.0, 1.0);
+        g.add_edge(input, z);
+        let mut outputs = Vec::new();
+        for q in 0..3 {
+            let out = vertex_at(&mut g, VType::B, q as f64, 2.0);
+            g.add_edge(z, out);
+            outputs.push(out);
+        }
+        g.set_inputs(vec![input]);
+        g.set_outputs(outputs);
+
+        let code = extract_code(&g).unwrap();
+        assert_eq!((code.n, code.k), (3, 1));
+        assert_eq!(code.stabilizers.rows(), 2);
+        assert_eq!(code.stabilizer_webs.len(), 2);
+        for i in 0..2 {
+            let s = pauli_string(&code.stabilizers, i);
+            assert!(s.chars().all(|c| c == 'Z' || c == 'I'), "{}", s);
+            assert_eq!(s.matches('Z').count() % 2, 0, "{}", s);
+            for j in 0..2 {
+                assert!(commute(&code.stabilizers, i, &code.stabilizers, j));
+            }
+            assert!(commute(&code.stabilizers, i, &code.logical_x, 0));
+        }
+        assert_eq!(pauli_string(&code.logical_x, 0), "XXX");
+        assert!(!commute(&code.logical_x, 0, &code.logical_z, 0));
+    }
+
+    #[test]
+    fn test_hadamard_edge_swaps_logicals() {
+        let mut g = Graph::new();
+        let input = vertex_at(&mut g, VType::B, 0.0, 0.0);
+        let z = vertex_at(&mut g, VType::Z, 0.0, 1.0);
+        let output = vertex_at(&mut g, VType::B, 0.0, 2.0);
+        g.add_edge(input, z);
+        g.add_edge_with_type(z, output, EType::H);
+        g.set_inputs(vec![input]);
+        g.set_outputs(vec![output]);
+
+        let code = extract_code(&g).unwrap();
+        assert_eq!(code.stabilizers.rows(), 0);
+        assert_eq!(pauli_string(&code.logical_x, 0), "Z");
+        assert_eq!(pauli_string(&code.logical_z, 0), "X");
+    }
+
+    #[test]
+    fn test_rejects_non_isometry() {
+        // The input is discarded into an X effect, the output comes from a fresh Z state
+        let mut g = Graph::new();
+        let input = vertex_at(&mut g, VType::B, 0.0, 0.0);
+        let x = vertex_at(&mut g, VType::X, 0.0, 1.0);
+        let z = vertex_at(&mut g, VType::Z, 0.0, 2.0);
+        let output = vertex_at(&mut g, VType::B, 0.0, 3.0);
+        g.add_edge(input, x);
+        g.add_edge(z, output);
+        g.set_inputs(vec![input]);
+        g.set_outputs(vec![output]);
+
+        assert!(extract_code(&g).unwrap_err().contains("not an isometry"));
+    }
+
+    #[test]
+    fn test_rejects_non_pauli_phase() {
+        let mut g = Graph::new();
+        let input = vertex_at(&mut g, VType::B, 0.0, 0.0);
+        let z = g.add_vertex_with_phase(VType::Z, quizx::phase::Phase::from_f64(0.25));
+        let output = vertex_at(&mut g, VType::B, 0.0, 2.0);
+        g.add_edge(input, z);
+        g.add_edge(z, output);
+        g.set_inputs(vec![input]);
+        g.set_outputs(vec![output]);
+
+        assert!(extract_code(&g).unwrap_err().contains("non-Pauli phase"));
+    }
+
+    #[test]
+    fn test_logical_pairs() {
+        // Qubit 0 encoded in a repetition code on outputs 0-2, qubit 1 a bare wire to output 3
+        let mut g = Graph::new();
+        let in0 = vertex_at(&mut g, VType::B, 1.0, 0.0);
+        let in1 = vertex_at(&mut g, VType::B, 3.0, 0.0);
+        let z = vertex_at(&mut g, VType::Z, 1.0, 1.0);
+        g.add_edge(in0, z);
+        let outputs: Vec<V> = (0..4).map(|q| vertex_at(&mut g, VType::B, q as f64, 2.0)).collect();
+        for &out in &outputs[..3] {
+            g.add_edge(z, out);
+        }
+        g.add_edge(in1, outputs[3]);
+        assert_eq!(logical_pairs(&g).unwrap(), vec![(vec![in0], outputs[..3].to_vec()), (vec![in1], vec![outputs[3]])]);
+
+        // A CNOT entangles its two qubits into one group
+        let mut g = Graph::new();
+        let (c_in, t_in) = (vertex_at(&mut g, VType::B, 0.0, 0.0), vertex_at(&mut g, VType::B, 1.0, 0.0));
+        let (c, t) = (vertex_at(&mut g, VType::Z, 0.0, 1.0), vertex_at(&mut g, VType::X, 1.0, 1.0));
+        let (c_out, t_out) = (vertex_at(&mut g, VType::B, 0.0, 2.0), vertex_at(&mut g, VType::B, 1.0, 2.0));
+        g.add_edge(c_in, c);
+        g.add_edge(c, c_out);
+        g.add_edge(t_in, t);
+        g.add_edge(t, t_out);
+        g.add_edge(c, t);
+        assert_eq!(logical_pairs(&g).unwrap(), vec![(vec![c_in, t_in], vec![c_out, t_out])]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Side-by-side comparison of experiment variants, e.g. codes of different distances or
+//! with different schedules
+//!
+//! [`compare_variants`] runs the web detection on each diagram and [`Comparison`] prints
+//! them as one table, with each figure also given relative to the first diagram and the
+//! largest differences called out.
+
+use crate::detection_webs::{get_detection_webs_lenient, WebOptions};
+use crate::graph_stats::graph_stats;
+use quizx::hash_graph::Graph;
+use serde::Serialize;
+use std::fmt;
+use std::time::Instant;
+
+/// Relative difference from the baseline from which [`Comparison::highlights`] reports it
+pub const HIGHLIGHT_THRESHOLD: f64 = 0.1;
+
+/// Figures of one variant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VariantReport {
+    pub name: String,
+    pub vertices: usize,
+    pub edges: usize,
+    pub webs: usize,
+    /// Edges of the smallest web
+    pub min_weight: Option<usize>,
+    /// Seconds spent converting to rg form and finding the webs
+    pub seconds: f64,
+}
+
+impl VariantReport {
+    /// The figures compared between variants, by column name
+    fn metrics(&self) -> [(&'static str, Option<f64>); 5] {
+        [
+            ("vertices", Some(self.vertices as f64)),
+            ("edges", Some(self.edges as f64)),
+            ("webs", Some(self.webs as f64)),
+            ("min weight", self.min_weight.map(|w| w as f64)),
+            ("time", Some(self.seconds)),
+        ]
+    }
+}
+
+/// Runs the web detection on a copy of `g` and collects its figures
+pub fn run_variant(name: &str, g: &Graph, options: &WebOptions) -> Result<VariantReport, String> {
+    let stats = graph_stats(g);
+    let mut g = g.clone();
+    let start = Instant::now();
+    let webs = get_detection_webs_lenient(&mut g, options)?;
+    let seconds = start.elapsed().as_secs_f64();
+    Ok(VariantReport {
+        name: name.to_string(),
+        vertices: stats.vertices,
+        edges: stats.edges,
+        webs: webs.len(),
+        min_weight: webs.iter().map(|web| web.edge_operators.len()).min(),
+        seconds,
+    })
+}
+
+/// Reports of several variants, the first being the baseline
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Comparison {
+    pub variants: Vec<VariantReport>,
+}
+
+/// One figure of a variant that differs notably from the baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Highlight {
+    pub variant: String,
+    pub metric: &'static str,
+    /// `value / baseline - 1`
+    pub change: f64,
+}
+
+impl fmt::Display for Highlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} {:+.0}%", self.variant, self.metric, self.change * 100.0)
+    }
+}
+
+/// `value` relative to `baseline`, `None` where either is missing or the baseline is zero
+fn relative(value: Option<f64>, baseline: Option<f64>) -> Option<f64> {
+    match (value, baseline) {
+        (Some(value), Some(baseline)) if baseline != 0.0 => Some(value / baseline),
+        _ => None,
+    }
+}
+
+impl Comparison {
+    /// Figures that differ from the baseline by at least [`HIGHLIGHT_THRESHOLD`], largest
+    /// difference first
+    pub fn highlights(&self) -> Vec<Highlight> {
+        let Some(baseline) = self.variants.first() else {
+            return Vec::new();
+        };
+        let mut highlights: Vec<Highlight> = self.variants[1..].iter()
+            .flat_map(|variant| variant.metrics().into_iter().zip(baseline.metrics()).filter_map(|((metric, value), (_, base))| {
+                let change = relative(value, base)? - 1.0;
+                (change.abs() >= HIGHLIGHT_THRESHOLD).then(|| Highlight { variant: variant.name.clone(), metric, change })
+            }))
+            .collect();
+        highlights.sort_by(|a, b| b.change.abs().total_cmp(&a.change.abs()));
+        highlights
+    }
+}
+
+/// Runs [`run_variant`] on each named graph, in order
+pub fn compare_variants(graphs: &[(String, Graph)], options: &WebOptions) -> Result<Comparison, String> {
+    let variants = graphs.iter()
+        .map(|(name, g)| run_variant(name, g, options).map_err(|e| format!("{}: {}", name, e)))
+        .collect::<Result<_, _>>()?;
+    Ok(Comparison { variants })
+}
+
+impl fmt::Display for Comparison {
+    /// The figures, the figures relative to the baseline and the highlights, as plain text
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(baseline) = self.variants.first() else {
+            return writeln!(f, "No variants");
+        };
+        let width = self.variants.iter().map(|v| v.name.len()).max().unwrap_or(0).max("variant".len());
+        let header = |f: &mut fmt::Formatter<'_>| {
+            write!(f, "{:<width$}", "variant")?;
+            for (metric, _) in baseline.metrics() {
+                write!(f, " {:>10}", metric)?;
+            }
+            writeln!(f)
+        };
+
+        header(f)?;
+        for variant in &self.variants {
+            write!(f, "{:<width$}", variant.name)?;
+            for (metric, value) in variant.metrics() {
+                match value {
+                    Some(seconds) if metric == "time" => write!(f, " {:>9.3}s", seconds)?,
+                    Some(value) => write!(f, " {:>10}", value)?,
+                    None => write!(f, " {:>10}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "\nRelative to {}:", baseline.name)?;
+        header(f)?;
+        for variant in &self.variants {
+            write!(f, "{:<width$}", variant.name)?;
+            for ((_, value), (_, base)) in variant.metrics().into_iter().zip(baseline.metrics()) {
+                match relative(value, base) {
+                    Some(ratio) => write!(f, " {:>9.2}×", ratio)?,
+                    None => write!(f, " {:>10}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        let highlights = self.highlights();
+        if !highlights.is_empty() {
+            writeln!(f, "\nNotable differences:")?;
+            for highlight in highlights {
+                writeln!(f, "  {}", highlight)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use quizx::hash_graph::GraphLike;
+
+    #[test]
+    fn test_compare_variants() {
+        let graphs = vec![
+            ("two".to_string(), create_steane_rounds(2)),
+            ("three".to_string(), create_steane_rounds(3)),
+        ];
+        let comparison = compare_variants(&graphs, &WebOptions::default()).unwrap();
+        let [two, three] = &comparison.variants[..] else { panic!() };
+        assert_eq!(two.vertices, graphs[0].1.num_vertices());
+        assert!(three.vertices > two.vertices);
+        assert!(three.webs > two.webs);
+        assert!(two.min_weight.is_some());
+
+        // The time is too noisy to pin down, the sizes aren't
+        let highlights: Vec<&str> = comparison.highlights().iter()
+            .filter(|h| h.metric != "time")
+            .map(|h| h.metric)
+            .collect();
+        assert!(highlights.contains(&"vertices") && highlights.contains(&"webs"), "{:?}", highlights);
+        assert!(comparison.highlights().iter().all(|h| h.variant == "three"));
+
+        let table = comparison.to_string();
+        assert!(table.starts_with("variant   vertices"), "{}", table);
+        assert!(table.contains("Relative to two:"));
+        assert!(table.contains("two          1.00×"), "{}", table);
+        assert!(table.contains("Notable differences:\n  three: "));
+    }
+}
@@ -0,0 +1,223 @@
+//! Disconnected fragments in a diagram, usually from an edge forgotten in the editor
+//!
+//! The webs of a disconnected diagram are the webs of its pieces side by side, which
+//! reads as if the pieces interacted. [`check_components`] reports the pieces before the
+//! analysis, and [`get_detection_webs_per_component`] analyses each on its own.
+
+use crate::detection_webs::{get_detection_webs_lenient_traced, BoundaryRoles, WebOptions, WebProvenance};
+use crate::logging::WEBS;
+use crate::pauliweb::PauliWeb;
+use quizx::graph::{GraphLike, VData, V};
+use quizx::hash_graph::Graph;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// A connected piece of a diagram
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    /// Its vertices, sorted
+    pub vertices: Vec<V>,
+    /// Row and qubit of its smallest vertex, to find it in the editor
+    pub coord: (f64, f64),
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} vertices around row {}, qubit {}",
+            self.vertices.len(), self.coord.0, self.coord.1
+        )
+    }
+}
+
+/// The connected components of `g`, largest first, ties broken by smallest vertex
+pub fn components<G: GraphLike>(g: &G) -> Vec<Component> {
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+    for start in vertices {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut stack = vec![start];
+        while let Some(v) = stack.pop() {
+            for n in g.neighbors(v) {
+                if seen.insert(n) {
+                    component.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+        component.sort();
+        components.push(Component { vertices: component, coord: (g.row(start), g.qubit(start)) });
+    }
+    components.sort_by(|a, b| b.vertices.len().cmp(&a.vertices.len()).then(a.vertices[0].cmp(&b.vertices[0])));
+    components
+}
+
+/// What to do with a diagram of more than one component
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ComponentPolicy {
+    /// Log a warning and analyse the diagram as a whole
+    #[default]
+    Warn,
+    /// Refuse to analyse it
+    Error,
+    /// Analyse each component on its own, see [`get_detection_webs_per_component`]
+    Split,
+}
+
+impl fmt::Display for ComponentPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ComponentPolicy::Warn => "warn",
+            ComponentPolicy::Error => "error",
+            ComponentPolicy::Split => "split",
+        })
+    }
+}
+
+impl FromStr for ComponentPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "warn" => Ok(ComponentPolicy::Warn),
+            "error" => Ok(ComponentPolicy::Error),
+            "split" => Ok(ComponentPolicy::Split),
+            _ => Err(format!("Unknown component policy '{}', expected warn, error or split", s)),
+        }
+    }
+}
+
+/// Finds the components of `g` and reports them according to `policy`
+///
+/// A connected diagram always passes. Otherwise [`ComponentPolicy::Error`] fails with the
+/// component sizes and locations, and the other policies log them.
+pub fn check_components<G: GraphLike>(g: &G, policy: ComponentPolicy) -> Result<Vec<Component>, String> {
+    let components = components(g);
+    if components.len() <= 1 {
+        return Ok(components);
+    }
+    let list: Vec<String> = components.iter().map(|c| c.to_string()).collect();
+    let message = format!("Diagram has {} disconnected components: {}", components.len(), list.join("; "));
+    match policy {
+        ComponentPolicy::Warn => log::warn!(target: WEBS, "{}; its webs may be misleading", message),
+        ComponentPolicy::Error => return Err(message),
+        ComponentPolicy::Split => log::info!(target: WEBS, "{}; analysing each on its own", message),
+    }
+    Ok(components)
+}
+
+/// The part of `g` spanned by `vertices`, with the edges among them and the inputs and
+/// outputs among them in their original order
+///
+/// Returns the subgraph and the original vertex of each of its vertices.
+pub fn subgraph(g: &Graph, vertices: &[V]) -> (Graph, HashMap<V, V>) {
+    let mut sub = Graph::new();
+    let mut new_of: HashMap<V, V> = HashMap::new();
+    for &v in vertices {
+        let data = VData { ty: g.vertex_type(v), phase: g.phase(v), qubit: g.qubit(v), row: g.row(v) };
+        new_of.insert(v, sub.add_vertex_with_data(data));
+    }
+    for &v in vertices {
+        for n in g.neighbors(v) {
+            if v < n && new_of.contains_key(&n) {
+                sub.add_edge_with_type(new_of[&v], new_of[&n], g.edge_type(v, n));
+            }
+        }
+    }
+    let keep = |boundary: &[V]| boundary.iter().filter_map(|v| new_of.get(v).copied()).collect();
+    sub.set_inputs(keep(g.inputs()));
+    sub.set_outputs(keep(g.outputs()));
+    let old_of = new_of.into_iter().map(|(old, new)| (new, old)).collect();
+    (sub, old_of)
+}
+
+/// Detection webs of each component of `g` on its own, in the vertex ids of `g`
+///
+/// Components are taken largest first, as [`components`] lists them, and their webs
+/// follow each other in that order. Explicit boundary roles are restricted to each
+/// component.
+pub fn get_detection_webs_per_component(
+    g: &Graph,
+    options: &WebOptions,
+) -> Result<Vec<(PauliWeb, WebProvenance)>, String> {
+    let mut webs = Vec::new();
+    for component in components(g) {
+        let (mut sub, old_of) = subgraph(g, &component.vertices);
+        let new_of: HashMap<V, V> = old_of.iter().map(|(&new, &old)| (old, new)).collect();
+        let roles = match &options.roles {
+            BoundaryRoles::Explicit { inputs, outputs } => {
+                let restrict = |boundary: &[V]| boundary.iter().filter_map(|v| new_of.get(v).copied()).collect();
+                BoundaryRoles::Explicit { inputs: restrict(inputs), outputs: restrict(outputs) }
+            }
+            roles => roles.clone(),
+        };
+        let options = WebOptions { roles, ..options.clone() };
+        for (web, mut provenance) in get_detection_webs_lenient_traced(&mut sub, &options)? {
+            let relabelled = web.relabel(&sub, &old_of)?;
+            for fired in &mut provenance.fired {
+                fired.vertex = old_of[&fired.vertex];
+            }
+            webs.push((relabelled, provenance));
+        }
+    }
+    Ok(webs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use crate::make_rg::make_rg;
+    use crate::web_space::WebSpace;
+
+    #[test]
+    fn test_components() {
+        let mut whole = create_steane_rounds(2);
+        make_rg(&mut whole).unwrap();
+        assert_eq!(check_components(&whole, ComponentPolicy::Error).unwrap().len(), 1);
+
+        // A stray pair of spiders far from the diagram
+        let mut g = whole.clone();
+        let a = g.add_vertex_with_data(VData { ty: quizx::graph::VType::Z, phase: Default::default(), qubit: 9.0, row: 4.0 });
+        let b = g.add_vertex(quizx::graph::VType::X);
+        g.add_edge(a, b);
+        let found = components(&g);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1], Component { vertices: vec![a, b], coord: (4.0, 9.0) });
+        let error = check_components(&g, ComponentPolicy::Error).unwrap_err();
+        assert!(error.contains("2 disconnected components"), "{}", error);
+        assert!(error.contains("2 vertices around row 4, qubit 9"), "{}", error);
+        assert!(check_components(&g, ComponentPolicy::Warn).is_ok());
+        assert_eq!("split".parse::<ComponentPolicy>(), Ok(ComponentPolicy::Split));
+
+        // The main component keeps its webs and vertex ids; the stray pair, closed off,
+        // only adds webs of its own
+        let options = WebOptions::default();
+        let expected: Vec<PauliWeb> = get_detection_webs_lenient_traced(&mut whole, &options).unwrap()
+            .into_iter().map(|(web, _)| web).collect();
+        let split: Vec<PauliWeb> = get_detection_webs_per_component(&g, &options).unwrap()
+            .into_iter().map(|(web, _)| web).collect();
+        let edges: Vec<(V, V)> = expected.iter().chain(&split).flat_map(|w| w.edge_operators.keys().copied()).collect();
+        let span = |webs: &[PauliWeb]| {
+            let mut space = WebSpace::new(edges.iter().copied());
+            for web in webs {
+                space.add(web).unwrap();
+            }
+            space
+        };
+        let stray: HashSet<V> = [a, b].into_iter().collect();
+        let (extra, main): (Vec<PauliWeb>, Vec<PauliWeb>) = split.into_iter()
+            .partition(|web| web.edge_operators.keys().any(|(x, _)| stray.contains(x)));
+        assert!(extra.iter().all(|web| web.edge_operators.keys().all(|(x, y)| stray.contains(x) && stray.contains(y))));
+        assert!(!expected.is_empty());
+        assert_eq!(span(&main).dim(), span(&expected).dim());
+        assert!(expected.iter().all(|web| span(&main).contains(web)));
+    }
+}
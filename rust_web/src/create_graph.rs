@@ -1,78 +1,61 @@
 use quizx::vec_graph::VType;
-use quizx::graph::VData;
-use quizx::{graph::GraphLike, hash_graph::Graph};
-use num::rational::Rational64;
-
-// Creates a vertex with Z-type and given position data
-
-fn create_spider_vertex(g: &mut Graph, ty: VType, phase: Rational64, qubit: f64, row: f64) -> usize {
-    g.add_vertex_with_data(VData {
-        ty,
-        phase: phase.into(),
-        qubit,
-        row,
-    })
+use quizx::hash_graph::{Graph, GraphLike};
+use zxcore::NodeKind;
+
+fn node_kind(ty: VType) -> NodeKind {
+    match ty {
+        VType::Z => NodeKind::Z,
+        VType::X => NodeKind::X,
+        VType::H => NodeKind::H,
+        VType::ZBox => NodeKind::ZBox,
+        VType::WInput => NodeKind::WInput,
+        VType::WOutput => NodeKind::WOutput,
+        _ => NodeKind::Boundary,
+    }
 }
 
 /// Creates a chain of spiders of the same type with optional boundary nodes
+///
+/// See [`zxcore::build::spider_chain`] for the layout and phases.
 pub fn create_spider_chain(n: usize, spider_type: VType, with_phases: bool, with_boundaries: bool) -> Graph {
     let mut g = Graph::new();
-    let mut prev = if with_boundaries {
-        g.add_vertex_with_data(VData {
-            ty: VType::B,
-            phase: Rational64::new(0, 1).into(),
-            qubit: 0.0,
-            row: 0.0,
-        })
-    } else {
-        create_spider_vertex(
-            &mut g,
-            spider_type,
-            if with_phases { Rational64::new(1, 4) } else { Rational64::new(0, 1) },
-            0.0,
-            0.0,
-        )
-    };
-
-    // Create chain of spiders
-    for i in 1..n {
-        let phase = if with_phases {
-            Rational64::new(i as i64, (i + 1) as i64)  // Creates different phases for each spider
-        } else {
-            Rational64::new(0, 1)
-        };
-        
-        let current = create_spider_vertex(&mut g, spider_type, phase, i as f64, 0.0);
-        g.add_edge(prev, current);
-        prev = current;
-    }
-
-    // Add final boundary node if requested
-    if with_boundaries {
-        let final_node = g.add_vertex_with_data(VData {
-            ty: VType::B,
-            phase: Rational64::new(0, 1).into(),
-            qubit: n as f64,
-            row: 0.0,
-        });
-        g.add_edge(prev, final_node);
-    }
+    zxcore::build::spider_chain(&mut g, n, node_kind(spider_type), with_phases, with_boundaries);
+    g
+}
 
+pub fn create_chain(n: i32) -> Graph {
+    let mut g = Graph::new();
+    zxcore::build::chain(&mut g, n.max(0) as usize, NodeKind::X);
     g
 }
 
-pub fn create_chain(n: i32)->Graph {
+/// Repeated stabilizer measurements, with inputs and outputs set
+///
+/// A stabilizer is the spider type it puts on its support, see
+/// [`zxcore::build::stabilizer_rounds`] for the layout.
+pub fn create_stabilizer_rounds(qubits: usize, stabilizers: &[(VType, &[usize])], rounds: usize) -> Graph {
+    let kinds: Vec<(NodeKind, &[usize])> = stabilizers.iter()
+        .map(|&(ty, support)| (node_kind(ty), support))
+        .collect();
     let mut g = Graph::new();
+    let (inputs, outputs) = zxcore::build::stabilizer_rounds(&mut g, qubits, &kinds, rounds);
+    g.set_inputs(inputs);
+    g.set_outputs(outputs);
+    g
+}
 
-    // Create the first node
-    let first = g.add_vertex(VType::X);
-    let mut prev = first;
+/// `rounds` measurements of XX on two qubits
+pub fn create_xx_stabilizer(rounds: usize) -> Graph {
+    create_stabilizer_rounds(2, &[(VType::X, &[0, 1])], rounds)
+}
 
-    // Add 999 more nodes and chain them
-    for _ in 0..n {
-        let current = g.add_vertex(VType::X);
-        g.add_edge(prev, current);
-        prev = current;
-    }
-    return g;
-}
\ No newline at end of file
+/// Supports of the Steane code's stabilizers, each measured as both X and Z
+pub const STEANE_SUPPORTS: [[usize; 4]; 3] = [[0, 1, 2, 3], [0, 1, 4, 5], [0, 2, 4, 6]];
+
+/// `rounds` rounds of the Steane code's Z then X stabilizers
+pub fn create_steane_rounds(rounds: usize) -> Graph {
+    let stabilizers: Vec<(VType, &[usize])> = [VType::Z, VType::X].into_iter()
+        .flat_map(|ty| STEANE_SUPPORTS.iter().map(move |support| (ty, &support[..])))
+        .collect();
+    create_stabilizer_rounds(7, &stabilizers, rounds)
+}
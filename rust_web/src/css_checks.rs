@@ -0,0 +1,190 @@
+//! Reading the parity checks of a CSS code straight off a phase-free diagram
+//!
+//! In a phase-free diagram of repeated stabilizer measurements, like the ones
+//! [`crate::create_graph::create_stabilizer_rounds`] builds, every spider off the data
+//! qubit wires is a parity check: an X spider joined to Z spiders on the data measures
+//! their Z parity, a Z spider joined to X spiders their X parity. [`css_checks`] collects
+//! those into the check matrices Hx and Hz, which form a CSS code exactly when every X
+//! check commutes with every Z check, HxHzᵀ = 0. This complements the detection webs,
+//! which find the same checks and how they combine over rounds, but need the solver.
+
+use crate::linalg::Mat2;
+use quizx::graph::{EType, GraphLike, VType, V};
+use quizx::hash_graph::Graph;
+use std::fmt;
+
+/// The parity checks of a phase-free diagram, see [`css_checks`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssChecks {
+    /// Number of data qubits, one column of each matrix
+    pub n: usize,
+    /// The Z spider of each X check, one row of `hx` each
+    pub x_checks: Vec<V>,
+    /// Row i is the support of X check i
+    pub hx: Mat2,
+    /// The X spider of each Z check, one row of `hz` each
+    pub z_checks: Vec<V>,
+    /// Row i is the support of Z check i
+    pub hz: Mat2,
+    /// Spiders off the data wires that aren't checks, e.g. because they touch a spider of
+    /// their own colour or another check
+    pub irregular: Vec<V>,
+}
+
+impl CssChecks {
+    /// Every X check and Z check overlapping on an odd number of qubits, by spider
+    pub fn anticommuting(&self) -> Vec<(V, V)> {
+        let mut pairs = Vec::new();
+        for (i, &x) in self.x_checks.iter().enumerate() {
+            for (j, &z) in self.z_checks.iter().enumerate() {
+                if (0..self.n).filter(|&q| self.hx.get(i, q) && self.hz.get(j, q)).count() % 2 == 1 {
+                    pairs.push((x, z));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Whether every spider off the data wires is a check and HxHzᵀ = 0
+    pub fn is_css(&self) -> bool {
+        self.irregular.is_empty() && self.anticommuting().is_empty()
+    }
+
+    /// Logical qubits of the code, n - rank Hx - rank Hz
+    pub fn logical_qubits(&self) -> usize {
+        self.n.saturating_sub(self.hx.rank() + self.hz.rank())
+    }
+}
+
+impl fmt::Display for CssChecks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} qubits, {} X checks (rank {}), {} Z checks (rank {})",
+            self.n,
+            self.x_checks.len(),
+            self.hx.rank(),
+            self.z_checks.len(),
+            self.hz.rank()
+        )?;
+        let anticommuting = self.anticommuting();
+        if self.is_css() {
+            return write!(f, ": a [[{}, {}]] CSS code", self.n, self.logical_qubits());
+        }
+        write!(f, ": not a CSS code")?;
+        if !anticommuting.is_empty() {
+            let pairs: Vec<String> = anticommuting.iter().map(|(x, z)| format!("{}/{}", x, z)).collect();
+            write!(f, ", X/Z checks {} anticommute", pairs.join(", "))?;
+        }
+        if !self.irregular.is_empty() {
+            let spiders: Vec<String> = self.irregular.iter().map(V::to_string).collect();
+            write!(f, ", spiders {} are no checks", spiders.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the X and Z check matrices of a phase-free diagram
+///
+/// The data qubits are the qubit coordinates of the boundaries, in increasing order, and
+/// a spider lies on the wire of the qubit whose coordinate it shares. Every other spider
+/// joined only to spiders of the other colour on data wires is a check on their qubits,
+/// a qubit it touches twice dropping out. Fails on phases, Hadamard edges and vertices
+/// other than boundaries and Z and X spiders, which the reading doesn't cover.
+pub fn css_checks(g: &Graph) -> Result<CssChecks, String> {
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    for &v in &vertices {
+        match g.vertex_type(v) {
+            VType::B => {}
+            VType::Z | VType::X if g.phase(v).to_rational() == 0.into() => {}
+            VType::Z | VType::X => return Err(format!("Spider {} has phase {}; parity checks need a phase-free diagram", v, g.phase(v))),
+            other => return Err(format!("Vertex {} is a {:?}; parity checks are read off Z and X spiders", v, other)),
+        }
+    }
+    if let Some((a, b, _)) = g.edges().find(|&(_, _, ety)| ety == EType::H) {
+        return Err(format!("Edge {}-{} is a Hadamard edge; parity checks are read off plain edges", a, b));
+    }
+
+    let mut qubits: Vec<f64> = vertices.iter().filter(|&&v| g.vertex_type(v) == VType::B).map(|&v| g.qubit(v)).collect();
+    qubits.sort_by(f64::total_cmp);
+    qubits.dedup();
+    let qubit = |v: V| qubits.iter().position(|&q| q == g.qubit(v));
+
+    let mut x_rows = Vec::new();
+    let mut z_rows = Vec::new();
+    let mut irregular = Vec::new();
+    for &v in vertices.iter().filter(|&&v| g.vertex_type(v) != VType::B && qubit(v).is_none()) {
+        let ty = g.vertex_type(v);
+        let mut support = vec![false; qubits.len()];
+        let mut regular = g.degree(v) > 0;
+        for n in g.neighbors(v) {
+            match qubit(n) {
+                Some(q) if g.vertex_type(n) != VType::B && g.vertex_type(n) != ty => support[q] ^= true,
+                _ => regular = false,
+            }
+        }
+        match (regular, ty) {
+            (false, _) => irregular.push(v),
+            (true, VType::Z) => x_rows.push((v, support)),
+            (true, _) => z_rows.push((v, support)),
+        }
+    }
+
+    let matrix = |rows: &[(V, Vec<bool>)]| {
+        let mut m = Mat2::new(rows.len(), qubits.len());
+        for (i, (_, support)) in rows.iter().enumerate() {
+            for (q, &bit) in support.iter().enumerate() {
+                m.set(i, q, bit);
+            }
+        }
+        m
+    };
+    Ok(CssChecks {
+        n: qubits.len(),
+        hx: matrix(&x_rows),
+        hz: matrix(&z_rows),
+        x_checks: x_rows.into_iter().map(|(v, _)| v).collect(),
+        z_checks: z_rows.into_iter().map(|(v, _)| v).collect(),
+        irregular,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::{create_steane_rounds, STEANE_SUPPORTS};
+    use quizx::graph::VData;
+    use quizx::phase::Phase;
+
+    #[test]
+    fn test_css_checks() {
+        // Two rounds of the Steane code measure each of its three X and Z checks twice
+        let mut g = create_steane_rounds(2);
+        let checks = css_checks(&g).unwrap();
+        assert_eq!((checks.n, checks.x_checks.len(), checks.z_checks.len()), (7, 6, 6));
+        assert!(checks.is_css());
+        assert_eq!(checks.logical_qubits(), 1);
+        let row = |m: &Mat2, i: usize| (0..7).filter(|&q| m.get(i, q)).collect::<Vec<_>>();
+        assert_eq!(row(&checks.hz, 0), STEANE_SUPPORTS[0]);
+        assert_eq!(row(&checks.hx, 0), STEANE_SUPPORTS[0]);
+        assert_eq!(checks.to_string(), "7 qubits, 6 X checks (rank 3), 6 Z checks (rank 3): a [[7, 1]] CSS code");
+
+        // An X check on qubit 0 alone anticommutes with the Z checks through qubit 0, and
+        // a Z spider joined to a Z spider on the data is no check
+        let on_qubit_0 = |g: &Graph, ty: VType| g.vertices().filter(|&v| g.vertex_type(v) == ty && g.qubit(v) == 0.0).min().unwrap();
+        let (x0, z0) = (on_qubit_0(&g, VType::X), on_qubit_0(&g, VType::Z));
+        let extra = |g: &mut Graph| g.add_vertex_with_data(VData { ty: VType::Z, phase: Phase::from_f64(0.0), qubit: -4.0, row: 1.0 });
+        let (check, stray) = (extra(&mut g), extra(&mut g));
+        g.add_edge(check, x0);
+        g.add_edge(stray, z0);
+        let checks = css_checks(&g).unwrap();
+        assert_eq!(checks.irregular, vec![stray]);
+        assert_eq!(checks.anticommuting().len(), 6);
+        assert!(checks.anticommuting().iter().all(|&(x, _)| x == check));
+        assert!(!checks.is_css());
+
+        g.set_phase(stray, Phase::from_f64(0.5));
+        assert!(css_checks(&g).unwrap_err().contains("phase-free"));
+    }
+}
@@ -0,0 +1,118 @@
+//! CSS codes built from classical parity check matrices
+//!
+//! [`diagram_from_css`] turns the check matrices Hx and Hz of a CSS code into the
+//! spacetime diagram of measuring its checks, ready for the detection web pipeline, and
+//! [`hypergraph_product`] makes such matrices out of two classical codes, e.g. the
+//! [`repetition_code`].
+
+use crate::create_graph::create_stabilizer_rounds;
+use crate::linalg::Mat2;
+use quizx::graph::VType;
+use quizx::hash_graph::Graph;
+
+/// `rounds` rounds of measuring the Z checks `hz`, then the X checks `hx`, with inputs
+/// and outputs set
+///
+/// Columns are data qubits and rows checks, like in
+/// [`crate::create_graph::create_steane_rounds`], which is this for the Steane code's
+/// matrices; [`crate::css_checks::css_checks`] reads them back off the diagram. Fails if
+/// the matrices differ in width or some check has no support.
+pub fn diagram_from_css(hx: &Mat2, hz: &Mat2, rounds: usize) -> Result<Graph, String> {
+    if hx.cols() != hz.cols() {
+        return Err(format!("Hx has {} columns and Hz {}; both need one per qubit", hx.cols(), hz.cols()));
+    }
+    let supports = |m: &Mat2, name: &str| -> Result<Vec<Vec<usize>>, String> {
+        (0..m.rows())
+            .map(|i| match m.row_ones(i).collect::<Vec<_>>() {
+                support if support.is_empty() => Err(format!("Row {} of {} is empty", i, name)),
+                support => Ok(support),
+            })
+            .collect()
+    };
+    let (z_supports, x_supports) = (supports(hz, "Hz")?, supports(hx, "Hx")?);
+    let stabilizers: Vec<(VType, &[usize])> = z_supports.iter().map(|s| (VType::Z, &s[..]))
+        .chain(x_supports.iter().map(|s| (VType::X, &s[..])))
+        .collect();
+    Ok(create_stabilizer_rounds(hx.cols(), &stabilizers, rounds))
+}
+
+/// Parity check matrix of the length `n` repetition code, checking each pair of
+/// neighbouring bits
+pub fn repetition_code(n: usize) -> Mat2 {
+    let mut h = Mat2::new(n.saturating_sub(1), n);
+    for i in 0..n.saturating_sub(1) {
+        h.set(i, i, true);
+        h.set(i, i + 1, true);
+    }
+    h
+}
+
+/// Hx and Hz of the hypergraph product of the classical codes `h1` and `h2`
+///
+/// With `h1` m1×n1 and `h2` m2×n2, the code has n1·n2 + m1·m2 qubits and
+/// Hx = (H1 ⊗ I | I ⊗ H2ᵀ), Hz = (I ⊗ H2 | H1ᵀ ⊗ I). The product of two repetition codes
+/// of length d is the distance d surface code.
+pub fn hypergraph_product(h1: &Mat2, h2: &Mat2) -> (Mat2, Mat2) {
+    let (m1, n1, m2, n2) = (h1.rows(), h1.cols(), h2.rows(), h2.cols());
+    let hx = kron(h1, &Mat2::id(n2)).hstack(&kron(&Mat2::id(m1), &transpose(h2)));
+    let hz = kron(&Mat2::id(n1), h2).hstack(&kron(&transpose(h1), &Mat2::id(m2)));
+    (hx, hz)
+}
+
+fn transpose(m: &Mat2) -> Mat2 {
+    let mut t = Mat2::new(m.cols(), m.rows());
+    for i in 0..m.rows() {
+        for j in m.row_ones(i) {
+            t.set(j, i, true);
+        }
+    }
+    t
+}
+
+/// Kronecker product of `a` and `b`
+fn kron(a: &Mat2, b: &Mat2) -> Mat2 {
+    let mut k = Mat2::new(a.rows() * b.rows(), a.cols() * b.cols());
+    for i in 0..a.rows() {
+        for j in a.row_ones(i) {
+            for r in 0..b.rows() {
+                for c in b.row_ones(r) {
+                    k.set(i * b.rows() + r, j * b.cols() + c, true);
+                }
+            }
+        }
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::{create_steane_rounds, STEANE_SUPPORTS};
+    use crate::css_checks::css_checks;
+    use quizx::graph::GraphLike;
+
+    #[test]
+    fn test_diagram_from_css() {
+        // The Steane code's matrices give the Steane diagram
+        let mut steane = Mat2::new(3, 7);
+        for (i, support) in STEANE_SUPPORTS.iter().enumerate() {
+            for &q in support {
+                steane.set(i, q, true);
+            }
+        }
+        let g = diagram_from_css(&steane, &steane, 2).unwrap();
+        let reference = create_steane_rounds(2);
+        assert_eq!((g.num_vertices(), g.num_edges()), (reference.num_vertices(), reference.num_edges()));
+
+        // The product of two length 3 repetition codes is the distance 3 surface code
+        let (hx, hz) = hypergraph_product(&repetition_code(3), &repetition_code(3));
+        assert_eq!((hx.rows(), hz.rows(), hx.cols()), (6, 6, 13));
+        let checks = css_checks(&diagram_from_css(&hx, &hz, 1).unwrap()).unwrap();
+        assert!(checks.is_css());
+        assert_eq!(checks.logical_qubits(), 1);
+        assert_eq!((checks.hx, checks.hz), (hx, hz));
+
+        assert!(diagram_from_css(&steane, &repetition_code(3), 1).unwrap_err().contains("columns"));
+        assert_eq!(diagram_from_css(&Mat2::new(1, 7), &steane, 1).unwrap_err(), "Row 0 of Hx is empty");
+    }
+}
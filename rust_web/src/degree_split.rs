@@ -0,0 +1,92 @@
+//! Splitting high-degree spiders into trees of low-degree ones
+//!
+//! A spider with hundreds of legs, e.g. a long-lived stabiliser parity, makes layouts
+//! unreadable and fills in the web matrices. Spider fusion runs backwards too: a spider
+//! equals a tree of same-colour spiders with zero phase, with its phase on the root and
+//! its legs spread over the tree, so [`split_high_degree`] bounds every degree without
+//! changing what the diagram means.
+
+use quizx::graph::{EType, GraphLike, VData, VType, V};
+use quizx::hash_graph::Graph;
+
+/// Splits every spider of more than `max_degree` legs into a tree of same-colour
+/// spiders of at most `max_degree` legs each, returning how many spiders it added
+///
+/// The original spider keeps its phase and id and becomes the root; its legs are spread
+/// over up to `max_degree` children, each placed between the root and the neighbours it
+/// takes over, and split again if still too large. Legs keep their edge type, the tree
+/// is joined by plain edges. Boundaries are left alone. Fails if `max_degree` is below
+/// three, since a tree of smaller spiders can't hold more legs.
+pub fn split_high_degree(g: &mut Graph, max_degree: usize) -> Result<usize, String> {
+    if max_degree < 3 {
+        return Err(format!("Can't split spiders down to degree {}, the least is 3", max_degree));
+    }
+    let mut pending: Vec<V> = g.vertices()
+        .filter(|&v| matches!(g.vertex_type(v), VType::Z | VType::X) && g.degree(v) > max_degree)
+        .collect();
+    pending.sort();
+    let mut added = 0;
+    while let Some(v) = pending.pop() {
+        let mut legs: Vec<(V, EType)> = g.neighbors(v).map(|n| (n, g.edge_type(v, n))).collect();
+        legs.sort();
+        // Split the legs into `max_degree` runs as even as possible; lone legs stay on the root
+        let (base, extra) = (legs.len() / max_degree, legs.len() % max_degree);
+        let mut rest = &legs[..];
+        for i in 0..max_degree {
+            let (run, tail) = rest.split_at(base + usize::from(i < extra));
+            rest = tail;
+            if run.len() < 2 {
+                continue;
+            }
+            let mean = |coord: fn(&Graph, V) -> f64| {
+                let around = run.iter().map(|&(n, _)| coord(g, n)).sum::<f64>() / run.len() as f64;
+                (coord(g, v) + around) / 2.0
+            };
+            let data = VData { ty: g.vertex_type(v), phase: Default::default(), qubit: mean(|g, n| g.qubit(n)), row: mean(|g, n| g.row(n)) };
+            let child = g.add_vertex_with_data(data);
+            for &(n, ety) in run {
+                g.remove_edge(v, n);
+                g.add_edge_with_type(child, n, ety);
+            }
+            g.add_edge(v, child);
+            added += 1;
+            if g.degree(child) > max_degree {
+                pending.push(child);
+            }
+        }
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use crate::detection_webs::get_detection_webs;
+    use crate::web_space::WebSpace;
+
+    #[test]
+    fn test_split_high_degree() {
+        let g = create_steane_rounds(2);
+        let max = |g: &Graph| g.vertices().map(|v| g.degree(v)).max().unwrap();
+        assert!(max(&g) > 3);
+
+        let mut split = g.clone();
+        let added = split_high_degree(&mut split, 3).unwrap();
+        assert_eq!(split.num_vertices(), g.num_vertices() + added);
+        assert!(max(&split) <= 3);
+        assert_eq!(split_high_degree(&mut split.clone(), 3), Ok(0));
+        assert!(split_high_degree(&mut split, 2).is_err());
+        // The roots keep their phases and the children sit between their neighbours
+        assert!(g.vertices().all(|v| split.phase(v) == g.phase(v)));
+        let (lo, hi) = g.vertices().map(|v| g.row(v)).fold((f64::MAX, f64::MIN), |(lo, hi), r| (lo.min(r), hi.max(r)));
+        assert!(split.vertices().all(|v| (lo..=hi).contains(&split.row(v))));
+
+        // Same space of webs, so the same number of independent webs
+        let mut before = g.clone();
+        let webs = get_detection_webs(&mut before);
+        let mut after = split.clone();
+        let split_webs = get_detection_webs(&mut after);
+        assert_eq!(WebSpace::spanned_by(&after, &split_webs).unwrap().dim(), WebSpace::spanned_by(&before, &webs).unwrap().dim());
+    }
+}
@@ -1,14 +1,26 @@
-use crate::bitwisef2linalg::Mat2;
+use crate::linalg::Mat2;
 use bitvec::prelude::*;
 
 // Constants for F2 values
 use quizx::hash_graph::{Graph, GraphLike};
-use crate::make_rg::make_rg;
-use std::collections::HashMap;
-use quizx::graph::{VType, V};
+use crate::make_rg::{debug_check_rg, make_rg, rg_violations};
+use crate::graph_like::make_graph_like;
+use crate::degree_split::split_high_degree;
+use std::collections::{HashMap, HashSet};
+use quizx::graph::{EType, VType, V};
 use crate::pauliweb::PauliWeb;
 use crate::pauliweb::Pauli;
 use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+use std::path::{Path, PathBuf};
+use crate::matrix_io::write_matrix_market;
+use crate::checkpoint::{gauss_checkpointed, Checkpoints};
+use crate::limits::{matrix_bytes, Limits};
+use crate::logging::{RG, WEBS};
+use crate::memory::format_bytes;
+use serde::Serialize;
 
 fn get_adjacency_matrix(g: &Graph, nodelist: &[V]) -> Mat2 {
     // Takes a quizx graph and returns the adjacency matrix of the graph in the order of nodelist
@@ -28,27 +40,21 @@ fn get_adjacency_matrix(g: &Graph, nodelist: &[V]) -> Mat2 {
 }
 
 fn ordered_nodes(g: &Graph) -> (Vec<usize>, HashMap<usize, usize>) {
-    // Get all vertices and sort them for consistent ordering
-    let mut original: Vec<usize> = g.vertices().collect();
-    original.sort();
-    
-    // First put outputs (nodes that are neither inputs nor outputs in the original graph)
-    let outputs: Vec<usize> = original.iter()
-        .filter(|&&v| !g.inputs().contains(&v) && !g.outputs().contains(&v))
-        .cloned()
+    // The solver pins the first nodes in place of the boundaries, so those are the
+    // neighbours of the inputs and outputs, in their order
+    let mut vertices: Vec<usize> = Vec::new();
+    for n in g.inputs().iter().chain(g.outputs()).flat_map(|&b| g.neighbors(b)) {
+        if g.vertex_type(n) != VType::B && !vertices.contains(&n) {
+            vertices.push(n);
+        }
+    }
+    // Then every other spider, sorted for a consistent order
+    let mut rest: Vec<usize> = g.vertices()
+        .filter(|&v| g.vertex_type(v) != VType::B && !vertices.contains(&v))
         .collect();
-    
-    // Then add the rest (inputs and outputs) that have type != 0 (B type is 0 in Python)
-    let mut vertices = outputs.clone();
-    vertices.extend(
-        original.iter()
-            .filter(|&&v| {
-                let vtype = g.vertex_type(v);
-                vtype != VType::B && !outputs.contains(&v)
-            })
-            .cloned()
-    );
-    
+    rest.sort();
+    vertices.extend(rest);
+
     // Create index map (matrix index -> original node index)
     let index_map: HashMap<usize, usize> = vertices
         .iter()
@@ -56,54 +62,171 @@ fn ordered_nodes(g: &Graph) -> (Vec<usize>, HashMap<usize, usize>) {
         .map(|(i, &v)| (i, v))
         .collect();
     
-    log::debug!("Ordered vertices: {:?}", vertices);
-    log::debug!("Index map: {:?}", index_map);
+    log::debug!(target: WEBS, "Ordered vertices: {:?}", vertices);
+    log::debug!(target: WEBS, "Index map: {:?}", index_map);
     
     (vertices, index_map)
 }
 
-pub fn get_pw(index_map: &HashMap<usize, usize>, v: &BitVec<usize, Lsb0>, g: &Graph) -> PauliWeb {
+/// The edges at each vertex of a graph, as [`GraphLike::edges`] lists them
+///
+/// Built once per graph, so that turning many basis vectors into webs only visits the
+/// legs of the fired nodes rather than every edge for each of them.
+pub struct Incidence(HashMap<V, Vec<(V, V, EType)>>);
+
+impl Incidence {
+    pub fn new(g: &Graph) -> Self {
+        let mut incident: HashMap<V, Vec<(V, V, EType)>> = HashMap::new();
+        for edge in g.edges() {
+            incident.entry(edge.0).or_default().push(edge);
+            incident.entry(edge.1).or_default().push(edge);
+        }
+        Incidence(incident)
+    }
+
+    /// Edges at `v`, empty for an isolated or unknown vertex
+    pub fn edges(&self, v: V) -> &[(V, V, EType)] {
+        self.0.get(&v).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A fixed order of edges, so that matrices with a column or row per edge, e.g. the
+/// incidence matrix or exported fault and check matrices, agree on which index is which
+///
+/// Edges are stored as vertex pairs, smaller first, like the columns of [`WebSpace`].
+///
+/// [`WebSpace`]: crate::web_space::WebSpace
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdgeOrder {
+    edges: Vec<(V, V)>,
+    index: HashMap<(V, V), usize>,
+}
+
+impl EdgeOrder {
+    /// The edges of `g`, sorted
+    pub fn new(g: &Graph) -> Self {
+        let mut edges: Vec<(V, V)> = g.edges().map(|(a, b, _)| (a.min(b), a.max(b))).collect();
+        edges.sort();
+        Self::from_edges(edges)
+    }
+
+    /// `edges` in the given order, each pair in either order; repeats keep their first
+    /// position
+    pub fn from_edges(edges: impl IntoIterator<Item = (V, V)>) -> Self {
+        let mut order = EdgeOrder::default();
+        for (a, b) in edges {
+            let edge = (a.min(b), a.max(b));
+            if !order.index.contains_key(&edge) {
+                order.index.insert(edge, order.edges.len());
+                order.edges.push(edge);
+            }
+        }
+        order
+    }
+
+    pub fn edges(&self) -> &[(V, V)] {
+        &self.edges
+    }
+
+    /// Position of the edge between `a` and `b`, given in either order
+    pub fn index(&self, a: V, b: V) -> Option<usize> {
+        self.index.get(&(a.min(b), a.max(b))).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
+/// The vertex-edge incidence matrix of `g`, with a row per vertex of `node_order` and a
+/// column per edge of `edge_order`
+///
+/// Entry (i, j) is set when vertex i is an end of edge j. Edges the graph doesn't have
+/// give zero columns, and ends outside `node_order` have no row.
+pub fn incidence_matrix(g: &Graph, node_order: &[V], edge_order: &EdgeOrder) -> Mat2 {
+    let row_of: HashMap<V, usize> = node_order.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let mut mat = Mat2::new(node_order.len(), edge_order.len());
+    for (j, &(a, b)) in edge_order.edges().iter().enumerate() {
+        if !g.connected(a, b) {
+            continue;
+        }
+        for end in [a, b] {
+            if let Some(&i) = row_of.get(&end) {
+                mat.set(i, j, true);
+            }
+        }
+    }
+    mat
+}
+
+/// The web of the nodes fired by basis vector `v`, laid out as described on
+/// [`WebProvenance`]
+///
+/// Takes any bit slice, e.g. a row of the nullspace [`Mat2`], so vectors need not be
+/// copied, and only visits the set bits. Use [`get_pw_with_incidence`] for many vectors
+/// on the same graph.
+pub fn get_pw(index_map: &HashMap<usize, usize>, v: &BitSlice<usize, Lsb0>, g: &Graph) -> PauliWeb {
+    get_pw_with_incidence(index_map, v, g, &Incidence::new(g))
+}
+
+/// Same as [`get_pw`], with the [`Incidence`] of `g` built beforehand
+pub fn get_pw_with_incidence(
+    index_map: &HashMap<usize, usize>,
+    v: &BitSlice<usize, Lsb0>,
+    g: &Graph,
+    incidence: &Incidence,
+) -> PauliWeb {
     let n_outs = g.inputs().len() + g.outputs().len();
     let mut red_edges = BTreeSet::new();
     let mut green_edges = BTreeSet::new();
     let mut pw = PauliWeb::new();
-    log::debug!("v: {:#?}", v);
+    log::debug!(target: WEBS, "v: {}", v);
     // Process each non-zero index in the bitvector
-    for (index, is_set) in v.iter().enumerate() {
-        log::debug!("Bit {}: {}", index, is_set);
-        if *is_set {
-            let node = *index_map.get(&(index - n_outs)).expect("Node index not found in index map.");
-            let node_color = g.vertex_type(node);
-            log::debug!("Node {}", node);
-            log::debug!("Node color {:#?}", node_color);
-            // Find all edges connected to this node
-            for edge in g.edges() {
-                if node == edge.0 || node == edge.1 {
-                    if node_color == VType::Z {
-                        green_edges.insert(edge);
-                    } else if node_color == VType::X {
-                        red_edges.insert(edge);
-                    }
-                    else {
-                        unreachable!("Unexpected Node color: {:?}", node_color);
-                    }
-                }
-            }
-        }
+    for index in v.iter_ones() {
+        let node = *index_map.get(&(index - n_outs)).expect("Node index not found in index map.");
+        let node_color = g.vertex_type(node);
+        log::debug!(target: WEBS, "Node {}", node);
+        log::debug!(target: WEBS, "Node color {:#?}", node_color);
+        // Its legs get the node's colour
+        let edges = match node_color {
+            VType::Z => &mut green_edges,
+            VType::X => &mut red_edges,
+            _ => unreachable!("Unexpected Node color: {:?}", node_color),
+        };
+        edges.extend(incidence.edges(node).iter().copied());
     }
-    // Add edges to PauliWeb
-    for e in red_edges {
-        pw.set_edge(e.0, e.1, Pauli::Z);
+    // Add edges to PauliWeb; an edge claimed by both colours carries their product, Y
+    for &e in &red_edges {
+        let pauli = if green_edges.contains(&e) { Pauli::Y } else { Pauli::Z };
+        pw.set_edge(e.0, e.1, pauli);
     }
-    for e in green_edges {
+    for e in green_edges.difference(&red_edges) {
         pw.set_edge(e.0, e.1, Pauli::X);
     }
     
     pw
 }
 
+/// Matrices wider than this are summarised rather than printed row by row
+const DRAW_MAT_MAX_COLS: usize = 100;
+
 fn draw_mat(name: &str, mat: &Mat2) {
-    log::debug!("Matrix {} ({}x{}):", name, mat.rows(), mat.cols());
+    if mat.cols() > DRAW_MAT_MAX_COLS {
+        if log::log_enabled!(target: WEBS, log::Level::Debug) {
+            let ones: usize = (0..mat.rows()).map(|r| mat.row_ones(r).count()).sum();
+            log::debug!(
+                target: WEBS,
+                "Matrix {} ({}x{}, {} ones) is too wide to print; see spy_plot::draw_mat_png",
+                name, mat.rows(), mat.cols(), ones
+            );
+        }
+        return;
+    }
+    log::debug!(target: WEBS, "Matrix {} ({}x{}):", name, mat.rows(), mat.cols());
     for i in 0..mat.rows() {
         let row: String = (0..mat.cols())
             .map(|j| if mat.get(i, j) { '1' } else { '0' })
@@ -112,39 +235,778 @@ fn draw_mat(name: &str, mat: &Mat2) {
             .map(|chunk| chunk.iter().collect::<String>())
             .collect::<Vec<String>>()
             .join(" ");  // Add space between chunks
-        log::debug!("[{}]", row);
+        log::debug!(target: WEBS, "[{}]", row);
+    }
+}
+/// How the boundary vertices of a diagram are split into inputs and outputs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum BoundaryRoles {
+    /// Boundaries on the minimal row are inputs, those on the maximal row are outputs
+    #[default]
+    ByRow,
+    /// Use the given inputs and outputs as-is
+    Explicit { inputs: Vec<V>, outputs: Vec<V> },
+}
+
+/// Splits the boundary vertices of a graph into (inputs, outputs) by their row coordinate.
+/// Boundaries on the minimal row are inputs, boundaries on the maximal row are outputs,
+/// both sorted by qubit. If all boundaries share one row they are all treated as outputs
+/// (a state). Any boundary in between is an error, since its role can't be guessed.
+pub fn classify_boundaries(g: &Graph) -> Result<(Vec<V>, Vec<V>), String> {
+    let mut boundaries: Vec<V> = g.vertices()
+        .filter(|&v| g.vertex_type(v) == VType::B)
+        .collect();
+    boundaries.sort_by(|&a, &b| g.qubit(a).total_cmp(&g.qubit(b)).then(a.cmp(&b)));
+
+    if boundaries.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
     }
+
+    let min_row = boundaries.iter().map(|&v| g.row(v)).fold(f64::INFINITY, f64::min);
+    let max_row = boundaries.iter().map(|&v| g.row(v)).fold(f64::NEG_INFINITY, f64::max);
+    if min_row == max_row {
+        return Ok((Vec::new(), boundaries));
+    }
+
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for v in boundaries {
+        let row = g.row(v);
+        if row == min_row {
+            inputs.push(v);
+        } else if row == max_row {
+            outputs.push(v);
+        } else {
+            return Err(format!(
+                "Boundary vertex {} at row {} is neither on the input row {} nor the output row {}",
+                v, row, min_row, max_row
+            ));
+        }
+    }
+    Ok((inputs, outputs))
 }
+
 /// Returns all detection webs of a quizx graph
 /// Will inplace convert the graph to rg form
-/// 
-/// TODO: perhaps handle the input/output stuff, currently we break it and just assume thats not a set
-/// property
+///
+/// Inputs and outputs are guessed from the boundary rows (see [`classify_boundaries`]).
+/// If that fails, all boundaries are treated as outputs. Use
+/// [`get_detection_webs_with_boundaries`] to control this explicitly.
 pub fn get_detection_webs(g: &mut Graph) -> Vec<PauliWeb> {
-    // First convert to RG form
-    make_rg(g);
+    get_detection_webs_ordered(g, VertexOrdering::Natural)
+}
 
-    // Lets make the whole outputs thing native:
-    let mut outputs = Vec::new();
-    for v in g.vertices() {
-        if g.vertex_type(v) == VType::B {
-            outputs.push(v);
+/// Same as [`get_detection_webs`], eliminating the nodes in the given `ordering`
+///
+/// No size [`Limits`] apply, since there is no way to report them. Panics if the graph
+/// has vertices other than spiders and boundaries, e.g. Z-boxes; use
+/// [`get_detection_webs_lenient`] to get an error instead.
+pub fn get_detection_webs_ordered(g: &mut Graph, ordering: VertexOrdering) -> Vec<PauliWeb> {
+    let options = WebOptions { ordering, limits: Limits::unlimited(), ..WebOptions::default() };
+    get_detection_webs_lenient(g, &options).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as [`get_detection_webs_with_options`], but if [`BoundaryRoles::ByRow`] can't
+/// classify the boundaries, all of them are treated as outputs like in [`get_detection_webs`]
+pub fn get_detection_webs_lenient(g: &mut Graph, options: &WebOptions) -> Result<Vec<PauliWeb>, String> {
+    let options = lenient_options(g, options);
+    get_detection_webs_with_options(g, &options)
+}
+
+/// Same as [`get_detection_webs_lenient`], with the [`WebProvenance`] of each web
+pub fn get_detection_webs_lenient_traced(
+    g: &mut Graph,
+    options: &WebOptions,
+) -> Result<Vec<(PauliWeb, WebProvenance)>, String> {
+    let options = lenient_options(g, options);
+    get_detection_webs_traced(g, &options)
+}
+
+/// `options` with [`BoundaryRoles::ByRow`] resolved, falling back to all boundaries as outputs
+fn lenient_options(g: &Graph, options: &WebOptions) -> WebOptions {
+    let roles = match &options.roles {
+        BoundaryRoles::ByRow => match classify_boundaries(g) {
+            Ok((inputs, outputs)) => BoundaryRoles::Explicit { inputs, outputs },
+            Err(e) => {
+                log::warn!(target: WEBS, "{}; treating all boundaries as outputs", e);
+                let outputs: Vec<V> = g.vertices()
+                    .filter(|&v| g.vertex_type(v) == VType::B)
+                    .collect();
+                BoundaryRoles::Explicit { inputs: Vec::new(), outputs }
+            }
+        },
+        explicit => explicit.clone(),
+    };
+    WebOptions { roles, ..options.clone() }
+}
+
+/// How the space of detection webs is computed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WebAlgorithm {
+    /// Nullspace of the adjacency matrix augmented with boundary and no-output blocks
+    #[default]
+    Nullspace,
+    /// Drops the boundary-constrained variables first, then finds the kernel of the
+    /// remaining adjacency columns by eliminating `[Aᵀ | I]`
+    BoundaryElimination,
+    /// Runs both methods and fails unless they span the same space; returns the
+    /// [`WebAlgorithm::Nullspace`] webs
+    CrossCheck,
+}
+
+/// Order of the node columns during elimination
+///
+/// Only the nodes that may fire are reordered; the webs span the same space under
+/// every ordering, but fill-in and hence elimination time can differ a lot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VertexOrdering {
+    /// Sorted by vertex id
+    #[default]
+    Natural,
+    /// Sorted by row coordinate, then qubit, i.e. roughly in time order
+    ByRow,
+    /// Greedy minimum degree: repeatedly takes the node with the fewest neighbours,
+    /// counting the fill its elimination adds between those neighbours
+    MinimumDegree,
+}
+
+impl fmt::Display for VertexOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VertexOrdering::Natural => "natural",
+            VertexOrdering::ByRow => "by-row",
+            VertexOrdering::MinimumDegree => "min-degree",
+        })
+    }
+}
+
+impl FromStr for VertexOrdering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "natural" => Ok(VertexOrdering::Natural),
+            "by-row" => Ok(VertexOrdering::ByRow),
+            "min-degree" => Ok(VertexOrdering::MinimumDegree),
+            _ => Err(format!("Unknown vertex ordering '{}', expected natural, by-row or min-degree", s)),
+        }
+    }
+}
+
+/// Normal form a diagram is brought into before its webs are solved for
+///
+/// Both forms give webs of the same diagram, but on different vertices: rg form adds a
+/// spider on every edge between same-colour spiders, graph-like form fuses spiders away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum NormalForm {
+    /// Red-green form from [`make_rg`], solved through the adjacency matrix
+    #[default]
+    RedGreen,
+    /// Graph-like form from [`make_graph_like`], solved through the biadjacency matrix of
+    /// [`get_detection_webs_graph_like`] with the spiders next to a boundary kept from
+    /// firing. Ignores the algorithm and ordering.
+    GraphLike,
+    /// The diagram as it is, which must be in rg form already
+    None,
+}
+
+impl NormalForm {
+    /// Converts `g` in place to this form; [`NormalForm::None`] only checks that `g` is
+    /// in rg form
+    pub fn apply(self, g: &mut Graph) -> Result<(), String> {
+        match self {
+            NormalForm::RedGreen => {
+                let before = (g.num_vertices(), g.num_edges());
+                make_rg(g)?;
+                debug_check_rg(g, "make_rg", false);
+                log::debug!(
+                    target: RG,
+                    "rg form has {} vertices and {} edges, up from {} and {}",
+                    g.num_vertices(), g.num_edges(), before.0, before.1
+                );
+            }
+            NormalForm::GraphLike => {
+                let fused = make_graph_like(g)?;
+                log::debug!(target: RG, "Fused {} spiders converting to graph-like form", fused);
+            }
+            NormalForm::None => {
+                let violations = rg_violations(g, false);
+                if !violations.is_empty() {
+                    return Err(format!("The diagram is not in rg form: {}", violations.join(", ")));
+                }
+            }
         }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NormalForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NormalForm::RedGreen => "rg",
+            NormalForm::GraphLike => "graph-like",
+            NormalForm::None => "none",
+        })
     }
+}
+
+impl FromStr for NormalForm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "rg" => Ok(NormalForm::RedGreen),
+            "graph-like" => Ok(NormalForm::GraphLike),
+            "none" => Ok(NormalForm::None),
+            _ => Err(format!("Unknown normal form '{}', expected rg, graph-like or none", s)),
+        }
+    }
+}
+
+/// Options for [`get_detection_webs_with_options`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebOptions {
+    pub roles: BoundaryRoles,
+    pub algorithm: WebAlgorithm,
+    pub ordering: VertexOrdering,
+    /// Write the main intermediate matrices to Matrix Market files in this directory
+    /// instead of the debug log, see [`MatrixSink`]
+    pub dump_matrices: Option<PathBuf>,
+    /// Checked against the matrix sizes before any of them is allocated
+    pub limits: Limits,
+    /// Checkpoint the elimination, resuming from an earlier checkpoint of the same matrix
+    pub checkpoints: Option<Checkpoints>,
+    /// Split spiders of more legs before the rg conversion, see [`split_high_degree`]
+    pub max_degree: Option<usize>,
+    pub normal_form: NormalForm,
+}
+
+/// Where the main intermediate matrices of the web computation go
+///
+/// Without a directory they are printed to the debug log by `draw_mat`; with one each
+/// is written to `<file>.mtx` there (see [`crate::matrix_io`]), which stays usable for
+/// matrices far too wide to read in a log.
+#[derive(Debug, Clone, Copy)]
+pub struct MatrixSink<'a> {
+    dir: Option<&'a Path>,
+}
+
+impl<'a> MatrixSink<'a> {
+    /// Creates `dir` if there is one
+    pub fn new(dir: Option<&'a Path>) -> Result<Self, String> {
+        if let Some(dir) = dir {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create matrix dump directory {}: {}", dir.display(), e))?;
+        }
+        Ok(MatrixSink { dir })
+    }
+
+    /// Emits `mat`, logged as `name` or written to `<file>.mtx`
+    fn emit(&self, name: &str, file: &str, mat: &Mat2) -> Result<(), String> {
+        match self.dir {
+            Some(dir) => {
+                let path = dir.join(format!("{}.mtx", file));
+                log::debug!(target: WEBS, "Writing matrix {} ({}x{}) to {}", name, mat.rows(), mat.cols(), path.display());
+                write_matrix_market(mat, path)
+            }
+            None => {
+                draw_mat(name, mat);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Basis vectors as the rows of a matrix
+fn basis_matrix(basis: &[BitVec<usize, Lsb0>], cols: usize) -> Mat2 {
+    let mut mat = Mat2::zeros(basis.len(), cols);
+    for (r, v) in basis.iter().enumerate() {
+        for c in v.iter_ones() {
+            mat.set(r, c, true);
+        }
+    }
+    mat
+}
+
+/// Reorders `nodes` according to `ordering`
+fn order_nodes(g: &Graph, nodes: &mut [V], ordering: VertexOrdering) {
+    match ordering {
+        VertexOrdering::Natural => nodes.sort(),
+        VertexOrdering::ByRow => nodes.sort_by(|&a, &b| {
+            g.row(a).total_cmp(&g.row(b))
+                .then(g.qubit(a).total_cmp(&g.qubit(b)))
+                .then(a.cmp(&b))
+        }),
+        VertexOrdering::MinimumDegree => {
+            let mut adjacency: HashMap<V, BTreeSet<V>> = nodes.iter()
+                .map(|&v| (v, g.neighbors(v).filter(|n| nodes.contains(n)).collect()))
+                .collect();
+            let mut remaining: BTreeSet<V> = nodes.iter().copied().collect();
+            for slot in nodes.iter_mut() {
+                let v = *remaining.iter()
+                    .min_by_key(|v| adjacency[v].len())
+                    .expect("one node left per slot");
+                remaining.remove(&v);
+                let neighbours = adjacency.remove(&v).unwrap_or_default();
+                for &n in &neighbours {
+                    let adj = adjacency.get_mut(&n).expect("neighbours are remaining");
+                    adj.remove(&v);
+                    adj.extend(neighbours.iter().filter(|&&m| m != n));
+                }
+                *slot = v;
+            }
+        }
+    }
+}
+
+/// Returns all detection webs of a quizx graph, with inputs and outputs set according to `roles`
+/// Will inplace convert the graph to rg form
+pub fn get_detection_webs_with_boundaries(g: &mut Graph, roles: BoundaryRoles) -> Result<Vec<PauliWeb>, String> {
+    get_detection_webs_with_algorithm(g, roles, WebAlgorithm::Nullspace)
+}
+
+/// Same as [`get_detection_webs_with_boundaries`], computing the webs with `algorithm`
+pub fn get_detection_webs_with_algorithm(
+    g: &mut Graph,
+    roles: BoundaryRoles,
+    algorithm: WebAlgorithm,
+) -> Result<Vec<PauliWeb>, String> {
+    get_detection_webs_with_options(g, &WebOptions { roles, algorithm, ..WebOptions::default() })
+}
+
+/// Returns all detection webs of a quizx graph, as configured by `options`
+/// Will inplace convert the graph to rg form
+pub fn get_detection_webs_with_options(g: &mut Graph, options: &WebOptions) -> Result<Vec<PauliWeb>, String> {
+    Ok(get_detection_webs_traced(g, options)?.into_iter().map(|(web, _)| web).collect())
+}
+
+/// Where a web came from in the linear algebra, to trace discrepancies back through it
+///
+/// Vector positions are those of the basis vectors: `outs` boundary bits, then one bit
+/// per node in elimination order, the node at position `outs + i` owning row and
+/// column `i` of the adjacency matrix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebProvenance {
+    /// Index of the web's vector in the basis found by the solver
+    pub basis_index: usize,
+    /// Vector position of the free variable the vector was built from, the highest
+    /// position it sets; `None` for [`WebAlgorithm::BoundaryElimination`], whose kernel
+    /// vectors come from row operations instead
+    pub free_column: Option<usize>,
+    /// Each set position of the vector with its adjacency matrix row and the vertex it fires
+    pub fired: Vec<FiredNode>,
+}
+
+/// One node fired by a basis vector, see [`WebProvenance`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FiredNode {
+    pub position: usize,
+    pub row: usize,
+    pub vertex: V,
+}
+
+/// Same as [`get_detection_webs_with_options`], with the [`WebProvenance`] of each web
+pub fn get_detection_webs_traced(g: &mut Graph, options: &WebOptions) -> Result<Vec<(PauliWeb, WebProvenance)>, String> {
+    if options.normal_form == NormalForm::GraphLike {
+        normalise(g, options)?;
+        return graph_like_traced(g, &boundary_neighbours(g), &options.limits);
+    }
+    let (outs, nodelist, index_map) = prepare_ordered(g, options)?;
+
+    // Get adjacency matrix in the specified node order
+    let sink = MatrixSink::new(options.dump_matrices.as_deref())?;
+    let big_n = get_adjacency_matrix(g, &nodelist);
+    sink.emit("N (adjacency)", "adjacency", &big_n)?;
+
+    let elimination_start = Instant::now();
+    let checkpoints = options.checkpoints.as_ref();
+    let basis = match options.algorithm {
+        WebAlgorithm::Nullspace => nullspace_basis(&big_n, outs, &sink, checkpoints)?,
+        WebAlgorithm::BoundaryElimination => boundary_elimination_basis(&big_n, outs, &sink, checkpoints)?,
+        WebAlgorithm::CrossCheck => {
+            let nullspace = nullspace_basis(&big_n, outs, &sink, checkpoints)?;
+            let eliminated = boundary_elimination_basis(&big_n, outs, &sink, checkpoints)?;
+            if !same_span(&nullspace, &eliminated) {
+                return Err(format!(
+                    "Web algorithms disagree: nullspace found {} webs, boundary elimination {}, and they span different spaces",
+                    nullspace.len(), eliminated.len()
+                ));
+            }
+            nullspace
+        }
+    };
+    log::info!(
+        target: WEBS,
+        "{:?} elimination with {} ordering took: {:?}",
+        options.algorithm, options.ordering, elimination_start.elapsed()
+    );
+    if sink.dir.is_some() {
+        sink.emit("nullspace", "nullspace", &basis_matrix(&basis, outs + big_n.cols()))?;
+    }
+
+    // Convert each basis vector to a PauliWeb
+    debug_check_rg(g, "elimination", false);
+    let incidence = Incidence::new(g);
+    Ok(basis.iter().enumerate().map(|(basis_index, vec)| {
+        let provenance = WebProvenance {
+            basis_index,
+            free_column: match options.algorithm {
+                WebAlgorithm::BoundaryElimination => None,
+                WebAlgorithm::Nullspace | WebAlgorithm::CrossCheck => vec.last_one(),
+            },
+            fired: vec.iter_ones()
+                .filter(|&position| position >= outs)
+                .map(|position| FiredNode { position, row: position - outs, vertex: index_map[&(position - outs)] })
+                .collect(),
+        };
+        (get_pw_with_incidence(&index_map, vec, g, &incidence), provenance)
+    }).collect())
+}
+
+/// Detection webs of a graph, built one at a time, see [`detection_web_iter`]
+pub struct WebIter<'a> {
+    g: &'a Graph,
+    index_map: HashMap<usize, usize>,
+    incidence: Incidence,
+    /// The augmented system of [`nullspace_basis`] in reduced form
+    reduced: Mat2,
+    pivot_cols: Vec<usize>,
+    /// Free columns not turned into webs yet, in basis order
+    free_columns: std::vec::IntoIter<usize>,
+}
+
+impl Iterator for WebIter<'_> {
+    type Item = PauliWeb;
+
+    fn next(&mut self) -> Option<PauliWeb> {
+        let free_column = self.free_columns.next()?;
+        let vec = self.reduced.reduced_nullspace_vector(&self.pivot_cols, free_column).into_rows().swap_remove(0);
+        Some(get_pw_with_incidence(&self.index_map, &vec, self.g, &self.incidence))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.free_columns.size_hint()
+    }
+}
+
+impl ExactSizeIterator for WebIter<'_> {}
+
+/// The webs of [`get_detection_webs`], in the same order, built as they are asked for
+/// Will inplace convert the graph to rg form
+///
+/// The elimination still runs up front, but only the basis vectors taken from the
+/// iterator become [`PauliWeb`]s, which saves time and memory when a caller stops early,
+/// e.g. after the first few webs of a large diagram. Panics like [`get_detection_webs`].
+pub fn detection_web_iter(g: &mut Graph) -> WebIter<'_> {
+    let options = WebOptions { limits: Limits::unlimited(), ..lenient_options(g, &WebOptions::default()) };
+    detection_web_iter_with_options(g, &options).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as [`detection_web_iter`], as configured by `options`
+///
+/// Only [`WebAlgorithm::Nullspace`] on rg form builds webs lazily, so other algorithms
+/// and normal forms are an error; checkpoints and matrix dumps apply as usual.
+pub fn detection_web_iter_with_options<'a>(g: &'a mut Graph, options: &WebOptions) -> Result<WebIter<'a>, String> {
+    if options.algorithm != WebAlgorithm::Nullspace {
+        return Err(format!("Webs are only built lazily with the nullspace algorithm, not {:?}", options.algorithm));
+    }
+    let (outs, nodelist, index_map) = prepare_ordered(g, options)?;
+    let sink = MatrixSink::new(options.dump_matrices.as_deref())?;
+    let big_n = get_adjacency_matrix(g, &nodelist);
+    sink.emit("N (adjacency)", "adjacency", &big_n)?;
+
+    let elimination_start = Instant::now();
+    let mut reduced = web_system(&big_n, outs, &sink)?;
+    let pivot_cols = match options.checkpoints.as_ref() {
+        Some(checkpoints) => gauss_checkpointed(&mut reduced, true, None, checkpoints, "md_no_output")?.1,
+        None => {
+            let mut pivot_cols = Vec::new();
+            reduced.gauss(true, None, None, 0, &mut pivot_cols);
+            pivot_cols
+        }
+    };
+    log::info!(target: WEBS, "Lazy elimination with {} ordering took: {:?}", options.ordering, elimination_start.elapsed());
+    let free_columns = reduced.free_columns(&pivot_cols);
+    log::debug!(target: WEBS, "Number of basis vectors in nullspace: {}", free_columns.len());
+
+    debug_check_rg(g, "elimination", false);
+    let g = &*g;
+    Ok(WebIter {
+        g,
+        index_map,
+        incidence: Incidence::new(g),
+        reduced,
+        pivot_cols,
+        free_columns: free_columns.into_iter(),
+    })
+}
+
+/// Number of boundaries, and the nodes in natural order with their index map
+type Prepared = (usize, Vec<V>, HashMap<usize, usize>);
+
+/// Same as [`prepare`], with the nodes in the elimination order of `options`
+fn prepare_ordered(g: &mut Graph, options: &WebOptions) -> Result<Prepared, String> {
+    let (outs, mut nodelist, mut index_map) = prepare(g, options)?;
+    // The first `outs` nodes are pinned to the boundary bits, so only the rest move
+    if options.ordering != VertexOrdering::Natural && nodelist.len() > outs {
+        order_nodes(g, &mut nodelist[outs..], options.ordering);
+        index_map = nodelist.iter().copied().enumerate().collect();
+    }
+    log::debug!(target: WEBS, "Ordered nodes ({} ordering): {:?}", options.ordering, nodelist);
+    log::debug!(target: WEBS, "outs: {}", outs);
+    Ok((outs, nodelist, index_map))
+}
+
+/// Sets the boundaries of `g` and brings it into the normal form of `options`
+fn normalise(g: &mut Graph, options: &WebOptions) -> Result<(), String> {
+    let (inputs, outputs) = match options.roles.clone() {
+        BoundaryRoles::ByRow => classify_boundaries(g)?,
+        BoundaryRoles::Explicit { inputs, outputs } => (inputs, outputs),
+    };
+    g.set_inputs(inputs);
     g.set_outputs(outputs);
-    
+    if let Some(max_degree) = options.max_degree {
+        let added = split_high_degree(g, max_degree)?;
+        if added > 0 {
+            log::info!(target: RG, "Split spiders of more than {} legs, adding {} spiders", max_degree, added);
+        }
+        debug_check_rg(g, "splitting high-degree spiders", true);
+    }
+    options.normal_form.apply(g)
+}
+
+/// Sets the boundaries of `g`, converts it to rg form and checks it can be solved
+fn prepare(g: &mut Graph, options: &WebOptions) -> Result<Prepared, String> {
+    if options.normal_form == NormalForm::GraphLike {
+        return Err("The adjacency matrix solver needs rg form, not graph-like form".to_string());
+    }
+    normalise(g, options)?;
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    if let Some(v) = vertices.into_iter().find(|&v| !matches!(g.vertex_type(v), VType::B | VType::Z | VType::X)) {
+        return Err(format!("Vertex {} is a {:?}; detection webs need Z and X spiders", v, g.vertex_type(v)));
+    }
+
     // Get number of inputs + outputs
     let outs = g.inputs().len() + g.outputs().len();
-    
-    // Get ordered nodes and index map
+    // One pinned node stands in for each boundary, so none may share or lack one
+    let pinned = boundary_neighbours(g);
+    if pinned.len() != outs || pinned.iter().any(|&v| g.vertex_type(v) == VType::B) {
+        return Err(format!(
+            "The {} inputs and outputs have {} neighbouring spiders; each needs one of its own",
+            outs, pinned.iter().filter(|&&v| g.vertex_type(v) != VType::B).count()
+        ));
+    }
     let (nodelist, index_map) = ordered_nodes(g);
-    log::debug!("Ordered nodes: {:?}", nodelist);
-    log::debug!("outs: {}", outs);
-    
-    // Get adjacency matrix in the specified node order
-    let big_n = get_adjacency_matrix(g, &nodelist);
-    draw_mat("N (adjacency)", &big_n);
-    
+
+    // The largest matrix is the adjacency matrix with a column added per boundary and
+    // two rows per boundary stacked below
+    options.limits.check_matrix(nodelist.len() + 2 * outs, nodelist.len() + outs)?;
+    Ok((outs, nodelist, index_map))
+}
+
+/// The nodes the solver keeps from firing in place of the boundaries, the first of the
+/// elimination order
+///
+/// Sets the boundaries and converts `g` to rg form like [`get_detection_webs_lenient`],
+/// without checking the size limits.
+pub(crate) fn pinned_nodes(g: &mut Graph, options: &WebOptions) -> Result<Vec<V>, String> {
+    let options = WebOptions { limits: Limits::unlimited(), ..lenient_options(g, options) };
+    let (outs, nodelist, _) = prepare(g, &options)?;
+    Ok(nodelist[..outs.min(nodelist.len())].to_vec())
+}
+
+/// One matrix built by the web computation, see [`WebPlan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedMatrix {
+    pub name: &'static str,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl PlannedMatrix {
+    /// Size of the bit-packed matrix
+    pub fn bytes(&self) -> u64 {
+        matrix_bytes(self.rows, self.cols)
+    }
+}
+
+/// What [`get_detection_webs_lenient_traced`] would do with a graph, worked out without
+/// building any matrix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebPlan {
+    pub inputs: usize,
+    pub outputs: usize,
+    /// Vertices after the conversion to the normal form
+    pub vertices: usize,
+    pub normal_form: NormalForm,
+    pub algorithm: WebAlgorithm,
+    pub ordering: VertexOrdering,
+    /// The main matrices in the order they are built
+    pub matrices: Vec<PlannedMatrix>,
+}
+
+impl WebPlan {
+    /// Rough peak memory of the matrices, assuming none is freed before the end
+    pub fn estimated_bytes(&self) -> u64 {
+        self.matrices.iter().map(PlannedMatrix::bytes).sum()
+    }
+}
+
+impl fmt::Display for WebPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:?} elimination with {} ordering on {} vertices in {} form ({} inputs, {} outputs)",
+            self.algorithm, self.ordering, self.vertices, self.normal_form, self.inputs, self.outputs
+        )?;
+        for matrix in &self.matrices {
+            writeln!(f, "  {:<16} {}×{} ({})", matrix.name, matrix.rows, matrix.cols, format_bytes(matrix.bytes()))?;
+        }
+        write!(f, "Estimated memory: {}", format_bytes(self.estimated_bytes()))
+    }
+}
+
+/// Plans the web computation of [`get_detection_webs_lenient_traced`] on a copy of `g`
+///
+/// Fails like the computation would on unclassifiable vertices or matrices over the
+/// limits, but only converts the copy to its normal form, so it is cheap even for graphs whose
+/// elimination takes hours.
+pub fn plan_detection_webs(g: &Graph, options: &WebOptions) -> Result<WebPlan, String> {
+    let mut g = g.clone();
+    let options = lenient_options(&g, options);
+    let matrix = |name, rows, cols| PlannedMatrix { name, rows, cols };
+    let plan = |g: &Graph, matrices| WebPlan {
+        inputs: g.inputs().len(),
+        outputs: g.outputs().len(),
+        vertices: g.num_vertices(),
+        normal_form: options.normal_form,
+        algorithm: options.algorithm,
+        ordering: options.ordering,
+        matrices,
+    };
+    if options.normal_form == NormalForm::GraphLike {
+        normalise(&mut g, &options)?;
+        let pinned = boundary_neighbours(&g);
+        let spiders: Vec<V> = g.vertices().filter(|&v| g.vertex_type(v) != VType::B).collect();
+        let firing = spiders.iter().filter(|&&v| g.phase(v).to_rational().is_integer() && !pinned.contains(&v)).count();
+        return Ok(plan(&g, vec![matrix("biadjacency", spiders.len(), firing)]));
+    }
+    let (outs, nodelist, _) = prepare(&mut g, &options)?;
+    let n = nodelist.len();
+    let mut matrices = vec![matrix("adjacency", n, n)];
+    if options.algorithm != WebAlgorithm::BoundaryElimination {
+        matrices.push(matrix("md", n, n + outs));
+        matrices.push(matrix("md_no_output", n + 2 * outs, n + outs));
+        // Gaussian elimination works on a copy
+        matrices.push(matrix("eliminated", n + 2 * outs, n + outs));
+    }
+    if options.algorithm != WebAlgorithm::Nullspace {
+        let k = n.saturating_sub(outs);
+        matrices.push(matrix("a_transpose", k, n));
+        matrices.push(matrix("row_operations", k, k));
+    }
+    Ok(plan(&g, matrices))
+}
+
+/// Spiders next to an input or output, which webs in graph-like form can't fire
+fn boundary_neighbours(g: &Graph) -> HashSet<V> {
+    g.inputs().iter().chain(g.outputs()).flat_map(|&b| g.neighbors(b)).collect()
+}
+
+/// Returns the detection webs of a graph-like diagram, without converting it to rg form
+///
+/// All spiders must be Z spiders joined by Hadamard edges; boundaries may hang off
+/// either edge type. A web fires a set of spiders, each putting X on all its legs,
+/// which a Hadamard edge turns into Z at the far end. It is valid when every spider
+/// sees an even number of fired neighbours, so the webs are the kernel of the
+/// biadjacency matrix between spiders (rows, one parity check each) and the spiders
+/// that may fire (columns). Spiders with a non-Pauli phase never fire, and neither do
+/// spiders next to a boundary, so that webs are trivial on the inputs and outputs.
+///
+/// Paulis on Hadamard edges are stored as seen from the smaller endpoint, see [`PauliWeb`].
+pub fn get_detection_webs_graph_like(g: &Graph) -> Result<Vec<PauliWeb>, String> {
+    // Every boundary counts, whether or not `g` lists it as an input or output
+    let pinned: HashSet<V> = g.vertices()
+        .filter(|&b| g.vertex_type(b) == VType::B)
+        .flat_map(|b| g.neighbors(b))
+        .collect();
+    let webs = graph_like_traced(g, &pinned, &Limits::unlimited())?;
+    Ok(webs.into_iter().map(|(web, _)| web).collect())
+}
+
+/// Webs of graph-like `g` in which no spider of `pinned` fires, with their provenance
+///
+/// Positions in the provenance are the columns of the biadjacency matrix, the spiders
+/// that may fire in order of id.
+fn graph_like_traced(g: &Graph, pinned: &HashSet<V>, limits: &Limits) -> Result<Vec<(PauliWeb, WebProvenance)>, String> {
+    let mut spiders: Vec<V> = g.vertices().filter(|&v| g.vertex_type(v) != VType::B).collect();
+    spiders.sort();
+    for &v in &spiders {
+        if g.vertex_type(v) != VType::Z {
+            return Err(format!("Vertex {} is a {:?} spider, graph-like diagrams only have Z spiders", v, g.vertex_type(v)));
+        }
+        for n in g.neighbors(v) {
+            if g.vertex_type(n) != VType::B && g.edge_type(v, n) != EType::H {
+                return Err(format!("Edge ({}, {}) between spiders is not a Hadamard edge", v.min(n), v.max(n)));
+            }
+        }
+    }
+
+    let firing: Vec<V> = spiders.iter().copied()
+        .filter(|&v| g.phase(v).to_rational().is_integer() && !pinned.contains(&v))
+        .collect();
+    limits.check_matrix(spiders.len(), firing.len())?;
+    let mut biadjacency = Mat2::zeros(spiders.len(), firing.len());
+    for (r, &v) in spiders.iter().enumerate() {
+        for (c, &u) in firing.iter().enumerate() {
+            biadjacency.set(r, c, g.connected(v, u));
+        }
+    }
+    draw_mat("biadjacency", &biadjacency);
+
+    Ok(biadjacency.nullspace(false).iter().enumerate().map(|(basis_index, kernel_vector)| {
+        let fired: Vec<FiredNode> = firing.iter().enumerate()
+            .filter(|&(c, _)| kernel_vector.get(0, c))
+            .map(|(c, &vertex)| FiredNode { position: c, row: c, vertex })
+            .collect();
+        let provenance = WebProvenance { basis_index, free_column: fired.last().map(|f| f.position), fired };
+        (fire_spiders(g, provenance.fired.iter().map(|f| f.vertex)), provenance)
+    }).collect())
+}
+
+/// The web of X on every leg of each fired Z spider, multiplied together
+fn fire_spiders(g: &Graph, fired: impl Iterator<Item = V>) -> PauliWeb {
+    // (X, Z) parts per edge, as seen from the smaller endpoint
+    let mut parts: HashMap<(V, V), (bool, bool)> = HashMap::new();
+    for v in fired {
+        for n in g.neighbors(v) {
+            let edge = (v.min(n), v.max(n));
+            let part = parts.entry(edge).or_default();
+            // X at the larger end of a Hadamard edge is Z at the smaller end
+            if g.edge_type(v, n) == EType::H && v > n {
+                part.1 ^= true;
+            } else {
+                part.0 ^= true;
+            }
+        }
+    }
+
+    let mut pw = PauliWeb::new();
+    for ((a, b), part) in parts {
+        match part {
+            (true, false) => pw.set_edge(a, b, Pauli::X),
+            (false, true) => pw.set_edge(a, b, Pauli::Z),
+            (true, true) => pw.set_edge(a, b, Pauli::Y),
+            (false, false) => {}
+        }
+    }
+    pw
+}
+
+/// The adjacency matrix augmented with the boundary and no-output blocks, whose
+/// nullspace [`nullspace_basis`] computes
+fn web_system(big_n: &Mat2, outs: usize, sink: &MatrixSink) -> Result<Mat2, String> {
     // Create I_n (identity matrix of size outs x outs)
     let i_n = Mat2::id(outs);
     draw_mat("I_n", &i_n);
@@ -158,8 +1020,8 @@ pub fn get_detection_webs(g: &mut Graph) -> Vec<PauliWeb> {
     draw_mat("mdl", &mdl);
     
     // Horizontally concatenate mdl and big_n
-    let md = mdl.hstack(&big_n);
-    draw_mat("md", &md);
+    let md = mdl.hstack(big_n);
+    sink.emit("md", "md", &md)?;
     
     // Create the no_output matrix that will be stacked below md
     // This is [I_{2*outs} | 0] where I is identity and 0 is zero matrix
@@ -169,32 +1031,465 @@ pub fn get_detection_webs(g: &mut Graph) -> Vec<PauliWeb> {
     
     // Vertically stack md and no_output
     let md_no_output = md.vstack(&no_output);
-    draw_mat("md_no_output", &md_no_output);
-    
+    sink.emit("md_no_output", "md_no_output", &md_no_output)?;
+    Ok(md_no_output)
+}
+
+/// Web basis from the nullspace of the augmented adjacency matrix
+///
+/// Vectors are laid out as `outs` boundary bits followed by one bit per node, as
+/// [`get_pw`] expects.
+fn nullspace_basis(
+    big_n: &Mat2,
+    outs: usize,
+    sink: &MatrixSink,
+    checkpoints: Option<&Checkpoints>,
+) -> Result<Vec<BitVec<usize, Lsb0>>, String> {
+    let md_no_output = web_system(big_n, outs, sink)?;
+
     // Compute nullspace
-    let mdnons = md_no_output.nullspace(false);
-    log::debug!("Number of basis vectors in nullspace: {}", mdnons.len());
+    let mdnons = match checkpoints {
+        Some(checkpoints) => {
+            let mut reduced = md_no_output;
+            let (_, pivot_cols) = gauss_checkpointed(&mut reduced, true, None, checkpoints, "md_no_output")?;
+            reduced.reduced_nullspace(&pivot_cols)
+        }
+        None => md_no_output.nullspace(false),
+    };
+    log::debug!(target: WEBS, "Number of basis vectors in nullspace: {}", mdnons.len());
     
-    // Convert each basis vector to a PauliWeb
-    let mut pws = Vec::with_capacity(mdnons.len());
-    for (i, basis) in mdnons.into_iter().enumerate() {
-        log::debug!("Basis vector {}: {}", i, basis);
-        
-        // The basis vector is a row vector from the nullspace
-        // We need to extract its elements to create our bitvector
-        log::debug!("Creating bitvector of length: {}", basis.cols());
-        let mut vec = bitvec![0; basis.cols()];
-        for i in 0..basis.cols() {
-            // Get the value from the basis row vector
-            let val = basis.get(0, i);
-            log::debug!("Setting bit {} to {}", i, val);
-            vec.set(i, val);
-        }
-        log::debug!("Bitvector: {:#?}", vec);
-        // Create and store the PauliWeb
-        let pw = get_pw(&index_map, &vec, g);
-        pws.push(pw);
+    // Each basis vector is a one-row matrix, whose row is moved out as is
+    Ok(mdnons.into_iter().enumerate().map(|(i, basis)| {
+        log::debug!(target: WEBS, "Basis vector {}: {}", i, basis);
+        basis.into_rows().swap_remove(0)
+    }).collect())
+}
+
+/// Web basis from the kernel of the adjacency columns of the free nodes
+///
+/// The augmented system of [`nullspace_basis`] pins the boundary bits and the first
+/// `outs` nodes to zero, so this drops those columns up front. The kernel of the
+/// remaining n × k matrix A is read off the row operations that zero out rows of Aᵀ.
+/// Vectors use the same layout as [`nullspace_basis`].
+fn boundary_elimination_basis(
+    big_n: &Mat2,
+    outs: usize,
+    sink: &MatrixSink,
+    checkpoints: Option<&Checkpoints>,
+) -> Result<Vec<BitVec<usize, Lsb0>>, String> {
+    let n = big_n.rows();
+    let k = big_n.cols() - outs;
+
+    // Aᵀ: one row per free node, one column per adjacency row
+    let mut a_t = Mat2::zeros(k, n);
+    for j in 0..k {
+        for i in 0..n {
+            a_t.set(j, i, big_n.get(i, outs + j));
+        }
+    }
+    sink.emit("Aᵀ", "a_transpose", &a_t)?;
+
+    let mut ops = Mat2::id(k);
+    let rank = match checkpoints {
+        Some(checkpoints) => gauss_checkpointed(&mut a_t, false, Some(&mut ops), checkpoints, "a_transpose")?.0,
+        None => a_t.gauss(false, Some(&mut ops), None, 0, &mut Vec::new()),
+    };
+    log::debug!(target: WEBS, "Boundary elimination: rank {} of {} free nodes", rank, k);
+
+    // Rows past the rank are zero in Aᵀ, so the matching rows of ops are kernel vectors
+    Ok((rank..k).map(|row| {
+        let mut vec = bitvec![0; 2 * outs + k];
+        for j in 0..k {
+            vec.set(2 * outs + j, ops.get(row, j));
+        }
+        vec
+    }).collect())
+}
+
+/// Whether two sets of vectors of equal length span the same space over F2
+fn same_span(a: &[BitVec<usize, Lsb0>], b: &[BitVec<usize, Lsb0>]) -> bool {
+    let to_mat = |vecs: &[&BitVec<usize, Lsb0>]| {
+        let cols = vecs.first().map_or(0, |v| v.len());
+        let mut mat = Mat2::zeros(vecs.len(), cols);
+        for (r, v) in vecs.iter().enumerate() {
+            for c in v.iter_ones() {
+                mat.set(r, c, true);
+            }
+        }
+        mat.rank()
+    };
+    let a: Vec<_> = a.iter().collect();
+    let b: Vec<_> = b.iter().collect();
+    let both: Vec<_> = a.iter().chain(&b).copied().collect();
+    let rank = to_mat(&both);
+    to_mat(&a) == rank && to_mat(&b) == rank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::VData;
+
+    fn add_boundary(g: &mut Graph, qubit: f64, row: f64) -> V {
+        g.add_vertex_with_data(VData {
+            ty: VType::B,
+            phase: 0.into(),
+            qubit,
+            row,
+        })
+    }
+
+    #[test]
+    fn test_classify_boundaries_by_row() {
+        let mut g = Graph::new();
+        let in1 = add_boundary(&mut g, 1.0, 0.0);
+        let in0 = add_boundary(&mut g, 0.0, 0.0);
+        let out0 = add_boundary(&mut g, 0.0, 3.0);
+        let z = g.add_vertex(VType::Z);
+        g.set_row(z, 1.0);
+
+        let (inputs, outputs) = classify_boundaries(&g).unwrap();
+        assert_eq!(inputs, vec![in0, in1]);
+        assert_eq!(outputs, vec![out0]);
+    }
+
+    #[test]
+    fn test_classify_boundaries_single_row_is_state() {
+        let mut g = Graph::new();
+        let b0 = add_boundary(&mut g, 0.0, 2.0);
+        let b1 = add_boundary(&mut g, 1.0, 2.0);
+
+        let (inputs, outputs) = classify_boundaries(&g).unwrap();
+        assert!(inputs.is_empty());
+        assert_eq!(outputs, vec![b0, b1]);
+    }
+
+    #[test]
+    fn test_classify_boundaries_rejects_middle_row() {
+        let mut g = Graph::new();
+        add_boundary(&mut g, 0.0, 0.0);
+        let middle = add_boundary(&mut g, 0.0, 1.0);
+        add_boundary(&mut g, 0.0, 2.0);
+
+        let err = classify_boundaries(&g).unwrap_err();
+        assert!(err.contains(&format!("Boundary vertex {}", middle)), "{}", err);
+    }
+
+    #[test]
+    fn test_explicit_boundaries_are_used() {
+        let mut g = Graph::new();
+        let b0 = add_boundary(&mut g, 0.0, 0.0);
+        let z0 = g.add_vertex(VType::Z);
+        let z1 = g.add_vertex(VType::Z);
+        let z2 = g.add_vertex(VType::Z);
+        let b1 = add_boundary(&mut g, 0.0, 0.0);
+        g.add_edge(b0, z0);
+        g.add_edge(z0, z1);
+        g.add_edge(z1, z2);
+        g.add_edge(z2, b1);
+
+        get_detection_webs_with_boundaries(
+            &mut g,
+            BoundaryRoles::Explicit { inputs: vec![b1], outputs: vec![b0] },
+        ).unwrap();
+        assert_eq!(g.inputs(), &vec![b1]);
+        assert_eq!(g.outputs(), &vec![b0]);
+    }
+
+    #[test]
+    fn test_same_span() {
+        let v = |bits: &[u8]| bits.iter().map(|&b| b == 1).collect::<BitVec<usize, Lsb0>>();
+        let a = [v(&[1, 1, 0]), v(&[0, 1, 1])];
+        let b = [v(&[1, 0, 1]), v(&[1, 1, 0])];
+        assert!(same_span(&a, &b));
+        assert!(!same_span(&a, &b[..1]));
+        assert!(!same_span(&a, &[v(&[1, 0, 0]), v(&[0, 1, 1])]));
+        assert!(same_span(&[], &[]));
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        let graph = crate::fixtures::fixture("steane_2_rounds").unwrap();
+        let webs = |algorithm| {
+            let mut g = graph.clone();
+            get_detection_webs_with_algorithm(&mut g, BoundaryRoles::ByRow, algorithm).unwrap()
+        };
+
+        let nullspace = webs(WebAlgorithm::Nullspace);
+        let eliminated = webs(WebAlgorithm::BoundaryElimination);
+        assert!(!nullspace.is_empty());
+        assert_eq!(nullspace.len(), eliminated.len());
+        assert_eq!(webs(WebAlgorithm::CrossCheck), nullspace);
+
+        // Checkpointing after every column changes nothing and leaves no files behind
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = Checkpoints { dir: dir.path().to_path_buf(), interval: std::time::Duration::ZERO };
+        let options = WebOptions { algorithm: WebAlgorithm::CrossCheck, checkpoints: Some(checkpoints), ..WebOptions::default() };
+        assert_eq!(get_detection_webs_with_options(&mut graph.clone(), &options).unwrap(), nullspace);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_provenance() {
+        let graph = crate::fixtures::fixture("steane_2_rounds").unwrap();
+        let mut g = graph.clone();
+        let traced = get_detection_webs_traced(&mut g, &WebOptions::default()).unwrap();
+        assert!(!traced.is_empty());
+        for (i, (web, provenance)) in traced.iter().enumerate() {
+            assert_eq!(provenance.basis_index, i);
+            assert_eq!(provenance.free_column, provenance.fired.iter().map(|f| f.position).max());
+            let fired: Vec<V> = provenance.fired.iter().map(|f| f.vertex).collect();
+            assert!(web.sorted_edges().iter().all(|((a, b), _)| fired.contains(a) || fired.contains(b)));
+        }
+
+        let mut g = graph.clone();
+        let options = WebOptions { algorithm: WebAlgorithm::BoundaryElimination, ..WebOptions::default() };
+        let traced = get_detection_webs_traced(&mut g, &options).unwrap();
+        assert!(traced.iter().all(|(_, provenance)| provenance.free_column.is_none()));
+    }
+
+    #[test]
+    fn test_detection_web_iter() {
+        let graph = crate::create_graph::create_steane_rounds(2);
+        let webs = get_detection_webs(&mut graph.clone());
+        let mut g = graph.clone();
+        let iter = detection_web_iter(&mut g);
+        assert_eq!(iter.len(), webs.len());
+        assert_eq!(iter.collect::<Vec<_>>(), webs);
+        assert_eq!(detection_web_iter(&mut graph.clone()).take(2).collect::<Vec<_>>(), webs[..2]);
+
+        let options = WebOptions { algorithm: WebAlgorithm::CrossCheck, ..WebOptions::default() };
+        assert!(detection_web_iter_with_options(&mut graph.clone(), &options).is_err());
+    }
+
+    #[test]
+    fn test_orderings_agree() {
+        let graph = crate::fixtures::fixture("steane_2_rounds").unwrap();
+        let webs = |ordering| {
+            let mut g = graph.clone();
+            let options = WebOptions { ordering, ..WebOptions::default() };
+            let webs = get_detection_webs_with_options(&mut g, &options).unwrap();
+            (crate::web_space::WebSpace::spanned_by(&g, &webs).unwrap(), webs.len())
+        };
+
+        let (natural, n) = webs(VertexOrdering::Natural);
+        assert_eq!(natural.dim(), n);
+        for ordering in [VertexOrdering::ByRow, VertexOrdering::MinimumDegree] {
+            let (space, len) = webs(ordering);
+            assert_eq!(len, n, "{}", ordering);
+            assert!(natural.basis().iter().all(|web| space.contains(web)), "{}", ordering);
+        }
+
+        for ordering in [VertexOrdering::Natural, VertexOrdering::ByRow, VertexOrdering::MinimumDegree] {
+            assert_eq!(ordering.to_string().parse(), Ok(ordering));
+        }
+        assert!("amd".parse::<VertexOrdering>().is_err());
+    }
+
+    #[test]
+    fn test_dump_matrices() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut g = crate::fixtures::fixture("steane_2_rounds").unwrap();
+        let options = WebOptions {
+            dump_matrices: Some(dir.path().join("matrices")),
+            ..WebOptions::default()
+        };
+        let webs = get_detection_webs_lenient(&mut g, &options).unwrap();
+
+        let header = |file: &str| {
+            let text = std::fs::read_to_string(dir.path().join("matrices").join(file)).unwrap();
+            text.lines().nth(1).unwrap().split(' ').map(|n| n.parse().unwrap()).collect::<Vec<usize>>()
+        };
+        let adjacency = header("adjacency.mtx");
+        assert_eq!(adjacency[0], adjacency[1]);
+        let md_no_output = header("md_no_output.mtx");
+        assert_eq!(header("md.mtx")[1], md_no_output[1]);
+        assert_eq!(header("nullspace.mtx")[..2], [webs.len(), md_no_output[1]]);
+    }
+
+    #[test]
+    fn test_plan_matches_dumped_matrices() {
+        let dir = tempfile::tempdir().unwrap();
+        let g = crate::fixtures::fixture("steane_2_rounds").unwrap();
+        let options = WebOptions {
+            algorithm: WebAlgorithm::CrossCheck,
+            dump_matrices: Some(dir.path().to_path_buf()),
+            ..WebOptions::default()
+        };
+        let plan = plan_detection_webs(&g, &options).unwrap();
+        assert_eq!(plan.matrices.len(), 6);
+        get_detection_webs_lenient(&mut g.clone(), &options).unwrap();
+        for matrix in plan.matrices.iter().filter(|m| !matches!(m.name, "eliminated" | "row_operations")) {
+            let text = std::fs::read_to_string(dir.path().join(format!("{}.mtx", matrix.name))).unwrap();
+            let size: Vec<usize> = text.lines().nth(1).unwrap().split(' ').take(2).map(|n| n.parse().unwrap()).collect();
+            assert_eq!(size, [matrix.rows, matrix.cols], "{}", matrix.name);
+        }
+        assert!(plan.to_string().contains("CrossCheck elimination with natural ordering"));
+        assert!(plan.estimated_bytes() > 0);
+
+        // Planning fails where the computation would
+        let largest = plan.matrices.iter().map(|m| m.rows.max(m.cols)).max().unwrap();
+        let tight = WebOptions { limits: Limits { max_matrix_side: Some(largest - 1), ..Limits::default() }, ..options };
+        assert!(plan_detection_webs(&g, &tight).unwrap_err().contains("--allow-huge"));
+    }
+
+    #[test]
+    fn test_minimum_degree_order() {
+        // Star with centre c and leaves l0..l2, plus a tail l2 - t
+        let mut g = Graph::new();
+        let c = g.add_vertex(VType::Z);
+        let l: Vec<V> = (0..3).map(|_| g.add_vertex(VType::X)).collect();
+        let t = g.add_vertex(VType::Z);
+        for &leaf in &l {
+            g.add_edge(c, leaf);
+        }
+        g.add_edge(l[2], t);
+
+        let mut nodes = vec![c, l[0], l[1], l[2], t];
+        order_nodes(&g, &mut nodes, VertexOrdering::MinimumDegree);
+        // Leaves go first; the centre only once its degree has dropped
+        assert_eq!(nodes[0], l[0]);
+        assert_eq!(nodes[1], l[1]);
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(nodes.iter().collect::<BTreeSet<_>>().len(), 5);
+    }
+
+    #[test]
+    fn test_get_pw_y_edges() {
+        // Z - X - Z - X square with one boundary leg, all four spiders in the web
+        let mut g = Graph::new();
+        let z1 = g.add_vertex(VType::Z);
+        let x1 = g.add_vertex(VType::X);
+        let z2 = g.add_vertex(VType::Z);
+        let x2 = g.add_vertex(VType::X);
+        let b = add_boundary(&mut g, 0.0, 0.0);
+        for (a, c) in [(z1, x1), (x1, z2), (z2, x2), (x2, z1), (z1, b)] {
+            g.add_edge(a, c);
+        }
+        let index_map: HashMap<usize, usize> = [z1, x1, z2, x2].into_iter().enumerate().collect();
+        let web = get_pw(&index_map, &bitvec![1; 4], &g);
+
+        for (a, c) in [(z1, x1), (x1, z2), (z2, x2), (x2, z1)] {
+            assert_eq!(web.get_edge(a, c), Some(Pauli::Y));
+        }
+        // Only the Z spider claims the boundary leg, which only pinning the boundary rules out
+        assert_eq!(web.get_edge(z1, b), Some(Pauli::X));
+        assert_eq!(
+            crate::web_check::web_violations(&g, &web),
+            Ok(vec![crate::web_check::Violation::BoundarySupport { vertex: b, edges: vec![(z1, b)] }])
+        );
+    }
+
+    #[test]
+    fn test_incidence() {
+        // Z - X with a boundary on the Z spider
+        let mut g = Graph::new();
+        let z = g.add_vertex(VType::Z);
+        let x = g.add_vertex(VType::X);
+        let b = add_boundary(&mut g, 0.0, 0.0);
+        g.add_edge(z, x);
+        g.add_edge(z, b);
+        let incidence = Incidence::new(&g);
+        assert_eq!((incidence.edges(z).len(), incidence.edges(x).len(), incidence.edges(b).len()), (2, 1, 1));
+        assert!(incidence.edges(99).is_empty());
+
+        // Same web as without the cached incidence
+        let index_map: HashMap<usize, usize> = [(0, z), (1, x)].into_iter().collect();
+        let web = get_pw_with_incidence(&index_map, &bitvec![1, 0], &g, &incidence);
+        assert_eq!(web, get_pw(&index_map, &bitvec![1, 0], &g));
+        assert_eq!(web.edge_operators.len(), 2);
+    }
+
+    #[test]
+    fn test_incidence_matrix() {
+        let g = crate::create_graph::create_steane_rounds(1);
+        let mut nodes: Vec<V> = g.vertices().collect();
+        nodes.sort();
+        let order = EdgeOrder::new(&g);
+        assert_eq!(order.len(), g.num_edges());
+        let mat = incidence_matrix(&g, &nodes, &order);
+        // Every edge has two ends and every vertex as many edges as its degree
+        assert!((0..order.len()).all(|j| (0..nodes.len()).filter(|&i| mat.get(i, j)).count() == 2));
+        assert!(nodes.iter().enumerate().all(|(i, &v)| mat.row_ones(i).count() == g.degree(v)));
+
+        // Orders are kept as given, missing edges are zero and unlisted ends have no row
+        let (a, b) = order.edges()[0];
+        let custom = EdgeOrder::from_edges([(b, a), (b, b), (a, b)]);
+        assert_eq!((custom.len(), custom.index(b, a), custom.index(b, b), custom.index(a, a)), (2, Some(0), Some(1), None));
+        let mat = incidence_matrix(&g, &[b], &custom);
+        assert_eq!(mat.to_u8_vec(), vec![vec![1, 0]]);
+    }
+
+    #[test]
+    fn test_normal_forms() {
+        let g = crate::create_graph::create_steane_rounds(2);
+        let solve = |g: &mut Graph, normal_form| {
+            let options = WebOptions { normal_form, ..WebOptions::default() };
+            get_detection_webs_lenient_traced(g, &options).map(|webs| webs.into_iter().map(|(web, _)| web).collect::<Vec<_>>())
+        };
+        let mut rg = g.clone();
+        let rg_webs = solve(&mut rg, NormalForm::RedGreen).unwrap();
+        let mut graph_like = g.clone();
+        let graph_like_webs = solve(&mut graph_like, NormalForm::GraphLike).unwrap();
+        // The same webs on fewer vertices
+        assert_eq!(graph_like_webs.len(), rg_webs.len());
+        assert!(graph_like.num_vertices() < rg.num_vertices());
+        for web in &graph_like_webs {
+            assert_eq!(crate::web_check::is_detection_web(&graph_like, web), Ok(true));
+            assert!(web.project_to_boundary(&graph_like).iter().all(Option::is_none));
+        }
+
+        // Without conversion the diagram must already be in rg form
+        assert!(solve(&mut g.clone(), NormalForm::None).unwrap_err().starts_with("The diagram is not in rg form"));
+        assert_eq!(solve(&mut rg.clone(), NormalForm::None).unwrap().len(), rg_webs.len());
+        assert_eq!("graph-like".parse(), Ok(NormalForm::GraphLike));
+        let plan = plan_detection_webs(&g, &WebOptions { normal_form: NormalForm::GraphLike, ..WebOptions::default() }).unwrap();
+        assert_eq!((plan.vertices, plan.matrices[0].name), (graph_like.num_vertices(), "biadjacency"));
+    }
+
+    #[test]
+    fn test_graph_like_webs() {
+        // b0 - z0 = z1 = z2 - b1 and z0 = z3 = z2, with Hadamard edges between the spiders
+        let mut g = Graph::new();
+        let b0 = add_boundary(&mut g, 0.0, 0.0);
+        let z: Vec<V> = (0..4).map(|_| g.add_vertex(VType::Z)).collect();
+        let b1 = add_boundary(&mut g, 0.0, 4.0);
+        g.add_edge(b0, z[0]);
+        g.add_edge(z[2], b1);
+        for (a, c) in [(0, 1), (1, 2), (0, 3), (2, 3)] {
+            g.add_edge_with_type(z[a], z[c], EType::H);
+        }
+
+        let webs = get_detection_webs_graph_like(&g).unwrap();
+        assert_eq!(webs.len(), 1);
+        let web = &webs[0];
+        // z0 and z2 are next to the boundaries, so only z1 and z3 fire; z0 and z2 see
+        // both of them, as Z at their end of each Hadamard edge
+        assert_eq!(web.get_edge(z[0], z[1]), Some(Pauli::Z));
+        assert_eq!(web.get_edge(z[1], z[2]), Some(Pauli::X));
+        assert_eq!(web.get_edge(z[0], z[3]), Some(Pauli::Z));
+        assert_eq!(web.get_edge(z[2], z[3]), Some(Pauli::Z));
+        assert!(webs.iter().all(|web| web.get_edge(b0, z[0]).is_none() && web.get_edge(z[2], b1).is_none()));
+        assert_eq!(crate::web_check::is_detection_web(&g, web), Ok(true));
+
+        // A T spider can't let X through
+        g.set_phase(z[3], quizx::phase::Phase::from(0.25));
+        assert!(get_detection_webs_graph_like(&g).unwrap().is_empty());
+
+        // Square of spiders: opposite corners fire together
+        let mut square = Graph::new();
+        let c: Vec<V> = (0..4).map(|_| square.add_vertex(VType::Z)).collect();
+        for i in 0..4 {
+            square.add_edge_with_type(c[i], c[(i + 1) % 4], EType::H);
+        }
+        let webs = get_detection_webs_graph_like(&square).unwrap();
+        assert_eq!(webs.len(), 2);
+        for web in &webs {
+            assert_eq!(web.edge_operators.len(), 4);
+            assert_eq!(crate::web_check::is_detection_web(&square, web), Ok(true));
+        }
+
+        square.add_edge(c[0], c[2]);
+        assert!(get_detection_webs_graph_like(&square).unwrap_err().contains("not a Hadamard edge"));
     }
-    
-    pws
 }
@@ -0,0 +1,199 @@
+use crate::graph_visualizer::{to_dot_with_options, RenderOptions};
+use crate::pauliweb::PauliWeb;
+use quizx::graph::{EType, GraphLike, VType, V};
+use std::collections::{HashMap, HashSet};
+
+/// A copy of a diagram with plain wire segments contracted, for drawing only
+///
+/// Vertex ids are those of the original graph. Every edge that replaced a chain
+/// remembers the hidden spiders, so webs and labels can be mapped onto it.
+#[derive(Debug, Clone)]
+pub struct ContractedView<G: GraphLike> {
+    pub graph: G,
+    /// Hidden spiders per contracted edge (smaller, larger id), walked from the smaller end
+    pub hidden: HashMap<(V, V), Vec<V>>,
+}
+
+/// Contracts every chain of phase-free, degree-2 Z/X spiders into a single edge
+///
+/// Such spiders are identities, so the picture still shows the same diagram: the new
+/// edge is a Hadamard edge iff the chain had an odd number of them. Chains closing a
+/// cycle, or whose ends are already connected, are left alone.
+pub fn contract_chains<G: GraphLike>(graph: &G) -> ContractedView<G> {
+    let contractible = |v: V| {
+        matches!(graph.vertex_type(v), VType::Z | VType::X)
+            && graph.phase(v).to_f64() == 0.0
+            && graph.degree(v) == 2
+    };
+
+    // Follows the chain from `from` into `start` until a non-contractible vertex.
+    // Returns the chain vertices, the end vertex and the number of Hadamard edges,
+    // or None if the walk comes back to `from`.
+    let walk = |from: V, start: V| -> Option<(Vec<V>, V, usize)> {
+        let (mut prev, mut cur) = (from, start);
+        let mut chain = Vec::new();
+        let mut hadamards = usize::from(graph.edge_type(prev, cur) == EType::H);
+        while contractible(cur) {
+            if cur == from {
+                return None;
+            }
+            chain.push(cur);
+            let next = graph.neighbors(cur).find(|&n| n != prev)?;
+            hadamards += usize::from(graph.edge_type(cur, next) == EType::H);
+            (prev, cur) = (cur, next);
+        }
+        Some((chain, cur, hadamards))
+    };
+
+    let mut contracted = graph.clone();
+    let mut hidden = HashMap::new();
+    let mut visited = HashSet::new();
+    let mut vertices: Vec<V> = graph.vertices().collect();
+    vertices.sort();
+
+    for v in vertices {
+        if visited.contains(&v) || !contractible(v) {
+            continue;
+        }
+        let mut ends = graph.neighbors(v);
+        let (n0, n1) = (ends.next().unwrap(), ends.next().unwrap());
+        let (Some((left, a, h0)), Some((right, b, h1))) = (walk(v, n0), walk(v, n1)) else {
+            // A closed loop of identities, nothing to attach it to
+            visited.insert(v);
+            continue;
+        };
+
+        let mut chain: Vec<V> = left.into_iter().rev().collect();
+        chain.push(v);
+        chain.extend(right);
+        visited.extend(chain.iter().copied());
+        if a == b || contracted.connected(a, b) {
+            continue;
+        }
+
+        for &c in &chain {
+            contracted.remove_vertex(c);
+        }
+        // Each walk counted its own edge out of v, so every chain edge is counted once
+        let ety = if (h0 + h1) % 2 == 1 { EType::H } else { EType::N };
+        contracted.add_edge_with_type(a, b, ety);
+        if a > b {
+            chain.reverse();
+        }
+        hidden.insert((a.min(b), a.max(b)), chain);
+    }
+
+    ContractedView { graph: contracted, hidden }
+}
+
+impl<G: GraphLike> ContractedView<G> {
+    /// Maps a web of the original graph onto the contracted one
+    ///
+    /// A contracted edge takes the Pauli of the first segment of its chain, seen
+    /// from the smaller endpoint.
+    pub fn contract_web(&self, web: &PauliWeb) -> PauliWeb {
+        let mut contracted = PauliWeb::new();
+        for (&(a, b), &pauli) in &web.edge_operators {
+            if self.graph.contains_vertex(a) && self.graph.contains_vertex(b) && self.graph.connected(a, b) {
+                contracted.set_edge(a, b, pauli);
+            }
+        }
+        for (&(a, b), chain) in &self.hidden {
+            if let Some(pauli) = web.get_edge(a, chain[0]) {
+                contracted.set_edge(a, b, pauli);
+            }
+        }
+        contracted
+    }
+
+    /// Edge labels counting the spiders hidden in each contracted edge
+    pub fn edge_labels(&self) -> HashMap<(V, V), String> {
+        self.hidden.iter()
+            .map(|(&edge, chain)| (edge, format!("+{}", chain.len())))
+            .collect()
+    }
+
+    /// DOT of the contracted graph, with the web mapped onto it and chain lengths on the edges
+    pub fn to_dot(&self, web: Option<&PauliWeb>, options: &RenderOptions) -> String {
+        let mut options = options.clone();
+        options.edge_labels.extend(self.edge_labels());
+        let web = web.map(|w| self.contract_web(w));
+        to_dot_with_options(&self.graph, web.as_ref(), &options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::Pauli;
+    use quizx::hash_graph::Graph;
+
+    /// B - Z - Z - X - Z(π/2) - B with an H edge in the middle of the chain
+    fn line() -> (Graph, Vec<V>) {
+        let mut g = Graph::new();
+        let vs = vec![
+            g.add_vertex(VType::B),
+            g.add_vertex(VType::Z),
+            g.add_vertex(VType::Z),
+            g.add_vertex(VType::X),
+            g.add_vertex_with_phase(VType::Z, 0.5),
+            g.add_vertex(VType::B),
+        ];
+        for (i, w) in vs.windows(2).enumerate() {
+            let ety = if i == 2 { EType::H } else { EType::N };
+            g.add_edge_with_type(w[0], w[1], ety);
+        }
+        (g, vs)
+    }
+
+    #[test]
+    fn test_contract_line() {
+        let (g, vs) = line();
+        let view = contract_chains(&g);
+
+        assert_eq!(view.graph.num_vertices(), 3);
+        assert!(view.graph.connected(vs[0], vs[4]));
+        assert_eq!(view.graph.edge_type(vs[0], vs[4]), EType::H);
+        assert_eq!(view.hidden[&(vs[0], vs[4])], vec![vs[1], vs[2], vs[3]]);
+        assert_eq!(view.edge_labels()[&(vs[0], vs[4])], "+3");
+        // The original graph is untouched
+        assert_eq!(g.num_vertices(), 6);
+    }
+
+    #[test]
+    fn test_contract_web_and_dot() {
+        let (g, vs) = line();
+        let mut web = PauliWeb::new();
+        web.set_edge(vs[0], vs[1], Pauli::Z);
+        web.set_edge(vs[4], vs[5], Pauli::X);
+
+        let view = contract_chains(&g);
+        let contracted = view.contract_web(&web);
+        assert_eq!(contracted.get_edge(vs[0], vs[4]), Some(Pauli::Z));
+        assert_eq!(contracted.get_edge(vs[4], vs[5]), Some(Pauli::X));
+
+        let dot = view.to_dot(Some(&web), &RenderOptions::default());
+        assert!(dot.contains("label=\"+3\""));
+    }
+
+    #[test]
+    fn test_keeps_triangle_and_loop() {
+        // Contracting the Z in a triangle would create a parallel edge
+        let mut g = Graph::new();
+        let a = g.add_vertex_with_phase(VType::Z, 0.5);
+        let b = g.add_vertex_with_phase(VType::Z, 0.5);
+        let z = g.add_vertex(VType::Z);
+        g.add_edge(a, b);
+        g.add_edge(a, z);
+        g.add_edge(z, b);
+        // A separate ring of identities
+        let ring: Vec<V> = (0..3).map(|_| g.add_vertex(VType::X)).collect();
+        for i in 0..3 {
+            g.add_edge(ring[i], ring[(i + 1) % 3]);
+        }
+
+        let view = contract_chains(&g);
+        assert_eq!(view.graph.num_vertices(), g.num_vertices());
+        assert!(view.hidden.is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+//! One set of options for every export format
+//!
+//! [`ExportOptions`] is built once and handed to any of the exporters, so a new visual
+//! option is added here and in [`RenderOptions`] rather than to each function signature,
+//! e.g. `ExportOptions::new().show_ids(true).web(web).scale(1.5).to_svg(&g)`.
+
+use crate::graph_visualizer::{
+    plan_png, run_graphviz_with_retry, to_dot_with_options, Highlights, PngPlan, RenderOptions, RetryPolicy,
+};
+use crate::layout::{FixedCoords, Layout, Scaled};
+use crate::palette::Palette;
+use crate::pauliweb::PauliWeb;
+use crate::svg_backend;
+use crate::tikz_export::tikz_to_string_with_options;
+use quizx::graph::GraphLike;
+use quizx::hash_graph::Graph;
+use std::sync::Arc;
+
+/// Options shared by the DOT, SVG, TikZ, PNG and HTML exporters
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    render: RenderOptions,
+    web: Option<PauliWeb>,
+    scale: f64,
+    policy: RetryPolicy,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            render: RenderOptions::default(),
+            web: None,
+            scale: 1.0,
+            policy: RetryPolicy::default(),
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from existing render options
+    pub fn from_render_options(render: RenderOptions) -> Self {
+        ExportOptions { render, ..Self::default() }
+    }
+
+    /// Show vertex IDs, see [`RenderOptions::show_node_ids`]
+    pub fn show_ids(mut self, show: bool) -> Self {
+        self.render.show_node_ids = show;
+        self
+    }
+
+    /// Colours of spiders and web edges
+    pub fn theme(mut self, palette: Palette) -> Self {
+        self.render.palette = palette;
+        self
+    }
+
+    /// Write the Pauli letter on each web edge
+    pub fn pauli_labels(mut self, show: bool) -> Self {
+        self.render.pauli_labels = show;
+        self
+    }
+
+    /// Extra text on edge (a, b), in either order
+    pub fn edge_label(mut self, a: usize, b: usize, label: impl Into<String>) -> Self {
+        self.render.edge_labels.insert((a.min(b), a.max(b)), label.into());
+        self
+    }
+
+    /// Text replacing the phase of vertex `v`
+    pub fn phase_label(mut self, v: usize, label: impl Into<String>) -> Self {
+        self.render.phase_labels.insert(v, label.into());
+        self
+    }
+
+    /// Draw a guide line per qubit
+    pub fn qubit_lines(mut self, show: bool) -> Self {
+        self.render.qubit_lines = show;
+        self
+    }
+
+    /// Mark phase gadgets
+    pub fn phase_gadgets(mut self, show: bool) -> Self {
+        self.render.phase_gadgets = show;
+        self
+    }
+
+    pub fn highlights(mut self, highlights: Highlights) -> Self {
+        self.render.highlights = highlights;
+        self
+    }
+
+    /// Web drawn over the diagram
+    pub fn web(mut self, web: PauliWeb) -> Self {
+        self.web = Some(web);
+        self
+    }
+
+    /// Multiplies the distances between vertices, leaving vertex sizes alone
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn layout(mut self, layout: FixedCoords) -> Self {
+        self.render.layout = layout;
+        self
+    }
+
+    /// Places vertices by `layout` instead of their coordinates
+    pub fn custom_layout(mut self, layout: Arc<dyn Layout>) -> Self {
+        self.render.custom_layout = Some(layout);
+        self
+    }
+
+    /// Retries and timeout of Graphviz, for PNG output
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The options as passed to the renderers, with the scale folded into the layout
+    pub fn render_options(&self) -> RenderOptions {
+        let mut render = self.render.clone();
+        if self.scale != 1.0 {
+            let inner: Arc<dyn Layout> = match render.custom_layout.take() {
+                Some(layout) => layout,
+                None => Arc::new(render.layout.clone()),
+            };
+            render.custom_layout = Some(Arc::new(Scaled { inner, factor: self.scale }));
+        }
+        render
+    }
+
+    pub fn to_dot<G: GraphLike>(&self, graph: &G) -> String {
+        to_dot_with_options(graph, self.web.as_ref(), &self.render_options())
+    }
+
+    /// SVG from the native backend, see [`svg_backend::to_svg`]
+    pub fn to_svg<G: GraphLike>(&self, graph: &G) -> String {
+        svg_backend::to_svg(graph, self.web.as_ref(), &self.render_options())
+    }
+
+    /// TikZ, with the scale applied to the picture. Highlights, layouts and guide lines
+    /// don't apply; TikZ keeps the graph's coordinates.
+    pub fn to_tikz(&self, graph: &Graph) -> String {
+        let web = self.web.clone().unwrap_or_default();
+        let tikz = tikz_to_string_with_options(graph, &web, &self.render);
+        tikz.replacen("[scale=1]", &format!("[scale={}]", self.scale), 1)
+    }
+
+    /// PNG rendered by neato, at a resolution lowered for huge graphs like
+    /// [`crate::graph_visualizer::render_image`] does
+    pub fn to_png<G: GraphLike>(&self, graph: &G) -> Result<Vec<u8>, String> {
+        let render = self.render_options();
+        let dpi = match plan_png(graph, &render) {
+            PngPlan::Png { dpi } => dpi,
+            PngPlan::SvgOnly => render.canvas_limits.min_dpi,
+        };
+        let dot = to_dot_with_options(graph, self.web.as_ref(), &render);
+        run_graphviz_with_retry("neato", &["-n2", "-Tpng", &format!("-Gdpi={}", dpi)], &dot, &self.policy)
+            .map_err(|e| format!("Graphviz failed: {}", e))
+    }
+
+    /// Standalone HTML page showing [`Self::to_svg`]
+    pub fn to_html<G: GraphLike>(&self, graph: &G) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>ZX diagram</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+            self.to_svg(graph)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::Pauli;
+    use quizx::graph::VType;
+
+    #[test]
+    fn test_export_options() {
+        let mut g = Graph::new();
+        let a = g.add_vertex(VType::Z);
+        let b = g.add_vertex(VType::X);
+        g.set_row(b, 2.0);
+        g.add_edge(a, b);
+        let mut web = PauliWeb::new();
+        web.set_edge(a, b, Pauli::X);
+
+        let options = ExportOptions::new()
+            .show_ids(true)
+            .theme(Palette::Viridis)
+            .pauli_labels(true)
+            .edge_label(b, a, "e")
+            .web(web);
+        let dot = options.to_dot(&g);
+        assert!(dot.contains("<font point-size='12'>"));
+        assert!(dot.contains("pos=\"300,0!\""));
+        assert!(dot.contains("label=\"e X\""));
+        assert!(dot.contains(Palette::Viridis.colors().web_x));
+
+        // Scaling spreads the vertices out, in every format
+        let scaled = options.clone().scale(2.0);
+        assert!(scaled.to_dot(&g).contains("pos=\"600,0!\""));
+        assert!(options.to_svg(&g).contains("width=\"380\""));
+        assert!(scaled.to_svg(&g).contains("width=\"680\""));
+        assert!(scaled.to_tikz(&g).contains("[scale=2]"));
+        let html = scaled.to_html(&g);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(&scaled.to_svg(&g)));
+    }
+}
@@ -0,0 +1,83 @@
+//! Canonical fixture graphs built in code, so tests don't depend on checked-in .zxg files
+//!
+//! The `gen_fixtures` binary writes them out as .zxg for use with the other binaries.
+
+use crate::create_graph::{create_steane_rounds, create_xx_stabilizer};
+use crate::zxg_export::write_zxg;
+use quizx::hash_graph::Graph;
+use std::path::{Path, PathBuf};
+
+/// A named fixture graph
+pub struct Fixture {
+    /// File stem of the written .zxg
+    pub name: &'static str,
+    pub build: fn() -> Graph,
+}
+
+/// All canonical fixtures
+pub const FIXTURES: [Fixture; 3] = [
+    Fixture { name: "xx_stab_2_rounds", build: || create_xx_stabilizer(2) },
+    Fixture { name: "steane_1_round", build: || create_steane_rounds(1) },
+    Fixture { name: "steane_2_rounds", build: || create_steane_rounds(2) },
+];
+
+/// Builds the fixture called `name`
+pub fn fixture(name: &str) -> Result<Graph, String> {
+    FIXTURES.iter()
+        .find(|f| f.name == name)
+        .map(|f| (f.build)())
+        .ok_or_else(|| format!("Unknown fixture {}", name))
+}
+
+/// Writes every fixture to `<dir>/<name>.zxg`, returning the paths written
+pub fn write_fixtures(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    FIXTURES.iter()
+        .map(|f| {
+            let path = dir.join(format!("{}.zxg", f.name));
+            write_zxg(&(f.build)(), &path)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coarsen::find_ancilla_gadgets;
+    use crate::detection_webs::get_detection_webs;
+    use crate::graph_loader::load_graph;
+    use quizx::hash_graph::GraphLike;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_fixtures(dir.path()).unwrap();
+        assert_eq!(paths.len(), FIXTURES.len());
+        for (path, f) in paths.iter().zip(&FIXTURES) {
+            let built = (f.build)();
+            let loaded = load_graph(path.to_str().unwrap()).unwrap();
+            assert_eq!(loaded.num_vertices(), built.num_vertices(), "{}", f.name);
+            assert_eq!(loaded.num_edges(), built.num_edges(), "{}", f.name);
+            assert_eq!(loaded.inputs().len(), built.inputs().len(), "{}", f.name);
+        }
+
+        // 7 qubits, 2 rounds of 6 stabilizers: each stabilizer is a degree-4 spider on
+        // the ancilla line, joined to a degree-3 spider on each qubit it touches
+        let mut steane = fixture("steane_2_rounds").unwrap();
+        assert_eq!(steane.num_vertices(), 14 + 60);
+        assert_eq!(steane.num_edges(), 103);
+        assert_eq!(find_ancilla_gadgets(&steane).len(), 12);
+        let mut shape: BTreeMap<(String, usize), usize> = BTreeMap::new();
+        for v in steane.vertices() {
+            *shape.entry((format!("{:?}", steane.vertex_type(v)), steane.degree(v))).or_default() += 1;
+        }
+        let expected = [("B", 1, 14), ("X", 3, 24), ("X", 4, 6), ("Z", 3, 24), ("Z", 4, 6)];
+        assert_eq!(shape, expected.iter().map(|&(ty, degree, n)| ((ty.to_string(), degree), n)).collect());
+        assert!(!get_detection_webs(&mut steane).is_empty());
+
+        assert!(fixture("nope").is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::linalg::Mat2;
+use crate::detection_webs::classify_boundaries;
+use quizx::graph::{EType, VType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The open graph underlying a graph-like diagram
+///
+/// Vertices are the Z spiders, connected whenever the diagram has a Hadamard edge
+/// between them. Inputs/outputs are the spiders attached to input/output boundaries,
+/// in the order of the boundaries.
+#[derive(Debug, Clone)]
+pub struct OpenGraph {
+    pub vertices: Vec<V>,
+    pub inputs: Vec<V>,
+    pub outputs: Vec<V>,
+    pub neighbors: HashMap<V, BTreeSet<V>>,
+}
+
+/// A causal flow: every measured vertex u is corrected by a single successor f(u)
+#[derive(Debug, Clone)]
+pub struct CausalFlow {
+    pub successor: HashMap<V, V>,
+    /// Outputs have depth 0, vertices measured earlier have higher depth
+    pub depth: HashMap<V, usize>,
+}
+
+/// A generalised flow: every measured vertex u is corrected by a set g(u)
+#[derive(Debug, Clone)]
+pub struct GFlow {
+    pub correction: HashMap<V, BTreeSet<V>>,
+    /// Outputs have depth 0, vertices measured earlier have higher depth
+    pub depth: HashMap<V, usize>,
+}
+
+impl OpenGraph {
+    /// Odd neighbourhood of a set: the vertices adjacent to an odd number of its members
+    pub fn odd_neighbourhood(&self, set: &BTreeSet<V>) -> BTreeSet<V> {
+        let mut odd = BTreeSet::new();
+        for v in set {
+            for &n in &self.neighbors[v] {
+                if !odd.remove(&n) {
+                    odd.insert(n);
+                }
+            }
+        }
+        odd
+    }
+}
+
+impl GFlow {
+    /// Non-output vertices in a valid measurement order (deepest first)
+    pub fn measurement_order(&self) -> Vec<V> {
+        let mut order: Vec<V> = self.correction.keys().copied().collect();
+        order.sort_by(|a, b| self.depth[b].cmp(&self.depth[a]).then(a.cmp(b)));
+        order
+    }
+}
+
+impl From<CausalFlow> for GFlow {
+    fn from(flow: CausalFlow) -> Self {
+        let correction = flow.successor.iter()
+            .map(|(&u, &v)| (u, BTreeSet::from([v])))
+            .collect();
+        GFlow { correction, depth: flow.depth }
+    }
+}
+
+/// Reads the open graph off a graph-like diagram
+///
+/// Fails if the diagram is not graph-like: only Z spiders and boundaries, Hadamard
+/// edges between spiders and every boundary attached to exactly one spider.
+pub fn open_graph(g: &Graph) -> Result<OpenGraph, String> {
+    let (input_boundaries, output_boundaries) = if g.inputs().is_empty() && g.outputs().is_empty() {
+        classify_boundaries(g)?
+    } else {
+        (g.inputs().clone(), g.outputs().clone())
+    };
+
+    let mut vertices = Vec::new();
+    let mut neighbors = HashMap::new();
+    let mut all: Vec<V> = g.vertices().collect();
+    all.sort();
+    for v in all {
+        match g.vertex_type(v) {
+            VType::Z => {
+                let mut adjacent = BTreeSet::new();
+                for n in g.neighbors(v) {
+                    if g.vertex_type(n) == VType::B {
+                        continue;
+                    }
+                    if g.edge_type(v, n) != EType::H {
+                        return Err(format!("Edge {}-{} between spiders is not a Hadamard edge", v, n));
+                    }
+                    adjacent.insert(n);
+                }
+                vertices.push(v);
+                neighbors.insert(v, adjacent);
+            }
+            VType::B => {}
+            other => return Err(format!("Vertex {} has type {:?}, expected a graph-like diagram", v, other)),
+        }
+    }
+
+    let attached = |boundaries: &[V]| -> Result<Vec<V>, String> {
+        let mut spiders = Vec::with_capacity(boundaries.len());
+        let mut seen = HashSet::new();
+        for &b in boundaries {
+            let mut ns = g.neighbors(b);
+            let spider = match (ns.next(), ns.next()) {
+                (Some(n), None) if g.vertex_type(n) == VType::Z => n,
+                _ => return Err(format!("Boundary {} must be attached to exactly one spider", b)),
+            };
+            if !seen.insert(spider) {
+                return Err(format!("Spider {} is attached to more than one boundary on the same side", spider));
+            }
+            spiders.push(spider);
+        }
+        Ok(spiders)
+    };
+
+    Ok(OpenGraph {
+        inputs: attached(&input_boundaries)?,
+        outputs: attached(&output_boundaries)?,
+        vertices,
+        neighbors,
+    })
+}
+
+/// Computes a maximally delayed causal flow, or `None` if the diagram has none
+///
+/// Works backwards from the outputs: a processed non-input vertex with exactly one
+/// unprocessed neighbour u becomes the successor of u.
+pub fn causal_flow(g: &Graph) -> Result<Option<CausalFlow>, String> {
+    let og = open_graph(g)?;
+    let inputs: HashSet<V> = og.inputs.iter().copied().collect();
+
+    let mut depth: HashMap<V, usize> = og.outputs.iter().map(|&v| (v, 0)).collect();
+    let mut successor = HashMap::new();
+    let mut correctors: BTreeSet<V> = og.outputs.iter().copied().filter(|v| !inputs.contains(v)).collect();
+    let mut layer = 1;
+
+    while depth.len() < og.vertices.len() {
+        let mut found = BTreeSet::new();
+        for &v in &correctors {
+            let mut unprocessed = og.neighbors[&v].iter().filter(|n| !depth.contains_key(n));
+            if let (Some(&u), None) = (unprocessed.next(), unprocessed.next())
+                && let Entry::Vacant(e) = successor.entry(u)
+            {
+                e.insert(v);
+                found.insert(u);
+            }
+        }
+        if found.is_empty() {
+            return Ok(None);
+        }
+
+        // Each corrector has no unprocessed neighbours left once used
+        correctors.retain(|v| !successor.values().any(|s| s == v));
+        for &u in &found {
+            depth.insert(u, layer);
+            if !inputs.contains(&u) {
+                correctors.insert(u);
+            }
+        }
+        layer += 1;
+    }
+
+    Ok(Some(CausalFlow { successor, depth }))
+}
+
+/// Computes a maximally delayed gflow (all measurements in the XY plane), or `None`
+/// if the diagram has none
+///
+/// Each layer solves, over F2, for a set K of processed non-input vertices whose odd
+/// neighbourhood meets the unprocessed vertices exactly in u.
+pub fn gflow(g: &Graph) -> Result<Option<GFlow>, String> {
+    let og = open_graph(g)?;
+    let inputs: HashSet<V> = og.inputs.iter().copied().collect();
+
+    let mut depth: HashMap<V, usize> = og.outputs.iter().map(|&v| (v, 0)).collect();
+    let mut correction = HashMap::new();
+    let mut correctors: Vec<V> = og.outputs.iter().copied().filter(|v| !inputs.contains(v)).collect();
+    correctors.sort();
+    let mut layer = 1;
+
+    while depth.len() < og.vertices.len() {
+        let unprocessed: Vec<V> = og.vertices.iter().copied().filter(|v| !depth.contains_key(v)).collect();
+
+        // Adjacency between unprocessed vertices (rows) and candidate correctors (columns)
+        let mut adjacency = Mat2::new(unprocessed.len(), correctors.len());
+        for (r, u) in unprocessed.iter().enumerate() {
+            for (c, v) in correctors.iter().enumerate() {
+                adjacency.set(r, c, og.neighbors[u].contains(v));
+            }
+        }
+
+        let mut found = Vec::new();
+        for (r, &u) in unprocessed.iter().enumerate() {
+            if let Some(x) = solve(&adjacency, r) {
+                let set = x.into_iter().map(|c| correctors[c]).collect();
+                correction.insert(u, set);
+                found.push(u);
+            }
+        }
+        if found.is_empty() {
+            return Ok(None);
+        }
+
+        for &u in &found {
+            depth.insert(u, layer);
+            if !inputs.contains(&u) {
+                correctors.push(u);
+            }
+        }
+        correctors.sort();
+        layer += 1;
+    }
+
+    Ok(Some(GFlow { correction, depth }))
+}
+
+/// Solves `m x = e_target` over F2, returning the columns set in some solution x
+fn solve(m: &Mat2, target: usize) -> Option<Vec<usize>> {
+    let mut rhs = Mat2::new(m.rows(), 1);
+    rhs.set(target, 0, true);
+    let x = m.solve(&rhs)?;
+    Some((0..m.cols()).filter(|&c| x.get(c, 0)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::vertex_at;
+
+    /// Builds a graph-like diagram: one spider per input row and output column,
+    /// with Hadamard edges from input i to output j wherever `adj[i][j]` is set
+    fn bipartite(adj: &[&[u8]]) -> Graph {
+        let mut g = Graph::new();
+        let (mut ins, mut outs) = (Vec::new(), Vec::new());
+        let mut spiders_in = Vec::new();
+        let mut spiders_out = Vec::new();
+        for q in 0..adj.len() {
+            let b = vertex_at(&mut g, VType::B, q as f64, 0.0);
+            let z = vertex_at(&mut g, VType::Z, q as f64, 1.0);
+            g.add_edge(b, z);
+            ins.push(b);
+            spiders_in.push(z);
+        }
+        for q in 0..adj[0].len() {
+            let z = vertex_at(&mut g, VType::Z, q as f64, 2.0);
+            let b = vertex_at(&mut g, VType::B, q as f64, 3.0);
+            g.add_edge(z, b);
+            outs.push(b);
+            spiders_out.push(z);
+        }
+        for (i, row) in adj.iter().enumerate() {
+            for (j, &bit) in row.iter().enumerate() {
+                if bit == 1 {
+                    g.add_edge_with_type(spiders_in[i], spiders_out[j], EType::H);
+                }
+            }
+        }
+        g.set_inputs(ins);
+        g.set_outputs(outs);
+        g
+    }
+
+    fn assert_valid_gflow(g: &Graph, flow: &GFlow) {
+        let og = open_graph(g).unwrap();
+        let outputs: HashSet<V> = og.outputs.iter().copied().collect();
+        assert_eq!(flow.depth.len(), og.vertices.len());
+        for &u in &og.vertices {
+            if outputs.contains(&u) {
+                assert!(!flow.correction.contains_key(&u));
+                continue;
+            }
+            let k = &flow.correction[&u];
+            let odd = og.odd_neighbourhood(k);
+            assert!(!k.contains(&u));
+            assert!(odd.contains(&u));
+            assert!(k.iter().all(|v| flow.depth[v] < flow.depth[&u]));
+            assert!(og.inputs.iter().all(|v| !k.contains(v)));
+            assert!(odd.iter().filter(|&&v| v != u).all(|v| flow.depth[v] < flow.depth[&u]));
+        }
+    }
+
+    #[test]
+    fn test_causal_flow_on_wire() {
+        // in - z0 - z1 - z2 - out with Hadamard edges between the spiders
+        let mut g = Graph::new();
+        let input = vertex_at(&mut g, VType::B, 0.0, 0.0);
+        let zs: Vec<V> = (1..4).map(|r| vertex_at(&mut g, VType::Z, 0.0, r as f64)).collect();
+        let output = vertex_at(&mut g, VType::B, 0.0, 4.0);
+        g.add_edge(input, zs[0]);
+        g.add_edge_with_type(zs[0], zs[1], EType::H);
+        g.add_edge_with_type(zs[1], zs[2], EType::H);
+        g.add_edge(zs[2], output);
+        g.set_inputs(vec![input]);
+        g.set_outputs(vec![output]);
+
+        let flow = causal_flow(&g).unwrap().expect("a wire has causal flow");
+        assert_eq!(flow.successor[&zs[0]], zs[1]);
+        assert_eq!(flow.successor[&zs[1]], zs[2]);
+        assert_eq!(flow.depth[&zs[2]], 0);
+        assert_eq!(flow.depth[&zs[0]], 2);
+
+        let gflow: GFlow = flow.into();
+        assert_eq!(gflow.measurement_order(), vec![zs[0], zs[1]]);
+        assert_valid_gflow(&g, &gflow);
+    }
+
+    #[test]
+    fn test_gflow_without_causal_flow() {
+        // Invertible biadjacency where every output sees at least two inputs
+        let g = bipartite(&[&[1, 1, 0], &[0, 1, 1], &[1, 1, 1]]);
+        assert!(causal_flow(&g).unwrap().is_none());
+
+        let flow = gflow(&g).unwrap().expect("invertible biadjacency has gflow");
+        assert_valid_gflow(&g, &flow);
+    }
+
+    #[test]
+    fn test_no_gflow() {
+        // Both inputs see both outputs: the map is not unitary
+        let g = bipartite(&[&[1, 1], &[1, 1]]);
+        assert!(causal_flow(&g).unwrap().is_none());
+        assert!(gflow(&g).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rejects_non_graph_like() {
+        let mut g = bipartite(&[&[1]]);
+        let x = vertex_at(&mut g, VType::X, 0.0, 1.5);
+        let z = g.inputs().iter().map(|&b| g.neighbors(b).next().unwrap()).next().unwrap();
+        g.add_edge(z, x);
+        assert!(open_graph(&g).is_err());
+        assert!(gflow(&g).is_err());
+    }
+}
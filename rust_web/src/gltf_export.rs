@@ -0,0 +1,323 @@
+//! glTF 2.0 scenes of 3D spacetime diagrams, to open in standard 3D viewers
+//!
+//! Each spider is a sphere and each edge a tube, coloured like the 2D renders, with the
+//! edges of a web drawn thicker in the web colours. Coordinates follow
+//! [`crate::graph_loader::load_graph_3d`]: the row runs along glTF's x axis, the qubit
+//! along z and the third coordinate `t` up along y. The geometry is embedded, so the
+//! `.gltf` file stands alone.
+
+use crate::graph_loader::TimeCoords;
+use crate::palette::Palette;
+use crate::pauliweb::{Pauli, PauliWeb};
+use quizx::graph::{GraphLike, VType};
+use quizx::hash_graph::Graph;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::path::Path;
+
+const SPIDER_RADIUS: f32 = 0.2;
+const EDGE_RADIUS: f32 = 0.05;
+const WEB_EDGE_RADIUS: f32 = 0.08;
+const EDGE_COLOR: &str = "#666666";
+/// Segments around spheres and tubes
+const SEGMENTS: usize = 16;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Vertex positions, normals and triangle indices of a mesh
+struct Geometry {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u16>,
+}
+
+/// Unit sphere around the origin
+fn sphere() -> Geometry {
+    let rings = SEGMENTS / 2;
+    let mut positions = Vec::new();
+    for ring in 0..=rings {
+        let theta = PI * ring as f32 / rings as f32;
+        for segment in 0..=SEGMENTS {
+            let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+            positions.push([theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()]);
+        }
+    }
+    Geometry { normals: positions.clone(), positions, indices: grid_indices(rings, SEGMENTS) }
+}
+
+/// Open tube of radius 1 from y = 0 to y = 1
+fn tube() -> Geometry {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    for y in [0.0, 1.0] {
+        for segment in 0..=SEGMENTS {
+            let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+            positions.push([phi.cos(), y, phi.sin()]);
+            normals.push([phi.cos(), 0.0, phi.sin()]);
+        }
+    }
+    Geometry { positions, normals, indices: grid_indices(1, SEGMENTS) }
+}
+
+/// Triangles covering a grid of `(rows + 1) x (columns + 1)` vertices, row by row
+fn grid_indices(rows: usize, columns: usize) -> Vec<u16> {
+    let mut indices = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let a = (row * (columns + 1) + column) as u16;
+            let b = a + columns as u16 + 1;
+            indices.extend([a, a + 1, b, b, a + 1, b + 1]);
+        }
+    }
+    indices
+}
+
+/// Linear RGBA for `#rrggbb`, since glTF colours are linear and palettes are sRGB
+fn linear_rgba(hex: &str) -> [f32; 4] {
+    let channel = |i: usize| {
+        let srgb = u8::from_str_radix(&hex[1 + 2 * i..3 + 2 * i], 16).expect("colours are #rrggbb") as f32 / 255.0;
+        srgb.powf(2.2)
+    };
+    [channel(0), channel(1), channel(2), 1.0]
+}
+
+/// Rotation taking +y to the unit vector `d`, as a glTF quaternion `[x, y, z, w]`
+fn rotation_from_y(d: [f32; 3]) -> [f32; 4] {
+    // Half-way quaternion: axis y × d, angle folded into w = 1 + y · d
+    let q = [d[2], 0.0, -d[0], 1.0 + d[1]];
+    let norm = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if norm < 1e-6 {
+        // d points straight down
+        return [1.0, 0.0, 0.0, 0.0];
+    }
+    q.map(|c| c / norm)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Collects the binary buffer and its views and accessors
+#[derive(Default)]
+struct Buffer {
+    bytes: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Buffer {
+    /// Appends `data` as a new view with one accessor, returning the accessor index
+    fn push(&mut self, data: Vec<u8>, target: u32, accessor: Value) -> usize {
+        let offset = self.bytes.len();
+        self.views.push(json!({ "buffer": 0, "byteOffset": offset, "byteLength": data.len(), "target": target }));
+        self.bytes.extend(data);
+        // Keep every view 4-byte aligned
+        while !self.bytes.len().is_multiple_of(4) {
+            self.bytes.push(0);
+        }
+        let mut accessor = accessor;
+        accessor["bufferView"] = json!(self.views.len() - 1);
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_vec3(&mut self, values: &[[f32; 3]]) -> usize {
+        let data = values.iter().flatten().flat_map(|c| c.to_le_bytes()).collect();
+        let bound = |pick: fn(f32, f32) -> f32, start| (0..3)
+            .map(|i| values.iter().map(|v| v[i]).fold(start, pick))
+            .collect::<Vec<_>>();
+        self.push(data, ARRAY_BUFFER, json!({
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": "VEC3",
+            "min": bound(f32::min, f32::MAX),
+            "max": bound(f32::max, f32::MIN),
+        }))
+    }
+
+    /// Adds a geometry, returning the attributes and indices of a glTF primitive
+    fn push_geometry(&mut self, geometry: &Geometry) -> Value {
+        let position = self.push_vec3(&geometry.positions);
+        let normal = self.push_vec3(&geometry.normals);
+        let data = geometry.indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let indices = self.push(data, ELEMENT_ARRAY_BUFFER, json!({
+            "componentType": UNSIGNED_SHORT,
+            "count": geometry.indices.len(),
+            "type": "SCALAR",
+        }));
+        json!({ "attributes": { "POSITION": position, "NORMAL": normal }, "indices": indices })
+    }
+}
+
+/// Meshes by shape and colour, created on first use
+struct Meshes {
+    shapes: [Value; 2],
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    by_key: HashMap<(usize, String), usize>,
+}
+
+impl Meshes {
+    /// Index of the mesh drawing shape 0 (sphere) or 1 (tube) in `color`
+    fn get(&mut self, shape: usize, color: &str) -> usize {
+        if let Some(&mesh) = self.by_key.get(&(shape, color.to_string())) {
+            return mesh;
+        }
+        let material = self.materials.iter()
+            .position(|m| m["name"] == color)
+            .unwrap_or_else(|| {
+                self.materials.push(json!({
+                    "name": color,
+                    "pbrMetallicRoughness": { "baseColorFactor": linear_rgba(color), "metallicFactor": 0.0, "roughnessFactor": 0.6 },
+                }));
+                self.materials.len() - 1
+            });
+        let mut primitive = self.shapes[shape].clone();
+        primitive["material"] = json!(material);
+        self.meshes.push(json!({ "primitives": [primitive] }));
+        self.by_key.insert((shape, color.to_string()), self.meshes.len() - 1);
+        self.meshes.len() - 1
+    }
+}
+
+/// glTF scene of `g` placed by its row, qubit and `t` coordinates, with `web` drawn on
+/// top if given
+///
+/// Vertices missing from `times` sit at t = 0.
+pub fn to_gltf(g: &Graph, times: &TimeCoords, web: Option<&PauliWeb>, palette: Palette) -> String {
+    let colors = palette.colors();
+    let position = |v| [g.row(v) as f32, times.get(&v).copied().unwrap_or(0.0) as f32, g.qubit(v) as f32];
+
+    let mut buffer = Buffer::default();
+    let shapes = [buffer.push_geometry(&sphere()), buffer.push_geometry(&tube())];
+    let mut meshes = Meshes { shapes, materials: Vec::new(), meshes: Vec::new(), by_key: HashMap::new() };
+    let mut nodes = Vec::new();
+
+    let mut vertices: Vec<_> = g.vertices().collect();
+    vertices.sort();
+    for &v in &vertices {
+        let color = match g.vertex_type(v) {
+            VType::Z => colors.z_spider,
+            VType::X => colors.x_spider,
+            VType::H => colors.h_box,
+            VType::B => "#000000",
+            _ => "#ffffff",
+        };
+        nodes.push(json!({
+            "name": format!("v{}", v),
+            "mesh": meshes.get(0, color),
+            "translation": position(v),
+            "scale": [SPIDER_RADIUS, SPIDER_RADIUS, SPIDER_RADIUS],
+        }));
+    }
+
+    let mut edges: Vec<_> = g.edges().map(|(a, b, _)| (a.min(b), a.max(b))).collect();
+    edges.sort();
+    for (a, b) in edges {
+        let (from, to) = (position(a), position(b));
+        let d = [to[0] - from[0], to[1] - from[1], to[2] - from[2]];
+        let length = d.iter().map(|c| c * c).sum::<f32>().sqrt();
+        if length == 0.0 {
+            continue;
+        }
+        let (color, radius) = match web.and_then(|w| w.get_edge(a, b)) {
+            Some(Pauli::X) => (colors.web_x, WEB_EDGE_RADIUS),
+            Some(Pauli::Z) => (colors.web_z, WEB_EDGE_RADIUS),
+            Some(Pauli::Y) => (colors.web_y, WEB_EDGE_RADIUS),
+            None => (EDGE_COLOR, EDGE_RADIUS),
+        };
+        nodes.push(json!({
+            "name": format!("e{}_{}", a, b),
+            "mesh": meshes.get(1, color),
+            "translation": from,
+            "rotation": rotation_from_y(d.map(|c| c / length)),
+            "scale": [radius, length, radius],
+        }));
+    }
+
+    let gltf = json!({
+        "asset": { "version": "2.0", "generator": "rust_web" },
+        "scene": 0,
+        "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+        "nodes": nodes,
+        "meshes": meshes.meshes,
+        "materials": meshes.materials,
+        "accessors": buffer.accessors,
+        "bufferViews": buffer.views,
+        "buffers": [{
+            "byteLength": buffer.bytes.len(),
+            "uri": format!("data:application/octet-stream;base64,{}", base64(&buffer.bytes)),
+        }],
+    });
+    serde_json::to_string_pretty(&gltf).expect("JSON values always serialise")
+}
+
+/// Writes [`to_gltf`] of `g` to `path`, conventionally a `.gltf` file
+pub fn write_gltf(g: &Graph, times: &TimeCoords, web: Option<&PauliWeb>, palette: Palette, path: &Path) -> Result<(), String> {
+    std::fs::write(path, to_gltf(g, times, web, palette))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gltf() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+
+        // Two spiders one above the other in time, joined to a boundary along the row
+        let mut g = Graph::new();
+        let z = g.add_vertex(VType::Z);
+        let x = g.add_vertex(VType::X);
+        let b = g.add_vertex(VType::B);
+        g.set_row(b, 1.0);
+        g.add_edge(z, x);
+        g.add_edge(z, b);
+        let times: TimeCoords = [(x, 2.0)].into_iter().collect();
+        let mut web = PauliWeb::new();
+        web.set_edge(z, x, Pauli::Z);
+
+        let gltf: Value = serde_json::from_str(&to_gltf(&g, &times, Some(&web), Palette::Classic)).unwrap();
+        assert_eq!(gltf["asset"]["version"], "2.0");
+        let nodes = gltf["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(gltf["scenes"][0]["nodes"].as_array().unwrap().len(), 5);
+        assert_eq!(nodes[1]["translation"], json!([0.0, 2.0, 0.0]));
+
+        // The web edge points straight up, so it needs no rotation
+        let web_edge = nodes.iter().find(|n| n["name"] == format!("e{}_{}", z, x)).unwrap();
+        assert_eq!(web_edge["rotation"], json!([0.0, 0.0, 0.0, 1.0]));
+        assert_eq!(web_edge["scale"], json!([WEB_EDGE_RADIUS, 2.0, WEB_EDGE_RADIUS]));
+        let material = &gltf["meshes"][web_edge["mesh"].as_u64().unwrap() as usize]["primitives"][0]["material"];
+        assert_eq!(gltf["materials"][material.as_u64().unwrap() as usize]["name"], Palette::Classic.colors().web_z);
+
+        // Along the row, +y turns onto +x
+        let [qx, qy, qz, qw] = rotation_from_y([1.0, 0.0, 0.0]);
+        assert!(qx.abs() < 1e-6 && qy.abs() < 1e-6 && (qz + 0.5f32.sqrt()).abs() < 1e-6 && (qw - 0.5f32.sqrt()).abs() < 1e-6);
+
+        let buffer = &gltf["buffers"][0];
+        let length = buffer["byteLength"].as_u64().unwrap() as usize;
+        let uri = buffer["uri"].as_str().unwrap();
+        let encoded = uri.strip_prefix("data:application/octet-stream;base64,").unwrap();
+        assert_eq!(encoded.len(), length.div_ceil(3) * 4);
+    }
+}
@@ -0,0 +1,253 @@
+//! Path, cut and component finding on ZX graphs, for QEC analysis
+//!
+//! Results are plain vertex lists, so they can be drawn with
+//! [`crate::graph_visualizer::Highlights::add_path`] or as highlighted vertices.
+
+use crate::pauliweb::{Pauli, PauliWeb};
+use quizx::graph::{GraphLike, VType, V};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Shortest path from any vertex of `from` to any vertex of `to`, e.g. between two
+/// boundaries
+///
+/// Vertices in between must have a type accepted by `allowed`; the end points may have
+/// any type. Breadth-first, visiting neighbours by id so ties always resolve the same
+/// way. Returns the vertices along the path, or `None` if `to` can't be reached.
+pub fn shortest_path<G: GraphLike>(
+    g: &G,
+    from: &[V],
+    to: &[V],
+    allowed: impl Fn(VType) -> bool,
+) -> Option<Vec<V>> {
+    let targets: HashSet<V> = to.iter().copied().collect();
+    let mut parent: HashMap<V, Option<V>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for &v in from {
+        if parent.insert(v, None).is_none() {
+            queue.push_back(v);
+        }
+    }
+
+    while let Some(v) = queue.pop_front() {
+        if targets.contains(&v) {
+            let mut path = vec![v];
+            let mut current = v;
+            while let Some(Some(prev)) = parent.get(&current) {
+                path.push(*prev);
+                current = *prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let mut neighbors: Vec<V> = g.neighbors(v).collect();
+        neighbors.sort();
+        for n in neighbors {
+            if parent.contains_key(&n) || !(targets.contains(&n) || allowed(g.vertex_type(n))) {
+                continue;
+            }
+            parent.insert(n, Some(v));
+            queue.push_back(n);
+        }
+    }
+    None
+}
+
+/// Fewest vertices whose removal disconnects `sources` from `sinks`, sorted
+///
+/// Vertices of `sources` and `sinks` themselves are never cut. Returns `None` if no cut
+/// exists, i.e. a source is a sink or adjacent to one.
+pub fn min_vertex_cut<G: GraphLike>(g: &G, sources: &[V], sinks: &[V]) -> Option<Vec<V>> {
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    let index: HashMap<V, usize> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let terminals: HashSet<V> = sources.iter().chain(sinks).copied().collect();
+
+    // Each vertex is split into an entry node 2i and an exit node 2i+1, joined by an
+    // arc of capacity 1, so a unit of flow through a vertex costs one cut vertex.
+    let infinite = vertices.len() + 1;
+    let (source, sink) = (2 * vertices.len(), 2 * vertices.len() + 1);
+    let mut flow = FlowNetwork::new(2 * vertices.len() + 2);
+    for (i, &v) in vertices.iter().enumerate() {
+        flow.add_arc(2 * i, 2 * i + 1, if terminals.contains(&v) { infinite } else { 1 });
+        for n in g.neighbors(v) {
+            flow.add_arc(2 * i + 1, 2 * index[&n], infinite);
+        }
+    }
+    for v in sources {
+        flow.add_arc(source, 2 * index[v], infinite);
+    }
+    for v in sinks {
+        flow.add_arc(2 * index[v] + 1, sink, infinite);
+    }
+
+    if flow.max_flow(source, sink, infinite) >= infinite {
+        return None;
+    }
+    let reachable = flow.residual_reachable(source);
+    Some(vertices.iter().enumerate()
+        .filter(|&(i, _)| reachable[2 * i] && !reachable[2 * i + 1])
+        .map(|(_, &v)| v)
+        .collect())
+}
+
+/// Connected pieces of `web` that carry exactly `pauli`, each a sorted list of vertices
+///
+/// Only edges labelled `pauli` join vertices; a Y edge does not count as X or Z.
+/// Components are sorted by their smallest vertex.
+pub fn pauli_components(web: &PauliWeb, pauli: Pauli) -> Vec<Vec<V>> {
+    let mut adjacency: HashMap<V, Vec<V>> = HashMap::new();
+    for ((a, b), p) in web.sorted_edges() {
+        if p == pauli {
+            adjacency.entry(a).or_default().push(b);
+            adjacency.entry(b).or_default().push(a);
+        }
+    }
+
+    let mut starts: Vec<V> = adjacency.keys().copied().collect();
+    starts.sort();
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+    for start in starts {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut stack = vec![start];
+        while let Some(v) = stack.pop() {
+            for &n in &adjacency[&v] {
+                if seen.insert(n) {
+                    component.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+    components
+}
+
+/// Directed network with integer capacities, for Edmonds–Karp max flow
+struct FlowNetwork {
+    /// Arcs as (head, residual capacity); arc `i ^ 1` is the reverse of arc `i`
+    arcs: Vec<(usize, usize)>,
+    /// Indices into `arcs` of the arcs leaving each node
+    outgoing: Vec<Vec<usize>>,
+}
+
+impl FlowNetwork {
+    fn new(nodes: usize) -> Self {
+        FlowNetwork { arcs: Vec::new(), outgoing: vec![Vec::new(); nodes] }
+    }
+
+    fn add_arc(&mut self, from: usize, to: usize, capacity: usize) {
+        self.outgoing[from].push(self.arcs.len());
+        self.arcs.push((to, capacity));
+        self.outgoing[to].push(self.arcs.len());
+        self.arcs.push((from, 0));
+    }
+
+    /// Pushes flow along shortest augmenting paths until none is left or `limit` is reached
+    fn max_flow(&mut self, source: usize, sink: usize, limit: usize) -> usize {
+        let mut total = 0;
+        while total < limit {
+            let mut via: Vec<Option<usize>> = vec![None; self.outgoing.len()];
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                for &arc in &self.outgoing[node] {
+                    let (head, capacity) = self.arcs[arc];
+                    if capacity > 0 && head != source && via[head].is_none() {
+                        via[head] = Some(arc);
+                        queue.push_back(head);
+                    }
+                }
+            }
+            if via[sink].is_none() {
+                break;
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+            while let Some(arc) = via[node] {
+                path.push(arc);
+                node = self.arcs[arc ^ 1].0;
+            }
+            let amount = path.iter().map(|&arc| self.arcs[arc].1).min().unwrap().min(limit - total);
+            for arc in path {
+                self.arcs[arc].1 -= amount;
+                self.arcs[arc ^ 1].1 += amount;
+            }
+            total += amount;
+        }
+        total
+    }
+
+    /// Which nodes `source` still reaches through arcs with capacity left
+    fn residual_reachable(&self, source: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.outgoing.len()];
+        reachable[source] = true;
+        let mut stack = vec![source];
+        while let Some(node) = stack.pop() {
+            for &arc in &self.outgoing[node] {
+                let (head, capacity) = self.arcs[arc];
+                if capacity > 0 && !reachable[head] {
+                    reachable[head] = true;
+                    stack.push(head);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_visualizer::{to_dot_with_options, RenderOptions, Style};
+    use quizx::hash_graph::Graph;
+
+    #[test]
+    fn test_paths_cuts_and_components() {
+        // in -- a -- b -- out, with a detour in -- c(X) -- d -- out
+        let mut g = Graph::new();
+        let input = g.add_vertex(VType::B);
+        let a = g.add_vertex(VType::Z);
+        let b = g.add_vertex(VType::Z);
+        let c = g.add_vertex(VType::X);
+        let d = g.add_vertex(VType::Z);
+        let e = g.add_vertex(VType::Z);
+        let output = g.add_vertex(VType::B);
+        g.add_edge(input, a);
+        g.add_edge(a, b);
+        g.add_edge(b, e);
+        g.add_edge(e, output);
+        g.add_edge(input, c);
+        g.add_edge(c, d);
+        g.add_edge(d, output);
+
+        assert_eq!(shortest_path(&g, &[input], &[output], |_| true), Some(vec![input, c, d, output]));
+        assert_eq!(shortest_path(&g, &[input], &[output], |ty| ty == VType::Z), Some(vec![input, a, b, e, output]));
+        assert_eq!(shortest_path(&g, &[input], &[output], |ty| ty == VType::X), None);
+        assert_eq!(shortest_path(&g, &[input], &[input], |_| false), Some(vec![input]));
+
+        assert_eq!(min_vertex_cut(&g, &[input], &[output]), Some(vec![a, c]));
+        // The other way round from a to e goes back through the input
+        assert_eq!(min_vertex_cut(&g, &[a], &[e]), Some(vec![input, b]));
+        assert_eq!(min_vertex_cut(&g, &[a], &[b]), None);
+
+        let mut web = PauliWeb::new();
+        web.set_edge(input, a, Pauli::Z);
+        web.set_edge(a, b, Pauli::Z);
+        web.set_edge(e, output, Pauli::Z);
+        web.set_edge(c, d, Pauli::X);
+        assert_eq!(pauli_components(&web, Pauli::Z), vec![vec![input, a, b], vec![e, output]]);
+        assert_eq!(pauli_components(&web, Pauli::X), vec![vec![c, d]]);
+        assert!(pauli_components(&web, Pauli::Y).is_empty());
+
+        let mut options = RenderOptions::default();
+        let path = shortest_path(&g, &[input], &[output], |_| true).unwrap();
+        options.highlights.add_path(&path, &Style::color("#0066ff"));
+        let dot = to_dot_with_options(&g, None, &options);
+        assert_eq!(dot.matches("color=\"#0066ff\"").count(), 3);
+    }
+}
@@ -0,0 +1,164 @@
+//! Graph-like form, the normal form PyZX's `to_graph_like` produces
+//!
+//! Where [`crate::make_rg::make_rg`] puts a spider of the other colour on every edge
+//! between same-colour spiders, [`make_graph_like`] turns every X spider into a Z spider
+//! by toggling its edges between plain and Hadamard, then fuses the Z spiders joined by
+//! plain edges. That removes spiders rather than adding them, and the result can be
+//! solved with [`crate::detection_webs::get_detection_webs_graph_like`].
+
+use quizx::graph::{EType, GraphLike, VData, VType, V};
+use quizx::hash_graph::Graph;
+use quizx::phase::Phase;
+
+/// Converts `g` in place to graph-like form, returning how many spiders it fused away
+///
+/// Afterwards all spiders are Z spiders, edges between spiders are Hadamard edges, and
+/// each boundary hangs off a spider by a plain edge, with spiders inserted where a
+/// boundary had a Hadamard edge or led straight to another boundary. Parallel Hadamard
+/// edges cancel and a Hadamard self-loop adds π, with scalars ignored. Fails before
+/// changing anything if `g` has vertices other than boundaries and Z and X spiders.
+pub fn make_graph_like(g: &mut Graph) -> Result<usize, String> {
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    if let Some(&v) = vertices.iter().find(|&&v| !matches!(g.vertex_type(v), VType::B | VType::Z | VType::X)) {
+        return Err(format!("Vertex {} is a {:?}; graph-like form only has Z and X spiders", v, g.vertex_type(v)));
+    }
+
+    // X spiders become Z spiders with every leg toggled
+    let x_spiders: Vec<V> = vertices.iter().copied().filter(|&v| g.vertex_type(v) == VType::X).collect();
+    let boundaries: Vec<V> = vertices.iter().copied().filter(|&v| g.vertex_type(v) == VType::B).collect();
+    for v in x_spiders {
+        g.set_vertex_type(v, VType::Z);
+        for n in g.neighbors(v) {
+            let toggled = if g.edge_type(v, n) == EType::H { EType::N } else { EType::H };
+            g.set_edge_type(v, n, toggled);
+        }
+    }
+
+    // Fuse Z spiders along plain edges, the smaller id absorbing the larger
+    let mut fused = 0;
+    for &v in &vertices {
+        if !g.contains_vertex(v) || g.vertex_type(v) != VType::Z {
+            continue;
+        }
+        while let Some(n) = plain_spider_neighbour(g, v) {
+            fuse(g, v, n);
+            fused += 1;
+        }
+    }
+
+    // Boundaries hang off a spider by a plain edge
+    for b in boundaries {
+        let Some(n) = g.neighbors(b).next() else { continue };
+        match (g.vertex_type(n), g.edge_type(b, n)) {
+            (VType::Z, EType::N) => {}
+            (VType::Z, _) => {
+                g.remove_edge(b, n);
+                let z = insert_spider(g, b, n);
+                g.add_edge(b, z);
+                g.add_edge_with_type(z, n, EType::H);
+            }
+            // A wire between two boundaries, handled from its smaller end
+            (_, ety) if b < n => {
+                g.remove_edge(b, n);
+                let first = insert_spider(g, b, n);
+                let last = insert_spider(g, n, b);
+                g.add_edge(b, first);
+                g.add_edge(last, n);
+                if ety == EType::H {
+                    g.add_edge_with_type(first, last, EType::H);
+                } else {
+                    // Two Hadamards in a row make a plain wire
+                    let middle = insert_spider(g, first, last);
+                    g.add_edge_with_type(first, middle, EType::H);
+                    g.add_edge_with_type(middle, last, EType::H);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(fused)
+}
+
+/// The smallest Z spider joined to `v` by a plain edge
+fn plain_spider_neighbour(g: &Graph, v: V) -> Option<V> {
+    g.neighbors(v)
+        .filter(|&n| g.vertex_type(n) == VType::Z && g.edge_type(v, n) == EType::N)
+        .min()
+}
+
+/// Fuses Z spider `b` into its neighbour `a`, adding the phases
+fn fuse(g: &mut Graph, a: V, b: V) {
+    let phase = g.phase(b);
+    g.add_to_phase(a, phase);
+    for n in g.neighbors(b).filter(|&n| n != a) {
+        let ety = g.edge_type(b, n);
+        match (g.edge_type_opt(a, n), ety) {
+            (None, _) => g.add_edge_with_type(a, n, ety),
+            // Plain edges fuse anyway, parallel Hadamard edges cancel
+            (Some(EType::N), EType::N) => {}
+            (Some(EType::H), EType::H) => g.remove_edge(a, n),
+            // The Hadamard edge becomes a self-loop once the plain one is fused
+            _ => {
+                g.set_edge_type(a, n, EType::N);
+                g.add_to_phase(a, Phase::from_f64(1.0));
+            }
+        }
+    }
+    g.remove_vertex(b);
+}
+
+/// A zero-phase Z spider a third of the way from `from` to `to`
+fn insert_spider(g: &mut Graph, from: V, to: V) -> V {
+    let between = |a: f64, b: f64| a + (b - a) / 3.0;
+    let (qubit, row) = (between(g.qubit(from), g.qubit(to)), between(g.row(from), g.row(to)));
+    g.add_vertex_with_data(VData { ty: VType::Z, phase: Default::default(), qubit, row })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use crate::detection_webs::get_detection_webs_graph_like;
+    use crate::make_rg::make_rg;
+    use crate::web_check::is_detection_web;
+
+    #[test]
+    fn test_make_graph_like() {
+        // Z - Z plain edge with an X spider on both: the Z spiders fuse and the two
+        // Hadamard edges to the old X spider cancel
+        let mut g = Graph::new();
+        let z1 = g.add_vertex_with_phase(VType::Z, Phase::from_f64(0.5));
+        let z2 = g.add_vertex_with_phase(VType::Z, Phase::from_f64(0.5));
+        let x = g.add_vertex(VType::X);
+        let (b1, b2, b3, b4) = (g.add_vertex(VType::B), g.add_vertex(VType::B), g.add_vertex(VType::B), g.add_vertex(VType::B));
+        for (a, b) in [(z1, z2), (z1, x), (z2, x), (x, b1), (b2, b3)] {
+            g.add_edge(a, b);
+        }
+        g.add_edge_with_type(z2, b4, EType::H);
+
+        assert_eq!(make_graph_like(&mut g), Ok(1));
+        assert!(!g.contains_vertex(z2));
+        assert_eq!(g.phase(z1), Phase::from_f64(1.0));
+        assert!(!g.connected(z1, x));
+        assert_eq!(g.vertex_type(x), VType::Z);
+        // Every boundary now hangs off a spider by a plain edge
+        for b in [b1, b4, b2, b3] {
+            let n = g.neighbors(b).next().unwrap();
+            assert_eq!((g.degree(b), g.vertex_type(n), g.edge_type(b, n)), (1, VType::Z, EType::N));
+        }
+        let mut h = Graph::new();
+        h.add_vertex(VType::H);
+        assert!(make_graph_like(&mut h).is_err());
+
+        // Fewer vertices than rg form, and webs that check out
+        let mut graph_like = create_steane_rounds(2);
+        let mut rg = graph_like.clone();
+        make_graph_like(&mut graph_like).unwrap();
+        make_rg(&mut rg).unwrap();
+        assert!(graph_like.num_vertices() < rg.num_vertices());
+        let webs = get_detection_webs_graph_like(&graph_like).unwrap();
+        assert!(!webs.is_empty());
+        assert!(webs.iter().all(|web| is_detection_web(&graph_like, web) == Ok(true)));
+    }
+}
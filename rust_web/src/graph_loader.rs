@@ -1,28 +1,446 @@
 use quizx::hash_graph::Graph;
 use quizx::phase::Phase;
-use quizx::graph::{VType, VData};
+use quizx::graph::{EType, VType, VData};
+use crate::logging::LOADER;
 use serde_json::Value;
 use quizx::hash_graph::GraphLike;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
+use crate::detection_webs::classify_boundaries;
+use crate::limits::Limits;
+use crate::tags::{parse_tags, TagValue, Tags};
+use num::{Rational64, Zero};
 
-#[allow(dead_code)] // Remove once used
-pub fn load_graph(path: &str) -> Result<Graph, String> {
+/// How [`load_graph_with_rigging`] marks boundary vertices as inputs and outputs
+///
+/// Files that list their `inputs` and `outputs`, like those written by
+/// [`crate::zxg_export::to_zxg`], keep those lists unless the rigging is `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoundaryRigging {
+    /// Boundaries on the minimal row are inputs, those on the maximal row outputs,
+    /// both sorted by qubit (see [`classify_boundaries`])
+    #[default]
+    ByRow,
+    /// Same as `ByRow` with time running right to left: maximal-row boundaries are inputs
+    ByRowReversed,
+    /// Leave inputs and outputs empty
+    None,
+}
+
+/// Which sanity repairs the loader applies to hand-edited .zxg files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepairOptions {
+    /// Drop undirected edges repeating an earlier edge between the same two vertices;
+    /// when false a duplicate edge is an error
+    pub dedupe_edges: bool,
+    /// Drop wire vertices without any edge; when false they are only reported
+    pub remove_orphans: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        RepairOptions { dedupe_edges: true, remove_orphans: false }
+    }
+}
+
+/// A problem found (and possibly fixed) while loading a .zxg file, named by its JSON keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// Edge `key` joins the same vertices as the earlier edge `duplicate_of` and was dropped
+    DuplicateEdge { key: String, duplicate_of: String, src: String, tgt: String },
+    /// Wire vertex `key` has no edges; `removed` tells whether it was dropped
+    Orphan { key: String, removed: bool },
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::DuplicateEdge { key, duplicate_of, src, tgt } =>
+                write!(f, "Removed edge {} ({} -- {}), a duplicate of {}", key, src, tgt, duplicate_of),
+            Repair::Orphan { key, removed: true } => write!(f, "Removed orphan wire vertex {}", key),
+            Repair::Orphan { key, removed: false } => write!(f, "Wire vertex {} has no edges", key),
+        }
+    }
+}
+
+/// A node vertex whose `data.type` has no quizx counterpart, e.g. a qudit spider
+/// from an experimental ZXLive build
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedVertexType {
+    /// JSON key of the node
+    pub key: String,
+    /// The type as written in the file, JSON-quoted, or `null` if missing
+    pub ty: String,
+}
+
+impl fmt::Display for UnsupportedVertexType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Vertex {} has unsupported type {}; expected one of Z, X, hadamard, Z_box, W_input, W_output",
+            self.key, self.ty
+        )
+    }
+}
+
+/// Why a diagram couldn't be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// A node has a type quizx can't represent
+    UnsupportedVertexType(UnsupportedVertexType),
+    /// The file can't be read, isn't a diagram, or is inconsistent
+    Invalid(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::UnsupportedVertexType(e) => e.fmt(f),
+            LoadError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<UnsupportedVertexType> for LoadError {
+    fn from(e: UnsupportedVertexType) -> Self {
+        LoadError::UnsupportedVertexType(e)
+    }
+}
+
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        LoadError::Invalid(message)
+    }
+}
+
+impl From<&str> for LoadError {
+    fn from(message: &str) -> Self {
+        LoadError::Invalid(message.to_string())
+    }
+}
+
+impl From<LoadError> for String {
+    fn from(e: LoadError) -> Self {
+        e.to_string()
+    }
+}
+
+/// The quizx type of a .zxg node type, as written by PyZX and ZXLive
+pub fn vertex_type(ty: &str) -> Option<VType> {
+    match ty {
+        "Z" => Some(VType::Z),
+        "X" => Some(VType::X),
+        "hadamard" | "H" | "H_box" => Some(VType::H),
+        "Z_box" | "ZBox" => Some(VType::ZBox),
+        "W_input" => Some(VType::WInput),
+        "W_output" => Some(VType::WOutput),
+        _ => None,
+    }
+}
+
+/// Whether a node vertex is ZXLive's stand-in for a Hadamard edge, a hadamard node with
+/// `is_edge` set
+fn is_hadamard_edge(dets: &Value) -> bool {
+    let data = &dets["data"];
+    data["type"] == "hadamard" && (data["is_edge"] == true || data["is_edge"] == "true")
+}
+
+/// Everything [`load_graph_with_options`] can be told
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    pub rigging: BoundaryRigging,
+    pub repair: RepairOptions,
+    /// Checked against the vertex and edge counts of the file before building the graph
+    pub limits: Limits,
+}
+
+/// Loads a .zxg file with inputs and outputs rigged by row
+///
+/// PyZX's native JSON is read too, see [`GraphFormat`]. ZXLive's `is_edge` hadamard
+/// nodes become Hadamard edges; other hadamard nodes stay H-boxes.
+///
+/// If the boundaries can't be split by row (one sits between the input and output rows),
+/// a warning is logged and inputs and outputs stay empty. Use [`load_graph_with_rigging`]
+/// to get an error instead, or to choose another rigging.
+pub fn load_graph(path: &str) -> Result<Graph, LoadError> {
+    load_graph_lenient(path, &LoadOptions::default())
+}
+
+/// Same as [`load_graph`] with the repairs, limits and rigging taken from `options`
+///
+/// A rigging that fails is logged as a warning and leaves inputs and outputs unset.
+pub fn load_graph_lenient(path: &str, options: &LoadOptions) -> Result<Graph, LoadError> {
+    let Parsed { graph, io, .. } = parse_graph(path, options)?;
+    Ok(rig_leniently(graph, io, options.rigging))
+}
+
+/// Loads a .zxg file and sets its inputs and outputs according to `rigging`
+pub fn load_graph_with_rigging(path: &str, rigging: BoundaryRigging) -> Result<Graph, LoadError> {
+    let (graph, _) = load_graph_with_options(path, &LoadOptions { rigging, ..Default::default() })?;
+    Ok(graph)
+}
+
+/// Loads a .zxg file, applying the repairs in `options` and then its rigging
+///
+/// Returns the graph together with every repair made or problem reported, each of which
+/// is also logged as a warning.
+pub fn load_graph_with_options(path: &str, options: &LoadOptions) -> Result<(Graph, Vec<Repair>), LoadError> {
+    let Parsed { mut graph, repairs, io, .. } = parse_graph(path, options)?;
+    rig_boundaries(&mut graph, io, options.rigging)?;
+    Ok((graph, repairs))
+}
+
+/// Same as [`load_graph_with_options`], returning the JSON key of every vertex instead
+/// of the repairs, e.g. to compare results with other tools reading the same file
+pub fn load_graph_with_keys(path: &str, options: &LoadOptions) -> Result<(Graph, HashMap<usize, String>), LoadError> {
+    let Parsed { mut graph, keys, io, .. } = parse_graph(path, options)?;
+    rig_boundaries(&mut graph, io, options.rigging)?;
+    Ok((graph, keys))
+}
+
+/// Third coordinate of each vertex of a 3D spacetime diagram
+///
+/// .zxg coordinates are `[row, qubit]`, or `[row, qubit, t]` for diagrams laid out in
+/// 3D. The graph only holds row and qubit, so `t` is kept alongside, by vertex; vertices
+/// without one are left out.
+pub type TimeCoords = HashMap<usize, f64>;
+
+/// Same as [`load_graph_lenient`], also returning the third coordinate of 3D diagrams
+pub fn load_graph_3d(path: &str, options: &LoadOptions) -> Result<(Graph, TimeCoords), LoadError> {
+    let Parsed { graph, times, io, .. } = parse_graph(path, options)?;
+    Ok((rig_leniently(graph, io, options.rigging), times))
+}
+
+/// Same as [`load_graph_lenient`], also returning the custom annotation fields as
+/// [`Tags`]
+pub fn load_graph_tagged(path: &str, options: &LoadOptions) -> Result<(Graph, Tags), LoadError> {
+    let Parsed { graph, tags, io, .. } = parse_graph(path, options)?;
+    Ok((rig_leniently(graph, io, options.rigging), tags))
+}
+
+/// Same as [`load_graph`] for .zxg contents already in memory, e.g. read from an archive
+pub fn load_graph_from_str(content: &str) -> Result<Graph, LoadError> {
+    load_graph_from_str_lenient(content, &LoadOptions::default())
+}
+
+/// Same as [`load_graph_lenient`] for .zxg contents already in memory
+pub fn load_graph_from_str_lenient(content: &str, options: &LoadOptions) -> Result<Graph, LoadError> {
+    let Parsed { graph, io, .. } = parse_graph_str(content, options)?;
+    Ok(rig_leniently(graph, io, options.rigging))
+}
+
+/// Same as [`load_graph_tagged`] for .zxg contents already in memory
+pub fn load_graph_from_str_tagged(content: &str, options: &LoadOptions) -> Result<(Graph, Tags), LoadError> {
+    let Parsed { graph, tags, io, .. } = parse_graph_str(content, options)?;
+    Ok((rig_leniently(graph, io, options.rigging), tags))
+}
+
+fn rig_leniently(mut graph: Graph, io: Option<Io>, rigging: BoundaryRigging) -> Graph {
+    if let Err(e) = rig_boundaries(&mut graph, io, rigging) {
+        log::warn!(target: LOADER, "{}; leaving inputs and outputs unset", e);
+    }
+    graph
+}
+
+fn rig_boundaries(graph: &mut Graph, io: Option<Io>, rigging: BoundaryRigging) -> Result<(), String> {
+    let (inputs, outputs) = match (rigging, io) {
+        (BoundaryRigging::None, _) => return Ok(()),
+        (_, Some(io)) => io,
+        (BoundaryRigging::ByRow, None) => classify_boundaries(graph)?,
+        (BoundaryRigging::ByRowReversed, None) => {
+            let (inputs, outputs) = classify_boundaries(graph)?;
+            (outputs, inputs)
+        }
+    };
+    graph.set_inputs(inputs);
+    graph.set_outputs(outputs);
+    Ok(())
+}
+
+/// Inputs and outputs, in order
+type Io = (Vec<usize>, Vec<usize>);
+
+/// A graph as read from a .zxg file
+struct Parsed {
+    graph: Graph,
+    repairs: Vec<Repair>,
+    /// JSON key of each vertex
+    keys: HashMap<usize, String>,
+    /// Third coordinate of the vertices that have one
+    times: TimeCoords,
+    /// Custom annotation fields of vertices and edges
+    tags: Tags,
+    /// Inputs and outputs listed in the file, if it lists them
+    io: Option<Io>,
+}
+
+fn parse_graph(path: &str, options: &LoadOptions) -> Result<Parsed, LoadError> {
     // Load as JSON file
     let file_content = match fs::read_to_string(path) {
         Ok(content) => content,
-        Err(e) => return Err(format!("Failed to read file: {}", e)),
+        Err(e) => return Err(format!("Failed to read file: {}", e).into()),
+    };
+    // Quantomatic leaves out empty fields, so a .qgraph may look like a .zxg inside
+    let format = path.ends_with(".qgraph").then_some(GraphFormat::Qgraph);
+    parse_graph_str_as(&file_content, format, options)
+}
+
+/// Edges to add, by the JSON keys of their endpoints, and the repairs made
+type RepairPlan<'a> = (Vec<(&'a str, &'a str)>, Vec<Repair>);
+
+/// Checks the edge list against the vertices, returning the edges to keep and the repairs
+///
+/// Wire vertices left without edges are added to `removed` if `options` asks for it.
+fn plan_repairs<'a>(
+    wire_vertices: &'a serde_json::Map<String, Value>,
+    node_vertices: &serde_json::Map<String, Value>,
+    undir_edges: &'a serde_json::Map<String, Value>,
+    options: &RepairOptions,
+    removed: &mut HashSet<&'a str>,
+) -> Result<RepairPlan<'a>, String> {
+    let mut repairs = Vec::new();
+    let mut kept = Vec::new();
+    let mut seen: HashMap<(&str, &str), &str> = HashMap::new();
+    let mut degree: HashMap<&str, usize> = HashMap::new();
+    for (key, dets) in undir_edges {
+        let endpoint = |field: &str| -> Result<&'a str, String> {
+            let name = dets[field].as_str()
+                .ok_or_else(|| format!("Edge {} has no {} vertex", key, field))?;
+            if !wire_vertices.contains_key(name) && !node_vertices.contains_key(name) {
+                return Err(format!("Edge {} refers to unknown vertex {}", key, name));
+            }
+            Ok(name)
+        };
+        let (src, tgt) = (endpoint("src")?, endpoint("tgt")?);
+        let pair = (src.min(tgt), src.max(tgt));
+        if let Some(&first) = seen.get(&pair) {
+            if !options.dedupe_edges {
+                return Err(format!("Edge {} ({} -- {}) duplicates edge {}", key, src, tgt, first));
+            }
+            repairs.push(Repair::DuplicateEdge {
+                key: key.clone(),
+                duplicate_of: first.to_string(),
+                src: src.to_string(),
+                tgt: tgt.to_string(),
+            });
+            continue;
+        }
+        seen.insert(pair, key);
+        *degree.entry(src).or_default() += 1;
+        *degree.entry(tgt).or_default() += 1;
+        kept.push((src, tgt));
+    }
+    for key in wire_vertices.keys() {
+        if !degree.contains_key(key.as_str()) {
+            if options.remove_orphans {
+                removed.insert(key);
+            }
+            repairs.push(Repair::Orphan { key: key.clone(), removed: options.remove_orphans });
+        }
+    }
+    for repair in &repairs {
+        log::warn!(target: LOADER, "{}", repair);
+    }
+    Ok((kept, repairs))
+}
+
+/// Version of the .zxg files written by [`crate::zxg_export::to_zxg`], stored as
+/// `format_version`
+///
+/// Files without one (hand-drawn in ZXLive, or written by PyZX) are version 0. Bump
+/// this when the written schema changes, and add a step to [`MIGRATIONS`] that brings
+/// files of the previous version up to date.
+pub const FORMAT_VERSION: u64 = zxcore::zxg::FORMAT_VERSION;
+
+/// `MIGRATIONS[i]` turns a version `i` file into a version `i + 1` file
+const MIGRATIONS: [fn(&mut Value); FORMAT_VERSION as usize] = [
+    // Version 1 only added the version field to the ZXLive schema
+    |_| {},
+    // Version 2 added the optional `inputs` and `outputs` lists of wire vertex keys
+    |_| {},
+];
+
+/// Brings a parsed .zxg file up to [`FORMAT_VERSION`]
+fn migrate(data: &mut Value) -> Result<(), String> {
+    let version = match &data["format_version"] {
+        Value::Null => 0,
+        v => v.as_u64().ok_or_else(|| format!("Invalid format_version {}", v))?,
     };
-    
-    let data: Value = match serde_json::from_str(&file_content) {
+    if version > FORMAT_VERSION {
+        return Err(format!(
+            "File has format version {}, newer than the supported {}; update rust_web to load it",
+            version, FORMAT_VERSION
+        ));
+    }
+    for step in &MIGRATIONS[version as usize..] {
+        step(data);
+    }
+    if let Some(object) = data.as_object_mut() {
+        object.insert("format_version".to_string(), FORMAT_VERSION.into());
+    }
+    Ok(())
+}
+
+/// The JSON schemas a graph file can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// ZXLive's .zxg, also PyZX's older `to_json`: `wire_vertices`, `node_vertices` and
+    /// `undir_edges` objects keyed by name
+    Zxg,
+    /// PyZX's `to_json` since 0.8: a `vertices` list of `{"id", "t", "pos", "phase"}`
+    /// objects, an `edges` list of `[source, target, type]` and explicit `inputs` and
+    /// `outputs`
+    PyzxJson,
+    /// Quantomatic's .qgraph, the schema .zxg grew out of, read through
+    /// [`crate::qgraph::qgraph_to_zxg`]
+    Qgraph,
+}
+
+impl GraphFormat {
+    /// The format of parsed graph JSON; anything without PyZX's `vertices` and `edges`
+    /// or Quantomatic's `dir_edges` and `bang_boxes` is read as .zxg
+    pub fn detect(data: &Value) -> Self {
+        if data.get("vertices").is_some() && data.get("edges").is_some() && data.get("node_vertices").is_none() {
+            GraphFormat::PyzxJson
+        } else if data.get("dir_edges").is_some() || data.get("bang_boxes").is_some() {
+            GraphFormat::Qgraph
+        } else {
+            GraphFormat::Zxg
+        }
+    }
+}
+
+fn parse_graph_str(file_content: &str, options: &LoadOptions) -> Result<Parsed, LoadError> {
+    parse_graph_str_as(file_content, None, options)
+}
+
+/// Parses `file_content` in `format`, detecting it if `None`
+fn parse_graph_str_as(file_content: &str, format: Option<GraphFormat>, options: &LoadOptions) -> Result<Parsed, LoadError> {
+    let mut data: Value = match serde_json::from_str(file_content) {
         Ok(json) => json,
-        Err(e) => return Err(format!("Failed to parse JSON: {}", e)),
+        Err(e) => return Err(format!("Failed to parse JSON: {}", e).into()),
     };
+    match format.unwrap_or_else(|| GraphFormat::detect(&data)) {
+        GraphFormat::PyzxJson => return parse_pyzx_json(&data, options),
+        GraphFormat::Qgraph => data = crate::qgraph::qgraph_to_zxg(&data)?,
+        GraphFormat::Zxg => {}
+    }
+    migrate(&mut data)?;
 
     // Verify required JSON structure
     let wire_vertices = data["wire_vertices"].as_object().ok_or("Missing or invalid wire_vertices")?;
     let node_vertices = data["node_vertices"].as_object().ok_or("Missing or invalid node_vertices")?;
-    let _undir_edges = data["undir_edges"].as_object().ok_or("Missing or invalid undir_edges")?;
+    let undir_edges = data["undir_edges"].as_object().ok_or("Missing or invalid undir_edges")?;
+    options.limits.check_graph(wire_vertices.len() + node_vertices.len(), undir_edges.len())?;
+    let variables: HashSet<&str> = data["variable_types"].as_object()
+        .map(|vars| vars.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+
+    let mut removed = HashSet::new();
+    let (edges, repairs) = plan_repairs(wire_vertices, node_vertices, undir_edges, &options.repair, &mut removed)?;
 
     let mut xcods: HashSet<i64> = HashSet::new();
     let mut ycods: HashSet<i64> = HashSet::new();
@@ -35,7 +453,7 @@ pub fn load_graph(path: &str) -> Result<Graph, String> {
                 // Handle boundary vertices with boundary field
                 let boundary = dets["annotation"]["boundary"].as_bool().ok_or("Invalid boundary field")?;
                 if !boundary {
-                    return Err("Invalid boundary vertex format".to_string());
+                    return Err("Invalid boundary vertex format".into());
                 }
                 continue;
             }
@@ -57,6 +475,8 @@ pub fn load_graph(path: &str) -> Result<Graph, String> {
 
     let mut graph = Graph::new();
     let mut id_map = HashMap::new();
+    let mut times = TimeCoords::new();
+    let mut tags = Tags::default();
 
     // Collect coordinates from wire vertices
     for (_node, dets) in wire_vertices {
@@ -89,54 +509,301 @@ pub fn load_graph(path: &str) -> Result<Graph, String> {
 
     // Boundary vertices
     for (node, dets) in data["wire_vertices"].as_object().unwrap() {
+        if removed.contains(node.as_str()) {
+            continue;
+        }
         let coord = dets["annotation"]["coord"].as_array().unwrap();
         let row = coord[0].as_f64().unwrap();
         let qubit = coord[1].as_f64().unwrap();
-        let v_val = dets["data"]["value"].as_f64().unwrap_or(0.0);
         let data: VData = VData {
             ty: VType::B,
-            phase: Phase::from_f64(v_val),
+            phase: vertex_phase(node, dets, &variables)?,
             qubit: qubit,
             row: row,
         };
         let vid = graph.add_vertex_with_data(data);
+        if let Some(t) = coord.get(2) {
+            times.insert(vid, t.as_f64().ok_or_else(|| format!("Vertex {} has invalid t coordinate {}", node, t))?);
+        }
+        let vertex_tags = parse_tags(node, &dets["annotation"]);
+        if !vertex_tags.is_empty() {
+            tags.vertices.insert(vid, vertex_tags);
+        }
         id_map.insert(node.clone(), vid);
     }
 
+    // ZXLive draws a Hadamard edge as an `is_edge` hadamard node between its two ends
+    let hadamard_edges: HashSet<&str> = node_vertices.iter()
+        .filter(|(_, dets)| is_hadamard_edge(dets))
+        .map(|(key, _)| key.as_str())
+        .collect();
+
     // Actual vertices
     for (node, dets) in data["node_vertices"].as_object().unwrap() {
+        if hadamard_edges.contains(node.as_str()) {
+            continue;
+        }
         let coord = dets["annotation"]["coord"].as_array().unwrap();
         let x = (coord[0].as_f64().unwrap() * 1000.0) as i64;
         let y = (coord[1].as_f64().unwrap() * 1000.0) as i64;
         let _row = x_cood_map[&x];
         let _qubit = y_cood_map[&y];
-        let v_val = dets["data"]["value"].as_f64().unwrap_or(0.0);
-        let v_type = match dets["data"]["type"].as_str().unwrap() {
-            "X" => VType::X,
-            "Z" => VType::Z,
-            _ => VType::H,
-        };
+        let v_type = vertex_type(dets["data"]["type"].as_str().unwrap_or(""))
+            .ok_or_else(|| UnsupportedVertexType {
+                key: node.clone(),
+                ty: dets["data"]["type"].to_string(),
+            })?;
         let data: VData = VData {
             ty: v_type,
-            phase: Phase::from_f64(v_val),
+            phase: vertex_phase(node, dets, &variables)?,
             qubit: y_cood_map_f64[&y],
             row: x_cood_map_f64[&x],
         };
         let vid = graph.add_vertex_with_data(data);
+        if let Some(t) = coord.get(2) {
+            times.insert(vid, t.as_f64().ok_or_else(|| format!("Vertex {} has invalid t coordinate {}", node, t))?);
+        }
+        let vertex_tags = parse_tags(node, &dets["annotation"]);
+        if !vertex_tags.is_empty() {
+            tags.vertices.insert(vid, vertex_tags);
+        }
         id_map.insert(node.clone(), vid);
     }
 
     // Edges
-    for (_edge, dets) in data["undir_edges"].as_object().unwrap() {
-        let src = dets["src"].as_str().unwrap();
-        let tgt = dets["tgt"].as_str().unwrap();
-        let src_id = id_map[src];
-        let tgt_id = id_map[tgt];
-        graph.add_edge(src_id, tgt_id);//, ety); for now lets just do simple edges
+    let mut hadamard_ends: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (src, tgt) in edges {
+        match (hadamard_edges.contains(src), hadamard_edges.contains(tgt)) {
+            (false, false) => graph.add_edge(id_map[src], id_map[tgt]),
+            (true, false) => hadamard_ends.entry(src).or_default().push(id_map[tgt]),
+            (false, true) => hadamard_ends.entry(tgt).or_default().push(id_map[src]),
+            (true, true) => return Err(format!("Hadamard edges {} and {} are joined directly", src, tgt).into()),
+        }
+    }
+    let mut hadamard_edges: Vec<&str> = hadamard_edges.into_iter().collect();
+    hadamard_edges.sort();
+    for key in hadamard_edges {
+        match hadamard_ends.get(key).map(Vec::as_slice) {
+            Some(&[a, b]) => graph.add_edge_smart(a, b, EType::H),
+            ends => return Err(format!(
+                "Hadamard edge {} has {} ends, expected 2",
+                key, ends.map_or(0, <[usize]>::len)
+            ).into()),
+        }
+    }
+    // Tags of the kept edges; a duplicate dropped by the repairs doesn't override the first
+    for (key, dets) in undir_edges {
+        let endpoint = |field: &str| dets[field].as_str().and_then(|name| id_map.get(name)).copied();
+        if let (Some(a), Some(b)) = (endpoint("src"), endpoint("tgt")) {
+            let edge_tags = parse_tags(key, &dets["annotation"]);
+            if !edge_tags.is_empty() {
+                tags.edges.entry((a.min(b), a.max(b))).or_insert(edge_tags);
+            }
+        }
     }
 
-    Ok(graph)
-} 
+    let io = listed_io(&data, |key| id_map.get(key).copied())?;
+    let keys = id_map.into_iter().map(|(key, v)| (v, key)).collect();
+    Ok(Parsed { graph, repairs, keys, times, tags, io })
+}
+
+/// The vertices listed in the top-level `inputs` and `outputs` of `data`, looked up by
+/// `vertex`; `None` if the file lists neither
+///
+/// Entries are vertex keys, as strings or, in PyZX's JSON, numbers.
+fn listed_io(data: &Value, vertex: impl Fn(&str) -> Option<usize>) -> Result<Option<Io>, String> {
+    if data.get("inputs").is_none() && data.get("outputs").is_none() {
+        return Ok(None);
+    }
+    let list = |field: &str| -> Result<Vec<usize>, String> {
+        let entries = match &data[field] {
+            Value::Null => return Ok(Vec::new()),
+            Value::Array(entries) => entries,
+            other => return Err(format!("Invalid {} {}, expected a list of vertices", field, other)),
+        };
+        entries.iter().map(|entry| {
+            let key = match entry {
+                Value::String(key) => Some(key.clone()),
+                Value::Number(n) => n.as_u64().map(|n| n.to_string()),
+                _ => None,
+            };
+            key.as_deref().and_then(&vertex).ok_or_else(|| format!("{} {} is not a vertex", field, entry))
+        }).collect()
+    };
+    Ok(Some((list("inputs")?, list("outputs")?)))
+}
+
+/// The quizx type of a PyZX `VertexType` number
+fn pyzx_vertex_type(ty: u64) -> Option<VType> {
+    match ty {
+        0 => Some(VType::B),
+        1 => Some(VType::Z),
+        2 => Some(VType::X),
+        3 => Some(VType::H),
+        4 => Some(VType::WInput),
+        5 => Some(VType::WOutput),
+        6 => Some(VType::ZBox),
+        _ => None,
+    }
+}
+
+/// Reads PyZX's native JSON, see [`GraphFormat::PyzxJson`]
+///
+/// Vertex keys are the PyZX ids. Keys of the vertex `data` become tags, and the
+/// position of each boundary among `inputs` or `outputs` its `input` or `output` tag,
+/// which [`Tags::boundary_roles`] turns back into roles. Hadamard edges keep their type.
+fn parse_pyzx_json(data: &Value, options: &LoadOptions) -> Result<Parsed, LoadError> {
+    let vertices: Vec<&Value> = match &data["vertices"] {
+        Value::Array(vertices) => vertices.iter().collect(),
+        Value::Object(vertices) => vertices.values().collect(),
+        _ => return Err("Invalid vertices, expected a list".into()),
+    };
+    let edges = data["edges"].as_array().ok_or("Invalid edges, expected a list")?;
+    options.limits.check_graph(vertices.len(), edges.len())?;
+    let variables: HashSet<&str> = data["variable_types"].as_object()
+        .map(|vars| vars.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    let id = |value: &Value| match value {
+        Value::Number(n) => n.as_u64().map(|n| n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    };
+
+    let mut graph = Graph::new();
+    let mut id_map: HashMap<String, usize> = HashMap::new();
+    let mut tags = Tags::default();
+    for dets in vertices {
+        let key = id(&dets["id"]).ok_or_else(|| format!("Vertex {} has no valid id", dets))?;
+        let ty = dets["t"].as_u64().and_then(pyzx_vertex_type)
+            .ok_or_else(|| UnsupportedVertexType { key: key.clone(), ty: dets["t"].to_string() })?;
+        let pos = dets["pos"].as_array().ok_or_else(|| format!("Vertex {} has no pos", key))?;
+        let coord = |i: usize| pos.get(i).and_then(Value::as_f64).ok_or_else(|| format!("Vertex {} has invalid pos {}", key, dets["pos"]));
+        let data = VData { ty, phase: phase_value(&key, &dets["phase"], &variables)?, row: coord(0)?, qubit: coord(1)? };
+        let vid = graph.add_vertex_with_data(data);
+        if id_map.insert(key.clone(), vid).is_some() {
+            return Err(format!("Vertex id {} appears twice", key).into());
+        }
+        let vertex_tags = parse_tags(&key, &dets["data"]);
+        if !vertex_tags.is_empty() {
+            tags.vertices.insert(vid, vertex_tags);
+        }
+    }
+
+    for (role, field) in [("input", "inputs"), ("output", "outputs")] {
+        for (i, value) in data[field].as_array().into_iter().flatten().enumerate() {
+            let v = id(value).and_then(|key| id_map.get(&key).copied())
+                .ok_or_else(|| format!("{} {} is not a vertex", field, value))?;
+            tags.vertices.entry(v).or_default().insert(role.to_string(), TagValue::Int(i as i64));
+        }
+    }
+
+    let mut repairs = Vec::new();
+    let mut seen: HashMap<(usize, usize), usize> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        let fields = edge.as_array().filter(|fields| fields.len() >= 2)
+            .ok_or_else(|| format!("Edge {} is {}, expected [source, target, type]", i, edge))?;
+        let endpoint = |j: usize| id(&fields[j]).and_then(|key| id_map.get(&key).copied())
+            .ok_or_else(|| format!("Edge {} refers to unknown vertex {}", i, fields[j]));
+        let (src, tgt) = (endpoint(0)?, endpoint(1)?);
+        let ety = match fields.get(2).map_or(Some(1), Value::as_u64) {
+            Some(1) => EType::N,
+            Some(2) => EType::H,
+            _ => return Err(format!("Edge {} has unsupported type {}; expected 1 (simple) or 2 (Hadamard)", i, fields[2]).into()),
+        };
+        if let Some(&first) = seen.get(&(src.min(tgt), src.max(tgt))) {
+            let (src, tgt) = (fields[0].to_string(), fields[1].to_string());
+            if !options.repair.dedupe_edges {
+                return Err(format!("Edge {} ({} -- {}) duplicates edge {}", i, src, tgt, first).into());
+            }
+            let repair = Repair::DuplicateEdge { key: i.to_string(), duplicate_of: first.to_string(), src, tgt };
+            log::warn!(target: LOADER, "{}", repair);
+            repairs.push(repair);
+            continue;
+        }
+        seen.insert((src.min(tgt), src.max(tgt)), i);
+        graph.add_edge_with_type(src, tgt, ety);
+    }
+
+    let io = listed_io(data, |key| id_map.get(key).copied())?;
+    let keys = id_map.into_iter().map(|(key, v)| (v, key)).collect();
+    Ok(Parsed { graph, repairs, keys, times: TimeCoords::new(), tags, io })
+}
+
+/// Phase of a vertex from its `data.value`, zero if there is none
+///
+/// Symbolic phases using one of the file's `variables` load as zero, since the graph
+/// can't hold them; see [`crate::phase_expr`] for keeping them alongside.
+fn vertex_phase(key: &str, dets: &Value, variables: &HashSet<&str>) -> Result<Phase, String> {
+    phase_value(key, &dets["data"]["value"], variables)
+}
+
+/// Phase of vertex `key` written as `value`, see [`vertex_phase`]
+fn phase_value(key: &str, value: &Value, variables: &HashSet<&str>) -> Result<Phase, String> {
+    match value {
+        Value::Null => Ok(Phase::from_f64(0.0)),
+        Value::Number(n) => Ok(Phase::from_f64(n.as_f64().unwrap_or(0.0))),
+        Value::String(s) => match parse_phase(s) {
+            Ok(phase) => Ok(Phase::from(phase)),
+            Err(_) if s.split(|c: char| !c.is_alphanumeric() && c != '_').any(|word| variables.contains(word)) => {
+                log::debug!(target: LOADER, "Vertex {} has symbolic phase \"{}\", loading it as 0", key, s);
+                Ok(Phase::from_f64(0.0))
+            }
+            Err(e) => Err(format!("Vertex {} has phase \"{}\": {}", key, s, e)),
+        },
+        other => Err(format!("Vertex {} has phase {}, expected a number or a string", key, other)),
+    }
+}
+
+/// Parses a phase written as a string, in units of π
+///
+/// ZXLive and PyZX write phases like `1/4`, `pi/2`, `π/2`, `3π/4`, `3*pi/4`, `-\pi` or
+/// `0.5`; with or without the π they mean the same multiple of π. Decimals are read
+/// exactly, so `0.1` is 1/10 rather than the nearest float. An empty string is zero.
+pub fn parse_phase(s: &str) -> Result<Rational64, String> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let (negative, rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(&s)),
+    };
+    if rest.is_empty() {
+        return if negative { Err("missing number".to_string()) } else { Ok(Rational64::zero()) };
+    }
+
+    let (numerator, denominator) = match rest.split_once('/') {
+        Some((num, den)) => (num, Some(den)),
+        None => (rest, None),
+    };
+    let coefficient = ["\\pi", "pi", "π"].iter()
+        .find_map(|pi| numerator.strip_suffix(pi))
+        .map(|c| c.strip_suffix('*').unwrap_or(c));
+    let value = match coefficient {
+        Some("") => Rational64::from_integer(1),
+        Some(c) => parse_decimal(c)?,
+        None => parse_decimal(numerator)?,
+    };
+    let value = match denominator {
+        None => value,
+        Some(den) => match den.parse::<i64>() {
+            Ok(d) if d > 0 => value / d,
+            _ => return Err(format!("invalid denominator '{}'", den)),
+        },
+    };
+    Ok(if negative { -value } else { value })
+}
+
+/// Reads a decimal like `3`, `0.25` or `.5` exactly
+fn parse_decimal(s: &str) -> Result<Rational64, String> {
+    let invalid = || format!("invalid number '{}'", s);
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) || fraction.len() > 18 {
+        return Err(invalid());
+    }
+    let digits: i64 = format!("{}{}", whole, fraction).parse().map_err(|_| invalid())?;
+    Ok(Rational64::new(digits, 10i64.pow(fraction.len() as u32)))
+}
 
 // Tests
 #[cfg(test)]
@@ -278,6 +945,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_hadamard_edge_nodes() {
+        // b0 - z0 -H- z1 - b1, the Hadamard edge drawn as ZXLive does
+        let zxg = r#"{
+            "wire_vertices": {
+                "b0": { "annotation": { "boundary": true, "coord": [0, 0] } },
+                "b1": { "annotation": { "boundary": true, "coord": [3, 0] } }
+            },
+            "node_vertices": {
+                "z0": { "annotation": { "coord": [1, 0] }, "data": { "type": "Z" } },
+                "h": { "annotation": { "coord": [1.5, 0] }, "data": { "type": "hadamard", "is_edge": "true" } },
+                "z1": { "annotation": { "coord": [2, 0] }, "data": { "type": "Z" } }
+            },
+            "undir_edges": {
+                "e0": { "src": "b0", "tgt": "z0" },
+                "e1": { "src": "z0", "tgt": "h" },
+                "e2": { "src": "h", "tgt": "z1" },
+                "e3": { "src": "z1", "tgt": "b1" }
+            }
+        }"#;
+        let (graph, keys) = parse_graph_str(zxg, &LoadOptions::default()).map(|p| (p.graph, p.keys)).unwrap();
+        assert_eq!((graph.num_vertices(), graph.num_edges()), (4, 3));
+        assert!(graph.vertices().all(|v| graph.vertex_type(v) != VType::H));
+        let vertex = |key: &str| *keys.iter().find(|(_, k)| k.as_str() == key).unwrap().0;
+        assert_eq!(graph.edge_type(vertex("z0"), vertex("z1")), EType::H);
+
+        // Solving it reports an error or webs instead of panicking on the H node
+        assert!(crate::detection_webs::get_detection_webs_lenient(&mut graph.clone(), &Default::default()).is_ok());
+
+        // A Hadamard box that isn't an edge stays a vertex
+        let h_box = zxg.replace(r#", "is_edge": "true""#, "");
+        let graph = parse_graph_str(&h_box, &LoadOptions::default()).unwrap().graph;
+        assert_eq!(graph.vertices().filter(|&v| graph.vertex_type(v) == VType::H).count(), 1);
+
+        let dangling = zxg.replace(r#""e2": { "src": "h", "tgt": "z1" },"#, "");
+        assert!(parse_graph_str(&dangling, &LoadOptions::default()).err().unwrap().to_string().contains("Hadamard edge h has 1 ends"));
+    }
+
     #[test]
     fn test_load_graph_edge_types() {
         let test_json = r#"
@@ -350,6 +1055,245 @@ mod tests {
         load_graph(temp_file.to_str().unwrap()).unwrap();
     }
 
+    #[test]
+    fn test_load_graph_rigging() {
+        // Two qubits listed out of order, plus a boundary between the rows
+        let test_json = r#"
+        {
+            "wire_vertices": {
+                "in1": { "annotation": { "coord": [0, 1] } },
+                "in0": { "annotation": { "coord": [0, 0] } },
+                "out1": { "annotation": { "coord": [2, 1] } },
+                "out0": { "annotation": { "coord": [2, 0] } }
+            },
+            "node_vertices": {
+                "n0": { "annotation": { "coord": [1, 0] }, "data": { "type": "Z" } },
+                "n1": { "annotation": { "coord": [1, 1] }, "data": { "type": "X" } }
+            },
+            "undir_edges": {
+                "e0": { "src": "in0", "tgt": "n0" },
+                "e1": { "src": "n0", "tgt": "out0" },
+                "e2": { "src": "in1", "tgt": "n1" },
+                "e3": { "src": "n1", "tgt": "out1" }
+            }
+        }"#;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp_file = temp_dir.path().join("rigged.zxg");
+        fs::write(&temp_file, test_json).unwrap();
+        let path = temp_file.to_str().unwrap();
+
+        let coords = |g: &Graph, vs: &[usize]| -> Vec<(f64, f64)> {
+            vs.iter().map(|&v| (g.row(v), g.qubit(v))).collect()
+        };
+        let g = load_graph(path).unwrap();
+        assert_eq!(coords(&g, g.inputs()), vec![(0.0, 0.0), (0.0, 1.0)]);
+        assert_eq!(coords(&g, g.outputs()), vec![(2.0, 0.0), (2.0, 1.0)]);
+
+        let g = load_graph_with_rigging(path, BoundaryRigging::ByRowReversed).unwrap();
+        assert_eq!(coords(&g, g.inputs()), vec![(2.0, 0.0), (2.0, 1.0)]);
+        assert_eq!(coords(&g, g.outputs()), vec![(0.0, 0.0), (0.0, 1.0)]);
+
+        let g = load_graph_with_rigging(path, BoundaryRigging::None).unwrap();
+        assert!(g.inputs().is_empty() && g.outputs().is_empty());
+
+        let (g, keys) = load_graph_with_keys(path, &LoadOptions::default()).unwrap();
+        assert_eq!(keys.len(), 6);
+        assert_eq!(keys[&g.inputs()[1]], "in1");
+        assert_eq!(keys[&g.outputs()[0]], "out0");
+
+        // A boundary in the middle row can't be rigged by row
+        let middle = test_json.replace(r#""out0": { "annotation": { "coord": [2, 0] } }"#,
+            r#""out0": { "annotation": { "coord": [1.5, 0] } }"#);
+        fs::write(&temp_file, middle).unwrap();
+        assert!(load_graph_with_rigging(path, BoundaryRigging::ByRow).is_err());
+        let g = load_graph(path).unwrap();
+        assert!(g.inputs().is_empty() && g.outputs().is_empty());
+    }
+
+    #[test]
+    fn test_format_version() {
+        let legacy = r#"{"wire_vertices": {}, "node_vertices": {}, "undir_edges": {}}"#;
+        let mut data: Value = serde_json::from_str(legacy).unwrap();
+        migrate(&mut data).unwrap();
+        assert_eq!(data["format_version"], FORMAT_VERSION);
+        assert!(load_graph_from_str(legacy).is_ok());
+
+        let newer = legacy.replace("{\"wire", &format!("{{\"format_version\": {}, \"wire", FORMAT_VERSION + 1));
+        let err = load_graph_from_str(&newer).unwrap_err().to_string();
+        assert!(err.contains("newer than the supported"), "{}", err);
+        let invalid = legacy.replace("{\"wire", "{\"format_version\": \"one\", \"wire");
+        assert!(load_graph_from_str(&invalid).unwrap_err().to_string().starts_with("Invalid format_version"));
+    }
+
+    #[test]
+    fn test_vertex_types() {
+        let content = |ty: &str| format!(r#"{{
+            "wire_vertices": {{}},
+            "node_vertices": {{
+                "v0": {{"annotation": {{"coord": [0.0, 0.0]}}, "data": {{"type": "Z"}}}},
+                "v1": {{"annotation": {{"coord": [1.0, 0.0]}}, "data": {ty}}}
+            }},
+            "undir_edges": {{"e0": {{"src": "v0", "tgt": "v1"}}}}
+        }}"#);
+        for (ty, expected) in [("hadamard", VType::H), ("Z_box", VType::ZBox), ("W_output", VType::WOutput)] {
+            let g = load_graph_from_str(&content(&format!(r#"{{"type": "{}"}}"#, ty))).unwrap();
+            assert!(g.vertices().any(|v| g.vertex_type(v) == expected), "{}", ty);
+        }
+
+        let err = load_graph_from_str(&content(r#"{"type": "W"}"#)).unwrap_err();
+        assert_eq!(err, LoadError::UnsupportedVertexType(UnsupportedVertexType { key: "v1".to_string(), ty: "\"W\"".to_string() }));
+        assert!(err.to_string().starts_with("Vertex v1 has unsupported type \"W\""), "{}", err);
+        let err = load_graph_from_str(&content("{}")).unwrap_err();
+        assert!(matches!(&err, LoadError::UnsupportedVertexType(e) if e.ty == "null"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_phase() {
+        let r = Rational64::new;
+        for (text, expected) in [
+            ("1/4", r(1, 4)),
+            ("pi/2", r(1, 2)),
+            ("π/2", r(1, 2)),
+            ("\\pi/2", r(1, 2)),
+            ("3π/4", r(3, 4)),
+            ("3*pi/4", r(3, 4)),
+            ("-pi", r(-1, 1)),
+            ("π", r(1, 1)),
+            ("0.5", r(1, 2)),
+            ("0.1", r(1, 10)),
+            (".25", r(1, 4)),
+            (" 7 / 4 ", r(7, 4)),
+            ("", r(0, 1)),
+        ] {
+            assert_eq!(parse_phase(text), Ok(expected), "{:?}", text);
+        }
+        for text in ["alpha", "1/0", "pi/x", "-", "1/-2", "1.2.3"] {
+            assert!(parse_phase(text).is_err(), "{:?}", text);
+        }
+
+        let content = r#"{
+            "wire_vertices": {},
+            "node_vertices": {
+                "v0": {"annotation": {"coord": [0.0, 0.0]}, "data": {"type": "Z", "value": "pi/4"}},
+                "v1": {"annotation": {"coord": [1.0, 0.0]}, "data": {"type": "X", "value": "3/4"}},
+                "v2": {"annotation": {"coord": [2.0, 0.0]}, "data": {"type": "Z", "value": 0.5}}
+            },
+            "undir_edges": {}
+        }"#;
+        let g = load_graph_from_str(content).unwrap();
+        let mut phases: Vec<Phase> = g.vertices().map(|v| g.phase(v)).collect();
+        phases.sort_by(|a, b| a.to_f64().total_cmp(&b.to_f64()));
+        assert_eq!(phases, vec![Phase::from(r(1, 4)), Phase::from_f64(0.5), Phase::from(r(3, 4))]);
+
+        // Declared variables load as zero, anything else unreadable is an error
+        let symbolic = content.replace("\"3/4\"", "\"theta\"");
+        let err = load_graph_from_str(&symbolic).unwrap_err().to_string();
+        assert!(err.contains("Vertex v1 has phase \"theta\""), "{}", err);
+        let declared = symbolic.replace("\"undir_edges\"", "\"variable_types\": {\"theta\": false}, \"undir_edges\"");
+        let g = load_graph_from_str(&declared).unwrap();
+        assert_eq!(g.vertices().filter(|&v| g.phase(v).to_f64() == 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_load_graph_repairs() {
+        let test_json = r#"
+        {
+            "wire_vertices": {
+                "in0": { "annotation": { "coord": [0, 0] } },
+                "out0": { "annotation": { "coord": [2, 0] } },
+                "stray": { "annotation": { "coord": [2, 1] } }
+            },
+            "node_vertices": {
+                "n0": { "annotation": { "coord": [1, 0] }, "data": { "type": "Z" } }
+            },
+            "undir_edges": {
+                "e0": { "src": "in0", "tgt": "n0" },
+                "e1": { "src": "n0", "tgt": "out0" },
+                "e2": { "src": "out0", "tgt": "n0" }
+            }
+        }"#;
+        let temp_dir = tempdir().unwrap();
+        let temp_file = temp_dir.path().join("repair.zxg");
+        fs::write(&temp_file, test_json).unwrap();
+        let path = temp_file.to_str().unwrap();
+        let options = |dedupe_edges, remove_orphans| LoadOptions {
+            rigging: BoundaryRigging::None,
+            repair: RepairOptions { dedupe_edges, remove_orphans },
+            ..Default::default()
+        };
+
+        let (g, repairs) = load_graph_with_options(path, &options(true, false)).unwrap();
+        assert_eq!(g.num_vertices(), 4);
+        assert_eq!(g.num_edges(), 2);
+        assert_eq!(repairs, vec![
+            Repair::DuplicateEdge {
+                key: "e2".to_string(),
+                duplicate_of: "e1".to_string(),
+                src: "out0".to_string(),
+                tgt: "n0".to_string(),
+            },
+            Repair::Orphan { key: "stray".to_string(), removed: false },
+        ]);
+        assert_eq!(repairs[0].to_string(), "Removed edge e2 (out0 -- n0), a duplicate of e1");
+
+        let (g, repairs) = load_graph_with_options(path, &options(true, true)).unwrap();
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(repairs[1].to_string(), "Removed orphan wire vertex stray");
+
+        let err = load_graph_with_options(path, &options(false, false)).unwrap_err().to_string();
+        assert!(err.contains("e2") && err.contains("e1"));
+
+        fs::write(&temp_file, test_json.replace(r#""tgt": "out0""#, r#""tgt": "gone""#)).unwrap();
+        let err = load_graph(path).unwrap_err();
+        assert_eq!(err, LoadError::Invalid("Edge e1 refers to unknown vertex gone".to_string()));
+
+        let tight = LoadOptions {
+            limits: Limits { max_vertices: Some(3), ..Limits::default() },
+            ..Default::default()
+        };
+        let err = load_graph_with_options(path, &tight).unwrap_err().to_string();
+        assert!(err.contains("4 vertices") && err.contains("--allow-huge"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_pyzx_json() {
+        // b0 - z -H- x - b1, as PyZX's to_json writes it, with a duplicated edge
+        let pyzx = serde_json::json!({
+            "version": 2,
+            "backend": "simple",
+            "variable_types": {},
+            "inputs": [0],
+            "outputs": [3],
+            "vertices": [
+                { "id": 0, "t": 0, "pos": [0, 0] },
+                { "id": 1, "t": 1, "pos": [1, 0], "phase": "1/2", "data": { "round": 2 } },
+                { "id": 2, "t": 2, "pos": [2, 0], "phase": "\\pi" },
+                { "id": 3, "t": 0, "pos": [3, 0] }
+            ],
+            "edges": [[0, 1, 1], [1, 2, 2], [2, 3, 1], [2, 1, 2]]
+        });
+        let content = pyzx.to_string();
+        assert_eq!(GraphFormat::detect(&pyzx), GraphFormat::PyzxJson);
+        let (g, tags) = load_graph_from_str_tagged(&content, &LoadOptions::default()).unwrap();
+        let by_row = |row: f64| g.vertices().find(|&v| g.row(v) == row).unwrap();
+        let (b0, z, x, b1) = (by_row(0.0), by_row(1.0), by_row(2.0), by_row(3.0));
+        assert_eq!((g.vertex_type(z), g.vertex_type(x)), (VType::Z, VType::X));
+        assert_eq!((g.phase(z), g.phase(x)), (Phase::from_f64(0.5), Phase::from_f64(1.0)));
+        assert_eq!(g.edge_type(z, x), EType::H);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.inputs(), &vec![b0]);
+        assert_eq!(tags.vertex::<usize>(z, "round"), Ok(Some(2)));
+        let Some(crate::detection_webs::BoundaryRoles::Explicit { inputs, outputs }) = tags.boundary_roles(&g).unwrap() else { panic!() };
+        assert_eq!((inputs, outputs), (vec![b0], vec![b1]));
+
+        let strict = LoadOptions { repair: RepairOptions { dedupe_edges: false, ..Default::default() }, ..Default::default() };
+        assert_eq!(load_graph_from_str_lenient(&content, &strict).unwrap_err().to_string(), "Edge 3 (2 -- 1) duplicates edge 1");
+        let w_edge = content.replace("[2,3,1]", "[2,3,3]");
+        assert!(load_graph_from_str(&w_edge).unwrap_err().to_string().starts_with("Edge 2 has unsupported type 3"));
+        // .zxg files are still read as before
+        assert_eq!(GraphFormat::detect(&serde_json::json!({ "wire_vertices": {}, "node_vertices": {}, "undir_edges": {} })), GraphFormat::Zxg);
+    }
+
     #[test]
     fn test_from_file() {
         // use std::fs;
@@ -357,12 +1301,9 @@ mod tests {
         // No need to import graph_loader since we're already in that module
         use super::*;  // This will import everything from the parent module
 
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
-            .unwrap_or_else(|_| ".".to_string());
-        let path = std::path::Path::new(&manifest_dir)
-            .join("tests")
-            .join("zxgs")
-            .join("xxx_final.zxg");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("xx_stab.zxg");
+        crate::zxg_export::write_zxg(&crate::fixtures::fixture("xx_stab_2_rounds").unwrap(), &path).unwrap();
         
         // Load the graph
         let _g = load_graph(path.to_str().unwrap()).unwrap();
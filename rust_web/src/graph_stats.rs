@@ -0,0 +1,147 @@
+use quizx::graph::{EType, VType};
+use quizx::hash_graph::{Graph, GraphLike};
+use serde::Serialize;
+use crate::phase_gadgets::find_phase_gadgets;
+
+/// Size and resource counts of a diagram
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GraphStats {
+    pub vertices: usize,
+    pub edges: usize,
+    pub hadamard_edges: usize,
+    pub z_spiders: usize,
+    pub x_spiders: usize,
+    pub h_boxes: usize,
+    pub boundaries: usize,
+    /// Z/X spiders by phase, entry k counts phases k·π/4 (mod 2π)
+    pub phase_classes: [usize; 8],
+    /// Z/X spiders whose phase is not a multiple of π/4
+    pub other_phases: usize,
+    /// Phase gadgets, see [`find_phase_gadgets`]
+    pub phase_gadgets: usize,
+}
+
+impl GraphStats {
+    /// Spiders with an odd multiple of π/4, i.e. T-like phases
+    pub fn t_count(&self) -> usize {
+        self.phase_classes.iter().skip(1).step_by(2).sum()
+    }
+
+    /// Spiders with a Clifford phase (multiple of π/2), including phase-free ones
+    pub fn clifford_count(&self) -> usize {
+        self.phase_classes.iter().step_by(2).sum()
+    }
+
+    /// All spiders with a non-Clifford phase
+    pub fn non_clifford_count(&self) -> usize {
+        self.t_count() + self.other_phases
+    }
+}
+
+/// Multiple of π/4 a phase (in units of π) corresponds to, if any
+fn quarter_class(phase: f64) -> Option<usize> {
+    let quarters = phase * 4.0;
+    if (quarters - quarters.round()).abs() < 1e-9 {
+        Some((quarters.round() as i64).rem_euclid(8) as usize)
+    } else {
+        None
+    }
+}
+
+/// Collects vertex, edge and phase statistics of a diagram
+pub fn graph_stats(g: &Graph) -> GraphStats {
+    let mut stats = GraphStats {
+        vertices: g.num_vertices(),
+        edges: g.num_edges(),
+        hadamard_edges: g.edges().filter(|&(_, _, ety)| ety == EType::H).count(),
+        phase_gadgets: find_phase_gadgets(g).len(),
+        ..Default::default()
+    };
+
+    for v in g.vertices() {
+        match g.vertex_type(v) {
+            VType::Z => stats.z_spiders += 1,
+            VType::X => stats.x_spiders += 1,
+            VType::H => stats.h_boxes += 1,
+            VType::B => stats.boundaries += 1,
+            _ => {}
+        }
+        if matches!(g.vertex_type(v), VType::Z | VType::X) {
+            match quarter_class(g.phase(v).to_f64()) {
+                Some(k) => stats.phase_classes[k] += 1,
+                None => stats.other_phases += 1,
+            }
+        }
+    }
+
+    stats
+}
+
+/// Number of Z/X spiders carrying an odd multiple of π/4
+pub fn count_tgates(g: &Graph) -> usize {
+    graph_stats(g).t_count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::VData;
+    use quizx::phase::Phase;
+
+    fn diagram(phases: &[f64]) -> Graph {
+        let mut g = Graph::new();
+        let mut prev = g.add_vertex(VType::B);
+        for (i, &p) in phases.iter().enumerate() {
+            let ty = if i % 2 == 0 { VType::Z } else { VType::X };
+            let v = g.add_vertex_with_data(VData { ty, phase: Phase::from_f64(p), qubit: 0.0, row: i as f64 + 1.0 });
+            g.add_edge(prev, v);
+            prev = v;
+        }
+        let b = g.add_vertex(VType::B);
+        g.add_edge(prev, b);
+        g
+    }
+
+    #[test]
+    fn test_count_tgates() {
+        let g = diagram(&[0.25, 0.5, 1.75, 1.0, 0.0, 0.75]);
+        assert_eq!(count_tgates(&g), 3);
+    }
+
+    #[test]
+    fn test_phase_breakdown() {
+        let g = diagram(&[0.25, 0.5, -0.25, 1.0, 0.0, 0.125]);
+        let stats = graph_stats(&g);
+
+        assert_eq!(stats.vertices, 8);
+        assert_eq!(stats.edges, 7);
+        assert_eq!(stats.z_spiders, 3);
+        assert_eq!(stats.x_spiders, 3);
+        assert_eq!(stats.boundaries, 2);
+        assert_eq!(stats.phase_classes, [1, 1, 1, 0, 1, 0, 0, 1]);
+        assert_eq!(stats.other_phases, 1);
+        assert_eq!(stats.t_count(), 2);
+        assert_eq!(stats.clifford_count(), 3);
+        assert_eq!(stats.non_clifford_count(), 3);
+        assert_eq!(stats.phase_gadgets, 0);
+    }
+
+    #[test]
+    fn test_gadget_count() {
+        // Two π/4 gadgets on the parity of the same pair of spiders
+        let mut g = diagram(&[0.0, 0.0]);
+        let targets: Vec<_> = g.vertices().filter(|&v| g.vertex_type(v) != VType::B).collect();
+        for _ in 0..2 {
+            let hub = g.add_vertex(VType::Z);
+            let leaf = g.add_vertex_with_phase(VType::Z, 0.25);
+            g.add_edge_with_type(hub, leaf, EType::H);
+            for &t in &targets {
+                g.add_edge_with_type(hub, t, EType::H);
+            }
+        }
+
+        let stats = graph_stats(&g);
+        assert_eq!(stats.phase_gadgets, 2);
+        assert_eq!(stats.t_count(), 2);
+    }
+}
@@ -1,13 +1,27 @@
 use std::fs;
-use std::process::Command;
-use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::logging::RENDER;
+use std::process::{Command, Stdio};
+use std::io::Read;
+use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
 use num::{Rational64, FromPrimitive};
 use quizx::graph::GraphLike;
+use crate::layout::{FixedCoords, Layout, LayoutGraph};
+use crate::palette::Palette;
 use crate::pauliweb::PauliWeb;
-use ordered_float::OrderedFloat;
+use crate::phase_gadgets::find_phase_gadgets;
+
+/// Border and edge colour of phase gadgets when [`RenderOptions::phase_gadgets`] is set
+pub(crate) const GADGET_COLOR: &str = "#9900cc";
+
+/// Colour of the vertices and edges in [`RenderOptions::highlights`]
+pub(crate) const HIGHLIGHT_COLOR: &str = "#ff8800";
 
 // Helper function to format phase values with fractional notation when possible
-fn format_phase(phase: f64) -> String {
+pub(crate) fn format_phase(phase: f64) -> String {
     if phase == 0.0 {
         return String::new();
     }
@@ -45,15 +59,243 @@ fn format_phase(phase: f64) -> String {
     }
 }
 
+/// Colour and pen width of an edge carrying `pauli` in a web
+pub(crate) fn web_edge_style(pauli: crate::pauliweb::Pauli, palette: Palette) -> (&'static str, &'static str) {
+    let colors = palette.colors();
+    match pauli {
+        crate::pauliweb::Pauli::X => (colors.web_x, "2.5"),
+        crate::pauliweb::Pauli::Z => (colors.web_z, "2.5"),
+        _ => (colors.web_y, "2.0"),
+    }
+}
+
+/// Options controlling how a graph is rendered to DOT
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Show vertex IDs above the phase label
+    pub show_node_ids: bool,
+    /// Draw a faint horizontal guide line per qubit coordinate, like circuit wires
+    pub qubit_lines: bool,
+    /// Extra text drawn on edges, keyed by (smaller, larger) vertex id
+    pub edge_labels: HashMap<(usize, usize), String>,
+    /// Phase labels replacing the graph's phase, keyed by vertex id, e.g. from
+    /// [`crate::phase_expr::PhaseTable::labels`]
+    pub phase_labels: HashMap<usize, String>,
+    /// Mark phase gadgets: double purple border on hub and leaf, dashed edge between them
+    pub phase_gadgets: bool,
+    /// When PNGs get too big to be useful, see [`plan_png`]
+    pub canvas_limits: CanvasLimits,
+    /// Vertices and edges drawn with a halo, orange unless styled otherwise
+    pub highlights: Highlights,
+    /// How row and qubit coordinates map to positions on the canvas
+    pub layout: FixedCoords,
+    /// Layout used instead of `layout`, e.g. [`crate::layout::Layered`] for graphs whose
+    /// coordinates mean nothing
+    pub custom_layout: Option<Arc<dyn Layout>>,
+    /// Write the Pauli letter on each web edge, so the web reads without colour
+    pub pauli_labels: bool,
+    /// Colours of spiders and web edges
+    pub palette: Palette,
+}
+
+impl RenderOptions {
+    /// The layout vertices are placed by: `custom_layout` if set, otherwise `layout`
+    pub fn active_layout(&self) -> &dyn Layout {
+        match &self.custom_layout {
+            Some(layout) => layout.as_ref(),
+            None => &self.layout,
+        }
+    }
+}
+
+/// How a highlighted vertex or edge is drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// Colour of the halo around a vertex, or of the whole edge
+    pub color: String,
+    /// Pen width in points; `None` for the default of 5 on vertices and 4 on edges
+    pub width: Option<f64>,
+    /// Draw the halo or edge dashed
+    pub dashed: bool,
+    /// Text drawn next to a vertex or on an edge (may be empty)
+    pub note: String,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style { color: HIGHLIGHT_COLOR.to_string(), width: None, dashed: false, note: String::new() }
+    }
+}
+
+impl Style {
+    /// The default orange style with a note
+    pub fn note(note: impl Into<String>) -> Self {
+        Style { note: note.into(), ..Style::default() }
+    }
+
+    /// The default style in another colour
+    pub fn color(color: impl Into<String>) -> Self {
+        Style { color: color.into(), ..Style::default() }
+    }
+}
+
+/// Vertices and edges to draw with a halo, e.g. where a web breaks a rule, a path or
+/// cut through the diagram, or the errors matched by a decoder
+#[derive(Debug, Clone, Default)]
+pub struct Highlights {
+    /// Highlighted vertices
+    pub vertices: HashMap<usize, Style>,
+    /// Highlighted edges, keyed by (smaller, larger) vertex id
+    pub edges: HashMap<(usize, usize), Style>,
+}
+
+impl Highlights {
+    /// Highlights edge (a, b), in either order
+    pub fn add_edge(&mut self, a: usize, b: usize, style: Style) {
+        self.edges.insert((a.min(b), a.max(b)), style);
+    }
+
+    /// Highlights every edge between consecutive vertices of `path`
+    pub fn add_path(&mut self, path: &[usize], style: &Style) {
+        for pair in path.windows(2) {
+            self.add_edge(pair[0], pair[1], style.clone());
+        }
+    }
+
+    fn has_vertex_notes(&self) -> bool {
+        self.vertices.values().any(|style| !style.note.is_empty())
+    }
+}
+
+/// Graphviz's default resolution for bitmap output
+pub const DEFAULT_DPI: f64 = 96.0;
+
+/// Bounds on the PNG canvas of huge graphs
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanvasLimits {
+    /// Largest canvas, in pixels, rendered at [`DEFAULT_DPI`]; above it the DPI is lowered
+    pub max_pixels: u64,
+    /// Lowest DPI worth rendering; if the canvas still doesn't fit, only SVG is produced
+    pub min_dpi: f64,
+}
+
+impl Default for CanvasLimits {
+    fn default() -> Self {
+        CanvasLimits { max_pixels: 25_000_000, min_dpi: 24.0 }
+    }
+}
+
+/// How a graph should be rendered given its expected canvas size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PngPlan {
+    /// Render a PNG at this resolution
+    Png { dpi: f64 },
+    /// Too big for a PNG at any useful resolution
+    SvgOnly,
+}
+
+/// DPI for callers that need a PNG regardless of size, with the default canvas limits
+fn forced_png_dpi<G: GraphLike>(graph: &G) -> f64 {
+    let options = RenderOptions::default();
+    match plan_png(graph, &options) {
+        PngPlan::Png { dpi } => dpi,
+        PngPlan::SvgOnly => options.canvas_limits.min_dpi,
+    }
+}
+
+/// Expected canvas size, in points, of the DOT output for `graph` laid out by `neato -n2`
+///
+/// Covers the vertex positions plus node size and page padding, and the guide line
+/// overhang when [`RenderOptions::qubit_lines`] is set.
+pub fn estimate_canvas_size<G: GraphLike>(graph: &G, options: &RenderOptions) -> (f64, f64) {
+    // Half a 0.6in node plus the 0.5in pad, on each side
+    let border = 2.0 * (0.3 + 0.5) * 72.0;
+
+    let layout_graph = LayoutGraph::new(graph);
+    let layout = options.active_layout();
+    let mut points: Vec<(f64, f64)> = layout.positions(&layout_graph).into_values().collect();
+    if points.is_empty() {
+        return (border, border);
+    }
+    if options.qubit_lines {
+        for (y, x_start, x_end) in layout.qubit_lines(&layout_graph) {
+            points.extend([(x_start, y), (x_end, y)]);
+        }
+    }
+    let (mut min_x, mut max_x) = (f64::MAX, f64::MIN);
+    let (mut min_y, mut max_y) = (f64::MAX, f64::MIN);
+    for (x, y) in points {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    (max_x - min_x + border, max_y - min_y + border)
+}
+
+/// Picks the PNG resolution for `graph` under `options.canvas_limits`
+///
+/// Keeps [`DEFAULT_DPI`] if the canvas fits in `max_pixels`, otherwise scales the DPI down
+/// until it does, and gives up on PNG below `min_dpi`.
+pub fn plan_png<G: GraphLike>(graph: &G, options: &RenderOptions) -> PngPlan {
+    let (width, height) = estimate_canvas_size(graph, options);
+    let limits = &options.canvas_limits;
+    let pixels_at = |dpi: f64| width * height * (dpi / 72.0).powi(2);
+    if pixels_at(DEFAULT_DPI) <= limits.max_pixels as f64 {
+        return PngPlan::Png { dpi: DEFAULT_DPI };
+    }
+    let dpi = 72.0 * (limits.max_pixels as f64 / (width * height)).sqrt();
+    if dpi < limits.min_dpi {
+        PngPlan::SvgOnly
+    } else {
+        PngPlan::Png { dpi: dpi.floor() }
+    }
+}
+
+/// Drop-in for quizx's `GraphLike::to_dot`, which ignores coordinates and colours
+///
+/// Vertices keep their row and qubit positions and are labelled with their IDs, so the
+/// output reads like quizx's. Render it with `neato -n2`.
+pub fn to_dot<G: GraphLike>(graph: &G) -> String {
+    to_dot_with_positions(graph, None, true)
+}
+
+/// Same as [`to_dot`], with `highlights` drawn on top
+pub fn to_dot_highlighted<G: GraphLike>(graph: &G, highlights: &Highlights) -> String {
+    let options = RenderOptions {
+        show_node_ids: true,
+        highlights: highlights.clone(),
+        ..RenderOptions::default()
+    };
+    to_dot_with_options(graph, None, &options)
+}
+
 pub fn to_dot_with_positions<G: GraphLike>(
     graph: &G, 
     pauli_web: Option<&PauliWeb>,
     show_node_ids: bool
+) -> String {
+    let options = RenderOptions {
+        show_node_ids,
+        ..RenderOptions::default()
+    };
+    to_dot_with_options(graph, pauli_web, &options)
+}
+
+/// Same as [`to_dot_with_positions`], with all rendering options explicit
+pub fn to_dot_with_options<G: GraphLike>(
+    graph: &G,
+    pauli_web: Option<&PauliWeb>,
+    options: &RenderOptions,
 ) -> String {
     let mut result = String::new();
     result.push_str("graph G {\n");
     // Set graph properties for better layout
     result.push_str("  graph [splines=true, overlap=false, pad=\"0.5\", nodesep=\"0.5\", ranksep=\"1.0\"];\n");
+    if options.highlights.has_vertex_notes() {
+        // Place highlight notes even where they overlap other nodes
+        result.push_str("  graph [forcelabels=true];\n");
+    }
     
     // Set default node attributes for consistent sizing and appearance
     result.push_str("  node [style=\"filled\", shape=\"circle\", width=\"0.6\", height=\"0.6\", fixedsize=\"true\", \n");
@@ -63,48 +305,115 @@ pub fn to_dot_with_positions<G: GraphLike>(
     // Set default edge style
     result.push_str("  edge [penwidth=2.0, color=\"#666666\"];\n");  // Default edge color is gray
 
-    // Calculate positions and collect vertex info
-    let mut vertices = Vec::new();
-    let mut qubits: HashMap<OrderedFloat<f64>, _> = HashMap::new();
-    let mut min_qubit = f64::MAX;
-    let mut max_qubit = f64::MIN;
-    let mut max_time = 0.0;
+    let styler = Styler::new(graph, pauli_web, options);
+    let layout_graph = LayoutGraph::new(graph);
+    let layout = options.active_layout();
+    let positions = layout.positions(&layout_graph);
+
+    // Guide lines go first so they are drawn underneath the diagram
+    if options.qubit_lines {
+        for (i, (y, x_start, x_end)) in layout.qubit_lines(&layout_graph).into_iter().enumerate() {
+            let y = y.round() as i32;
+            for (end, x) in [("start", x_start), ("end", x_end)] {
+                result.push_str(&format!(
+                    "  qubit_line_{}_{} [pos=\"{},{}!\",shape=point,width=0,height=0,style=invis,label=\"\"]\n",
+                    i, end, x.round() as i32, y
+                ));
+            }
+            result.push_str(&format!(
+                "  qubit_line_{0}_start -- qubit_line_{0}_end [color=\"#cccccc\",style=dashed,penwidth=1.0]\n",
+                i
+            ));
+        }
+    }
+
+    // Vertices in sorted order, so the output is stable across graph backends
+    for &v in &layout_graph.vertices {
+        let (x, y) = positions[&v];
+        let mut attrs = vec![format!("pos=\"{},{}!\"", x.round() as i32, y.round() as i32)];
+        attrs.extend(styler.vertex_attrs(v));
+
+        // Ensure node ID is properly quoted if it contains special characters
+        let node_id = if v.to_string().chars().any(|c| !c.is_ascii_alphanumeric() && c != '_') {
+            format!("\"{}\"", v)
+        } else {
+            v.to_string()
+        };
+        result.push_str(&format!("  {} [{}]\n", node_id, attrs.join(",")));
+    }
 
-    for v in graph.vertices() {
-        let data = graph.vertex_data(v);
-        qubits.entry(OrderedFloat(data.qubit)).or_insert_with(Vec::new).push((v, data.row));
-        min_qubit = f64::min(min_qubit, data.qubit);
-        max_qubit = f64::max(max_qubit, data.qubit);
-        max_time = f64::max(max_time, data.row);
+    for &v in &layout_graph.vertices {
+        for &n in &layout_graph.neighbors[&v] {
+            if v < n {  // Only add each edge once
+                result.push_str(&format!("  {} -- {} [{}]\n", v, n, styler.edge_attrs(v, n).join(",")));
+            }
+        }
     }
 
-    let grid_spacing = 100.0;
-    let time_spacing = grid_spacing * 1.5;
+    result.push_str("}\n");
+    result
+}
+
+/// Node and edge attributes of the DOT output, everything but the positions
+///
+/// [`to_dot_with_options`] pairs it with the positions of the active [`Layout`], so any
+/// layout gets the same styling.
+pub struct Styler<'a, G: GraphLike> {
+    graph: &'a G,
+    web: Option<&'a PauliWeb>,
+    options: &'a RenderOptions,
+    /// Boundary roles, used to draw inputs and outputs differently
+    inputs: HashSet<usize>,
+    outputs: HashSet<usize>,
+    /// Gadget hubs and leaves, and the edges joining them
+    gadget_vertices: HashSet<usize>,
+    gadget_edges: HashSet<(usize, usize)>,
+}
+
+impl<'a, G: GraphLike> Styler<'a, G> {
+    pub fn new(graph: &'a G, web: Option<&'a PauliWeb>, options: &'a RenderOptions) -> Self {
+        let (gadget_vertices, gadget_edges) = if options.phase_gadgets {
+            let gadgets = find_phase_gadgets(graph);
+            (
+                gadgets.iter().flat_map(|g| [g.hub, g.leaf]).collect(),
+                gadgets.iter().map(|g| (g.hub.min(g.leaf), g.hub.max(g.leaf))).collect(),
+            )
+        } else {
+            Default::default()
+        };
+        Styler {
+            graph,
+            web,
+            options,
+            inputs: graph.inputs().iter().copied().collect(),
+            outputs: graph.outputs().iter().copied().collect(),
+            gadget_vertices,
+            gadget_edges,
+        }
+    }
 
-    // Add vertices
-    for v in graph.vertices() {
-        let data = graph.vertex_data(v);
+    /// Attributes of vertex `v`, apart from its position
+    pub fn vertex_attrs(&self, v: usize) -> Vec<String> {
+        let colors = self.options.palette.colors();
+        let data = self.graph.vertex_data(v);
+        let phase_label = || self.options.phase_labels.get(&v).cloned()
+            .unwrap_or_else(|| format_phase(data.phase.to_f64()));
         let (fill_color, border_color, shape, label, font_color) = match data.ty {
             quizx::graph::VType::Z => {
-                let phase_str = format_phase(data.phase.to_f64());
-                let label = if phase_str.is_empty() {
-                    if show_node_ids { v.to_string() } else { String::new() }
-                } else {
-                    phase_str
-                };
-                ("#88ff88", "#000000", "circle", label, "#000000")  // Brighter green fill, black border
+                // The node ID (if requested) is added above the phase below
+                let label = phase_label();
+                (colors.z_spider, "#000000", "circle", label, "#000000")
             },
             quizx::graph::VType::X => {
-                let phase_str = format_phase(data.phase.to_f64());
-                let label = if phase_str.is_empty() {
-                    if show_node_ids { v.to_string() } else { String::new() }
-                } else {
-                    phase_str
-                };
-                ("#ff8888", "#000000", "circle", label, "#000000")  // Brighter red fill, black border
+                // The node ID (if requested) is added above the phase below
+                let label = phase_label();
+                (colors.x_spider, "#000000", "circle", label, "#000000")
             },
             quizx::graph::VType::H => {
-                ("#ffff88", "#000000", "square", String::new(), "#000000")  // Brighter yellow fill, black border
+                (colors.h_box, "#000000", "square", String::new(), "#000000")
+            },
+            quizx::graph::VType::B if self.inputs.contains(&v) => {
+                ("#ffffff", "#000000", "circle", String::from("B"), "#000000")  // Open circle for inputs
             },
             quizx::graph::VType::B => {
                 ("#000000", "#000000", "circle", String::from("B"), "#ffffff")  // Black box with white text
@@ -114,20 +423,18 @@ pub fn to_dot_with_positions<G: GraphLike>(
             },
         };
 
-        let x = (data.row * time_spacing).round() as i32;
-        let y = ((data.qubit - min_qubit) * grid_spacing).round() as i32;
-        let pos = format!("{},{}!", x, y);
-        
         // Create HTML-like label with ID above and phase inside
-        let html_label = if show_node_ids || !label.is_empty() {
-            let id_part = if show_node_ids {
+        let html_label = if self.options.show_node_ids || !label.is_empty() {
+            let id_part = if self.options.show_node_ids {
                 // Escape special characters in node ID
                 let escaped_id = v.to_string()
                     .replace('&', "&amp;")
                     .replace('<', "&lt;")
                     .replace('>', "&gt;")
                     .replace('"', "&quot;");
-                format!("<font point-size='12'>{}</font><br/>", escaped_id)
+                // Only break the line if a phase follows
+                let line_break = if label.is_empty() { "" } else { "<br/>" };
+                format!("<font point-size='12'>{}</font>{}", escaped_id, line_break)
             } else {
                 String::new()
             };
@@ -154,7 +461,6 @@ pub fn to_dot_with_positions<G: GraphLike>(
         }
 
         let mut attrs = vec![
-            format!("pos=\"{}\"", pos),
             format!("shape=\"{}\"", shape),
             format!("fillcolor=\"{}\"", fill_color),
             format!("color=\"{}\"", border_color),
@@ -180,61 +486,168 @@ pub fn to_dot_with_positions<G: GraphLike>(
             attrs.push("width=0.4".to_string());
             attrs.push("height=0.4".to_string());
         }
-        
-        // Ensure node ID is properly quoted if it contains special characters
-        let node_id = if v.to_string().chars().any(|c| !c.is_ascii_alphanumeric() && c != '_') {
-            format!("\"{}\"", v)
-        } else {
-            v.to_string()
-        };
-        vertices.push(format!("  {} [{}]", node_id, attrs.join(",")));
-    }
 
-    // Add vertices to the DOT string
-    for vertex in vertices {
-        result.push_str(&vertex);
-        result.push_str("\n");
+        if self.gadget_vertices.contains(&v) {
+            attrs.push(format!("color=\"{}\"", GADGET_COLOR));
+            attrs.push("peripheries=2".to_string());
+        }
+
+        if let Some(style) = self.options.highlights.vertices.get(&v) {
+            attrs.push(format!("color=\"{}\"", style.color));
+            attrs.push(format!("penwidth={}", style.width.unwrap_or(5.0)));
+            if style.dashed {
+                attrs.push("style=\"filled,dashed\"".to_string());
+            }
+            if !style.note.is_empty() {
+                attrs.push(format!("xlabel=\"{}\"", style.note.replace('"', "\\\"")));
+            }
+        }
+        attrs
     }
 
-    // Add edges with colors based on PauliWeb if provided
-    for v in graph.vertices() {
-        for n in graph.neighbors(v) {
-            if v < n {  // Only add each edge once
-                // Default edge style (black)
-                let mut edge_attrs = vec![
+    /// Attributes of the edge between `a` and `b`, where `a < b`
+    pub fn edge_attrs(&self, a: usize, b: usize) -> Vec<String> {
+        // Default edge style (black)
+        let mut edge_attrs = vec![
+            "len=1.0".to_string(),
+            "penwidth=1.5".to_string(),
+            "color=\"#000000\"".to_string(),
+            "style=solid".to_string()
+        ];
+        
+        // Custom styling for Pauli web edges
+        if let Some(pauli_web) = self.web {
+            if let Some(pauli) = pauli_web.get_edge(a.into(), b.into()) {
+                let (color, penwidth) = web_edge_style(pauli, self.options.palette);
+                
+                // Update edge attributes for Pauli web edges
+                edge_attrs = vec![
                     "len=1.0".to_string(),
-                    "penwidth=1.5".to_string(),
-                    "color=\"#000000\"".to_string(),
-                    "style=solid".to_string()
+                    format!("penwidth={}", penwidth),
+                    format!("color=\"{}\"", color),
+                    "style=bold".to_string()
                 ];
-                
-                // Custom styling for Pauli web edges
-                if let Some(pauli_web) = pauli_web {
-                    if let Some(pauli) = pauli_web.get_edge(v.into(), n.into()) {
-                        let (color, penwidth) = match pauli {
-                            crate::pauliweb::Pauli::X => ("#ff0000", "2.5"),  // Red for X
-                            crate::pauliweb::Pauli::Z => ("#00aa00", "2.5"),  // Green for Z
-                            _ => ("#0000ff", "2.0"),                         // Blue for others
-                        };
-                        
-                        // Update edge attributes for Pauli web edges
-                        edge_attrs = vec![
-                            "len=1.0".to_string(),
-                            format!("penwidth={}", penwidth),
-                            format!("color=\"{}\"", color),
-                            "style=bold".to_string()
-                        ];
-                    }
-                }
-                
-                // Add the edge with final attributes
-                result.push_str(&format!("  {} -- {} [{}]\n", v, n, edge_attrs.join(",")));
             }
         }
+        
+        // Gadget edges keep their web colour, if any, but are always dashed
+        if self.gadget_edges.contains(&(a, b)) {
+            if self.web.and_then(|w| w.get_edge(a, b)).is_none() {
+                edge_attrs.push(format!("color=\"{}\"", GADGET_COLOR));
+            }
+            edge_attrs.push("style=dashed".to_string());
+        }
+
+        // Arrowheads on boundary wires point from inputs into the diagram
+        // and from the diagram into outputs
+        if self.inputs.contains(&a) || self.outputs.contains(&b) {
+            edge_attrs.push("dir=forward".to_string());
+            edge_attrs.push("arrowsize=0.8".to_string());
+        } else if self.inputs.contains(&b) || self.outputs.contains(&a) {
+            edge_attrs.push("dir=back".to_string());
+            edge_attrs.push("arrowsize=0.8".to_string());
+        }
+
+        if let Some(label) = edge_label(self.options, self.web, a, b) {
+            edge_attrs.push(format!("label=\"{}\"", label));
+            edge_attrs.push("fontsize=14".to_string());
+        }
+
+        if let Some(style) = self.options.highlights.edges.get(&(a, b)) {
+            edge_attrs.push(format!("color=\"{}\"", style.color));
+            edge_attrs.push(format!("penwidth={}", style.width.unwrap_or(4.0)));
+            if style.dashed {
+                edge_attrs.push("style=dashed".to_string());
+            }
+        }
+        edge_attrs
     }
+}
 
-    result.push_str("}\n");
-    result
+/// Text drawn on edge (a, b): its entry in `edge_labels`, followed by the Pauli letter
+/// of the web if [`RenderOptions::pauli_labels`] is set and the note of its highlight
+pub(crate) fn edge_label(options: &RenderOptions, web: Option<&PauliWeb>, a: usize, b: usize) -> Option<String> {
+    let letter = web.filter(|_| options.pauli_labels)
+        .and_then(|w| w.get_edge(a, b))
+        .map(|pauli| pauli.to_string());
+    let note = options.highlights.edges.get(&(a, b))
+        .map(|style| style.note.clone())
+        .filter(|note| !note.is_empty());
+    let parts: Vec<String> = [options.edge_labels.get(&(a, b)).cloned(), letter, note]
+        .into_iter()
+        .flatten()
+        .collect();
+    if parts.is_empty() { None } else { Some(parts.join(" ")) }
+}
+
+/// Renders a small graph as ASCII art for logs and test failures
+///
+/// Rows map to columns (scaled to `width` characters) and every unit of qubit
+/// coordinate to two lines. Spiders are drawn as Z/X/H/B, edges as `-`, `|`, `/`
+/// and `\`, with Hadamard edges as `=` and `:` when horizontal or vertical.
+pub fn to_ascii<G: GraphLike>(graph: &G, width: usize) -> String {
+    let mut vertices: Vec<_> = graph.vertices().collect();
+    if vertices.is_empty() {
+        return String::new();
+    }
+    vertices.sort();
+
+    let min_row = vertices.iter().map(|&v| graph.row(v)).fold(f64::MAX, f64::min);
+    let max_row = vertices.iter().map(|&v| graph.row(v)).fold(f64::MIN, f64::max);
+    let min_qubit = vertices.iter().map(|&v| graph.qubit(v)).fold(f64::MAX, f64::min);
+    let max_qubit = vertices.iter().map(|&v| graph.qubit(v)).fold(f64::MIN, f64::max);
+
+    let width = width.max(1);
+    let x_scale = if max_row > min_row { (width - 1) as f64 / (max_row - min_row) } else { 0.0 };
+    let height = ((max_qubit - min_qubit) * 2.0).round() as usize + 1;
+    let cell = |v| {
+        let x = ((graph.row(v) - min_row) * x_scale).round() as usize;
+        let y = ((graph.qubit(v) - min_qubit) * 2.0).round() as usize;
+        (x, y)
+    };
+
+    let mut grid = vec![vec![' '; width]; height];
+
+    let mut edges: Vec<_> = graph.edges().map(|(a, b, ety)| (a.min(b), a.max(b), ety)).collect();
+    edges.sort_by_key(|&(a, b, _)| (a, b));
+    for (a, b, ety) in edges {
+        let (x0, y0) = cell(a);
+        let (x1, y1) = cell(b);
+        let hadamard = ety == quizx::graph::EType::H;
+        let glyph = if y0 == y1 {
+            if hadamard { '=' } else { '-' }
+        } else if x0 == x1 {
+            if hadamard { ':' } else { '|' }
+        } else if (x1 > x0) == (y1 > y0) {
+            '\\'
+        } else {
+            '/'
+        };
+        // Walk the segment one cell at a time along its longer axis
+        let steps = x0.abs_diff(x1).max(y0.abs_diff(y1));
+        for i in 1..steps {
+            let t = i as f64 / steps as f64;
+            let x = (x0 as f64 + (x1 as f64 - x0 as f64) * t).round() as usize;
+            let y = (y0 as f64 + (y1 as f64 - y0 as f64) * t).round() as usize;
+            grid[y][x] = glyph;
+        }
+    }
+
+    for &v in &vertices {
+        let (x, y) = cell(v);
+        grid[y][x] = match graph.vertex_type(v) {
+            quizx::graph::VType::Z => 'Z',
+            quizx::graph::VType::X => 'X',
+            quizx::graph::VType::H => 'H',
+            quizx::graph::VType::B => 'B',
+            _ => '?',
+        };
+    }
+
+    grid.iter()
+        .map(|line| line.iter().collect::<String>().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 pub fn graph_to_png<G: GraphLike>(
@@ -253,21 +666,12 @@ pub fn graph_to_png<G: GraphLike>(
     let dot_string = to_dot_with_positions(graph, pauli_web, show_node_ids);
     
     // Write DOT file
-    fs::write(dot_path, dot_string)?;
-
-    // Call neato to generate PNG
-    let status = Command::new("neato")
-        .args(&["-n2", "-Tpng", dot_path, "-o", png_path])
-        .status()?;
+    fs::write(dot_path, &dot_string)?;
 
-    if status.success() {
-        Ok(())
-    } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "neato command failed",
-        ))
-    }
+    // Call neato to generate PNG, at a lower resolution for huge graphs
+    let dpi = format!("-Gdpi={}", forced_png_dpi(graph));
+    let png = run_graphviz_with_retry("neato", &["-n2", "-Tpng", &dpi], &dot_string, &RetryPolicy::default())?;
+    fs::write(png_path, png)
 }
 
 /// Draw a graph with Pauli web overlaid and save to file
@@ -284,36 +688,283 @@ pub fn draw_graph_with_pauliweb<G: GraphLike>(
     pauli_web: &PauliWeb,
     output_path: &str,
 ) -> Result<(), String> {
-    // Create a temporary DOT file
-    let dot_path = format!("{}.dot", output_path);
-    let dot_content = to_dot_with_positions(graph, Some(pauli_web), false);
-    
-    // Write DOT content to file
-    std::fs::write(&dot_path, dot_content)
-        .map_err(|e| format!("Failed to write DOT file: {}", e))?;
-    
-    // Run Graphviz to generate SVG
-    let output = Command::new("dot")
-        .arg("-Tsvg")
-        .arg(&dot_path)
-        .output()
-        .map_err(|e| format!("Failed to execute dot command: {}", e))?;
-    
-    if !output.status.success() {
-        return Err(format!(
-            "Graphviz failed: {}",
+    // The DOT is piped into Graphviz, so no scratch file is left behind on failure
+    let svg = svg_to_string(graph, pauli_web)?;
+    std::fs::write(output_path, svg)
+        .map_err(|e| format!("Failed to write SVG file: {}", e))
+}
+
+/// Whether the Graphviz `program` is installed, e.g. to fail early where the native
+/// SVG fallback isn't good enough
+pub fn graphviz_available(program: &str) -> bool {
+    Command::new(program)
+        .arg("-V")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Pipes DOT into a Graphviz program and returns what it writes to stdout
+///
+/// A program still running after `timeout` is killed and a `TimedOut` error returned.
+fn run_graphviz(program: &str, args: &[&str], dot: &str, timeout: Option<Duration>) -> std::io::Result<Vec<u8>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let Some(timeout) = timeout else {
+        child.stdin.take()
+            .ok_or_else(|| std::io::Error::other("failed to open stdin"))?
+            .write_all(dot.as_bytes())?;
+        return graphviz_output(program, child.wait_with_output()?);
+    };
+
+    // Feed and drain the pipes on their own threads, so a hung program can't block us
+    let mut stdin = child.stdin.take().ok_or_else(|| std::io::Error::other("failed to open stdin"))?;
+    let dot = dot.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(dot.as_bytes()));
+    let stdout = drain_pipe(child.stdout.take());
+    let stderr = drain_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("{} timed out after {:?} and was killed", program, timeout),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    // A program exiting without reading all its input is judged by its status alone
+    let _ = writer.join();
+    let output = std::process::Output {
+        status,
+        stdout: stdout.join().map_err(|_| std::io::Error::other("failed to read stdout"))?,
+        stderr: stderr.join().map_err(|_| std::io::Error::other("failed to read stderr"))?,
+    };
+    graphviz_output(program, output)
+}
+
+/// Reads a child's pipe to the end on a new thread
+fn drain_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+fn graphviz_output(program: &str, output: std::process::Output) -> std::io::Result<Vec<u8>> {
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(std::io::Error::other(format!(
+            "{} failed: {}",
+            program,
             String::from_utf8_lossy(&output.stderr)
-        ));
+        )))
+    }
+}
+
+/// Same as [`graph_to_png`], writing the PNG to any writer without touching the filesystem
+pub fn png_to_writer<G: GraphLike, W: Write>(
+    graph: &G,
+    pauli_web: Option<&PauliWeb>,
+    show_node_ids: bool,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    writer.write_all(&png_to_vec(graph, pauli_web, show_node_ids)?)
+}
+
+/// Same as [`graph_to_png`], returning the PNG bytes
+///
+/// Graphviz is retried and killed on timeout as in the default [`RetryPolicy`].
+pub fn png_to_vec<G: GraphLike>(
+    graph: &G,
+    pauli_web: Option<&PauliWeb>,
+    show_node_ids: bool,
+) -> std::io::Result<Vec<u8>> {
+    let dot_string = to_dot_with_positions(graph, pauli_web, show_node_ids);
+    let dpi = format!("-Gdpi={}", forced_png_dpi(graph));
+    run_graphviz_with_retry("neato", &["-n2", "-Tpng", &dpi], &dot_string, &RetryPolicy::default())
+}
+
+/// Same as [`draw_graph_with_pauliweb`], writing the SVG to any writer
+pub fn svg_to_writer<G: GraphLike, W: Write>(
+    graph: &G,
+    pauli_web: &PauliWeb,
+    writer: &mut W,
+) -> Result<(), String> {
+    let svg = svg_to_string(graph, pauli_web)?;
+    writer.write_all(svg.as_bytes())
+        .map_err(|e| format!("Failed to write SVG: {}", e))
+}
+
+/// Same as [`draw_graph_with_pauliweb`], returning the SVG document
+///
+/// Graphviz is retried and killed on timeout as in the default [`RetryPolicy`].
+pub fn svg_to_string<G: GraphLike>(graph: &G, pauli_web: &PauliWeb) -> Result<String, String> {
+    let dot_content = to_dot_with_positions(graph, Some(pauli_web), false);
+    let svg = run_graphviz_with_retry("dot", &["-Tsvg"], &dot_content, &RetryPolicy::default())
+        .map_err(|e| format!("Graphviz failed: {}", e))?;
+    String::from_utf8(svg).map_err(|e| format!("Graphviz wrote invalid UTF-8: {}", e))
+}
+
+/// How often a failing Graphviz call is retried before giving up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first
+    pub attempts: u32,
+    /// Wait before the first retry, doubled before each further one
+    pub initial_backoff: Duration,
+    /// Kill a Graphviz process running longer than this
+    pub timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            timeout: Some(Duration::from_secs(120)),
+        }
+    }
+}
+
+/// [`run_graphviz`] with retries. A missing program fails at once, since waiting won't
+/// install it, and so does a timeout, since a layout that hung once hangs again.
+pub(crate) fn run_graphviz_with_retry(
+    program: &str,
+    args: &[&str],
+    dot: &str,
+    policy: &RetryPolicy,
+) -> std::io::Result<Vec<u8>> {
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match run_graphviz(program, args, dot, policy.timeout) {
+            Ok(output) => return Ok(output),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::TimedOut)
+                || attempt >= policy.attempts => return Err(e),
+            Err(e) => {
+                log::warn!(target: RENDER, "{} attempt {}/{} failed: {}; retrying in {:?}", program, attempt, policy.attempts, e, backoff);
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// An image rendered by [`render_image`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderedImage {
+    /// Rendered by Graphviz
+    Png(Vec<u8>),
+    /// Rendered by Graphviz for graphs too large for a PNG, or by the native fallback,
+    /// [`crate::svg_backend::to_svg`]
+    Svg(String),
+}
+
+impl RenderedImage {
+    /// File extension matching the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RenderedImage::Png(_) => "png",
+            RenderedImage::Svg(_) => "svg",
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            RenderedImage::Png(bytes) => bytes,
+            RenderedImage::Svg(svg) => svg.as_bytes(),
+        }
+    }
+
+    /// Writes the image to `path` with its extension replaced by the image format,
+    /// returning the path written
+    pub fn save(&self, path: &Path) -> std::io::Result<PathBuf> {
+        let path = path.with_extension(self.extension());
+        fs::write(&path, self.as_bytes())?;
+        Ok(path)
+    }
+}
+
+/// Renders a PNG with neato, retrying according to `policy`
+///
+/// The resolution follows [`plan_png`]; graphs too large for a PNG are rendered to SVG.
+/// If neato is missing or keeps failing, falls back to the native SVG backend, so batch
+/// jobs still produce an image for every diagram on machines without a working Graphviz.
+pub fn render_image<G: GraphLike>(
+    graph: &G,
+    pauli_web: Option<&PauliWeb>,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> RenderedImage {
+    render_image_reporting(graph, pauli_web, options, policy).0
+}
+
+/// Why [`render_image_reporting`] fell back to the native SVG renderer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderFallback {
+    /// Graphviz was killed after [`RetryPolicy::timeout`]
+    pub timed_out: bool,
+    pub reason: String,
+}
+
+/// Same as [`render_image`], also returning why Graphviz was skipped if it was,
+/// e.g. to summarise the renders that timed out at the end of a batch
+pub fn render_image_reporting<G: GraphLike>(
+    graph: &G,
+    pauli_web: Option<&PauliWeb>,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> (RenderedImage, Option<RenderFallback>) {
+    render_image_with("neato", graph, pauli_web, options, policy)
+}
+
+fn render_image_with<G: GraphLike>(
+    program: &str,
+    graph: &G,
+    pauli_web: Option<&PauliWeb>,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> (RenderedImage, Option<RenderFallback>) {
+    let dot = to_dot_with_options(graph, pauli_web, options);
+    let result = match plan_png(graph, options) {
+        PngPlan::Png { dpi } => {
+            run_graphviz_with_retry(program, &["-n2", "-Tpng", &format!("-Gdpi={}", dpi)], &dot, policy)
+                .map(RenderedImage::Png)
+        }
+        PngPlan::SvgOnly => {
+            log::info!(target: RENDER, "Canvas too large for a PNG; rendering SVG only");
+            run_graphviz_with_retry(program, &["-n2", "-Tsvg"], &dot, policy)
+                .map(|svg| RenderedImage::Svg(String::from_utf8_lossy(&svg).into_owned()))
+        }
+    };
+    match result {
+        Ok(image) => (image, None),
+        Err(e) => {
+            log::warn!(target: RENDER, "{} unavailable ({}); falling back to the native SVG renderer", program, e);
+            let fallback = RenderFallback {
+                timed_out: e.kind() == std::io::ErrorKind::TimedOut,
+                reason: e.to_string(),
+            };
+            (RenderedImage::Svg(crate::svg_backend::to_svg(graph, pauli_web, options)), Some(fallback))
+        }
     }
-    
-    // Write SVG to output file
-    std::fs::write(output_path, &output.stdout)
-        .map_err(|e| format!("Failed to write SVG file: {}", e))?;
-    
-    // Clean up temporary DOT file
-    let _ = std::fs::remove_file(dot_path);
-    
-    Ok(())
 }
 
 #[cfg(test)]
@@ -322,7 +973,100 @@ mod tests {
     use quizx::{hash_graph::Graph, phase::Phase};
     use crate::pauliweb::{Pauli, PauliWeb};
     use std::convert::TryInto;
-    
+
+    type Attrs = HashMap<String, String>;
+
+    /// Minimal parser for the DOT emitted by `to_dot_with_positions`.
+    /// Returns node attributes keyed by node id and edge attributes keyed by (src, tgt).
+    /// Later duplicates of an attribute overwrite earlier ones, as in Graphviz.
+    fn parse_dot(dot: &str) -> (HashMap<String, Attrs>, HashMap<(String, String), Attrs>) {
+        let mut nodes = HashMap::new();
+        let mut edges = HashMap::new();
+        for line in dot.lines().map(str::trim) {
+            let Some(open) = line.find('[') else { continue };
+            if !line.ends_with(']') {
+                continue;
+            }
+            let head = line[..open].trim();
+            if matches!(head, "graph" | "node" | "edge") {
+                continue;
+            }
+            let attrs = parse_attrs(&line[open + 1..line.len() - 1]);
+            match head.split_once("--") {
+                Some((a, b)) => {
+                    edges.insert((a.trim().to_string(), b.trim().to_string()), attrs);
+                }
+                None => {
+                    nodes.insert(head.trim_matches('"').to_string(), attrs);
+                }
+            }
+        }
+        (nodes, edges)
+    }
+
+    /// Split an attribute list on top-level commas, respecting quotes and `<...>` HTML labels
+    fn parse_attrs(list: &str) -> Attrs {
+        let mut attrs = HashMap::new();
+        let mut parts = Vec::new();
+        let (mut depth, mut in_quotes, mut start) = (0i32, false, 0);
+        for (i, c) in list.char_indices() {
+            match c {
+                '"' if depth == 0 => in_quotes = !in_quotes,
+                '<' if !in_quotes => depth += 1,
+                '>' if !in_quotes => depth -= 1,
+                ',' if depth == 0 && !in_quotes => {
+                    parts.push(&list[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(&list[start..]);
+        for part in parts {
+            if let Some((key, value)) = part.split_once('=') {
+                attrs.insert(key.trim().to_string(), value.trim().trim_matches('"').to_string());
+            }
+        }
+        attrs
+    }
+
+    /// Text content of an HTML-like label, one entry per text run between tags
+    fn label_text(attrs: &Attrs) -> Vec<String> {
+        let Some(label) = attrs.get("label") else { return Vec::new() };
+        let mut texts = Vec::new();
+        let mut current = String::new();
+        let mut in_tag = false;
+        for c in label.chars() {
+            match c {
+                '<' => {
+                    in_tag = true;
+                    if !current.trim().is_empty() {
+                        texts.push(current.trim().to_string());
+                    }
+                    current.clear();
+                }
+                '>' => in_tag = false,
+                _ if !in_tag => current.push(c),
+                _ => {}
+            }
+        }
+        texts
+    }
+
+    #[test]
+    fn test_parse_dot_helpers() {
+        let (nodes, edges) = parse_dot(
+            "graph G {\n  node [shape=\"circle\"];\n  \
+             3 [pos=\"1,2!\",label=<<table><tr><td><font>3</font><br/><font>π/2</font></td></tr></table>>,fillcolor=\"#ff8888\"]\n  \
+             3 -- 4 [color=\"#000000\",style=solid]\n}\n",
+        );
+        assert_eq!(nodes["3"]["pos"], "1,2!");
+        assert_eq!(nodes["3"]["fillcolor"], "#ff8888");
+        assert_eq!(label_text(&nodes["3"]), vec!["3", "π/2"]);
+        assert_eq!(edges[&("3".to_string(), "4".to_string())]["style"], "solid");
+        assert!(!nodes.contains_key("node"));
+    }
+
     #[test]
     fn test_draw_graph_simple() -> std::io::Result<()> {
         let mut graph = Graph::new();
@@ -353,41 +1097,29 @@ mod tests {
             false
         )?;
         
-        // Verify the DOT strings
-        let dot_string_no_ids = to_dot_with_positions(&graph, None, false);
-        
-        // Check that node IDs don't appear in the label
-        assert!(
-            !dot_string_no_ids.contains(&format!("\n({})\n", v1)) && 
-            !dot_string_no_ids.contains(&format!("label=\"{}\\n({})\"", "", v1)) &&
-            !dot_string_no_ids.contains(&format!("label=\"{}\\n({})\"", "0", v1)),
-            "Node ID {} should not be in the output. Full output:\n\n{}", v1, dot_string_no_ids
-        );
-        
-        let dot_string_with_ids = to_dot_with_positions(&graph, None, true);
-        
-        // Check that node IDs appear as the label
-        // The format is: label="0" for node 0, or "π\n(1)" for node 1 with phase π
-        assert!(
-            dot_string_with_ids.contains(&format!("label=\"{}\"", v1)) ||
-            dot_string_with_ids.contains(&format!("label=\"0\"")),
-            "Node ID {} not found in DOT output. Full output:\n\n{}", v1, dot_string_with_ids
-        );
-        
-        // For v2, we expect "π\n(1)" as the label
-        assert!(
-            dot_string_with_ids.contains(&format!("label=\"π\
-({})\"", v2)) ||
-            dot_string_with_ids.contains("label=\"π\n(1)\""),
-            "Node ID {} not found in DOT output. Full output:\n\n{}", v2, dot_string_with_ids
-        );
-        
-        // For v3, we expect the node ID as the label
-        assert!(
-            dot_string_with_ids.contains(&format!("label=\"{}\"", v3)) ||
-            dot_string_with_ids.contains("label=\"2\""),
-            "Node ID {} not found in DOT output. Full output:\n\n{}", v3, dot_string_with_ids
-        );
+        // Without IDs, phase-free spiders carry no label and the π spider only its phase
+        let (nodes, _) = parse_dot(&to_dot_with_positions(&graph, None, false));
+        assert!(label_text(&nodes[&v1.to_string()]).is_empty());
+        assert_eq!(label_text(&nodes[&v2.to_string()]), vec!["π"]);
+        assert!(label_text(&nodes[&v3.to_string()]).is_empty());
+
+        // With IDs, the ID sits above the phase inside the HTML table
+        let (nodes, edges) = parse_dot(&to_dot_with_positions(&graph, None, true));
+        assert_eq!(label_text(&nodes[&v1.to_string()]), vec![v1.to_string()]);
+        assert_eq!(label_text(&nodes[&v2.to_string()]), vec![v2.to_string(), "π".to_string()]);
+        assert_eq!(label_text(&nodes[&v3.to_string()]), vec![v3.to_string()]);
+
+        // Spider colors
+        assert_eq!(nodes[&v1.to_string()]["fillcolor"], "#88ff88");
+        assert_eq!(nodes[&v2.to_string()]["fillcolor"], "#ff8888");
+        assert_eq!(nodes[&v3.to_string()]["fillcolor"], "#88ff88");
+
+        // Plain edges are black and solid
+        assert_eq!(edges.len(), 2);
+        for attrs in edges.values() {
+            assert_eq!(attrs["color"], "#000000");
+            assert_eq!(attrs["style"], "solid");
+        }
         
         Ok(())
     }
@@ -427,71 +1159,411 @@ mod tests {
             false
         )?;
         
-        // Verify the DOT strings
-        let dot_string_no_ids = to_dot_with_positions(&g, Some(&pauli_web), false);
-        
-        // Check that node IDs don't appear in the label
-        assert!(
-            !dot_string_no_ids.contains(&format!("\n({})\n", v1)) && 
-            !dot_string_no_ids.contains(&format!("label=\"{}\\n({})\"", "", v1)) &&
-            !dot_string_no_ids.contains(&format!("label=\"{}\\n({})\"", "0", v1)),
-            "Node ID {} should not be in the output. Full output:\n\n{}", v1, dot_string_no_ids
-        );
-        
-        let dot_string_with_ids = to_dot_with_positions(&g, Some(&pauli_web), true);
-        
-        // Check that node IDs appear as the label
-        // The format is: label="0" for node 0
-        assert!(
-            dot_string_with_ids.contains(&format!("label=\"{}\"", v1)) ||
-            dot_string_with_ids.contains("label=\"0\""),
-            "Node ID {} not found in DOT output. Full output:\n\n{}", v1, dot_string_with_ids
-        );
-        
-        // Check for red color in the output (in node or edge attributes)
-        assert!(
-            dot_string_with_ids.contains("color=red") ||
-            dot_string_with_ids.contains("color=\"red\"") ||
-            dot_string_with_ids.contains("0 -- 1 [color=red]") ||
-            dot_string_with_ids.contains("0 -- 1 [color=\"red\"]"),
-            "Red color not found in DOT output. Full output:\n\n{}", 
-            dot_string_with_ids
-        );
-        
-        // Check for green color in the output (in node or edge attributes)
-        assert!(
-            dot_string_with_ids.contains("color=green") ||
-            dot_string_with_ids.contains("color=\"green\"") ||
-            dot_string_with_ids.contains("1 -- 2 [color=green]") ||
-            dot_string_with_ids.contains("1 -- 2 [color=\"green\"]"),
-            "Green color not found in DOT output. Full output:\n\n{}",
-            dot_string_with_ids
-        );
-        
-        // Check that node 0 is green
-        assert!(
-            dot_string_with_ids.contains("0 [color=green") ||
-            dot_string_with_ids.contains("0 [color=\"green\""),
-            "Node 0 should be green. Full output:\n\n{}",
-            dot_string_with_ids
-        );
-        
-        // Check that node 1 is green
-        assert!(
-            dot_string_with_ids.contains("1 [color=green") ||
-            dot_string_with_ids.contains("1 [color=\"green\""),
-            "Node 1 should be green. Full output:\n\n{}",
-            dot_string_with_ids
-        );
-        
-        // Check that node 2 is green
-        assert!(
-            dot_string_with_ids.contains("2 [color=green") ||
-            dot_string_with_ids.contains("2 [color=\"green\""),
-            "Node 2 should be green. Full output:\n\n{}",
-            dot_string_with_ids
-        );
+        let (nodes, _) = parse_dot(&to_dot_with_positions(&g, Some(&pauli_web), false));
+        assert!(nodes.values().all(|attrs| !attrs.contains_key("label")));
+
+        let (nodes, edges) = parse_dot(&to_dot_with_positions(&g, Some(&pauli_web), true));
+        assert_eq!(label_text(&nodes[&v1.to_string()]), vec![v1.to_string()]);
+
+        // All nodes are Z spiders, the web does not recolor them
+        for v in [v1, v2, v3] {
+            assert_eq!(nodes[&v.to_string()]["fillcolor"], "#88ff88");
+        }
+
+        // X edge is red, Z edge is green, both drawn bold
+        let x_edge = &edges[&(v1.to_string(), v2.to_string())];
+        assert_eq!(x_edge["color"], "#ff0000");
+        assert_eq!(x_edge["style"], "bold");
+        let z_edge = &edges[&(v2.to_string(), v3.to_string())];
+        assert_eq!(z_edge["color"], "#00aa00");
+        assert_eq!(z_edge["style"], "bold");
+        assert!(!x_edge.contains_key("label"));
+        let mut y_web = pauli_web.clone();
+        y_web.set_edge(v1, v2, Pauli::Y);
+        let (_, edges) = parse_dot(&to_dot_with_positions(&g, Some(&y_web), false));
+        assert_eq!(edges[&(v1.to_string(), v2.to_string())]["color"], "#0000ff");
+
+        // Pauli letters for readers who can't tell the colours apart
+        let mut options = RenderOptions { pauli_labels: true, ..RenderOptions::default() };
+        options.edge_labels.insert((v2, v3), "e".to_string());
+        let (_, edges) = parse_dot(&to_dot_with_options(&g, Some(&pauli_web), &options));
+        assert_eq!(edges[&(v1.to_string(), v2.to_string())]["label"], "X");
+        assert_eq!(edges[&(v2.to_string(), v3.to_string())]["label"], "e Z");
+        let svg = crate::svg_backend::to_svg(&g, Some(&pauli_web), &options);
+        assert!(svg.contains(">X</text>") && svg.contains(">e Z</text>"));
+
+        let options = RenderOptions { palette: Palette::OkabeIto, ..RenderOptions::default() };
+        let (nodes, edges) = parse_dot(&to_dot_with_options(&g, Some(&pauli_web), &options));
+        assert_eq!(nodes[&v1.to_string()]["fillcolor"], "#80ceb9");
+        assert_eq!(edges[&(v1.to_string(), v2.to_string())]["color"], "#d55e00");
+        let svg = crate::svg_backend::to_svg(&g, Some(&pauli_web), &options);
+        assert!(svg.contains("fill=\"#80ceb9\"") && svg.contains("stroke=\"#009e73\""));
         
         Ok(())
     }
+
+    #[test]
+    fn test_render_to_memory() {
+        let mut g = Graph::new();
+        let v1 = g.add_vertex(quizx::graph::VType::Z);
+        let v2 = g.add_vertex(quizx::graph::VType::X);
+        g.add_edge(v1, v2);
+        let mut pauli_web = PauliWeb::new();
+        pauli_web.set_edge(v1, v2, Pauli::Z);
+
+        let png = png_to_vec(&g, Some(&pauli_web), false).unwrap();
+        assert!(!png.is_empty());
+        let mut buffer = Vec::new();
+        png_to_writer(&g, None, true, &mut buffer).unwrap();
+        assert!(!buffer.is_empty());
+
+        let svg = svg_to_string(&g, &pauli_web).unwrap();
+        assert!(svg.contains("<svg"));
+        let mut buffer = Vec::new();
+        svg_to_writer(&g, &pauli_web, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), svg);
+    }
+
+    #[test]
+    fn test_canvas_plan() {
+        let mut g = Graph::new();
+        let a = g.add_vertex(quizx::graph::VType::Z);
+        let b = g.add_vertex(quizx::graph::VType::Z);
+        g.set_row(b, 10.0);
+        g.set_qubit(b, 2.0);
+        g.add_edge(a, b);
+
+        let options = RenderOptions::default();
+        assert_eq!(estimate_canvas_size(&g, &options), (1500.0 + 115.2, 200.0 + 115.2));
+        let lines = RenderOptions { qubit_lines: true, ..RenderOptions::default() };
+        assert_eq!(estimate_canvas_size(&g, &lines).0, 1650.0 + 115.2);
+        assert_eq!(plan_png(&g, &options), PngPlan::Png { dpi: DEFAULT_DPI });
+
+        // A 1000-row chain would be about 200k by 420 pixels at full resolution
+        g.set_row(b, 1000.0);
+        let PngPlan::Png { dpi } = plan_png(&g, &options) else { panic!("expected a PNG") };
+        assert!(dpi < DEFAULT_DPI && dpi >= options.canvas_limits.min_dpi);
+        let (width, height) = estimate_canvas_size(&g, &options);
+        assert!(width * height * (dpi / 72.0).powi(2) <= options.canvas_limits.max_pixels as f64);
+
+        let strict = RenderOptions {
+            canvas_limits: CanvasLimits { max_pixels: 1_000_000, min_dpi: 24.0 },
+            ..RenderOptions::default()
+        };
+        assert_eq!(plan_png(&g, &strict), PngPlan::SvgOnly);
+        let (RenderedImage::Svg(_), _) = render_image_with("no-such-graphviz", &g, None, &strict, &RetryPolicy::default())
+            else { panic!("expected SVG") };
+    }
+
+    #[test]
+    fn test_retry_and_fallback() {
+        let mut g = Graph::new();
+        let z = g.add_vertex(quizx::graph::VType::Z);
+        let x = g.add_vertex(quizx::graph::VType::X);
+        g.add_edge(z, x);
+        let policy = RetryPolicy { attempts: 3, initial_backoff: Duration::ZERO, timeout: None };
+
+        // A missing program isn't retried
+        let err = run_graphviz_with_retry("no-such-graphviz", &[], "", &policy).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(run_graphviz_with_retry("false", &[], "", &policy).is_err());
+
+        let (image, fallback) = render_image_with("no-such-graphviz", &g, None, &RenderOptions::default(), &policy);
+        let RenderedImage::Svg(svg) = &image else { panic!("expected the SVG fallback") };
+        assert!(svg.contains("<svg"));
+        assert!(!fallback.unwrap().timed_out);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = image.save(&dir.path().join("graph.png")).unwrap();
+        assert_eq!(path.extension().unwrap(), "svg");
+        assert_eq!(fs::read_to_string(path).unwrap(), *svg);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_graphviz_timeout() {
+        // `sleep` stands in for a layout that never finishes
+        let policy = RetryPolicy {
+            attempts: 3,
+            initial_backoff: Duration::ZERO,
+            timeout: Some(Duration::from_millis(100)),
+        };
+        let start = Instant::now();
+        let err = run_graphviz_with_retry("sleep", &["10"], "", &policy).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        // Killed after the first attempt, without retries
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        assert_eq!(run_graphviz("cat", &[], "digraph {}", policy.timeout).unwrap(), b"digraph {}");
+        assert!(run_graphviz("false", &[], "", policy.timeout).is_err());
+
+        // A fake neato hanging whatever its arguments
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let hang = dir.path().join("hang");
+        fs::write(&hang, "#!/bin/sh\nexec sleep 10\n").unwrap();
+        fs::set_permissions(&hang, fs::Permissions::from_mode(0o755)).unwrap();
+        let mut g = Graph::new();
+        g.add_vertex(quizx::graph::VType::Z);
+        let (image, fallback) = render_image_with(hang.to_str().unwrap(), &g, None, &RenderOptions::default(), &policy);
+        assert_eq!(image.extension(), "svg");
+        assert!(fallback.unwrap().timed_out);
+    }
+
+    #[test]
+    fn test_phase_gadget_style() {
+        let mut g = Graph::new();
+        let q0 = g.add_vertex(quizx::graph::VType::Z);
+        let q1 = g.add_vertex(quizx::graph::VType::Z);
+        let hub = g.add_vertex(quizx::graph::VType::Z);
+        let leaf = g.add_vertex_with_phase(quizx::graph::VType::Z, Phase::from(0.25));
+        g.add_edge_with_type(q0, hub, quizx::graph::EType::H);
+        g.add_edge_with_type(q1, hub, quizx::graph::EType::H);
+        g.add_edge_with_type(hub, leaf, quizx::graph::EType::H);
+
+        let (nodes, edges) = parse_dot(&to_dot_with_positions(&g, None, false));
+        assert!(!nodes[&hub.to_string()].contains_key("peripheries"));
+
+        let options = RenderOptions { phase_gadgets: true, ..RenderOptions::default() };
+        let (nodes, gadget_edges) = parse_dot(&to_dot_with_options(&g, None, &options));
+        for v in [hub, leaf] {
+            assert_eq!(nodes[&v.to_string()]["peripheries"], "2");
+            assert_eq!(nodes[&v.to_string()]["color"], GADGET_COLOR);
+        }
+        assert!(!nodes[&q0.to_string()].contains_key("peripheries"));
+        let gadget_edge = &gadget_edges[&(hub.to_string(), leaf.to_string())];
+        assert_eq!(gadget_edge["style"], "dashed");
+        assert_eq!(gadget_edge["color"], GADGET_COLOR);
+        // Other edges are drawn as usual
+        let q0_edge = (q0.to_string(), hub.to_string());
+        assert_eq!(gadget_edges[&q0_edge], edges[&q0_edge]);
+    }
+
+    #[test]
+    fn test_symbolic_phase_labels() {
+        use crate::phase_expr::{PhaseExpr, PhaseTable};
+
+        let mut g = Graph::new();
+        let z = g.add_vertex(quizx::graph::VType::Z);
+        let x = g.add_vertex_with_phase(quizx::graph::VType::X, Phase::from(0.5));
+        g.add_edge(z, x);
+        let mut table = PhaseTable::new();
+        table.set_phase(&mut g, z, PhaseExpr::param("θ") + PhaseExpr::from(Rational64::new(1, 4)));
+
+        let options = RenderOptions { phase_labels: table.labels(), ..RenderOptions::default() };
+        let (nodes, _) = parse_dot(&to_dot_with_options(&g, None, &options));
+        assert_eq!(label_text(&nodes[&z.to_string()]), vec!["θ + π/4"]);
+        assert_eq!(label_text(&nodes[&x.to_string()]), vec!["π/2"]);
+    }
+
+    #[test]
+    fn test_highlights() {
+        let mut g = Graph::new();
+        let z = g.add_vertex(quizx::graph::VType::Z);
+        let x = g.add_vertex(quizx::graph::VType::X);
+        let z2 = g.add_vertex(quizx::graph::VType::Z);
+        g.add_edge(z, x);
+        g.add_edge(x, z2);
+
+        let mut options = RenderOptions::default();
+        options.highlights.vertices.insert(x, Style::note("odd parity"));
+        options.highlights.vertices.insert(z2, Style::default());
+        options.highlights.add_edge(x, z, Style::default());
+        let dot = to_dot_with_options(&g, None, &options);
+        assert!(dot.contains("forcelabels=true"));
+        let (nodes, edges) = parse_dot(&dot);
+        for v in [x, z2] {
+            assert_eq!(nodes[&v.to_string()]["color"], HIGHLIGHT_COLOR);
+            assert_eq!(nodes[&v.to_string()]["penwidth"], "5");
+        }
+        assert_eq!(nodes[&x.to_string()]["xlabel"], "odd parity");
+        assert!(!nodes[&z2.to_string()].contains_key("xlabel"));
+        assert_eq!(nodes[&z.to_string()]["color"], "#000000");
+        assert_eq!(edges[&(z.to_string(), x.to_string())]["color"], HIGHLIGHT_COLOR);
+        assert_eq!(edges[&(x.to_string(), z2.to_string())]["color"], "#000000");
+
+        // Empty notes alone don't need forced labels
+        options.highlights.vertices.remove(&x);
+        assert!(!to_dot_with_options(&g, None, &options).contains("forcelabels"));
+
+        // Caller-styled path, e.g. a decoder's matching
+        let mut options = RenderOptions::default();
+        let matched = Style { width: Some(2.5), dashed: true, note: "m1".to_string(), ..Style::color("#0066ff") };
+        options.highlights.add_path(&[z2, x, z], &matched);
+        options.highlights.vertices.insert(z, Style { dashed: true, ..Style::color("#0066ff") });
+        let (nodes, edges) = parse_dot(&to_dot_with_options(&g, None, &options));
+        for edge in [(z, x), (x, z2)] {
+            let attrs = &edges[&(edge.0.to_string(), edge.1.to_string())];
+            assert_eq!(attrs["color"], "#0066ff");
+            assert_eq!(attrs["penwidth"], "2.5");
+            assert_eq!(attrs["style"], "dashed");
+            assert_eq!(attrs["label"], "m1");
+        }
+        assert_eq!(nodes[&z.to_string()]["color"], "#0066ff");
+        assert_eq!(nodes[&z.to_string()]["style"], "filled,dashed");
+        assert_eq!(nodes[&z.to_string()]["penwidth"], "5");
+    }
+
+    #[test]
+    fn test_h_box_and_boundary_nodes() {
+        let mut g = Graph::new();
+        let b = g.add_vertex(quizx::graph::VType::B);
+        let h = g.add_vertex(quizx::graph::VType::H);
+        let z = g.add_vertex_with_phase(quizx::graph::VType::Z, Phase::from(0.5));
+        g.add_edge(b, h);
+        g.add_edge(h, z);
+
+        let (nodes, edges) = parse_dot(&to_dot_with_positions(&g, None, false));
+
+        // Boundaries are black with a white "B"
+        let boundary = &nodes[&b.to_string()];
+        assert_eq!(boundary["fillcolor"], "#000000");
+        assert_eq!(boundary["fontcolor"], "#ffffff");
+        assert_eq!(boundary["shape"], "circle");
+        assert_eq!(label_text(boundary), vec!["B"]);
+
+        // H-boxes are small yellow squares without a label
+        let hbox = &nodes[&h.to_string()];
+        assert_eq!(hbox["fillcolor"], "#ffff88");
+        assert_eq!(hbox["shape"], "square");
+        assert_eq!(hbox["width"], "0.4");
+        assert_eq!(hbox["height"], "0.4");
+        assert!(label_text(hbox).is_empty());
+
+        assert_eq!(label_text(&nodes[&z.to_string()]), vec!["π/2"]);
+        assert_eq!(edges.len(), 2);
+    }
+
+    #[test]
+    fn test_input_output_boundaries() {
+        let mut g = Graph::new();
+        let b_in = g.add_vertex(quizx::graph::VType::B);
+        let z = g.add_vertex(quizx::graph::VType::Z);
+        let b_out = g.add_vertex(quizx::graph::VType::B);
+        let b_other = g.add_vertex(quizx::graph::VType::B);
+        g.add_edge(b_in, z);
+        g.add_edge(z, b_out);
+        g.add_edge(z, b_other);
+        g.set_inputs(vec![b_in]);
+        g.set_outputs(vec![b_out]);
+
+        let (nodes, edges) = parse_dot(&to_dot_with_positions(&g, None, false));
+
+        // Inputs are open circles, outputs stay filled
+        assert_eq!(nodes[&b_in.to_string()]["fillcolor"], "#ffffff");
+        assert_eq!(nodes[&b_in.to_string()]["fontcolor"], "#000000");
+        assert_eq!(nodes[&b_out.to_string()]["fillcolor"], "#000000");
+        assert_eq!(nodes[&b_other.to_string()]["fillcolor"], "#000000");
+
+        // Arrows run input -> diagram -> output; unassigned boundaries get none
+        assert_eq!(edges[&(b_in.to_string(), z.to_string())]["dir"], "forward");
+        assert_eq!(edges[&(z.to_string(), b_out.to_string())]["dir"], "forward");
+        assert!(!edges[&(z.to_string(), b_other.to_string())].contains_key("dir"));
+    }
+
+    #[test]
+    fn test_layout_options() {
+        // Tall and thin: three rows across twenty qubits
+        let mut g = Graph::new();
+        let a = g.add_vertex(quizx::graph::VType::Z);
+        let b = g.add_vertex(quizx::graph::VType::X);
+        g.set_row(b, 2.0);
+        g.set_qubit(b, 20.0);
+        g.add_edge(a, b);
+
+        let (nodes, _) = parse_dot(&to_dot_with_positions(&g, None, false));
+        assert_eq!(nodes[&b.to_string()]["pos"], "300,2000!");
+
+        let layout = FixedCoords { row_spacing: 50.0, qubit_spacing: 20.0, flip_y: true, ..FixedCoords::default() };
+        let options = RenderOptions { layout, ..RenderOptions::default() };
+        let (nodes, _) = parse_dot(&to_dot_with_options(&g, None, &options));
+        assert_eq!(nodes[&a.to_string()]["pos"], "0,400!");
+        assert_eq!(nodes[&b.to_string()]["pos"], "100,0!");
+
+        // At most 2:1, so the 2000pt tall diagram shrinks to 600pt next to its 300pt width
+        let layout = FixedCoords { max_aspect_ratio: Some(2.0), ..FixedCoords::default() };
+        assert_eq!(layout.spacings(&g), (150.0, 30.0));
+        let options = RenderOptions { layout, ..RenderOptions::default() };
+        let (nodes, _) = parse_dot(&to_dot_with_options(&g, None, &options));
+        assert_eq!(nodes[&b.to_string()]["pos"], "300,600!");
+        assert_eq!(estimate_canvas_size(&g, &options), (300.0 + 115.2, 600.0 + 115.2));
+        g.set_qubit(b, 0.0);
+        assert_eq!(options.layout.spacings(&g), (150.0, 100.0));
+    }
+
+    #[test]
+    fn test_qubit_guide_lines() {
+        let mut g = Graph::new();
+        let z0 = g.add_vertex(quizx::graph::VType::Z);
+        let z1 = g.add_vertex(quizx::graph::VType::Z);
+        let x = g.add_vertex(quizx::graph::VType::X);
+        g.set_qubit(z1, 1.0);
+        g.set_row(z1, 2.0);
+        g.set_qubit(x, 1.0);
+        g.set_row(x, 1.0);
+        g.add_edge(z0, x);
+        g.add_edge(x, z1);
+
+        // Off by default
+        let (nodes, _) = parse_dot(&to_dot_with_positions(&g, None, false));
+        assert!(!nodes.keys().any(|n| n.starts_with("qubit_line")));
+
+        let options = RenderOptions { qubit_lines: true, ..RenderOptions::default() };
+        let (nodes, edges) = parse_dot(&to_dot_with_options(&g, None, &options));
+
+        // One dashed line per distinct qubit, spanning the full time range
+        assert_eq!(nodes["qubit_line_0_start"]["pos"], "-75,0!");
+        assert_eq!(nodes["qubit_line_0_end"]["pos"], "375,0!");
+        assert_eq!(nodes["qubit_line_1_start"]["pos"], "-75,100!");
+        assert!(!nodes.contains_key("qubit_line_2_start"));
+        let line = &edges[&("qubit_line_1_start".to_string(), "qubit_line_1_end".to_string())];
+        assert_eq!(line["style"], "dashed");
+        assert_eq!(edges.len(), 4);
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        let mut g = Graph::new();
+        let mut add = |ty, qubit, row| {
+            let v = g.add_vertex(ty);
+            g.set_qubit(v, qubit);
+            g.set_row(v, row);
+            v
+        };
+        let b0 = add(quizx::graph::VType::B, 0.0, 0.0);
+        let z = add(quizx::graph::VType::Z, 0.0, 1.0);
+        let x = add(quizx::graph::VType::X, 0.0, 2.0);
+        let b1 = add(quizx::graph::VType::B, 0.0, 3.0);
+        let z_low = add(quizx::graph::VType::Z, 1.0, 2.0);
+        g.add_edge(b0, z);
+        g.add_edge(z, x);
+        g.add_edge(x, b1);
+        g.add_edge(z, z_low);
+        g.add_edge_with_type(x, z_low, quizx::graph::EType::H);
+
+        let expected = ["B--Z--X--B", "    \\\\:", "      Z"].join("\n");
+        assert_eq!(to_ascii(&g, 10), expected);
+        assert_eq!(to_ascii(&Graph::new(), 10), "");
+    }
+
+    #[test]
+    fn test_to_dot_drop_in() {
+        let mut g = Graph::new();
+        let a = g.add_vertex(quizx::graph::VType::Z);
+        let b = g.add_vertex(quizx::graph::VType::X);
+        g.set_row(b, 1.0);
+        g.set_qubit(b, 2.0);
+        g.add_edge(a, b);
+
+        let (nodes, edges) = parse_dot(&to_dot(&g));
+        assert_eq!(nodes[&b.to_string()]["pos"], "150,200!");
+        assert_eq!(nodes[&a.to_string()]["fillcolor"], Palette::Classic.colors().z_spider);
+        assert!(nodes[&b.to_string()]["label"].contains(&format!(">{}</font>", b)));
+        assert_eq!(edges.len(), 1);
+
+        let mut highlights = Highlights::default();
+        highlights.add_edge(b, a, Style::default());
+        let (_, edges) = parse_dot(&to_dot_highlighted(&g, &highlights));
+        assert_eq!(edges[&(a.to_string(), b.to_string())]["color"], HIGHLIGHT_COLOR);
+    }
 }
@@ -0,0 +1,479 @@
+//! Where the visualizer places vertices, separate from how it draws them
+//!
+//! A [`Layout`] maps a [`LayoutGraph`] to positions in points; DOT and SVG output both
+//! take their positions from [`crate::graph_visualizer::RenderOptions::active_layout`].
+//! [`FixedCoords`] keeps the row and qubit coordinates of the diagram, [`Layered`] and
+//! [`ForceDirected`] ignore them, for graphs whose coordinates mean nothing, and
+//! [`Projected`] flattens the coordinates of 3D spacetime diagrams.
+
+use crate::graph_loader::TimeCoords;
+use quizx::graph::GraphLike;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The parts of a graph a layout may look at
+#[derive(Debug, Clone, Default)]
+pub struct LayoutGraph {
+    /// All vertices, sorted
+    pub vertices: Vec<usize>,
+    /// (row, qubit) coordinates of each vertex
+    pub coords: HashMap<usize, (f64, f64)>,
+    /// Neighbours of each vertex, sorted
+    pub neighbors: HashMap<usize, Vec<usize>>,
+    /// Input boundaries, in the graph's order
+    pub inputs: Vec<usize>,
+}
+
+impl LayoutGraph {
+    pub fn new<G: GraphLike>(graph: &G) -> Self {
+        let mut vertices: Vec<usize> = graph.vertices().collect();
+        vertices.sort();
+        let coords = vertices.iter().map(|&v| (v, (graph.row(v), graph.qubit(v)))).collect();
+        let neighbors = vertices.iter()
+            .map(|&v| {
+                let mut neighbors: Vec<usize> = graph.neighbors(v).collect();
+                neighbors.sort();
+                (v, neighbors)
+            })
+            .collect();
+        LayoutGraph { vertices, coords, neighbors, inputs: graph.inputs().clone() }
+    }
+
+    /// Smallest and largest value of `coord` over all vertices, (0, 0) without vertices
+    fn range(&self, coord: impl Fn((f64, f64)) -> f64) -> (f64, f64) {
+        if self.vertices.is_empty() {
+            return (0.0, 0.0);
+        }
+        self.coords.values().fold((f64::MAX, f64::MIN), |(lo, hi), &c| (lo.min(coord(c)), hi.max(coord(c))))
+    }
+}
+
+/// A way of placing vertices on the canvas
+///
+/// Implement this to add a layout; styling and the DOT and SVG assembly are shared.
+pub trait Layout: fmt::Debug + Send + Sync {
+    /// Position of every vertex of `graph`, in points, with y pointing up
+    fn positions(&self, graph: &LayoutGraph) -> HashMap<usize, (f64, f64)>;
+
+    /// Guide lines drawn when [`crate::graph_visualizer::RenderOptions::qubit_lines`] is
+    /// set, as (y, x_start, x_end); none unless the layout has qubits to follow
+    fn qubit_lines(&self, _graph: &LayoutGraph) -> Vec<(f64, f64, f64)> {
+        Vec::new()
+    }
+}
+
+/// Mapping from row and qubit coordinates to canvas positions, in points
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedCoords {
+    /// Horizontal distance between consecutive rows
+    pub row_spacing: f64,
+    /// Vertical distance between consecutive qubits
+    pub qubit_spacing: f64,
+    /// Largest ratio between the longer and the shorter side of the diagram; the
+    /// spacing along the longer side is reduced to stay within it. `None` keeps the
+    /// spacings as given, which makes long spacetime diagrams extremely thin.
+    pub max_aspect_ratio: Option<f64>,
+    /// Draw higher qubits further down instead of further up
+    pub flip_y: bool,
+}
+
+impl Default for FixedCoords {
+    fn default() -> Self {
+        FixedCoords { row_spacing: 150.0, qubit_spacing: 100.0, max_aspect_ratio: None, flip_y: false }
+    }
+}
+
+impl FixedCoords {
+    /// Row and qubit spacing for `graph`, after applying `max_aspect_ratio`
+    pub fn spacings<G: GraphLike>(&self, graph: &G) -> (f64, f64) {
+        self.layout_spacings(&LayoutGraph::new(graph))
+    }
+
+    fn layout_spacings(&self, graph: &LayoutGraph) -> (f64, f64) {
+        let (mut row_spacing, mut qubit_spacing) = (self.row_spacing, self.qubit_spacing);
+        let Some(max_ratio) = self.max_aspect_ratio else { return (row_spacing, qubit_spacing) };
+        let extent = |(min, max): (f64, f64)| if max > min { max - min } else { 0.0 };
+        let width = extent(graph.range(|(row, _)| row)) * row_spacing;
+        let height = extent(graph.range(|(_, qubit)| qubit)) * qubit_spacing;
+        if width > 0.0 && height > 0.0 {
+            if width > max_ratio * height {
+                row_spacing *= max_ratio * height / width;
+            } else if height > max_ratio * width {
+                qubit_spacing *= max_ratio * width / height;
+            }
+        }
+        (row_spacing, qubit_spacing)
+    }
+
+    /// Vertical offset of `qubit` from the bottom, in units of qubits
+    fn qubit_offset(&self, qubit: f64, min_qubit: f64, max_qubit: f64) -> f64 {
+        if self.flip_y { max_qubit - qubit } else { qubit - min_qubit }
+    }
+}
+
+impl Layout for FixedCoords {
+    fn positions(&self, graph: &LayoutGraph) -> HashMap<usize, (f64, f64)> {
+        let (row_spacing, qubit_spacing) = self.layout_spacings(graph);
+        let (min_qubit, max_qubit) = graph.range(|(_, qubit)| qubit);
+        graph.coords.iter()
+            .map(|(&v, &(row, qubit))| {
+                (v, (row * row_spacing, self.qubit_offset(qubit, min_qubit, max_qubit) * qubit_spacing))
+            })
+            .collect()
+    }
+
+    /// One line per qubit coordinate, overhanging the first and last row by half a row
+    fn qubit_lines(&self, graph: &LayoutGraph) -> Vec<(f64, f64, f64)> {
+        let (row_spacing, qubit_spacing) = self.layout_spacings(graph);
+        let (min_row, max_row) = graph.range(|(row, _)| row);
+        let (min_qubit, max_qubit) = graph.range(|(_, qubit)| qubit);
+        let mut qubits: Vec<f64> = graph.coords.values().map(|&(_, qubit)| qubit).collect();
+        qubits.sort_by(f64::total_cmp);
+        qubits.dedup();
+        qubits.into_iter()
+            .map(|qubit| (
+                self.qubit_offset(qubit, min_qubit, max_qubit) * qubit_spacing,
+                (min_row - 0.5) * row_spacing,
+                (max_row + 0.5) * row_spacing,
+            ))
+            .collect()
+    }
+}
+
+/// Layers by distance from the inputs, left to right
+///
+/// Each layer is ordered by the mean position of its neighbours in the layer before, to
+/// keep edges from crossing, and centred vertically. Components without inputs start
+/// from their smallest vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layered {
+    /// Horizontal distance between consecutive layers
+    pub layer_spacing: f64,
+    /// Vertical distance between vertices of a layer
+    pub vertex_spacing: f64,
+}
+
+impl Default for Layered {
+    fn default() -> Self {
+        Layered { layer_spacing: 150.0, vertex_spacing: 100.0 }
+    }
+}
+
+impl Layered {
+    /// Vertices of each layer, in drawing order from the top
+    fn layers(&self, graph: &LayoutGraph) -> Vec<Vec<usize>> {
+        let mut depth: HashMap<usize, usize> = HashMap::new();
+        let mut roots: Vec<usize> = graph.inputs.clone();
+        roots.sort();
+        let mut queue = VecDeque::new();
+        let mut unvisited = graph.vertices.iter();
+        loop {
+            for root in roots.drain(..) {
+                if let Entry::Vacant(entry) = depth.entry(root) {
+                    entry.insert(0);
+                    queue.push_back(root);
+                }
+            }
+            while let Some(v) = queue.pop_front() {
+                let next = depth[&v] + 1;
+                for &n in &graph.neighbors[&v] {
+                    if let Entry::Vacant(entry) = depth.entry(n) {
+                        entry.insert(next);
+                        queue.push_back(n);
+                    }
+                }
+            }
+            match unvisited.find(|v| !depth.contains_key(v)) {
+                Some(&v) => roots.push(v),
+                None => break,
+            }
+        }
+
+        let mut layers: Vec<Vec<usize>> = vec![Vec::new(); depth.values().max().map_or(0, |d| d + 1)];
+        for &v in &graph.vertices {
+            layers[depth[&v]].push(v);
+        }
+        for i in 1..layers.len() {
+            let index: HashMap<usize, usize> = layers[i - 1].iter().enumerate().map(|(j, &v)| (v, j)).collect();
+            let barycentre = |v: &usize| {
+                let above: Vec<usize> = graph.neighbors[v].iter().filter_map(|n| index.get(n).copied()).collect();
+                above.iter().sum::<usize>() as f64 / above.len().max(1) as f64
+            };
+            layers[i].sort_by(|a, b| barycentre(a).total_cmp(&barycentre(b)).then(a.cmp(b)));
+        }
+        layers
+    }
+}
+
+impl Layout for Layered {
+    fn positions(&self, graph: &LayoutGraph) -> HashMap<usize, (f64, f64)> {
+        let mut positions = HashMap::new();
+        for (depth, layer) in self.layers(graph).into_iter().enumerate() {
+            let middle = (layer.len() - 1) as f64 / 2.0;
+            for (i, v) in layer.into_iter().enumerate() {
+                positions.insert(v, (depth as f64 * self.layer_spacing, (middle - i as f64) * self.vertex_spacing));
+            }
+        }
+        positions
+    }
+}
+
+/// Spring embedding: edges pull their ends together, all vertices push each other apart
+///
+/// Fruchterman–Reingold, started from [`Layered`] rather than at random, so the result
+/// is the same on every run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForceDirected {
+    /// Number of steps; each moves vertices less than the one before
+    pub iterations: usize,
+    /// Preferred length of an edge
+    pub spacing: f64,
+}
+
+impl Default for ForceDirected {
+    fn default() -> Self {
+        ForceDirected { iterations: 200, spacing: 150.0 }
+    }
+}
+
+impl Layout for ForceDirected {
+    fn positions(&self, graph: &LayoutGraph) -> HashMap<usize, (f64, f64)> {
+        let start = Layered { layer_spacing: self.spacing, vertex_spacing: self.spacing }.positions(graph);
+        let mut points: Vec<(f64, f64)> = graph.vertices.iter().map(|v| start[v]).collect();
+        let index: HashMap<usize, usize> = graph.vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let edges: HashSet<(usize, usize)> = graph.vertices.iter()
+            .flat_map(|v| graph.neighbors[v].iter().map(|n| (index[v], index[n])))
+            .filter(|(a, b)| a < b)
+            .collect();
+        let mut edges: Vec<(usize, usize)> = edges.into_iter().collect();
+        edges.sort();
+
+        let k = self.spacing;
+        for step in 0..self.iterations {
+            let temperature = k * (1.0 - step as f64 / self.iterations as f64);
+            let mut shift = vec![(0.0, 0.0); points.len()];
+            for a in 0..points.len() {
+                for b in a + 1..points.len() {
+                    let (dx, dy) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+                    // Coincident vertices are pushed apart along x, smaller id to the left
+                    let (dx, dy) = if dx == 0.0 && dy == 0.0 { (-0.01, 0.0) } else { (dx, dy) };
+                    let distance = dx.hypot(dy);
+                    let force = k * k / distance;
+                    shift[a].0 += dx / distance * force;
+                    shift[a].1 += dy / distance * force;
+                    shift[b].0 -= dx / distance * force;
+                    shift[b].1 -= dy / distance * force;
+                }
+            }
+            for &(a, b) in &edges {
+                let (dx, dy) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+                let distance = dx.hypot(dy);
+                if distance == 0.0 {
+                    continue;
+                }
+                let force = distance * distance / k;
+                shift[a].0 -= dx / distance * force;
+                shift[a].1 -= dy / distance * force;
+                shift[b].0 += dx / distance * force;
+                shift[b].1 += dy / distance * force;
+            }
+            for (point, (sx, sy)) in points.iter_mut().zip(shift) {
+                let length = sx.hypot(sy);
+                if length > 0.0 {
+                    let step = length.min(temperature);
+                    point.0 += sx / length * step;
+                    point.1 += sy / length * step;
+                }
+            }
+        }
+        graph.vertices.iter().copied().zip(points).collect()
+    }
+}
+
+/// An axis of a 3D spacetime diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// The row coordinate
+    X,
+    /// The qubit coordinate
+    Y,
+    /// The third coordinate, see [`TimeCoords`]
+    T,
+}
+
+/// How 3D coordinates are flattened onto the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// Drop the third axis: the first axis runs right, the second up
+    Axes(Axis, Axis),
+    /// T up, X towards the lower right and Y towards the lower left, at 30° each
+    Isometric,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Axes(Axis::X, Axis::Y)
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    /// Parses `isometric` or two axes such as `xt`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let axis = |c| match c {
+            'x' => Some(Axis::X),
+            'y' => Some(Axis::Y),
+            't' => Some(Axis::T),
+            _ => None,
+        };
+        let chars: Vec<char> = s.chars().collect();
+        match (s, chars.as_slice()) {
+            ("isometric", _) => Ok(Projection::Isometric),
+            (_, &[a, b]) if a != b && axis(a).is_some() && axis(b).is_some() => {
+                Ok(Projection::Axes(axis(a).unwrap(), axis(b).unwrap()))
+            }
+            _ => Err(format!("Unknown projection '{}', expected isometric or two of x, y and t like xt", s)),
+        }
+    }
+}
+
+/// Places the vertices of a 3D diagram by a [`Projection`] of their coordinates
+///
+/// Vertices missing from `times` sit at t = 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projected {
+    pub times: TimeCoords,
+    pub projection: Projection,
+    /// Distance of one unit along any axis
+    pub spacing: f64,
+}
+
+impl Projected {
+    pub fn new(times: TimeCoords, projection: Projection) -> Self {
+        Projected { times, projection, spacing: 100.0 }
+    }
+}
+
+impl Layout for Projected {
+    fn positions(&self, graph: &LayoutGraph) -> HashMap<usize, (f64, f64)> {
+        let (cos, sin) = (30f64.to_radians().cos(), 30f64.to_radians().sin());
+        graph.coords.iter()
+            .map(|(&v, &(x, y))| {
+                let t = self.times.get(&v).copied().unwrap_or(0.0);
+                let value = |axis| match axis {
+                    Axis::X => x,
+                    Axis::Y => y,
+                    Axis::T => t,
+                };
+                let (px, py) = match self.projection {
+                    Projection::Axes(a, b) => (value(a), value(b)),
+                    Projection::Isometric => ((x - y) * cos, t - (x + y) * sin),
+                };
+                (v, (px * self.spacing, py * self.spacing))
+            })
+            .collect()
+    }
+}
+
+/// Another layout with every distance multiplied by `factor`; vertices keep their size
+#[derive(Debug, Clone)]
+pub struct Scaled {
+    pub inner: Arc<dyn Layout>,
+    pub factor: f64,
+}
+
+impl Layout for Scaled {
+    fn positions(&self, graph: &LayoutGraph) -> HashMap<usize, (f64, f64)> {
+        self.inner.positions(graph).into_iter()
+            .map(|(v, (x, y))| (v, (x * self.factor, y * self.factor)))
+            .collect()
+    }
+
+    fn qubit_lines(&self, graph: &LayoutGraph) -> Vec<(f64, f64, f64)> {
+        self.inner.qubit_lines(graph).into_iter()
+            .map(|(y, x_start, x_end)| (y * self.factor, x_start * self.factor, x_end * self.factor))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_visualizer::{to_dot_with_options, RenderOptions};
+    use quizx::graph::VType;
+    use quizx::hash_graph::Graph;
+
+    #[test]
+    fn test_layered_and_force_directed() {
+        // in -- a -- b -- out, with b also joined to c, and a separate pair d -- e
+        let mut g = Graph::new();
+        let input = g.add_vertex(VType::B);
+        let a = g.add_vertex(VType::Z);
+        let b = g.add_vertex(VType::X);
+        let c = g.add_vertex(VType::Z);
+        let output = g.add_vertex(VType::B);
+        let d = g.add_vertex(VType::Z);
+        let e = g.add_vertex(VType::Z);
+        g.add_edge(input, a);
+        g.add_edge(a, b);
+        g.add_edge(b, output);
+        g.add_edge(b, c);
+        g.add_edge(d, e);
+        g.set_inputs(vec![input]);
+        // Coordinates a layered or force-directed layout must ignore
+        for v in g.vertices().collect::<Vec<_>>() {
+            g.set_row(v, 7.0);
+        }
+        let graph = LayoutGraph::new(&g);
+
+        let positions = Layered::default().positions(&graph);
+        // The input and d, the smallest vertex of the other component, share the first layer
+        assert_eq!(positions[&input], (0.0, 50.0));
+        assert_eq!(positions[&d], (0.0, -50.0));
+        assert_eq!(positions[&a], (150.0, 50.0));
+        assert_eq!(positions[&e], (150.0, -50.0));
+        assert_eq!(positions[&b], (300.0, 0.0));
+        // c and out both hang off b, so they keep id order
+        assert_eq!(positions[&c], (450.0, 50.0));
+        assert_eq!(positions[&output], (450.0, -50.0));
+        assert!(Layered::default().qubit_lines(&graph).is_empty());
+        let options = RenderOptions { custom_layout: Some(Arc::new(Layered::default())), ..RenderOptions::default() };
+        let dot = to_dot_with_options(&g, None, &options);
+        assert!(dot.contains(&format!("  {} [pos=\"450,-50!\",", output)));
+
+        let layout = ForceDirected::default();
+        let positions = layout.positions(&graph);
+        assert_eq!(positions, layout.positions(&graph));
+        assert_eq!(positions.len(), 7);
+        let distance = |x: usize, y: usize| (positions[&x].0 - positions[&y].0).hypot(positions[&x].1 - positions[&y].1);
+        assert!(distance(input, a) < distance(input, output));
+        assert!(distance(d, e) < distance(a, e));
+        assert!(positions.values().all(|&(x, y)| x.is_finite() && y.is_finite()));
+    }
+
+    #[test]
+    fn test_projected() {
+        // Two vertices on top of each other in the row/qubit plane, apart in time
+        let mut g = Graph::new();
+        let a = g.add_vertex(VType::Z);
+        let b = g.add_vertex(VType::Z);
+        g.set_row(a, 1.0);
+        g.set_row(b, 1.0);
+        g.add_edge(a, b);
+        let graph = LayoutGraph::new(&g);
+        let times: TimeCoords = [(b, 2.0)].into_iter().collect();
+
+        let positions = Projected::new(times.clone(), "xt".parse().unwrap()).positions(&graph);
+        assert_eq!(positions[&a], (100.0, 0.0));
+        assert_eq!(positions[&b], (100.0, 200.0));
+        let positions = Projected::new(times, Projection::Isometric).positions(&graph);
+        assert!((positions[&a].0 - 86.6).abs() < 0.1 && (positions[&a].1 + 50.0).abs() < 1e-9);
+        assert!((positions[&b].1 - 150.0).abs() < 1e-9);
+        assert_eq!("isometric".parse(), Ok(Projection::Isometric));
+        assert!("tt".parse::<Projection>().is_err());
+    }
+}
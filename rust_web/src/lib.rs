@@ -1,18 +1,73 @@
 // Core modules
 pub mod tikz_export;
 pub mod create_graph;
+pub mod fixtures;
+pub mod noise;
 pub mod graph_loader;
+pub mod tags;
+pub mod zxg_export;
+pub mod qgraph;
+pub mod gltf_export;
+pub mod archive;
 pub mod graph_visualizer;
+pub mod layout;
+pub mod svg_backend;
+pub mod export;
+pub mod palette;
+pub mod spy_plot;
 pub mod pauliweb;
 pub mod make_rg;
+pub mod graph_like;
+pub mod degree_split;
 pub mod detection_webs;
 pub mod bitwisef2linalg;
+pub mod linalg;
+pub mod checkpoint;
+pub mod matrix_io;
+pub mod slice;
+pub mod code_extraction;
+pub mod css_checks;
+pub mod css_codes;
+pub mod flow;
+pub mod mbqc_export;
+pub mod phase_polynomial;
+pub mod phase_expr;
+pub mod graph_stats;
+pub mod compare;
+pub mod memory;
+pub mod bench_plot;
+pub mod limits;
+pub mod cli;
+pub mod logging;
+pub mod simplify;
+pub mod session;
+pub mod display_contract;
+pub mod web_legend;
+pub mod shared;
+pub mod phase_gadgets;
+pub mod coarsen;
+pub mod graph_algos;
+pub mod connectivity;
+pub mod regions;
+pub mod web_set;
+pub mod web_space;
+pub mod web_check;
+pub mod web_diff;
+pub mod web_stats;
+pub mod reference_webs;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 // Re-export detection_web function from the binary target
 // pub use use_detection_webs::use_det_web;
 // pub use detection_webs::DetectionWebs;
 pub use graph_visualizer::draw_graph_with_pauliweb;
+pub use graph_visualizer::to_dot;
 pub use pauliweb::PauliWeb;
+pub use web_set::WebSet;
+pub use web_space::WebSpace;
+pub use shared::SharedDiagram;
 pub use graph_loader::load_graph;
+pub use zxg_export::write_zxg as save_graph;
 pub use quizx::hash_graph::Graph;
 pub use quizx::graph::GraphLike;
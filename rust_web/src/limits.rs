@@ -0,0 +1,88 @@
+use crate::memory::format_bytes;
+
+/// Size limits checked before anything large is allocated
+///
+/// A malformed or accidentally huge input would otherwise only fail once the process
+/// runs out of memory. Each limit is `None` for no limit; [`Limits::unlimited`] turns
+/// them all off, which the binaries expose as `--allow-huge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Most vertices a loaded graph may have
+    pub max_vertices: Option<usize>,
+    /// Most edges a loaded graph may have
+    pub max_edges: Option<usize>,
+    /// Most rows or columns of a dense F2 matrix in the web computation
+    pub max_matrix_side: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_vertices: Some(200_000),
+            max_edges: Some(1_000_000),
+            max_matrix_side: Some(50_000),
+        }
+    }
+}
+
+impl Limits {
+    /// No limits at all
+    pub fn unlimited() -> Self {
+        Limits { max_vertices: None, max_edges: None, max_matrix_side: None }
+    }
+
+    /// Fails if a graph with this many vertices and edges is over the limits
+    pub fn check_graph(&self, vertices: usize, edges: usize) -> Result<(), String> {
+        if let Some(max) = self.max_vertices.filter(|&max| vertices > max) {
+            return Err(format!(
+                "graph would have {} vertices, more than the limit of {}; pass --allow-huge to load it anyway",
+                vertices, max
+            ));
+        }
+        if let Some(max) = self.max_edges.filter(|&max| edges > max) {
+            return Err(format!(
+                "graph would have {} edges, more than the limit of {}; pass --allow-huge to load it anyway",
+                edges, max
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails if a `rows`×`cols` matrix is over the limits
+    pub fn check_matrix(&self, rows: usize, cols: usize) -> Result<(), String> {
+        match self.max_matrix_side {
+            Some(max) if rows > max || cols > max => Err(format!(
+                "matrix would be {}×{} (about {}), more than the limit of {} per side; pass --allow-huge to build it anyway",
+                rows, cols, format_bytes(matrix_bytes(rows, cols)), max
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Rough size of a bit-packed `rows`×`cols` matrix
+pub(crate) fn matrix_bytes(rows: usize, cols: usize) -> u64 {
+    (rows as u64).saturating_mul(cols.div_ceil(8) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let limits = Limits::default();
+        assert!(limits.check_graph(100, 200).is_ok());
+        assert!(limits.check_matrix(100, 120).is_ok());
+
+        let err = limits.check_matrix(120_000, 120_000).unwrap_err();
+        assert!(err.starts_with("matrix would be 120000×120000"), "{}", err);
+        assert!(err.contains("--allow-huge"));
+        assert!(limits.check_graph(300_000, 10).unwrap_err().contains("300000 vertices"));
+        assert!(limits.check_graph(10, 2_000_000).unwrap_err().contains("2000000 edges"));
+
+        let unlimited = Limits::unlimited();
+        assert!(unlimited.check_graph(usize::MAX, usize::MAX).is_ok());
+        assert!(unlimited.check_matrix(120_000, 120_000).is_ok());
+    }
+}
@@ -0,0 +1,20 @@
+//! F2 linear algebra used throughout the crate
+//!
+//! Import [`Mat2`] from here rather than from a specific backend; the bit-packed
+//! [`crate::bitwisef2linalg`] implementation is the only one.
+
+pub use crate::bitwisef2linalg::{GaussState, Mat2};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitvec::prelude::*;
+
+    #[test]
+    fn test_rows() {
+        let mat = Mat2::from_u8(vec![vec![1, 1, 0], vec![0, 1, 1]]);
+        assert_eq!(mat.row(0), bits![1, 1, 0]);
+        assert_eq!(mat.row(1).iter_ones().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(mat.into_rows(), vec![bitvec![1, 1, 0], bitvec![0, 1, 1]]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Log targets and the verbosity flags shared by the binaries
+//!
+//! Each stage of the pipeline logs under a fixed target, whatever module the message
+//! comes from, so one stage can be turned up or down on its own: [`LOADER`] for reading
+//! diagrams, [`RG`] for bringing them into normal form, [`WEBS`] for solving for the webs
+//! and [`RENDER`] for drawing them. The binaries call [`init`], which sets the overall
+//! level with `-q`, `-v` and `-vv` and per target with `--log`, e.g.
+//! `--log webs=debug,render=warn`. `RUST_LOG` is applied last, so it overrides both.
+
+use log::LevelFilter;
+
+/// Reading and repairing diagrams
+pub const LOADER: &str = "rust_web::loader";
+/// Conversion to rg, graph-like or another normal form
+pub const RG: &str = "rust_web::rg";
+/// The linear algebra finding the webs
+pub const WEBS: &str = "rust_web::webs";
+/// Drawing diagrams and webs
+pub const RENDER: &str = "rust_web::render";
+
+/// The level set by `-q`, `-v` and `-vv`, info without any
+///
+/// Each `-v` raises the level by one and each `-q` lowers it, so `-vv` is trace and
+/// `-q` warnings only.
+pub fn level_from_args(args: &[String]) -> LevelFilter {
+    let steps: i32 = args.iter()
+        .map(|arg| match arg.as_str() {
+            "-v" | "--verbose" => 1,
+            "-vv" => 2,
+            "-q" | "--quiet" => -1,
+            _ => 0,
+        })
+        .sum();
+    match steps {
+        i32::MIN..=-2 => LevelFilter::Error,
+        -1 => LevelFilter::Warn,
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// `spec` in `RUST_LOG` syntax, with the short names `loader`, `rg`, `webs` and
+/// `render` expanded to their targets
+pub fn expand_targets(spec: &str) -> String {
+    spec.split(',')
+        .map(|directive| match directive.split_once('=') {
+            Some((target, level)) => format!("{}={}", full_target(target), level),
+            None => directive.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn full_target(name: &str) -> &str {
+    match name {
+        "loader" => LOADER,
+        "rg" => RG,
+        "webs" => WEBS,
+        "render" => RENDER,
+        other => other,
+    }
+}
+
+/// Sets up the logger from the verbosity flags and `--log` in `args`, returning the
+/// other arguments
+///
+/// `--log` without a value is left in for the binary to reject.
+pub fn init(args: &[String]) -> Vec<String> {
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level_from_args(args));
+    let mut rest = Vec::with_capacity(args.len());
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-v" | "-vv" | "-q" | "--verbose" | "--quiet" => {}
+            "--log" if iter.peek().is_some() => {
+                builder.parse_filters(&expand_targets(iter.next().unwrap()));
+            }
+            _ => rest.push(arg.clone()),
+        }
+    }
+    builder.parse_env(env_logger::Env::default());
+    builder.init();
+    rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_flags() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(level_from_args(&args("bin g.zxg")), LevelFilter::Info);
+        assert_eq!(level_from_args(&args("bin -v g.zxg")), LevelFilter::Debug);
+        assert_eq!(level_from_args(&args("bin g.zxg -vv")), LevelFilter::Trace);
+        assert_eq!(level_from_args(&args("bin -v -v -v")), LevelFilter::Trace);
+        assert_eq!(level_from_args(&args("bin -q")), LevelFilter::Warn);
+        assert_eq!(level_from_args(&args("bin -q --quiet -q")), LevelFilter::Error);
+        assert_eq!(level_from_args(&args("bin -v -q")), LevelFilter::Info);
+
+        assert_eq!(expand_targets("webs=debug,render=warn"), "rust_web::webs=debug,rust_web::render=warn");
+        assert_eq!(expand_targets("info,rayon=off,rg=trace"), "info,rayon=off,rust_web::rg=trace");
+    }
+}
@@ -2,9 +2,15 @@ use quizx::hash_graph::Graph;
 use quizx::graph::{GraphLike, VType};
 use std::collections::HashSet;
 
-pub fn make_rg(oldg: &mut Graph) -> () {
-    // Modifies a graph in-place to make it in red-green form
+/// Modifies a graph in-place to make it in red-green form
+///
+/// Adjacent spiders of the same colour get a spider of the other colour between them.
+/// Other vertex types have no such rule, so adjacent pairs of e.g. H-boxes are left in
+/// place and reported: fails listing them, with the rest of the graph converted, so
+/// callers can trust that a graph that passed is in rg form.
+pub fn make_rg(oldg: &mut Graph) -> Result<(), String> {
     let mut visited: HashSet<(usize, usize)> = HashSet::new();
+    let mut unresolved: Vec<(usize, usize)> = Vec::new();
 
     loop {
         let g = oldg.clone(); // freshly cloned every time
@@ -22,16 +28,20 @@ pub fn make_rg(oldg: &mut Graph) -> () {
                 visited.insert(key);
 
                 if g.vertex_type(neighbor) == node_type {
-                    let row = (g.row(node) + g.row(neighbor)) / 2.0;
-                    let qubit = (g.qubit(node) + g.qubit(neighbor)) / 2.0;
-
-                    oldg.remove_edge(node, neighbor);
-
                     let new_type = match node_type {
                         VType::X => VType::Z,
                         VType::Z => VType::X,
-                        _ => continue,
+                        // A wire between two boundaries is fine
+                        VType::B => continue,
+                        _ => {
+                            unresolved.push(key);
+                            continue;
+                        }
                     };
+                    let row = (g.row(node) + g.row(neighbor)) / 2.0;
+                    let qubit = (g.qubit(node) + g.qubit(neighbor)) / 2.0;
+
+                    oldg.remove_edge(node, neighbor);
 
                     let new_vertex = oldg.add_vertex_with_data(quizx::graph::VData {
                         ty: new_type,
@@ -57,6 +67,50 @@ pub fn make_rg(oldg: &mut Graph) -> () {
             break;
         }
     }
+
+    if unresolved.is_empty() {
+        return Ok(());
+    }
+    unresolved.sort();
+    let pairs: Vec<String> = unresolved.iter()
+        .map(|&(a, b)| format!("{}-{} ({:?})", a, b, oldg.vertex_type(a)))
+        .collect();
+    Err(format!("Can't put the graph in rg form, no rule for adjacent vertices {}", pairs.join(", ")))
+}
+
+/// Breaches of the rg form in `g`, sorted: boundaries without exactly one leg and, unless
+/// `boundaries_only`, adjacent spiders of the same colour
+///
+/// Stages that may leave same-colour neighbours on purpose, like splitting spiders before
+/// the conversion, check only their boundaries.
+pub fn rg_violations(g: &Graph, boundaries_only: bool) -> Vec<String> {
+    let mut vertices: Vec<usize> = g.vertices().collect();
+    vertices.sort();
+    let mut violations = Vec::new();
+    for &v in &vertices {
+        let ty = g.vertex_type(v);
+        if ty == VType::B && g.degree(v) != 1 {
+            violations.push(format!("boundary {} has {} legs", v, g.degree(v)));
+        }
+        if boundaries_only || !matches!(ty, VType::Z | VType::X) {
+            continue;
+        }
+        let mut same: Vec<usize> = g.neighbors(v).filter(|&n| n > v && g.vertex_type(n) == ty).collect();
+        same.sort();
+        violations.extend(same.into_iter().map(|n| format!("{:?} spiders {} and {} are adjacent", ty, v, n)));
+    }
+    violations
+}
+
+/// Panics naming `stage` if `g` breaks the rg form, see [`rg_violations`]
+///
+/// Only checks in debug builds, so corruption is caught at the stage that caused it
+/// rather than when webs are built from the graph.
+pub(crate) fn debug_check_rg(g: &Graph, stage: &str, boundaries_only: bool) {
+    if cfg!(debug_assertions) {
+        let violations = rg_violations(g, boundaries_only);
+        assert!(violations.is_empty(), "Graph breaks rg form after {}: {}", stage, violations.join(", "));
+    }
 }
 
 // Tests
@@ -78,7 +132,7 @@ mod tests {
         // Debug output
         println!("Original graph: {} vertices, {} edges", graph.num_vertices(), graph.num_edges());
         // Apply RG transformation
-        make_rg(&mut graph);
+        make_rg(&mut graph).unwrap();
         println!("Transformed graph: {} vertices, {} edges", graph.num_vertices(), graph.num_edges());
         
         // In RG form, we expect:
@@ -101,4 +155,35 @@ mod tests {
         assert!(graph.connected(v1, new_node), "v1 should be connected to new node");
         assert!(graph.connected(v2, new_node), "v2 should be connected to new node");
     }
+
+    #[test]
+    fn test_make_rg_reports_unsupported_pairs() {
+        // Two adjacent H-boxes between a pair of X spiders, and a bare wire
+        let mut graph = Graph::new();
+        let x1 = graph.add_vertex(VType::X);
+        let h1 = graph.add_vertex(VType::H);
+        let h2 = graph.add_vertex(VType::H);
+        let x2 = graph.add_vertex(VType::X);
+        let b1 = graph.add_vertex(VType::B);
+        let b2 = graph.add_vertex(VType::B);
+        for (a, b) in [(x1, h1), (h1, h2), (h2, x2), (x1, x2), (b1, b2)] {
+            graph.add_edge(a, b);
+        }
+
+        let error = make_rg(&mut graph).unwrap_err();
+        assert_eq!(error, format!("Can't put the graph in rg form, no rule for adjacent vertices {}-{} (H)", h1, h2));
+        // The pair keeps its edge, and the rest is still converted
+        assert!(graph.connected(h1, h2) && graph.connected(b1, b2));
+        assert!(!graph.connected(x1, x2));
+        assert_eq!(graph.num_vertices(), 7);
+
+        assert_eq!(rg_violations(&graph, false), Vec::<String>::new());
+        graph.add_edge(x1, b1);
+        graph.add_edge(h2, b2);
+        graph.add_edge(x1, x2);
+        assert_eq!(rg_violations(&graph, true), [format!("boundary {} has 2 legs", b1), format!("boundary {} has 2 legs", b2)]);
+        assert_eq!(rg_violations(&graph, false)[0], format!("X spiders {} and {} are adjacent", x1, x2));
+        let result = std::panic::catch_unwind(|| debug_check_rg(&graph, "test", false));
+        assert_eq!(result.is_err(), cfg!(debug_assertions));
+    }
 }
@@ -0,0 +1,116 @@
+use crate::linalg::Mat2;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Writes `mat` in Matrix Market coordinate format, listing only the ones
+///
+/// Indices are 1-based as the format requires. The files load directly with e.g.
+/// `scipy.io.mmread`, and stay small for the sparse matrices built from diagrams.
+pub fn to_matrix_market(mat: &Mat2) -> String {
+    let ones: usize = (0..mat.rows()).map(|r| mat.row_ones(r).count()).sum();
+    let mut out = String::from("%%MatrixMarket matrix coordinate pattern general\n");
+    writeln!(out, "{} {} {}", mat.rows(), mat.cols(), ones).unwrap();
+    for r in 0..mat.rows() {
+        for c in mat.row_ones(r) {
+            writeln!(out, "{} {}", r + 1, c + 1).unwrap();
+        }
+    }
+    out
+}
+
+/// Writes [`to_matrix_market`] of `mat` to `path`
+pub fn write_matrix_market(mat: &Mat2, path: impl AsRef<Path>) -> Result<(), String> {
+    let path = path.as_ref();
+    std::fs::write(path, to_matrix_market(mat))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Reads a parity check matrix in MacKay's alist format, as codes are published in
+///
+/// The header gives the column and row counts, the maximal weights and the weight of
+/// every column and row, followed by the 1-based row indices of each column and the
+/// column indices of each row. Zeros padding a list to the maximal weight are skipped.
+/// Fails if the two lists disagree.
+pub fn parse_alist(content: &str) -> Result<Mat2, String> {
+    let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty()).enumerate();
+    let mut numbers = |what: &str| -> Result<Vec<usize>, String> {
+        let (i, line) = lines.next().ok_or_else(|| format!("alist ends before the {}", what))?;
+        line.split_whitespace()
+            .map(|n| n.parse().map_err(|_| format!("Line {} of the alist has {}, expected a number in the {}", i + 1, n, what)))
+            .collect()
+    };
+    let (cols, rows) = match numbers("column and row counts")?[..] {
+        [cols, rows] => (cols, rows),
+        _ => return Err("The first alist line must be the column and row counts".to_string()),
+    };
+    numbers("maximal weights")?;
+    let col_weights = numbers("column weights")?;
+    let row_weights = numbers("row weights")?;
+    if col_weights.len() != cols || row_weights.len() != rows {
+        return Err(format!("alist has {} column and {} row weights for a {}x{} matrix", col_weights.len(), row_weights.len(), rows, cols));
+    }
+
+    let mut by_col = Mat2::new(rows, cols);
+    for (j, &weight) in col_weights.iter().enumerate() {
+        let entries: Vec<usize> = numbers("column lists")?.into_iter().filter(|&i| i != 0).collect();
+        if entries.len() != weight || entries.iter().any(|&i| i > rows) {
+            return Err(format!("Column {} of the alist should list {} rows out of {}, not {:?}", j + 1, weight, rows, entries));
+        }
+        for i in entries {
+            by_col.set(i - 1, j, true);
+        }
+    }
+    let mut by_row = Mat2::new(rows, cols);
+    for (i, &weight) in row_weights.iter().enumerate() {
+        let entries: Vec<usize> = numbers("row lists")?.into_iter().filter(|&j| j != 0).collect();
+        if entries.len() != weight || entries.iter().any(|&j| j > cols) {
+            return Err(format!("Row {} of the alist should list {} columns out of {}, not {:?}", i + 1, weight, cols, entries));
+        }
+        for j in entries {
+            by_row.set(i, j - 1, true);
+        }
+    }
+    if by_col != by_row {
+        return Err("The column and row lists of the alist describe different matrices".to_string());
+    }
+    Ok(by_row)
+}
+
+/// Reads [`parse_alist`] of the file at `path`
+pub fn read_alist(path: impl AsRef<Path>) -> Result<Mat2, String> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_alist(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_market() {
+        let mat = Mat2::from_u8(vec![vec![1, 0, 1], vec![0, 0, 0]]);
+        assert_eq!(
+            to_matrix_market(&mat),
+            "%%MatrixMarket matrix coordinate pattern general\n2 3 2\n1 1\n1 3\n"
+        );
+        assert_eq!(to_matrix_market(&Mat2::zeros(0, 4)).lines().nth(1), Some("0 4 0"));
+    }
+
+    #[test]
+    fn test_alist() {
+        // The [7, 4] Hamming code's checks, which are the Steane code's
+        let hamming = read_alist("tests/matrices/hamming_7_4.alist").unwrap();
+        let rows: Vec<Vec<usize>> = (0..hamming.rows()).map(|r| hamming.row_ones(r).collect()).collect();
+        let steane: Vec<Vec<usize>> = crate::create_graph::STEANE_SUPPORTS.iter().map(|s| s.to_vec()).collect();
+        assert_eq!(rows, steane);
+        let g = crate::css_codes::diagram_from_css(&hamming, &hamming, 1).unwrap();
+        assert_eq!(crate::css_checks::css_checks(&g).unwrap().logical_qubits(), 1);
+
+        // Unpadded lists read the same, lists that disagree don't read
+        let unpadded = "3 2\n1 2\n1 1 1\n2 1\n1\n1\n2\n1 2\n3\n";
+        assert_eq!(parse_alist(unpadded).unwrap(), Mat2::from_u8(vec![vec![1, 1, 0], vec![0, 0, 1]]));
+        assert!(parse_alist(&unpadded.replace("\n3\n", "\n2\n")).unwrap_err().contains("different matrices"));
+        assert!(parse_alist("3 2\n1 2\n").unwrap_err().starts_with("alist ends before"));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::flow::{gflow, open_graph};
+use quizx::graph::{EType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+
+/// Measurement plane of a pattern command. Graph-like diagrams only give XY measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Plane {
+    XY,
+}
+
+/// One command of a measurement pattern, in the usual N/E/M/X/Z notation
+///
+/// Domains list the vertices whose measurement outcomes (summed mod 2) control the
+/// command. Angles are in units of π.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd")]
+pub enum Command {
+    /// Prepare `node` in |+>
+    N { node: V },
+    /// Entangle two nodes with a CZ
+    E { nodes: [V; 2] },
+    /// Measure `node`, with the angle flipped by `s_domain` and shifted by π by `t_domain`
+    M { node: V, plane: Plane, angle: f64, s_domain: Vec<V>, t_domain: Vec<V> },
+    /// Pauli X correction on an output
+    X { node: V, domain: Vec<V> },
+    /// Pauli Z correction on an output
+    Z { node: V, domain: Vec<V> },
+}
+
+/// A measurement pattern in NEMC standard form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeasurementPattern {
+    pub inputs: Vec<V>,
+    pub outputs: Vec<V>,
+    pub commands: Vec<Command>,
+}
+
+/// Builds the measurement pattern of a graph-like diagram from its gflow
+///
+/// Every spider becomes a qubit, every Hadamard edge a CZ. Non-output spiders are
+/// measured in gflow order: a spider with phase α is measured in the XY plane at
+/// angle -α. The corrections of g(u) are folded into the domains of later
+/// measurements and the remaining ones applied to the outputs.
+///
+/// Fails if the diagram has no gflow, if an output spider carries a phase or if a
+/// boundary is attached through a Hadamard edge.
+pub fn measurement_pattern(g: &Graph) -> Result<MeasurementPattern, String> {
+    let og = open_graph(g)?;
+    let flow = gflow(g)?.ok_or("Diagram has no gflow, it can't be turned into a pattern")?;
+
+    for &b in g.inputs().iter().chain(g.outputs().iter()) {
+        if g.neighbors(b).any(|n| g.edge_type(b, n) == EType::H) {
+            return Err(format!("Boundary {} is attached through a Hadamard edge", b));
+        }
+    }
+    for &o in &og.outputs {
+        if g.phase(o).to_f64() != 0.0 {
+            return Err(format!("Output spider {} carries a phase", o));
+        }
+    }
+
+    let inputs: HashSet<V> = og.inputs.iter().copied().collect();
+    let mut commands = Vec::new();
+
+    for &v in &og.vertices {
+        if !inputs.contains(&v) {
+            commands.push(Command::N { node: v });
+        }
+    }
+    for &v in &og.vertices {
+        for &n in og.neighbors[&v].range(v + 1..) {
+            commands.push(Command::E { nodes: [v, n] });
+        }
+    }
+
+    // Outcomes accumulated on each vertex so far, toggled mod 2
+    let mut x_domain: HashMap<V, BTreeSet<V>> = HashMap::new();
+    let mut z_domain: HashMap<V, BTreeSet<V>> = HashMap::new();
+    let toggle = |domains: &mut HashMap<V, BTreeSet<V>>, target: V, signal: V| {
+        let domain = domains.entry(target).or_default();
+        if !domain.remove(&signal) {
+            domain.insert(signal);
+        }
+    };
+    let take = |domains: &mut HashMap<V, BTreeSet<V>>, v: V| -> Vec<V> {
+        domains.remove(&v).unwrap_or_default().into_iter().collect()
+    };
+
+    for u in flow.measurement_order() {
+        let phase = g.phase(u).to_f64();
+        commands.push(Command::M {
+            node: u,
+            plane: Plane::XY,
+            angle: (2.0 - phase.rem_euclid(2.0)) % 2.0,
+            s_domain: take(&mut x_domain, u),
+            t_domain: take(&mut z_domain, u),
+        });
+
+        let correction = &flow.correction[&u];
+        for &v in correction {
+            toggle(&mut x_domain, v, u);
+        }
+        for v in og.odd_neighbourhood(correction) {
+            if v != u {
+                toggle(&mut z_domain, v, u);
+            }
+        }
+    }
+
+    for &o in &og.outputs {
+        let domain = take(&mut x_domain, o);
+        if !domain.is_empty() {
+            commands.push(Command::X { node: o, domain });
+        }
+    }
+    for &o in &og.outputs {
+        let domain = take(&mut z_domain, o);
+        if !domain.is_empty() {
+            commands.push(Command::Z { node: o, domain });
+        }
+    }
+
+    Ok(MeasurementPattern { inputs: og.inputs, outputs: og.outputs, commands })
+}
+
+impl MeasurementPattern {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("pattern is always serialisable")
+    }
+}
+
+/// Writes the measurement pattern of `g` as JSON to `filename`
+pub fn export_to_mbqc_json(g: &Graph, filename: &str) -> Result<(), String> {
+    let pattern = measurement_pattern(g)?;
+    fs::write(filename, pattern.to_json())
+        .map_err(|e| format!("Failed to write {}: {}", filename, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::vertex_at;
+    use quizx::graph::VType;
+    use quizx::phase::Phase;
+
+    /// in - z0 - z1 - z2 - out, Hadamard edges between the spiders
+    fn wire(phases: [f64; 3]) -> (Graph, Vec<V>) {
+        let mut g = Graph::new();
+        let input = vertex_at(&mut g, VType::B, 0.0, 0.0);
+        let zs: Vec<V> = (0..3).map(|i| vertex_at(&mut g, VType::Z, 0.0, i as f64 + 1.0)).collect();
+        let output = vertex_at(&mut g, VType::B, 0.0, 4.0);
+        for (&z, phase) in zs.iter().zip(phases) {
+            g.set_phase(z, Phase::from_f64(phase));
+        }
+        g.add_edge(input, zs[0]);
+        g.add_edge_with_type(zs[0], zs[1], EType::H);
+        g.add_edge_with_type(zs[1], zs[2], EType::H);
+        g.add_edge(zs[2], output);
+        g.set_inputs(vec![input]);
+        g.set_outputs(vec![output]);
+        (g, zs)
+    }
+
+    #[test]
+    fn test_wire_pattern() {
+        let (g, zs) = wire([0.5, 0.25, 0.0]);
+        let pattern = measurement_pattern(&g).unwrap();
+
+        assert_eq!(pattern.inputs, vec![zs[0]]);
+        assert_eq!(pattern.outputs, vec![zs[2]]);
+        assert_eq!(pattern.commands, vec![
+            Command::N { node: zs[1] },
+            Command::N { node: zs[2] },
+            Command::E { nodes: [zs[0], zs[1]] },
+            Command::E { nodes: [zs[1], zs[2]] },
+            Command::M { node: zs[0], plane: Plane::XY, angle: 1.5, s_domain: vec![], t_domain: vec![] },
+            // X from z0 flips z1's angle, Z from z0 lands on z2
+            Command::M { node: zs[1], plane: Plane::XY, angle: 1.75, s_domain: vec![zs[0]], t_domain: vec![] },
+            Command::X { node: zs[2], domain: vec![zs[1]] },
+            Command::Z { node: zs[2], domain: vec![zs[0]] },
+        ]);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let (g, _) = wire([0.0, 1.0, 0.0]);
+        let pattern = measurement_pattern(&g).unwrap();
+        let json = pattern.to_json();
+        assert!(json.contains("\"cmd\": \"M\""));
+        let parsed: MeasurementPattern = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, pattern);
+    }
+
+    #[test]
+    fn test_rejects_output_phase() {
+        let (g, _) = wire([0.0, 0.0, 0.5]);
+        assert!(measurement_pattern(&g).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::logging::WEBS;
+use std::fmt;
+
+/// Resident set size of the current process, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub current: u64,
+    /// High-water mark since the process started
+    pub peak: u64,
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RSS {}, peak {}", format_bytes(self.current), format_bytes(self.peak))
+    }
+}
+
+/// Reads the memory usage of this process from `/proc/self/status`
+///
+/// Returns `None` where procfs isn't available, e.g. on macOS.
+pub fn memory_usage() -> Option<MemoryUsage> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_status(&status)
+}
+
+/// Logs the memory usage at info level, tagged with the pipeline `stage`
+///
+/// Logs under [`WEBS`], since the web computation is what the figures are watched for.
+pub fn log_memory(stage: &str) {
+    match memory_usage() {
+        Some(usage) => log::info!(target: WEBS, "Memory after {}: {}", stage, usage),
+        None => log::debug!(target: WEBS, "Memory usage is not available on this platform"),
+    }
+}
+
+/// Picks `VmRSS` and `VmHWM` out of a `/proc/<pid>/status` file
+fn parse_status(status: &str) -> Option<MemoryUsage> {
+    let field = |name: &str| {
+        let line = status.lines().find(|line| line.starts_with(name))?;
+        let kib: u64 = line[name.len()..].trim().trim_end_matches("kB").trim().parse().ok()?;
+        Some(kib * 1024)
+    };
+    Some(MemoryUsage { current: field("VmRSS:")?, peak: field("VmHWM:")? })
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 MiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = "Name:\tbenchmark\nVmPeak:\t  300000 kB\nVmHWM:\t    2048 kB\nVmRSS:\t    1536 kB\n";
+        let usage = parse_status(status).unwrap();
+        assert_eq!(usage, MemoryUsage { current: 1536 * 1024, peak: 2048 * 1024 });
+        assert_eq!(usage.to_string(), "RSS 1.5 MiB, peak 2.0 MiB");
+        assert_eq!(parse_status("Name:\tbenchmark\n"), None);
+
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+        if cfg!(target_os = "linux") {
+            let usage = memory_usage().unwrap();
+            assert!(usage.peak >= usage.current && usage.current > 0);
+        }
+    }
+}
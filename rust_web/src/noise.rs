@@ -0,0 +1,167 @@
+//! Seeded random corruption of diagrams, for robustness tests
+//!
+//! [`Noise`] deletes edges and vertices and flips spider colours the way a slip in the
+//! editor would, and reports each change as a [`Corruption`], so that a test can check
+//! the validation downstream catches it and names the element that was changed. The
+//! same seed always corrupts the same elements of the same graph.
+
+use quizx::graph::{GraphLike, VType, V};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+/// One change made by [`Noise`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    /// The edge between the two vertices, smaller first, was removed
+    DeletedEdge(V, V),
+    /// The vertex was removed with all its edges
+    DeletedVertex(V),
+    /// The spider was turned from Z into X or back
+    FlippedColour(V),
+}
+
+impl Corruption {
+    /// Vertices the corruption touched
+    pub fn vertices(&self) -> Vec<V> {
+        match *self {
+            Corruption::DeletedEdge(a, b) => vec![a, b],
+            Corruption::DeletedVertex(v) | Corruption::FlippedColour(v) => vec![v],
+        }
+    }
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Corruption::DeletedEdge(a, b) => write!(f, "deleted edge ({}, {})", a, b),
+            Corruption::DeletedVertex(v) => write!(f, "deleted vertex {}", v),
+            Corruption::FlippedColour(v) => write!(f, "flipped the colour of spider {}", v),
+        }
+    }
+}
+
+/// A seeded source of corruptions
+///
+/// Elements are picked from the vertices and edges in sorted order, so picks don't
+/// depend on the order the graph stores them in.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    rng: StdRng,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Noise { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> Option<T> {
+        (!items.is_empty()).then(|| items[self.rng.gen_range(0..items.len())])
+    }
+
+    /// Removes a random edge, `None` if there is none
+    pub fn delete_edge<G: GraphLike>(&mut self, g: &mut G) -> Option<Corruption> {
+        let mut edges: Vec<(V, V)> = g.edges().map(|(a, b, _)| (a.min(b), a.max(b))).collect();
+        edges.sort();
+        let (a, b) = self.pick(&edges)?;
+        g.remove_edge(a, b);
+        Some(Corruption::DeletedEdge(a, b))
+    }
+
+    /// Removes a random spider, `None` if there is none
+    ///
+    /// Boundaries are kept, since removing one also changes the inputs and outputs.
+    pub fn delete_vertex<G: GraphLike>(&mut self, g: &mut G) -> Option<Corruption> {
+        let v = self.pick(&spiders(g))?;
+        g.remove_vertex(v);
+        Some(Corruption::DeletedVertex(v))
+    }
+
+    /// Turns a random Z spider into an X spider or the other way round, `None` if there
+    /// is none
+    pub fn flip_colour<G: GraphLike>(&mut self, g: &mut G) -> Option<Corruption> {
+        let v = self.pick(&spiders(g))?;
+        let flipped = if g.vertex_type(v) == VType::Z { VType::X } else { VType::Z };
+        g.set_vertex_type(v, flipped);
+        Some(Corruption::FlippedColour(v))
+    }
+
+    /// Applies `count` corruptions of random kinds, fewer if the graph runs out of
+    /// elements
+    pub fn corrupt<G: GraphLike>(&mut self, g: &mut G, count: usize) -> Vec<Corruption> {
+        (0..count)
+            .filter_map(|_| match self.rng.gen_range(0..3) {
+                0 => self.delete_edge(g),
+                1 => self.delete_vertex(g),
+                _ => self.flip_colour(g),
+            })
+            .collect()
+    }
+}
+
+/// Z and X spiders of `g`, sorted
+fn spiders<G: GraphLike>(g: &G) -> Vec<V> {
+    let mut spiders: Vec<V> = g.vertices().filter(|&v| matches!(g.vertex_type(v), VType::Z | VType::X)).collect();
+    spiders.sort();
+    spiders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use crate::detection_webs::get_detection_webs;
+    use crate::pauliweb::{Pauli, PauliWeb};
+    use crate::web_check::{web_violations, Issue};
+    use quizx::hash_graph::Graph;
+
+    #[test]
+    fn test_noise() {
+        let mut g = create_steane_rounds(2);
+        let webs = get_detection_webs(&mut g);
+        let touches = |web: &PauliWeb, v: V| web.edge_operators.keys().any(|&(a, b)| a == v || b == v);
+
+        // Same seed, same corruptions
+        let corrupted = |seed| {
+            let mut h = g.clone();
+            (Noise::new(seed).corrupt(&mut h, 5), h)
+        };
+        assert_eq!(corrupted(7).0, corrupted(7).0);
+        assert_ne!(corrupted(7).0, corrupted(8).0);
+        assert_eq!(Noise::new(0).delete_edge(&mut Graph::new()), None);
+
+        for seed in 0..4 {
+            let mut h = g.clone();
+            let Some(Corruption::DeletedEdge(a, b)) = Noise::new(seed).delete_edge(&mut h) else { panic!() };
+            assert_eq!(h.num_edges(), g.num_edges() - 1);
+            for web in webs.iter().filter(|web| web.edge_operators.contains_key(&(a, b))) {
+                assert_eq!(web_violations(&h, web), Err(Issue::MissingEdge(a, b)));
+            }
+
+            let mut h = g.clone();
+            let Some(Corruption::DeletedVertex(v)) = Noise::new(seed).delete_vertex(&mut h) else { panic!() };
+            for web in webs.iter().filter(|web| touches(web, v)) {
+                let Err(Issue::MissingEdge(a, b)) = web_violations(&h, web) else { panic!() };
+                assert!(a == v || b == v);
+            }
+
+            // A flipped spider breaks the webs through it there and nowhere else
+            let mut h = g.clone();
+            let corruption = Noise::new(seed).flip_colour(&mut h).unwrap();
+            let Corruption::FlippedColour(v) = corruption else { panic!() };
+            assert_ne!(h.vertex_type(v), g.vertex_type(v));
+            let flagged: Vec<V> = webs.iter()
+                .flat_map(|web| web_violations(&h, web).unwrap())
+                .map(|violation| violation.vertex())
+                .collect();
+            // Only a web with the old opposite colour on an even number of legs, all of
+            // them, reads the same through the flipped spider
+            let opposite = if g.vertex_type(v) == VType::Z { Pauli::X } else { Pauli::Z };
+            let unnoticed = |web: &PauliWeb| {
+                g.degree(v) % 2 == 0 && g.neighbors(v).all(|n| web.get_edge(v, n) == Some(opposite))
+            };
+            assert!(!flagged.is_empty() || webs.iter().filter(|web| touches(web, v)).all(unnoticed), "{}", corruption);
+            assert!(flagged.iter().all(|&u| u == v), "{}: {:?}", corruption, flagged);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Colour schemes for diagrams and web overlays
+///
+/// `Classic` keeps the usual ZX colours: green Z and red X spiders, with red, green and
+/// blue for X, Z and Y web edges. The other two stay distinguishable with red-green
+/// colour blindness and in grayscale, where `Viridis` also orders the web colours by
+/// lightness.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Classic,
+    /// Okabe and Ito's colour-universal design palette
+    OkabeIto,
+    /// Stops of the viridis colour map
+    Viridis,
+}
+
+/// Colours of one palette, as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colors {
+    pub z_spider: &'static str,
+    pub x_spider: &'static str,
+    pub h_box: &'static str,
+    pub web_x: &'static str,
+    pub web_z: &'static str,
+    pub web_y: &'static str,
+}
+
+impl Palette {
+    pub fn colors(self) -> Colors {
+        match self {
+            Palette::Classic => Colors {
+                z_spider: "#88ff88",
+                x_spider: "#ff8888",
+                h_box: "#ffff88",
+                web_x: "#ff0000",
+                web_z: "#00aa00",
+                web_y: "#0000ff",
+            },
+            // Spider fills are the web colours halfway to white, so labels stay legible
+            Palette::OkabeIto => Colors {
+                z_spider: "#80ceb9",
+                x_spider: "#eaae80",
+                h_box: "#f0e442",
+                web_x: "#d55e00",
+                web_z: "#009e73",
+                web_y: "#0072b2",
+            },
+            Palette::Viridis => Colors {
+                z_spider: "#aee4b0",
+                x_spider: "#a180aa",
+                h_box: "#fde725",
+                web_x: "#440154",
+                web_z: "#5ec962",
+                web_y: "#21918c",
+            },
+        }
+    }
+
+    /// Colours told apart by index, e.g. one per web in a composite figure
+    ///
+    /// `Classic` uses the Tableau 10 colours, the others stay within their scheme.
+    pub fn series(self) -> &'static [&'static str] {
+        match self {
+            Palette::Classic => &[
+                "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd",
+                "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf",
+            ],
+            Palette::OkabeIto => &[
+                "#e69f00", "#56b4e9", "#009e73", "#f0e442",
+                "#0072b2", "#d55e00", "#cc79a7", "#000000",
+            ],
+            Palette::Viridis => &[
+                "#440154", "#482878", "#3e4989", "#31688e", "#26828e",
+                "#1f9e89", "#35b779", "#6ece58", "#b5de2b", "#fde725",
+            ],
+        }
+    }
+
+    /// Colour `index` of [`Palette::series`], cycling when there are more items than colours
+    pub fn series_color(self, index: usize) -> &'static str {
+        let series = self.series();
+        series[index % series.len()]
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Palette::Classic => "classic",
+            Palette::OkabeIto => "okabe-ito",
+            Palette::Viridis => "viridis",
+        })
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "classic" => Ok(Palette::Classic),
+            "okabe-ito" => Ok(Palette::OkabeIto),
+            "viridis" => Ok(Palette::Viridis),
+            _ => Err(format!("Unknown palette '{}', expected classic, okabe-ito or viridis", s)),
+        }
+    }
+}
+
+/// `#rrggbb` as an xcolor expression TikZ accepts, e.g. `{rgb,255:red,213;green,94;blue,0}`
+pub fn tikz_color(hex: &str) -> String {
+    let channel = |i: usize| u8::from_str_radix(&hex[1 + 2 * i..3 + 2 * i], 16).expect("palette colours are #rrggbb");
+    format!("{{rgb,255:red,{};green,{};blue,{}}}", channel(0), channel(1), channel(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palettes() {
+        for palette in [Palette::Classic, Palette::OkabeIto, Palette::Viridis] {
+            assert_eq!(palette.to_string().parse(), Ok(palette));
+            let colors = palette.colors();
+            let all = [colors.z_spider, colors.x_spider, colors.h_box, colors.web_x, colors.web_z, colors.web_y];
+            assert!(all.iter().chain(palette.series()).all(|c| c.len() == 7 && c.starts_with('#')));
+            assert_eq!(palette.series_color(palette.series().len()), palette.series_color(0));
+        }
+        assert!("rainbow".parse::<Palette>().is_err());
+        assert_eq!(tikz_color("#d55e00"), "{rgb,255:red,213;green,94;blue,0}");
+    }
+}
@@ -1,15 +1,46 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use serde::{Serialize, Deserialize};
+use quizx::graph::{EType, GraphLike};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Pauli {
     X,
     Y,
     Z,
 }
 
+impl Pauli {
+    /// The Pauli on the other side of a Hadamard, X and Z swapped
+    pub fn through_hadamard(self) -> Pauli {
+        match self {
+            Pauli::X => Pauli::Z,
+            Pauli::Z => Pauli::X,
+            Pauli::Y => Pauli::Y,
+        }
+    }
+}
+
+impl fmt::Display for Pauli {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Pauli::X => "X",
+            Pauli::Y => "Y",
+            Pauli::Z => "Z",
+        })
+    }
+}
+
 /// Represents a Pauli web in a ZX diagram
-#[derive(Debug, Default, Clone)]
+///
+/// Equality, hashing and ordering only depend on the set of (edge, Pauli) pairs,
+/// never on HashMap iteration order.
+///
+/// On a Hadamard edge the Pauli is the one seen from the smaller endpoint; the larger
+/// endpoint sees X and Z swapped.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct PauliWeb {
     /// Maps edge (from, to) to Pauli operator
     /// Note: from < to to ensure consistent ordering
@@ -40,6 +71,153 @@ impl PauliWeb {
             Pauli::Z => "red",    // Red for Z operators
         })
     }
+
+    /// Keep only the edges with both endpoints in `vertices`
+    pub fn restrict_to(&self, vertices: &HashSet<usize>) -> PauliWeb {
+        PauliWeb {
+            edge_operators: self.edge_operators.iter()
+                .filter(|((from, to), _)| vertices.contains(from) && vertices.contains(to))
+                .map(|(&edge, &pauli)| (edge, pauli))
+                .collect(),
+        }
+    }
+
+    /// Maps the web on `graph` through a vertex relabeling, e.g. after compacting or
+    /// composing graphs
+    ///
+    /// A Hadamard edge of `graph` whose endpoints swap order under `map` gets X and Z
+    /// swapped, so its Pauli is still the one seen from the smaller endpoint. Fails if an
+    /// endpoint of some web edge has no image in `map`, or two of them share an image,
+    /// which would merge edges.
+    pub fn relabel<G: GraphLike>(&self, graph: &G, map: &HashMap<usize, usize>) -> Result<PauliWeb, String> {
+        let mut relabeled = PauliWeb::new();
+        let mut preimage: HashMap<usize, usize> = HashMap::new();
+        for ((from, to), pauli) in self.sorted_edges() {
+            let mut image = |v: usize| {
+                let w = map.get(&v).copied()
+                    .ok_or_else(|| format!("Vertex {} of edge ({}, {}) has no image in the relabeling", v, from, to))?;
+                match preimage.insert(w, v) {
+                    Some(u) if u != v => Err(format!("Vertices {} and {} both map to {} in the relabeling", u, v, w)),
+                    _ => Ok(w),
+                }
+            };
+            let (a, b) = (image(from)?, image(to)?);
+            let flipped = a > b && graph.edge_type_opt(from, to) == Some(EType::H);
+            relabeled.set_edge(a, b, if flipped { pauli.through_hadamard() } else { pauli });
+        }
+        Ok(relabeled)
+    }
+
+    /// Edges with their operators, sorted by edge
+    pub fn sorted_edges(&self) -> Vec<((usize, usize), Pauli)> {
+        let mut edges: Vec<_> = self.edge_operators.iter().map(|(&e, &p)| (e, p)).collect();
+        edges.sort();
+        edges
+    }
+
+    /// Byte encoding that is identical for equal webs, across runs and platforms
+    ///
+    /// Each edge in sorted order contributes `from` and `to` as little-endian u64
+    /// followed by one of the bytes `X`, `Y`, `Z`.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let edges = self.sorted_edges();
+        let mut bytes = Vec::with_capacity(edges.len() * 17);
+        for ((from, to), pauli) in edges {
+            bytes.extend_from_slice(&(from as u64).to_le_bytes());
+            bytes.extend_from_slice(&(to as u64).to_le_bytes());
+            bytes.push(match pauli {
+                Pauli::X => b'X',
+                Pauli::Y => b'Y',
+                Pauli::Z => b'Z',
+            });
+        }
+        bytes
+    }
+
+    /// The Pauli string the web induces on the boundary of `graph`
+    ///
+    /// One entry per boundary, inputs first then outputs, in the graph's order.
+    /// Each entry is the operator on the boundary's edge, or `None` for identity.
+    pub fn project_to_boundary<G: GraphLike>(&self, graph: &G) -> Vec<Option<Pauli>> {
+        graph.inputs().iter()
+            .chain(graph.outputs().iter())
+            .map(|&b| graph.neighbors(b).find_map(|n| self.get_edge(b, n)))
+            .collect()
+    }
+
+    /// Name of the web that only depends on the web itself, not on the order or the
+    /// thread it was computed in
+    ///
+    /// Lists the boundary support as `<Pauli><index>` in [`Self::project_to_boundary`]
+    /// order (`I` if there is none), then the number of edges and a hash of
+    /// [`Self::canonical_bytes`], e.g. `Z0X3_w12_5f0e2a91`. Safe to use in file names.
+    pub fn signature<G: GraphLike>(&self, graph: &G) -> String {
+        let support: String = self.project_to_boundary(graph).iter()
+            .enumerate()
+            .filter_map(|(i, pauli)| pauli.map(|p| format!("{}{}", p, i)))
+            .collect();
+        let support = if support.is_empty() { "I".to_string() } else { support };
+        let hash = fnv1a(&self.canonical_bytes());
+        format!("{}_w{}_{:08x}", support, self.edge_operators.len(), (hash ^ (hash >> 32)) as u32)
+    }
+}
+
+/// [`PauliWeb::signature`] of each web, with `_2`, `_3`, ... appended to repeats
+pub fn web_names<G: GraphLike>(graph: &G, webs: &[PauliWeb]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    webs.iter()
+        .map(|web| {
+            let signature = web.signature(graph);
+            let count = seen.entry(signature.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 { signature } else { format!("{}_{}", signature, count) }
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a, fixed by its specification unlike the std hashers
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+impl Hash for PauliWeb {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sorted_edges().hash(state);
+    }
+}
+
+impl PartialOrd for PauliWeb {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PauliWeb {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted_edges().cmp(&other.sorted_edges())
+    }
+}
+
+/// Helpers for the tests of other modules
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use quizx::graph::{VData, VType, V};
+    use quizx::hash_graph::Graph;
+
+    /// Adds a phase-free vertex at `qubit`, `row`
+    pub(crate) fn vertex_at(g: &mut Graph, ty: VType, qubit: f64, row: f64) -> V {
+        g.add_vertex_with_data(VData { ty, phase: 0.into(), qubit, row })
+    }
+
+    /// The web with `pauli` on each of the edges
+    pub(crate) fn web(edges: &[((usize, usize), Pauli)]) -> PauliWeb {
+        let mut web = PauliWeb::new();
+        for &((a, b), pauli) in edges {
+            web.set_edge(a, b, pauli);
+        }
+        web
+    }
 }
 
 #[cfg(test)]
@@ -96,4 +274,149 @@ mod tests {
         pw.set_edge(1, 2, Pauli::Z);
         assert_eq!(pw.get_edge(2, 1), Some(Pauli::Z));
     }
+
+    #[test]
+    fn test_restrict_to() {
+        let mut pw = PauliWeb::new();
+        pw.set_edge(1, 2, Pauli::X);
+        pw.set_edge(2, 3, Pauli::Z);
+        pw.set_edge(3, 4, Pauli::Y);
+
+        let restricted = pw.restrict_to(&HashSet::from([1, 2, 3]));
+        assert_eq!(restricted.edge_operators.len(), 2);
+        assert_eq!(restricted.get_edge(1, 2), Some(Pauli::X));
+        assert_eq!(restricted.get_edge(2, 3), Some(Pauli::Z));
+        assert_eq!(restricted.get_edge(3, 4), None);
+    }
+
+    #[test]
+    fn test_project_to_boundary() {
+        use quizx::graph::VType;
+        use quizx::hash_graph::Graph;
+
+        let mut g = Graph::new();
+        let b_in = g.add_vertex(VType::B);
+        let z = g.add_vertex(VType::Z);
+        let b_out0 = g.add_vertex(VType::B);
+        let b_out1 = g.add_vertex(VType::B);
+        g.add_edge(b_in, z);
+        g.add_edge(z, b_out0);
+        g.add_edge(z, b_out1);
+        g.set_inputs(vec![b_in]);
+        g.set_outputs(vec![b_out0, b_out1]);
+
+        let mut pw = PauliWeb::new();
+        pw.set_edge(b_in, z, Pauli::X);
+        pw.set_edge(z, b_out1, Pauli::X);
+
+        assert_eq!(pw.project_to_boundary(&g), vec![Some(Pauli::X), None, Some(Pauli::X)]);
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        use std::collections::BTreeSet;
+
+        // Same web built in different orders
+        let mut a = PauliWeb::new();
+        for i in 0..50 {
+            a.set_edge(i, i + 1, if i % 2 == 0 { Pauli::X } else { Pauli::Z });
+        }
+        let mut b = PauliWeb::new();
+        for i in (0..50).rev() {
+            b.set_edge(i + 1, i, if i % 2 == 0 { Pauli::X } else { Pauli::Z });
+        }
+        assert_eq!(a, b);
+        assert_eq!(a.canonical_bytes(), b.canonical_bytes());
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(HashSet::from([a.clone(), b.clone()]).len(), 1);
+
+        let mut c = a.clone();
+        c.set_edge(0, 1, Pauli::Y);
+        assert_ne!(a.canonical_bytes(), c.canonical_bytes());
+        assert_eq!(BTreeSet::from([a.clone(), b, c]).len(), 2);
+
+        let mut small = PauliWeb::new();
+        small.set_edge(1, 2, Pauli::Z);
+        assert_eq!(small.canonical_bytes(), [&1u64.to_le_bytes()[..], &2u64.to_le_bytes()[..], b"Z"].concat());
+        assert!(PauliWeb::new() < small);
+    }
+
+    #[test]
+    fn test_signature() {
+        use quizx::hash_graph::Graph;
+
+        // in - z - out, with the web on both legs
+        let mut g = Graph::new();
+        let input = g.add_vertex(quizx::graph::VType::B);
+        let z = g.add_vertex(quizx::graph::VType::Z);
+        let output = g.add_vertex(quizx::graph::VType::B);
+        g.add_edge(input, z);
+        g.add_edge(z, output);
+        g.set_inputs(vec![input]);
+        g.set_outputs(vec![output]);
+
+        let mut web = PauliWeb::new();
+        web.set_edge(input, z, Pauli::Z);
+        web.set_edge(z, output, Pauli::Z);
+        let signature = web.signature(&g);
+        assert!(signature.starts_with("Z0Z1_w2_"), "{}", signature);
+        assert_eq!(signature.len(), "Z0Z1_w2_".len() + 8);
+        // Pinned, so names stay the same across versions
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+
+        let mut inner = PauliWeb::new();
+        inner.set_edge(z, output, Pauli::X);
+        let names = web_names(&g, &[inner.clone(), web.clone(), inner]);
+        assert!(names[0].starts_with("X1_w1_"));
+        assert_eq!(names[2], format!("{}_2", names[0]));
+        assert!(PauliWeb::new().signature(&g).starts_with("I_w0_"));
+    }
+
+    #[test]
+    fn test_relabel() {
+        let mut g = quizx::hash_graph::Graph::new();
+        let vs: Vec<usize> = (0..4).map(|_| g.add_vertex(quizx::graph::VType::Z)).collect();
+        g.add_edge(vs[1], vs[2]);
+        g.add_edge(vs[2], vs[3]);
+        let mut pw = PauliWeb::new();
+        pw.set_edge(vs[1], vs[2], Pauli::X);
+        pw.set_edge(vs[2], vs[3], Pauli::Z);
+
+        // Reversing the order flips the stored orientation of each edge, which only
+        // matters on Hadamard edges
+        let map = HashMap::from([(vs[1], 12), (vs[2], 11), (vs[3], 10)]);
+        let relabeled = pw.relabel(&g, &map).unwrap();
+        assert_eq!(relabeled.edge_operators.len(), 2);
+        assert_eq!(relabeled.edge_operators[&(11, 12)], Pauli::X);
+        assert_eq!(relabeled.get_edge(10, 11), Some(Pauli::Z));
+
+        let partial = HashMap::from([(vs[1], 0), (vs[2], 1)]);
+        let err = pw.relabel(&g, &partial).unwrap_err();
+        assert!(err.contains(&format!("Vertex {}", vs[3])));
+
+        let merging = HashMap::from([(vs[1], 10), (vs[2], 11), (vs[3], 10)]);
+        assert!(pw.relabel(&g, &merging).unwrap_err().contains("both map to 10"));
+    }
+
+    #[test]
+    fn test_relabel_hadamard_edge() {
+        let mut g = quizx::hash_graph::Graph::new();
+        let vs: Vec<usize> = (0..3).map(|_| g.add_vertex(quizx::graph::VType::Z)).collect();
+        g.add_edge_with_type(vs[0], vs[1], EType::H);
+        g.add_edge_with_type(vs[1], vs[2], EType::H);
+        let mut pw = PauliWeb::new();
+        pw.set_edge(vs[0], vs[1], Pauli::X);
+        pw.set_edge(vs[1], vs[2], Pauli::Y);
+
+        // The endpoints of the first edge swap order, so X seen from vs[0] is Z seen
+        // from the new smaller endpoint, the image of vs[1]; Y stays Y
+        let map = HashMap::from([(vs[0], 20), (vs[1], 5), (vs[2], 3)]);
+        let relabeled = pw.relabel(&g, &map).unwrap();
+        assert_eq!(relabeled.get_edge(5, 20), Some(Pauli::Z));
+        assert_eq!(relabeled.get_edge(3, 5), Some(Pauli::Y));
+
+        // Order kept, nothing swapped
+        let shifted = HashMap::from([(vs[0], 100), (vs[1], 101), (vs[2], 102)]);
+        assert_eq!(pw.relabel(&g, &shifted).unwrap().get_edge(100, 101), Some(Pauli::X));
+    }
 }
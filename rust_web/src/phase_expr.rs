@@ -0,0 +1,260 @@
+use num::{Rational64, Zero};
+use quizx::graph::{GraphLike, V};
+use quizx::phase::Phase;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Add;
+
+/// A phase in units of π that may depend on named parameters
+///
+/// Build sums with `+`; they are kept flat, with all constants folded into one term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhaseExpr {
+    Const(Rational64),
+    Param(String),
+    Sum(Vec<PhaseExpr>),
+}
+
+impl PhaseExpr {
+    pub fn param(name: impl Into<String>) -> Self {
+        PhaseExpr::Param(name.into())
+    }
+
+    /// Names of the parameters the expression depends on
+    pub fn params(&self) -> BTreeSet<&str> {
+        match self {
+            PhaseExpr::Const(_) => BTreeSet::new(),
+            PhaseExpr::Param(name) => BTreeSet::from([name.as_str()]),
+            PhaseExpr::Sum(terms) => terms.iter().flat_map(|t| t.params()).collect(),
+        }
+    }
+
+    /// The value of the expression, if no parameters are left
+    pub fn as_const(&self) -> Option<Rational64> {
+        match self {
+            PhaseExpr::Const(r) => Some(*r),
+            PhaseExpr::Param(_) => None,
+            PhaseExpr::Sum(terms) => terms.iter().map(|t| t.as_const()).sum(),
+        }
+    }
+
+    /// The sum of the constant terms, i.e. the value with every parameter set to 0
+    pub fn constant_part(&self) -> Rational64 {
+        match self {
+            PhaseExpr::Const(r) => *r,
+            PhaseExpr::Param(_) => Rational64::zero(),
+            PhaseExpr::Sum(terms) => terms.iter().map(|t| t.constant_part()).sum(),
+        }
+    }
+
+    /// Replaces the parameters found in `values`; the others stay symbolic
+    pub fn substitute(&self, values: &HashMap<String, Rational64>) -> PhaseExpr {
+        match self {
+            PhaseExpr::Param(name) => match values.get(name) {
+                Some(&r) => PhaseExpr::Const(r),
+                None => self.clone(),
+            },
+            PhaseExpr::Const(_) => self.clone(),
+            PhaseExpr::Sum(terms) => terms.iter()
+                .map(|t| t.substitute(values))
+                .fold(PhaseExpr::Const(Rational64::zero()), |acc, t| acc + t),
+        }
+    }
+
+    fn into_terms(self) -> Vec<PhaseExpr> {
+        match self {
+            PhaseExpr::Sum(terms) => terms,
+            term => vec![term],
+        }
+    }
+}
+
+impl Add for PhaseExpr {
+    type Output = PhaseExpr;
+
+    fn add(self, other: PhaseExpr) -> PhaseExpr {
+        let mut constant = Rational64::zero();
+        let mut params = Vec::new();
+        for term in self.into_terms().into_iter().chain(other.into_terms()) {
+            match term {
+                PhaseExpr::Const(r) => constant += r,
+                param => params.push(param),
+            }
+        }
+        if !constant.is_zero() || params.is_empty() {
+            params.push(PhaseExpr::Const(constant));
+        }
+        if params.len() == 1 {
+            params.pop().unwrap()
+        } else {
+            PhaseExpr::Sum(params)
+        }
+    }
+}
+
+impl From<Rational64> for PhaseExpr {
+    fn from(r: Rational64) -> Self {
+        PhaseExpr::Const(r)
+    }
+}
+
+/// Writes `r` as a multiple of π, e.g. `π/2`, `-3π/4` or `0`
+fn fmt_pi(r: Rational64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (numer, denom) = (*r.numer(), *r.denom());
+    match numer {
+        0 => write!(f, "0")?,
+        1 => write!(f, "π")?,
+        -1 => write!(f, "-π")?,
+        n => write!(f, "{}π", n)?,
+    }
+    if numer != 0 && denom != 1 {
+        write!(f, "/{}", denom)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for PhaseExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhaseExpr::Const(r) => fmt_pi(*r, f),
+            PhaseExpr::Param(name) => write!(f, "{}", name),
+            PhaseExpr::Sum(terms) => {
+                for (i, term) in terms.iter().enumerate() {
+                    match term {
+                        PhaseExpr::Const(r) if i > 0 && *r < Rational64::zero() => {
+                            write!(f, " - ")?;
+                            fmt_pi(-*r, f)?;
+                        }
+                        _ if i > 0 => write!(f, " + {}", term)?,
+                        _ => write!(f, "{}", term)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Symbolic phases of some vertices, kept in a table next to the graph
+///
+/// The graph stores the constant part of each symbolic phase, so rewrites and numeric
+/// code still see a valid diagram. Pass [`PhaseTable::labels`] as
+/// [`crate::graph_visualizer::RenderOptions::phase_labels`] to draw the parameter names.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTable {
+    exprs: HashMap<V, PhaseExpr>,
+}
+
+impl PhaseTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the phase of `v`; constant expressions go straight into the graph
+    pub fn set_phase<G: GraphLike>(&mut self, g: &mut G, v: V, expr: PhaseExpr) {
+        g.set_phase(v, Phase::from(expr.constant_part()));
+        if expr.as_const().is_some() {
+            self.exprs.remove(&v);
+        } else {
+            self.exprs.insert(v, expr);
+        }
+    }
+
+    /// The symbolic phase of `v`, or `None` if the graph's phase is all there is
+    pub fn get(&self, v: V) -> Option<&PhaseExpr> {
+        self.exprs.get(&v)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exprs.is_empty()
+    }
+
+    /// All parameters used in the diagram
+    pub fn params(&self) -> BTreeSet<String> {
+        self.exprs.values().flat_map(|e| e.params()).map(String::from).collect()
+    }
+
+    /// Substitutes `values` into every symbolic phase
+    ///
+    /// Vertices whose phase becomes constant get it written to the graph and leave the table.
+    pub fn substitute<G: GraphLike>(&mut self, g: &mut G, values: &HashMap<String, Rational64>) {
+        let mut vertices: Vec<V> = self.exprs.keys().copied().collect();
+        vertices.sort();
+        for v in vertices {
+            let expr = self.exprs[&v].substitute(values);
+            self.set_phase(g, v, expr);
+        }
+    }
+
+    /// Forgets vertices that no longer exist in `g`, e.g. after a rewrite removed them
+    pub fn retain_vertices<G: GraphLike>(&mut self, g: &G) {
+        self.exprs.retain(|&v, _| g.contains_vertex(v));
+    }
+
+    /// Phase labels of the symbolic vertices, for rendering
+    pub fn labels(&self) -> HashMap<V, String> {
+        self.exprs.iter().map(|(&v, e)| (v, e.to_string())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::VType;
+    use quizx::hash_graph::Graph;
+
+    fn r(n: i64, d: i64) -> Rational64 {
+        Rational64::new(n, d)
+    }
+
+    #[test]
+    fn test_expr_arithmetic_and_display() {
+        let theta = PhaseExpr::param("θ");
+        let expr = theta.clone() + PhaseExpr::from(r(1, 4)) + PhaseExpr::param("φ") + PhaseExpr::from(r(1, 4));
+        assert_eq!(expr, PhaseExpr::Sum(vec![theta.clone(), PhaseExpr::param("φ"), PhaseExpr::Const(r(1, 2))]));
+        assert_eq!(expr.to_string(), "θ + φ + π/2");
+        assert_eq!(expr.params(), BTreeSet::from(["θ", "φ"]));
+        assert_eq!(expr.as_const(), None);
+        assert_eq!(expr.constant_part(), r(1, 2));
+
+        assert_eq!((theta.clone() + PhaseExpr::from(r(-3, 4))).to_string(), "θ - 3π/4");
+        assert_eq!(PhaseExpr::from(r(0, 1)).to_string(), "0");
+        assert_eq!(PhaseExpr::from(r(-1, 1)).to_string(), "-π");
+
+        let values = HashMap::from([("θ".to_string(), r(1, 2))]);
+        let partial = expr.substitute(&values);
+        assert_eq!(partial.to_string(), "φ + π");
+        let full = partial.substitute(&HashMap::from([("φ".to_string(), r(-1, 1))]));
+        assert_eq!(full, PhaseExpr::Const(r(0, 1)));
+        assert_eq!(full.as_const(), Some(r(0, 1)));
+    }
+
+    #[test]
+    fn test_phase_table() {
+        let mut g = Graph::new();
+        let a = g.add_vertex(VType::Z);
+        let b = g.add_vertex(VType::X);
+        let mut table = PhaseTable::new();
+
+        table.set_phase(&mut g, a, PhaseExpr::param("α") + PhaseExpr::from(r(1, 2)));
+        table.set_phase(&mut g, b, PhaseExpr::param("β"));
+        assert_eq!(g.phase(a), Phase::from(r(1, 2)));
+        assert_eq!(table.params(), BTreeSet::from(["α".to_string(), "β".to_string()]));
+        assert_eq!(table.labels()[&a], "α + π/2");
+
+        table.substitute(&mut g, &HashMap::from([("α".to_string(), r(1, 4))]));
+        assert_eq!(table.get(a), None);
+        assert_eq!(g.phase(a), Phase::from(r(3, 4)));
+        assert_eq!(table.get(b), Some(&PhaseExpr::param("β")));
+
+        // A constant phase replaces the symbolic one
+        table.set_phase(&mut g, b, PhaseExpr::from(r(1, 1)));
+        assert!(table.is_empty());
+        assert_eq!(g.phase(b), Phase::from(r(1, 1)));
+
+        table.set_phase(&mut g, b, PhaseExpr::param("γ"));
+        g.remove_vertex(b);
+        table.retain_vertices(&g);
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use quizx::graph::{GraphLike, VType, V};
+
+/// A phase gadget: a phase-carrying leaf hanging off a phase-free hub
+///
+/// The gadget applies the leaf's phase to the parity of the hub's other neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseGadget {
+    pub hub: V,
+    pub leaf: V,
+    /// Neighbours of the hub other than the leaf, sorted
+    pub targets: Vec<V>,
+}
+
+/// Finds all phase gadgets: a Z/X spider of degree 1 with a non-zero phase whose only
+/// neighbour is a phase-free Z/X spider with at least one other neighbour
+///
+/// Gadgets are sorted by hub. A hub with several leaves yields one gadget per leaf.
+pub fn find_phase_gadgets<G: GraphLike>(g: &G) -> Vec<PhaseGadget> {
+    let is_spider = |v: V| matches!(g.vertex_type(v), VType::Z | VType::X);
+    let mut gadgets = Vec::new();
+
+    let mut vertices: Vec<V> = g.vertices().collect();
+    vertices.sort();
+    for leaf in vertices {
+        if !is_spider(leaf) || g.degree(leaf) != 1 || g.phase(leaf).to_f64() == 0.0 {
+            continue;
+        }
+        let hub = g.neighbors(leaf).next().unwrap();
+        if !is_spider(hub) || g.phase(hub).to_f64() != 0.0 || g.degree(hub) < 2 {
+            continue;
+        }
+        let mut targets: Vec<V> = g.neighbors(hub).filter(|&n| n != leaf).collect();
+        targets.sort();
+        gadgets.push(PhaseGadget { hub, leaf, targets });
+    }
+
+    gadgets.sort_by_key(|gadget| (gadget.hub, gadget.leaf));
+    gadgets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::EType;
+    use quizx::hash_graph::Graph;
+
+    #[test]
+    fn test_find_gadget() {
+        // Two qubit spiders with a π/4 gadget on their parity
+        let mut g = Graph::new();
+        let q0 = g.add_vertex(VType::Z);
+        let q1 = g.add_vertex(VType::Z);
+        let hub = g.add_vertex(VType::Z);
+        let leaf = g.add_vertex_with_phase(VType::Z, 0.25);
+        g.add_edge_with_type(q0, hub, EType::H);
+        g.add_edge_with_type(q1, hub, EType::H);
+        g.add_edge_with_type(hub, leaf, EType::H);
+
+        let gadgets = find_phase_gadgets(&g);
+        assert_eq!(gadgets, vec![PhaseGadget { hub, leaf, targets: vec![q0, q1] }]);
+    }
+
+    #[test]
+    fn test_not_gadgets() {
+        let mut g = Graph::new();
+        // Phase-free leaf
+        let a = g.add_vertex(VType::Z);
+        let b = g.add_vertex(VType::Z);
+        let c = g.add_vertex(VType::Z);
+        g.add_edge(a, b);
+        g.add_edge(b, c);
+        // Hub with a phase
+        let hub = g.add_vertex_with_phase(VType::X, 0.5);
+        let leaf = g.add_vertex_with_phase(VType::Z, 0.25);
+        g.add_edge(c, hub);
+        g.add_edge(hub, leaf);
+        // Isolated pair: the hub has nothing to act on
+        let lone_hub = g.add_vertex(VType::Z);
+        let lone_leaf = g.add_vertex_with_phase(VType::Z, 0.25);
+        g.add_edge(lone_hub, lone_leaf);
+
+        assert!(find_phase_gadgets(&g).is_empty());
+    }
+}
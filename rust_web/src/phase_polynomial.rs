@@ -0,0 +1,275 @@
+use crate::linalg::Mat2;
+use crate::detection_webs::classify_boundaries;
+use quizx::graph::{EType, VType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Phase polynomial of a CNOT + diagonal circuit
+///
+/// The circuit acts as |x> -> exp(iπ Σ_j phases[j] · (parities[j] · x)) |linear · x>.
+#[derive(Debug, Clone)]
+pub struct PhasePolynomial {
+    pub num_qubits: usize,
+    /// One row per term: the parity of the inputs the phase acts on
+    pub parities: Mat2,
+    /// Coefficient of each term, in units of π and reduced to [0, 2)
+    pub phases: Vec<f64>,
+    /// Row i is the parity of the inputs carried by output wire i
+    pub linear: Mat2,
+}
+
+impl fmt::Display for PhasePolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (t, phase) in self.phases.iter().enumerate() {
+            let vars: Vec<String> = (0..self.num_qubits)
+                .filter(|&q| self.parities.get(t, q))
+                .map(|q| format!("x{}", q))
+                .collect();
+            writeln!(f, "{}π : {}", phase, vars.join(" ⊕ "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the phase polynomial of a circuit-form diagram
+///
+/// Each spider sits on the wire of the input with the same qubit coordinate, at most
+/// one per wire and row. Within a row, a phase-free X spider joined by plain edges to
+/// Z spiders on other wires is a (multi-)CNOT target, two Z spiders joined by a
+/// Hadamard edge form a CZ, and a Z spider's phase is a rotation of its wire's parity.
+/// Anything else (X phases, Hadamards on wires, edges across rows) is an error.
+pub fn phase_polynomial(g: &Graph) -> Result<PhasePolynomial, String> {
+    let (inputs, _) = if g.inputs().is_empty() && g.outputs().is_empty() {
+        classify_boundaries(g)?
+    } else {
+        (g.inputs().clone(), g.outputs().clone())
+    };
+    let n = inputs.len();
+
+    let wire_of_qubit: Vec<(f64, usize)> = inputs.iter()
+        .enumerate()
+        .map(|(i, &b)| (g.qubit(b), i))
+        .collect();
+    let wire = |v: V| -> Result<usize, String> {
+        wire_of_qubit.iter()
+            .find(|&&(q, _)| q == g.qubit(v))
+            .map(|&(_, i)| i)
+            .ok_or_else(|| format!("Vertex {} is not on the wire of any input", v))
+    };
+
+    // Group spiders by row, in time order
+    let mut rows: Vec<(f64, Vec<V>)> = Vec::new();
+    let mut spiders: Vec<V> = g.vertices().filter(|&v| g.vertex_type(v) != VType::B).collect();
+    spiders.sort_by(|&a, &b| g.row(a).total_cmp(&g.row(b)).then(a.cmp(&b)));
+    for v in spiders {
+        match rows.last_mut() {
+            Some((r, vs)) if *r == g.row(v) => vs.push(v),
+            _ => rows.push((g.row(v), vec![v])),
+        }
+    }
+
+    // Current parity on every wire, starting from the identity
+    let mut parity: Vec<Vec<bool>> = (0..n).map(|i| (0..n).map(|j| i == j).collect()).collect();
+    let mut terms: BTreeMap<Vec<bool>, f64> = BTreeMap::new();
+    let mut add_term = |p: &[bool], phase: f64| {
+        *terms.entry(p.to_vec()).or_insert(0.0) += phase;
+    };
+    let xor = |a: &[bool], b: &[bool]| -> Vec<bool> {
+        a.iter().zip(b).map(|(&x, &y)| x ^ y).collect()
+    };
+
+    for (row, vs) in &rows {
+        let mut wires: HashMap<V, usize> = HashMap::new();
+        for &v in vs {
+            let w = wire(v)?;
+            if wires.values().any(|&other| other == w) {
+                return Err(format!("Two spiders on wire {} at row {}", w, row));
+            }
+            wires.insert(v, w);
+        }
+
+        let mut updates: Vec<(usize, Vec<bool>)> = Vec::new();
+        for &v in vs {
+            let w = wires[&v];
+            let phase = g.phase(v).to_f64();
+            let mut controls = Vec::new();
+            for u in g.neighbors(v) {
+                let ety = g.edge_type(v, u);
+                if g.vertex_type(u) == VType::B || g.row(u) != *row {
+                    if ety != EType::N {
+                        return Err(format!("Hadamard edge {}-{} on a wire is not supported", v, u));
+                    }
+                    if g.vertex_type(u) != VType::B && g.qubit(u) != g.qubit(v) {
+                        return Err(format!("Edge {}-{} crosses wires between rows", v, u));
+                    }
+                    continue;
+                }
+                match (g.vertex_type(v), g.vertex_type(u), ety) {
+                    (VType::X, VType::Z, EType::N) => controls.push(wires[&u]),
+                    (VType::Z, VType::X, EType::N) => {}
+                    // CZ = exp(iπ/2 (x_a + x_b - x_a ⊕ x_b)), counted once from the lower id
+                    (VType::Z, VType::Z, EType::H) if v < u => {
+                        let a = &parity[w];
+                        let b = &parity[wires[&u]];
+                        add_term(a, 0.5);
+                        add_term(b, 0.5);
+                        add_term(&xor(a, b), -0.5);
+                    }
+                    (VType::Z, VType::Z, EType::H) => {}
+                    _ => return Err(format!("Unsupported gate between {} and {} at row {}", v, u, row)),
+                }
+            }
+
+            match g.vertex_type(v) {
+                VType::Z => {
+                    if phase != 0.0 {
+                        add_term(&parity[w], phase);
+                    }
+                }
+                VType::X => {
+                    if phase != 0.0 {
+                        return Err(format!("X spider {} has a phase, the circuit is not diagonal", v));
+                    }
+                    let mut p = parity[w].clone();
+                    for c in controls {
+                        p = xor(&p, &parity[c]);
+                    }
+                    updates.push((w, p));
+                }
+                other => return Err(format!("Vertex {} has unsupported type {:?}", v, other)),
+            }
+        }
+        for (w, p) in updates {
+            parity[w] = p;
+        }
+    }
+
+    let terms: Vec<(Vec<bool>, f64)> = terms.into_iter()
+        .map(|(p, phase)| (p, phase.rem_euclid(2.0)))
+        .filter(|(p, phase)| p.iter().any(|&b| b) && *phase > 1e-9 && *phase < 2.0 - 1e-9)
+        .collect();
+
+    let mut parities = Mat2::new(terms.len(), n);
+    for (t, (p, _)) in terms.iter().enumerate() {
+        for (q, &b) in p.iter().enumerate() {
+            parities.set(t, q, b);
+        }
+    }
+    let mut linear = Mat2::new(n, n);
+    for (w, p) in parity.iter().enumerate() {
+        for (q, &b) in p.iter().enumerate() {
+            linear.set(w, q, b);
+        }
+    }
+
+    Ok(PhasePolynomial {
+        num_qubits: n,
+        parities,
+        phases: terms.into_iter().map(|(_, phase)| phase).collect(),
+        linear,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::VData;
+    use quizx::phase::Phase;
+
+    /// Circuit scaffold with `n` wires, inputs at row 0 and outputs added by `finish`
+    struct Circuit {
+        g: Graph,
+        last: Vec<V>,
+    }
+
+    impl Circuit {
+        fn new(n: usize) -> Self {
+            let mut g = Graph::new();
+            let last: Vec<V> = (0..n)
+                .map(|q| g.add_vertex_with_data(VData { ty: VType::B, phase: 0.into(), qubit: q as f64, row: 0.0 }))
+                .collect();
+            g.set_inputs(last.clone());
+            Circuit { g, last }
+        }
+
+        fn spider(&mut self, ty: VType, phase: f64, qubit: usize, row: f64) -> V {
+            let v = self.g.add_vertex_with_data(VData { ty, phase: Phase::from_f64(phase), qubit: qubit as f64, row });
+            self.g.add_edge(self.last[qubit], v);
+            self.last[qubit] = v;
+            v
+        }
+
+        fn cnot(&mut self, control: usize, target: usize, row: f64) {
+            let c = self.spider(VType::Z, 0.0, control, row);
+            let t = self.spider(VType::X, 0.0, target, row);
+            self.g.add_edge(c, t);
+        }
+
+        fn finish(mut self, row: f64) -> Graph {
+            let outputs: Vec<V> = (0..self.last.len())
+                .map(|q| {
+                    let b = self.g.add_vertex_with_data(VData { ty: VType::B, phase: 0.into(), qubit: q as f64, row });
+                    self.g.add_edge(self.last[q], b);
+                    b
+                })
+                .collect();
+            self.g.set_outputs(outputs);
+            self.g
+        }
+    }
+
+    fn row(m: &Mat2, r: usize) -> Vec<bool> {
+        (0..m.cols()).map(|c| m.get(r, c)).collect()
+    }
+
+    #[test]
+    fn test_cnot_t_cnot() {
+        // CNOT(0,1); T on qubit 1; CNOT(0,1): a single T on x0 ⊕ x1
+        let mut c = Circuit::new(2);
+        c.cnot(0, 1, 1.0);
+        c.spider(VType::Z, 0.25, 1, 2.0);
+        c.cnot(0, 1, 3.0);
+        let poly = phase_polynomial(&c.finish(4.0)).unwrap();
+
+        assert_eq!(poly.phases, vec![0.25]);
+        assert_eq!(row(&poly.parities, 0), vec![true, true]);
+        assert_eq!(row(&poly.linear, 0), vec![true, false]);
+        assert_eq!(row(&poly.linear, 1), vec![false, true]);
+        assert_eq!(poly.to_string(), "0.25π : x0 ⊕ x1\n");
+    }
+
+    #[test]
+    fn test_terms_merge_and_cancel() {
+        let mut c = Circuit::new(2);
+        c.spider(VType::Z, 0.25, 0, 1.0);
+        c.spider(VType::Z, 0.25, 0, 2.0);
+        c.spider(VType::Z, 0.5, 1, 2.0);
+        c.spider(VType::Z, 1.5, 1, 3.0);
+        c.cnot(1, 0, 4.0);
+        let poly = phase_polynomial(&c.finish(5.0)).unwrap();
+
+        // Qubit 0 gets S, qubit 1's phases cancel out
+        assert_eq!(poly.phases, vec![0.5]);
+        assert_eq!(row(&poly.parities, 0), vec![true, false]);
+        assert_eq!(row(&poly.linear, 0), vec![true, true]);
+    }
+
+    #[test]
+    fn test_cz() {
+        let mut c = Circuit::new(2);
+        let a = c.spider(VType::Z, 0.0, 0, 1.0);
+        let b = c.spider(VType::Z, 0.0, 1, 1.0);
+        c.g.add_edge_with_type(a, b, EType::H);
+        let poly = phase_polynomial(&c.finish(2.0)).unwrap();
+        assert_eq!(poly.phases.len(), 3);
+        assert_eq!(poly.phases.iter().sum::<f64>(), 2.5);
+    }
+
+    #[test]
+    fn test_rejects_x_phase() {
+        let mut c = Circuit::new(1);
+        c.spider(VType::X, 0.5, 0, 1.0);
+        assert!(phase_polynomial(&c.finish(2.0)).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Quantomatic's .qgraph files
+//!
+//! .zxg grew out of the .qgraph schema, so both share `wire_vertices`, `node_vertices`
+//! and `undir_edges`. Quantomatic also has `dir_edges`, `bang_boxes`, and wire vertices
+//! that are points along a wire rather than boundaries. [`qgraph_to_zxg`] rewrites a
+//! .qgraph file into .zxg for [`crate::graph_loader`] to read, and [`to_qgraph`] writes a
+//! graph the way Quantomatic's red_green theory expects.
+
+use crate::graph_loader::parse_phase;
+use crate::zxg_export::to_zxg;
+use num::{Rational64, Signed, Zero};
+use quizx::graph::{GraphLike, VType};
+use quizx::hash_graph::Graph;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Rewrites parsed .qgraph JSON as .zxg JSON
+///
+/// Directed edges become undirected, wire vertices on two edges are dropped and their
+/// edges joined, and the remaining wire vertices are marked as boundaries. Words in
+/// phases that don't parse, like `\alpha` in `\alpha + \pi`, are declared as variables.
+/// Fails on non-empty !-boxes and on wire vertices with more than two edges.
+pub fn qgraph_to_zxg(data: &Value) -> Result<Value, String> {
+    if let Some(boxes) = data["bang_boxes"].as_object().filter(|boxes| !boxes.is_empty()) {
+        let names: Vec<&str> = boxes.keys().map(String::as_str).collect();
+        return Err(format!("File has !-boxes ({}), which aren't supported", names.join(", ")));
+    }
+    let mut wire_vertices = match &data["wire_vertices"] {
+        Value::Object(vertices) => vertices.clone(),
+        // Old Quantomatic files list wire vertex names without annotations
+        Value::Array(names) => names.iter().filter_map(Value::as_str).map(|name| (name.to_string(), json!({}))).collect(),
+        Value::Null => Map::new(),
+        _ => return Err("Invalid wire_vertices".to_string()),
+    };
+    let node_vertices = data["node_vertices"].as_object().cloned().ok_or("Missing or invalid node_vertices")?;
+
+    // Every edge as (key, src, tgt), undirected first
+    let mut edges: Vec<(String, String, String)> = Vec::new();
+    for field in ["undir_edges", "dir_edges"] {
+        let Some(object) = data[field].as_object() else { continue };
+        for (key, dets) in object {
+            let endpoint = |end: &str| dets[end].as_str().map(str::to_string)
+                .ok_or_else(|| format!("Edge {} has no {}", key, end));
+            edges.push((key.clone(), endpoint("src")?, endpoint("tgt")?));
+        }
+    }
+
+    // Join the two edges of each wire vertex in the middle of a wire
+    let mut interior: Vec<String> = Vec::new();
+    for name in wire_vertices.keys() {
+        let degree = edges.iter().filter(|(_, src, tgt)| src == name || tgt == name).count();
+        match degree {
+            0 | 1 => {}
+            2 => interior.push(name.clone()),
+            _ => return Err(format!("Wire vertex {} has {} edges; a wire vertex has at most two", name, degree)),
+        }
+    }
+    for name in &interior {
+        let (mut ends, rest): (Vec<_>, Vec<_>) = edges.into_iter().partition(|(_, src, tgt)| src == name || tgt == name);
+        edges = rest;
+        if let [(key, a_src, a_tgt), (_, b_src, b_tgt)] = &mut ends[..] {
+            let other = |src: &String, tgt: &String| if src == name { tgt.clone() } else { src.clone() };
+            edges.push((std::mem::take(key), other(a_src, a_tgt), other(b_src, b_tgt)));
+        }
+        wire_vertices.remove(name);
+    }
+    for dets in wire_vertices.values_mut() {
+        dets["annotation"]["boundary"] = json!(true);
+    }
+
+    let mut variable_types = data["variable_types"].as_object().cloned().unwrap_or_default();
+    for dets in node_vertices.values() {
+        let Some(value) = dets["data"]["value"].as_str().filter(|value| parse_phase(value).is_err()) else { continue };
+        for word in value.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            if word.starts_with(|c: char| c.is_alphabetic()) && word != "pi" {
+                variable_types.entry(word.to_string()).or_insert(json!("Parameter"));
+            }
+        }
+    }
+
+    let undir_edges: Map<String, Value> = edges.into_iter()
+        .map(|(key, src, tgt)| (key, json!({ "src": src, "tgt": tgt })))
+        .collect();
+    Ok(json!({
+        "wire_vertices": wire_vertices,
+        "node_vertices": node_vertices,
+        "undir_edges": undir_edges,
+        "variable_types": variable_types,
+    }))
+}
+
+/// A phase in Quantomatic's notation, e.g. `3\pi/4`; empty for zero
+fn pi_string(phase: Rational64) -> String {
+    if phase.is_zero() {
+        return String::new();
+    }
+    let sign = if phase.is_negative() { "-" } else { "" };
+    let (numer, denom) = (phase.numer().abs(), *phase.denom());
+    let numer = if numer == 1 { String::new() } else { numer.to_string() };
+    match denom {
+        1 => format!("{}{}\\pi", sign, numer),
+        _ => format!("{}{}\\pi/{}", sign, numer, denom),
+    }
+}
+
+/// Serialises a graph to .qgraph JSON for Quantomatic's red_green theory
+///
+/// Vertices are named as in [`to_zxg`], Hadamard edges again get a `hadamard` node, and
+/// phases are written like `\pi/2`. There are no directed edges or !-boxes.
+pub fn to_qgraph(g: &Graph) -> Result<String, String> {
+    let zxg: Value = serde_json::from_str(&to_zxg(g)?).expect("to_zxg writes valid JSON");
+    let mut node_vertices = zxg["node_vertices"].as_object().cloned().unwrap_or_default();
+    let phases: HashMap<String, Rational64> = g.vertices()
+        .filter(|&v| g.vertex_type(v) != VType::B)
+        .map(|v| (format!("v{}", v), g.phase(v).to_rational()))
+        .collect();
+    for (name, dets) in node_vertices.iter_mut() {
+        let data = &mut dets["data"];
+        match phases.get(name).map(|&phase| pi_string(phase)) {
+            Some(phase) if !phase.is_empty() => data["value"] = json!(phase),
+            _ => {
+                if let Some(data) = data.as_object_mut() {
+                    data.remove("value");
+                }
+            }
+        }
+    }
+    let qgraph = json!({
+        "wire_vertices": zxg["wire_vertices"],
+        "node_vertices": node_vertices,
+        "dir_edges": {},
+        "undir_edges": zxg["undir_edges"],
+        "bang_boxes": {},
+    });
+    Ok(serde_json::to_string_pretty(&qgraph).expect("JSON values always serialise"))
+}
+
+/// Writes [`to_qgraph`] of `g` to `path`
+pub fn write_qgraph(g: &Graph, path: &Path) -> Result<(), String> {
+    std::fs::write(path, to_qgraph(g)?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_loader::load_graph_from_str;
+    use quizx::graph::{EType, V};
+    use quizx::phase::Phase;
+
+    fn load_qgraph(data: Value) -> Graph {
+        load_graph_from_str(&qgraph_to_zxg(&data).unwrap().to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_qgraph() {
+        // A CNOT drawn in Quantomatic, with its interior wire vertex w4 and directed edge
+        // folded away, matches the same CNOT drawn in ZXLive
+        let node = |ty: &str, x: f64, y: f64| json!({ "annotation": { "coord": [x, y] }, "data": { "type": ty, "value": "" } });
+        let wire = |x: f64, y: f64| json!({ "annotation": { "coord": [x, y] } });
+        let edge = |src: &str, tgt: &str| json!({ "src": src, "tgt": tgt });
+        let qgraph = load_qgraph(json!({
+            "wire_vertices": { "w0": wire(-4.0, 2.0), "w1": wire(-4.0, 0.0), "w2": wire(0.0, 2.0), "w3": wire(0.0, 0.0), "w4": wire(-3.5, 2.0) },
+            "node_vertices": {
+                "v0": node("Z", -2.0, 2.0), "v1": node("X", -2.0, 0.0), "v2": node("Z", -3.0, 2.0),
+                "v3": node("Z", -3.0, 0.0), "v4": node("Z", -1.0, 2.0), "v5": node("Z", -1.0, 0.0)
+            },
+            "dir_edges": { "e9": edge("w4", "v2") },
+            "undir_edges": {
+                "e0": edge("w0", "w4"), "e1": edge("w1", "v3"), "e2": edge("v0", "v1"), "e3": edge("v0", "v2"), "e4": edge("v0", "v4"),
+                "e5": edge("v1", "v3"), "e6": edge("v1", "v5"), "e7": edge("w2", "v4"), "e8": edge("w3", "v5")
+            },
+            "bang_boxes": {}
+        }));
+        let mut zxg = Graph::new();
+        let b: Vec<V> = (0..4).map(|_| zxg.add_vertex(VType::B)).collect();
+        let v: Vec<V> = [VType::Z, VType::X, VType::Z, VType::Z, VType::Z, VType::Z].into_iter().map(|ty| zxg.add_vertex(ty)).collect();
+        for (s, t) in [(b[0], v[2]), (b[1], v[3]), (v[0], v[1]), (v[0], v[2]), (v[0], v[4]), (v[1], v[3]), (v[1], v[5]), (b[2], v[4]), (b[3], v[5])] {
+            zxg.add_edge(s, t);
+        }
+        assert_eq!((qgraph.num_vertices(), qgraph.num_edges()), (zxg.num_vertices(), zxg.num_edges()));
+        assert_eq!((qgraph.inputs().len(), qgraph.outputs().len()), (2, 2));
+        let hadamard = load_qgraph(json!({
+            "wire_vertices": { "w0": wire(0.0, 0.0), "w1": wire(4.0, 0.0) },
+            "node_vertices": {
+                "v0": { "annotation": { "coord": [1.0, 0.0] }, "data": { "type": "Z", "value": "\\pi/2" } },
+                "v1": node("hadamard", 2.0, 0.0),
+                "v2": node("Z", 3.0, 0.0)
+            },
+            "undir_edges": { "e0": edge("w0", "v0"), "e1": edge("v0", "v1"), "e2": edge("v1", "v2"), "e3": edge("v2", "w1") }
+        }));
+        let mut phases: Vec<Phase> = hadamard.vertices().filter(|&v| hadamard.vertex_type(v) == VType::Z).map(|v| hadamard.phase(v)).collect();
+        phases.sort_by_key(|phase| phase.to_rational());
+        assert_eq!(phases, vec![Phase::from_f64(0.0), Phase::from_f64(0.5)]);
+        assert_eq!(hadamard.vertices().filter(|&v| hadamard.vertex_type(v) == VType::H).count(), 1);
+
+        let with_box = json!({ "wire_vertices": {}, "node_vertices": {}, "undir_edges": {}, "bang_boxes": { "B0": { "contents": [] } } });
+        assert_eq!(qgraph_to_zxg(&with_box).unwrap_err(), "File has !-boxes (B0), which aren't supported");
+        let symbolic = qgraph_to_zxg(&json!({ "node_vertices": { "v0": { "data": { "type": "Z", "value": "\\alpha + \\pi" } } } })).unwrap();
+        assert_eq!(symbolic["variable_types"], json!({ "alpha": "Parameter" }));
+
+        // Written out and read back
+        let mut g = Graph::new();
+        let b0 = g.add_vertex(VType::B);
+        let z = g.add_vertex_with_phase(VType::Z, Phase::from_f64(-0.75));
+        let x = g.add_vertex_with_phase(VType::X, Phase::from_f64(1.0));
+        let b1 = g.add_vertex(VType::B);
+        g.add_edge(b0, z);
+        g.add_edge_with_type(z, x, EType::H);
+        g.add_edge(x, b1);
+        let written = to_qgraph(&g).unwrap();
+        let json: Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(json["node_vertices"][format!("v{}", z)]["data"]["value"], "-3\\pi/4");
+        assert_eq!(json["node_vertices"][format!("v{}", x)]["data"]["value"], "\\pi");
+        let loaded = load_graph_from_str(&written).unwrap();
+        assert_eq!((loaded.num_vertices(), loaded.num_edges()), (5, 4));
+        let mut phases: Vec<f64> = loaded.vertices().map(|v| loaded.phase(v).to_f64()).filter(|&p| p != 0.0).collect();
+        phases.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(phases, vec![-0.75, 1.0]);
+    }
+}
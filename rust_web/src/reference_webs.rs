@@ -0,0 +1,237 @@
+//! Comparing detection webs with reference webs from other tools, e.g. the PyZX-based
+//! Python code this crate was ported from
+//!
+//! Reference webs are read in the JSON `python/golden_webs.py` prints,
+//! `{"webs": [[[<name>, <name>, <pauli>], ...], ...]}`, with vertices named by their .zxg
+//! key and each spider added by the rg conversion named `rg(<a>,<b>)` after its two
+//! neighbours. [`compare_with_reference`] sorts every web into matching, missing and
+//! extra by the spans, like [`diff_webs`], and [`render_comparison`] draws each of them.
+
+use crate::graph_visualizer::{render_image, Highlights, RenderOptions, RetryPolicy, Style};
+use crate::pauliweb::{Pauli, PauliWeb};
+use crate::web_diff::{diff_webs, render_diff, ADDED_COLOR, REMOVED_COLOR};
+use crate::web_set::WebSet;
+use quizx::graph::{GraphLike, V};
+use quizx::hash_graph::Graph;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Colour of the edges of webs found on both sides
+pub const MATCHING_COLOR: &str = "#3366cc";
+
+/// Where a web stands against the other side of a comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebStatus {
+    /// In the span of the other side's webs
+    Matching,
+    /// A reference web outside the span of the computed webs
+    Missing,
+    /// A computed web outside the span of the reference webs
+    Extra,
+}
+
+impl WebStatus {
+    pub fn color(self) -> &'static str {
+        match self {
+            WebStatus::Matching => MATCHING_COLOR,
+            WebStatus::Missing => REMOVED_COLOR,
+            WebStatus::Extra => ADDED_COLOR,
+        }
+    }
+}
+
+/// One web of a comparison, named `reference_<i>` or `computed_<j>` after its side and
+/// index there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebComparison {
+    pub name: String,
+    pub status: WebStatus,
+}
+
+/// How computed webs compare with reference webs, see [`compare_with_reference`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReferenceComparison {
+    /// Every reference web, matching or missing
+    pub reference: Vec<WebComparison>,
+    /// Every computed web, matching or extra
+    pub computed: Vec<WebComparison>,
+}
+
+impl ReferenceComparison {
+    pub fn count(&self, status: WebStatus) -> usize {
+        self.reference.iter().chain(&self.computed).filter(|web| web.status == status).count()
+    }
+
+    /// Whether both sides span the same space
+    pub fn agrees(&self) -> bool {
+        self.count(WebStatus::Missing) == 0 && self.count(WebStatus::Extra) == 0
+    }
+}
+
+impl fmt::Display for ReferenceComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reference and {} computed webs: {} matching, {} missing, {} extra",
+            self.reference.len(),
+            self.computed.len(),
+            self.count(WebStatus::Matching),
+            self.count(WebStatus::Missing),
+            self.count(WebStatus::Extra)
+        )
+    }
+}
+
+/// The vertex of `g` for each name reference webs may use: the .zxg `keys` of the loaded
+/// vertices, and `rg(<a>,<b>)` for the other vertices of degree two
+pub fn vertex_names(g: &Graph, keys: &HashMap<V, String>) -> HashMap<String, V> {
+    let mut names: HashMap<String, V> = keys.iter().map(|(&v, key)| (key.clone(), v)).collect();
+    for v in g.vertices().filter(|v| !keys.contains_key(v)) {
+        let mut neighbours: Vec<&str> = g.neighbors(v).filter_map(|n| keys.get(&n).map(String::as_str)).collect();
+        if neighbours.len() == 2 && g.degree(v) == 2 {
+            neighbours.sort();
+            names.insert(format!("rg({})", neighbours.join(",")), v);
+        }
+    }
+    names
+}
+
+/// Reads reference webs from `json`, naming vertices as in `names`
+///
+/// Fails on JSON of another shape, unknown Paulis and names missing from `names`.
+pub fn parse_reference_webs(json: &str, names: &HashMap<String, V>) -> Result<WebSet, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid reference JSON: {}", e))?;
+    let webs = value.get("webs").and_then(Value::as_array).ok_or("Reference JSON has no \"webs\" list")?;
+    let webs = webs.iter().enumerate().map(|(i, web)| {
+        let edges = web.as_array().ok_or_else(|| format!("Reference web {} is not a list of edges", i))?;
+        let mut pw = PauliWeb::new();
+        for edge in edges {
+            let fields: Option<Vec<&str>> = edge.as_array().map(|fields| fields.iter().filter_map(Value::as_str).collect());
+            let Some([a, b, pauli]) = fields.as_deref() else {
+                return Err(format!("Reference web {} has edge {}, expected [name, name, pauli]", i, edge));
+            };
+            let vertex = |name: &str| names.get(name).copied()
+                .ok_or_else(|| format!("Reference web {} uses vertex {}, which isn't in the diagram", i, name));
+            let pauli = match *pauli {
+                "X" => Pauli::X,
+                "Y" => Pauli::Y,
+                "Z" => Pauli::Z,
+                other => return Err(format!("Reference web {} has unknown Pauli {}", i, other)),
+            };
+            pw.set_edge(vertex(a)?, vertex(b)?, pauli);
+        }
+        Ok(pw)
+    }).collect::<Result<Vec<_>, String>>()?;
+    Ok(WebSet::new(webs))
+}
+
+/// Same as [`parse_reference_webs`], reading the file at `path`
+pub fn load_reference_webs(path: &Path, names: &HashMap<String, V>) -> Result<WebSet, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_reference_webs(&json, names)
+}
+
+/// Sorts the `reference` and `computed` webs into matching, missing and extra
+pub fn compare_with_reference(reference: &WebSet, computed: &WebSet) -> ReferenceComparison {
+    let diff = diff_webs(reference, computed);
+    let side = |prefix: &str, len: usize, outside: &[usize], status: WebStatus| -> Vec<WebComparison> {
+        (0..len)
+            .map(|i| WebComparison {
+                name: format!("{}_{}", prefix, i),
+                status: if outside.contains(&i) { status } else { WebStatus::Matching },
+            })
+            .collect()
+    };
+    ReferenceComparison {
+        reference: side("reference", reference.webs.len(), &diff.removed, WebStatus::Missing),
+        computed: side("computed", computed.webs.len(), &diff.added, WebStatus::Extra),
+    }
+}
+
+/// Renders the comparison of `computed` with `reference` webs on `g` to `dir`
+///
+/// Writes `diff` with the edges of missing webs in red and of extra webs in green (see
+/// [`render_diff`]), and one image per web named as in the comparison, its edges
+/// coloured by status and labelled with their Paulis. Returns the comparison and the
+/// paths written.
+pub fn render_comparison(
+    g: &Graph,
+    reference: &WebSet,
+    computed: &WebSet,
+    dir: &Path,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> Result<(ReferenceComparison, Vec<PathBuf>), String> {
+    let comparison = compare_with_reference(reference, computed);
+    let save = |image: crate::graph_visualizer::RenderedImage, name: &str| {
+        image.save(&dir.join(name)).map_err(|e| format!("Failed to write {} in {}: {}", name, dir.display(), e))
+    };
+    let (image, _) = render_diff(g, reference, computed, options, policy);
+    let mut paths = vec![save(image, "diff")?];
+
+    let webs = reference.webs.iter().zip(&comparison.reference).chain(computed.webs.iter().zip(&comparison.computed));
+    for (web, compared) in webs {
+        let mut highlights = Highlights::default();
+        for ((a, b), pauli) in web.sorted_edges() {
+            highlights.add_edge(a, b, Style { note: pauli.to_string(), ..Style::color(compared.status.color()) });
+        }
+        let options = RenderOptions { highlights, ..options.clone() };
+        paths.push(save(render_image(g, None, &options, policy), &compared.name)?);
+    }
+    Ok((comparison, paths))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection_webs::get_detection_webs;
+    use crate::fixtures::fixture;
+    use crate::zxg_export::write_zxg;
+    use crate::graph_loader::{load_graph_with_keys, LoadOptions};
+    use serde_json::json;
+
+    #[test]
+    fn test_reference_webs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("steane.zxg");
+        write_zxg(&fixture("steane_2_rounds").unwrap(), &path).unwrap();
+        let (mut g, keys) = load_graph_with_keys(path.to_str().unwrap(), &LoadOptions::default()).unwrap();
+        let computed = WebSet::new(get_detection_webs(&mut g));
+        let names = vertex_names(&g, &keys);
+        assert!(!computed.webs.is_empty() && names.len() == g.num_vertices());
+        assert!(names.keys().any(|name| name.starts_with("rg(")));
+        let name = |v: V| names.iter().find(|&(_, &u)| u == v).unwrap().0.clone();
+
+        // The computed webs written out under their names read back the same
+        let written: Vec<Value> = computed.webs.iter()
+            .map(|web| web.sorted_edges().into_iter().map(|((a, b), p)| json!([name(a), name(b), p.to_string()])).collect())
+            .collect();
+        let reference = parse_reference_webs(&json!({ "webs": written }).to_string(), &names).unwrap();
+        assert_eq!(reference.webs, computed.webs);
+        let comparison = compare_with_reference(&reference, &computed);
+        assert!(comparison.agrees());
+        assert_eq!(comparison.count(WebStatus::Matching), 2 * computed.webs.len());
+
+        // Dropping a reference web makes a computed one extra, a made-up one is missing
+        let (a, b, _) = g.edges().next().unwrap();
+        let mut made_up = PauliWeb::new();
+        made_up.set_edge(a, b, Pauli::Y);
+        let reference = WebSet::new(vec![made_up]);
+        let comparison = compare_with_reference(&reference, &computed);
+        assert_eq!(comparison.reference[0], WebComparison { name: "reference_0".to_string(), status: WebStatus::Missing });
+        assert_eq!(comparison.count(WebStatus::Extra), computed.webs.len());
+        assert!(comparison.to_string().ends_with(&format!("0 matching, 1 missing, {} extra", computed.webs.len())));
+
+        let (_, paths) = render_comparison(&g, &reference, &computed, dir.path(), &RenderOptions::default(), &RetryPolicy::default()).unwrap();
+        assert_eq!(paths.len(), 2 + computed.webs.len());
+        assert!(paths.iter().all(|path| path.exists()));
+
+        let error = parse_reference_webs(r#"{"webs": [[["nowhere", "v0", "X"]]]}"#, &names).unwrap_err();
+        assert_eq!(error, "Reference web 0 uses vertex nowhere, which isn't in the diagram");
+        assert!(parse_reference_webs(r#"{"webs": [[["a", "b"]]]}"#, &names).unwrap_err().contains("expected [name, name, pauli]"));
+    }
+}
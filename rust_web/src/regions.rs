@@ -0,0 +1,200 @@
+//! Detection webs confined to detector regions
+//!
+//! Often only the webs inside one part of an experiment matter, e.g. the detectors of a
+//! round or around a lattice surgery merge. Rather than solving the whole diagram and
+//! filtering, [`get_detection_webs_in_regions`] solves each region on its own: each
+//! spider with an edge leaving the region gets a boundary, and since detection webs are
+//! trivial on boundaries, the webs of this closed-off region are exactly the webs of the
+//! diagram supported within it. Its matrix only has the region's rows and columns.
+
+use crate::connectivity::subgraph;
+use crate::detection_webs::{get_detection_webs_lenient_traced, pinned_nodes, BoundaryRoles, WebOptions, WebProvenance};
+use crate::logging::WEBS;
+use crate::make_rg::debug_check_rg;
+use crate::pauliweb::PauliWeb;
+use crate::tags::Tags;
+use quizx::graph::{GraphLike, VData, VType, V};
+use quizx::hash_graph::Graph;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A named set of spiders whose webs are wanted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+    pub vertices: Vec<V>,
+}
+
+impl Region {
+    pub fn new(name: &str, vertices: impl IntoIterator<Item = V>) -> Self {
+        Region { name: name.to_string(), vertices: vertices.into_iter().collect() }
+    }
+
+    /// One region per value of tag `name`, named `name=value` and sorted by value, e.g.
+    /// the detectors of each `detector_round`
+    pub fn by_tag(tags: &Tags, name: &str) -> Vec<Region> {
+        let mut groups: BTreeMap<String, Vec<V>> = BTreeMap::new();
+        for (&v, vertex_tags) in &tags.vertices {
+            if let Some(value) = vertex_tags.get(name) {
+                groups.entry(value.to_string()).or_default().push(v);
+            }
+        }
+        groups.into_iter()
+            .map(|(value, mut vertices)| {
+                vertices.sort();
+                Region { name: format!("{}={}", name, value), vertices }
+            })
+            .collect()
+    }
+}
+
+/// The webs found in one region, in the vertex ids of the whole diagram
+#[derive(Debug, Clone)]
+pub struct RegionWebs {
+    pub name: String,
+    pub webs: Vec<(PauliWeb, WebProvenance)>,
+}
+
+/// The spiders among `vertices`, with a boundary on each spider that has an edge leaving
+/// them or is among `pinned`
+///
+/// The solver keeps the spiders next to a boundary from firing, so the webs of the result
+/// are the webs of `g` supported on edges among `vertices` that leave `pinned` unfired.
+/// It pins the first spiders by id, so these frontier spiders are added first. Returns
+/// the region and the vertex of `g` each of its spiders stands for; the added boundaries
+/// have none. Boundaries of `g` are always outside.
+pub fn closed_region(g: &Graph, vertices: &[V], pinned: &HashSet<V>) -> (Graph, HashMap<V, V>) {
+    let inside: HashSet<V> = vertices.iter().copied().filter(|&v| g.vertex_type(v) != VType::B).collect();
+    let (mut frontier, mut interior): (Vec<V>, Vec<V>) = inside.iter()
+        .partition(|&&v| pinned.contains(&v) || g.neighbors(v).any(|n| !inside.contains(&n)));
+    frontier.sort();
+    interior.sort();
+    let stubs = frontier.len();
+    let ordered: Vec<V> = frontier.into_iter().chain(interior).collect();
+
+    let (mut sub, old_of) = subgraph(g, &ordered);
+    let new_of: HashMap<V, V> = old_of.iter().map(|(&new, &old)| (old, new)).collect();
+    for &v in &ordered[..stubs] {
+        let stub = sub.add_vertex_with_data(VData { ty: VType::B, phase: Default::default(), qubit: g.qubit(v), row: g.row(v) });
+        sub.add_edge(new_of[&v], stub);
+    }
+    (sub, old_of)
+}
+
+/// Detection webs of `g` supported within each of `regions`, in order
+///
+/// These are the webs of [`get_detection_webs_lenient_traced`] on the whole of `g` whose
+/// edges all lie within the region, up to products. Converts `g` to rg form in place.
+/// Regions are given in the vertex ids from before the conversion, and each takes in the
+/// spiders the conversion adds between its own. The size limits in `options` apply to
+/// each region rather than to the whole diagram.
+pub fn get_detection_webs_in_regions(
+    g: &mut Graph,
+    regions: &[Region],
+    options: &WebOptions,
+) -> Result<Vec<RegionWebs>, String> {
+    let before: HashSet<V> = g.vertices().collect();
+    let pinned: HashSet<V> = pinned_nodes(g, options)?.into_iter().collect();
+    let added: Vec<V> = g.vertices().filter(|v| !before.contains(v)).collect();
+
+    regions.iter().map(|region| {
+        let mut vertices: HashSet<V> = region.vertices.iter().copied().collect();
+        if let Some(v) = vertices.iter().find(|v| !before.contains(v)) {
+            return Err(format!("Region {} contains vertex {}, which isn't in the diagram", region.name, v));
+        }
+        let joined: Vec<V> = added.iter().copied()
+            .filter(|&v| g.neighbors(v).all(|n| vertices.contains(&n)))
+            .collect();
+        vertices.extend(joined);
+        let mut vertices: Vec<V> = vertices.into_iter().collect();
+        vertices.sort();
+
+        let (mut sub, old_of) = closed_region(g, &vertices, &pinned);
+        debug_check_rg(&sub, "closing off a region", false);
+        let mut boundaries: Vec<V> = sub.vertices().filter(|&v| sub.vertex_type(v) == VType::B).collect();
+        boundaries.sort();
+        log::info!(target: WEBS, "Region {}: {} spiders, {} on its frontier", region.name, sub.num_vertices() - boundaries.len(), boundaries.len());
+        let options = WebOptions { roles: BoundaryRoles::Explicit { inputs: Vec::new(), outputs: boundaries }, ..options.clone() };
+
+        let webs = get_detection_webs_lenient_traced(&mut sub, &options)
+            .map_err(|e| format!("Region {}: {}", region.name, e))?
+            .into_iter()
+            .map(|(web, mut provenance)| {
+                let relabelled = web.relabel(&sub, &old_of)?;
+                for fired in &mut provenance.fired {
+                    fired.vertex = old_of[&fired.vertex];
+                }
+                Ok((relabelled, provenance))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(RegionWebs { name: region.name.clone(), webs })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use crate::pauliweb::Pauli;
+    use crate::tags::TagValue;
+    use crate::web_space::WebSpace;
+
+    #[test]
+    fn test_region_webs() {
+        let g = create_steane_rounds(3);
+        let mut spiders: Vec<V> = g.vertices().filter(|&v| g.vertex_type(v) != VType::B).collect();
+        spiders.sort();
+        let mut rows: Vec<f64> = spiders.iter().map(|&v| g.row(v)).collect();
+        rows.sort_by(f64::total_cmp);
+        let cut = rows[rows.len() * 3 / 4];
+        let early = Region::new("early", spiders.iter().copied().filter(|&v| g.row(v) <= cut));
+
+        let options = WebOptions::default();
+        let mut whole = g.clone();
+        let found = get_detection_webs_in_regions(&mut whole, &[early.clone(), Region::new("all", spiders.clone())], &options).unwrap();
+        // The ids of the spiders added by the rg conversion vary between runs, so the
+        // global webs are found on the converted graph
+        let global: Vec<PauliWeb> = get_detection_webs_lenient_traced(&mut whole.clone(), &options).unwrap()
+            .into_iter().map(|(web, _)| web).collect();
+        let global = WebSpace::spanned_by(&whole, &global).unwrap();
+        let [early_webs, all_webs] = &found[..] else { panic!() };
+        assert_eq!((early_webs.name.as_str(), all_webs.name.as_str()), ("early", "all"));
+        let original: HashSet<V> = g.vertices().collect();
+
+        // Each region's webs are exactly the global webs on edges within it
+        let check = |region: &Region, found: &RegionWebs| {
+            let mut inside: HashSet<V> = region.vertices.iter().copied().collect();
+            let joined: Vec<V> = whole.vertices()
+                .filter(|&v| !original.contains(&v) && whole.neighbors(v).all(|n| inside.contains(&n)))
+                .collect();
+            inside.extend(joined);
+            let mut within = WebSpace::new(global.edges().iter().copied());
+            for &(a, b) in global.edges().iter().filter(|(a, b)| inside.contains(a) && inside.contains(b)) {
+                for pauli in [Pauli::X, Pauli::Z] {
+                    let mut web = PauliWeb::new();
+                    web.set_edge(a, b, pauli);
+                    within.add(&web).unwrap();
+                }
+            }
+            assert!(found.webs.iter().all(|(web, _)| global.contains(web) && within.contains(web)));
+            let webs: Vec<PauliWeb> = found.webs.iter().map(|(web, _)| web.clone()).collect();
+            let span = WebSpace::spanned_by(&whole, &webs).unwrap();
+            assert_eq!(span.dim(), global.intersect(&within).unwrap().dim());
+            span.dim()
+        };
+        let early_dim = check(&early, early_webs);
+        // The whole diagram as a region leaves out only the webs reaching its boundaries
+        let all_dim = check(&Region::new("all", spiders.clone()), all_webs);
+        assert!(0 < early_dim && early_dim < all_dim && all_dim <= global.dim());
+
+        let error = get_detection_webs_in_regions(&mut g.clone(), &[Region::new("stray", [9999])], &options).unwrap_err();
+        assert_eq!(error, "Region stray contains vertex 9999, which isn't in the diagram");
+
+        let mut tags = Tags::default();
+        for (i, &v) in spiders.iter().enumerate() {
+            tags.vertices.insert(v, [("half".to_string(), TagValue::Int((i >= spiders.len() / 2) as i64))].into());
+        }
+        let halves = Region::by_tag(&tags, "half");
+        assert_eq!(halves.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["half=0", "half=1"]);
+        assert_eq!(halves[0].vertices, spiders[..spiders.len() / 2]);
+    }
+}
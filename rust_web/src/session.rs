@@ -0,0 +1,189 @@
+use quizx::basic_rules::{check_spider_fusion, spider_fusion_unchecked};
+use quizx::graph::{EType, VType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use quizx::phase::Phase;
+use serde::Serialize;
+use std::fs;
+
+/// One entry of the operation log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Operation {
+    pub name: String,
+    /// Size of the diagram right after the operation
+    pub vertices: usize,
+    pub edges: usize,
+}
+
+/// A diagram being edited, with undo/redo over every mutation
+///
+/// Each operation keeps a snapshot of the graph it replaced, so arbitrary quizx
+/// rewrites can be undone without writing an inverse for them.
+#[derive(Debug, Clone)]
+pub struct GraphSession {
+    graph: Graph,
+    undo_stack: Vec<(Operation, Graph)>,
+    redo_stack: Vec<(Operation, Graph)>,
+}
+
+impl GraphSession {
+    pub fn new(graph: Graph) -> Self {
+        GraphSession { graph, undo_stack: Vec::new(), redo_stack: Vec::new() }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> Graph {
+        self.graph
+    }
+
+    /// Applies an arbitrary mutation as one undoable step. Clears the redo history.
+    pub fn apply<R>(&mut self, name: impl Into<String>, f: impl FnOnce(&mut Graph) -> R) -> R {
+        let before = self.graph.clone();
+        let result = f(&mut self.graph);
+        let op = Operation {
+            name: name.into(),
+            vertices: self.graph.num_vertices(),
+            edges: self.graph.num_edges(),
+        };
+        self.undo_stack.push((op, before));
+        self.redo_stack.clear();
+        result
+    }
+
+    pub fn add_vertex(&mut self, ty: VType) -> V {
+        self.apply(format!("add_vertex {:?}", ty), |g| g.add_vertex(ty))
+    }
+
+    pub fn remove_vertex(&mut self, v: V) {
+        self.apply(format!("remove_vertex {}", v), |g| g.remove_vertex(v))
+    }
+
+    pub fn add_edge(&mut self, a: V, b: V, ety: EType) {
+        self.apply(format!("add_edge {}-{} {:?}", a, b, ety), |g| g.add_edge_with_type(a, b, ety))
+    }
+
+    pub fn remove_edge(&mut self, a: V, b: V) {
+        self.apply(format!("remove_edge {}-{}", a, b), |g| g.remove_edge(a, b))
+    }
+
+    pub fn set_phase(&mut self, v: V, phase: Phase) {
+        self.apply(format!("set_phase {} {}", v, phase.to_f64()), |g| g.set_phase(v, phase))
+    }
+
+    /// Fuses spider `b` into `a`, failing without recording anything if they can't fuse
+    pub fn fuse(&mut self, a: V, b: V) -> Result<(), String> {
+        if !check_spider_fusion(&self.graph, a, b) {
+            return Err(format!("Spiders {} and {} can't be fused", a, b));
+        }
+        self.apply(format!("fuse {} {}", a, b), |g| spider_fusion_unchecked(g, a, b));
+        Ok(())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Reverts the last operation, returning false if there was none
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some((op, before)) => {
+                let after = std::mem::replace(&mut self.graph, before);
+                self.redo_stack.push((op, after));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-applies the last undone operation, returning false if there was none
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some((op, after)) => {
+                let before = std::mem::replace(&mut self.graph, after);
+                self.undo_stack.push((op, before));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Operations currently applied, oldest first
+    pub fn history(&self) -> Vec<&Operation> {
+        self.undo_stack.iter().map(|(op, _)| op).collect()
+    }
+
+    /// Writes the applied operations as a JSON array
+    pub fn export_log(&self, filename: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.history())
+            .map_err(|e| format!("Failed to serialise operation log: {}", e))?;
+        fs::write(filename, json).map_err(|e| format!("Failed to write {}: {}", filename, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut session = GraphSession::new(Graph::new());
+        let a = session.add_vertex(VType::Z);
+        let b = session.add_vertex(VType::Z);
+        session.add_edge(a, b, EType::N);
+        assert_eq!(session.graph().num_edges(), 1);
+
+        assert!(session.undo());
+        assert_eq!(session.graph().num_edges(), 0);
+        assert_eq!(session.graph().num_vertices(), 2);
+        assert!(session.redo());
+        assert_eq!(session.graph().num_edges(), 1);
+        assert!(!session.redo());
+
+        while session.undo() {}
+        assert_eq!(session.graph().num_vertices(), 0);
+        assert!(!session.can_undo());
+        assert!(session.can_redo());
+    }
+
+    #[test]
+    fn test_new_operation_clears_redo() {
+        let mut session = GraphSession::new(Graph::new());
+        let a = session.add_vertex(VType::Z);
+        session.set_phase(a, Phase::from_f64(0.5));
+        session.undo();
+        session.add_vertex(VType::X);
+        assert!(!session.can_redo());
+        assert_eq!(session.history().len(), 2);
+        assert_eq!(session.history()[1].name, "add_vertex X");
+    }
+
+    #[test]
+    fn test_fuse_and_log() {
+        let mut session = GraphSession::new(Graph::new());
+        let a = session.add_vertex(VType::Z);
+        let b = session.add_vertex(VType::Z);
+        let x = session.add_vertex(VType::X);
+        session.add_edge(a, b, EType::N);
+        session.add_edge(b, x, EType::N);
+
+        assert!(session.fuse(a, x).is_err());
+        assert_eq!(session.history().len(), 5);
+        session.fuse(a, b).unwrap();
+        assert_eq!(session.graph().num_vertices(), 2);
+        assert!(session.graph().connected(a, x));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.json");
+        session.export_log(path.to_str().unwrap()).unwrap();
+        let log: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(log.as_array().unwrap().len(), 6);
+        assert_eq!(log[5]["name"], format!("fuse {} {}", a, b));
+        assert_eq!(log[5]["vertices"], 2);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::detection_webs::get_detection_webs;
+use crate::pauliweb::PauliWeb;
+use quizx::hash_graph::Graph;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A diagram shared between threads, cheap to clone
+///
+/// The handle itself doesn't implement `GraphLike`; library APIs taking `&G: GraphLike`
+/// get the graph through [`SharedDiagram::graph`] or `&*diagram`. Mutation goes through
+/// [`SharedDiagram::make_mut`], which copies the graph only if another handle still
+/// looks at it.
+#[derive(Debug, Clone)]
+pub struct SharedDiagram(Arc<Graph>);
+
+impl SharedDiagram {
+    pub fn new(graph: Graph) -> Self {
+        SharedDiagram(Arc::new(graph))
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.0
+    }
+
+    /// Mutable access, cloning the graph first if it is shared
+    pub fn make_mut(&mut self) -> &mut Graph {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// The graph itself, cloned only if other handles remain
+    pub fn into_graph(self) -> Graph {
+        Arc::unwrap_or_clone(self.0)
+    }
+
+    /// Runs [`get_detection_webs`], which brings this diagram into red-green form
+    pub fn detection_webs(&mut self) -> Vec<PauliWeb> {
+        get_detection_webs(self.make_mut())
+    }
+}
+
+impl Deref for SharedDiagram {
+    type Target = Graph;
+
+    fn deref(&self) -> &Graph {
+        &self.0
+    }
+}
+
+impl AsRef<Graph> for SharedDiagram {
+    fn as_ref(&self) -> &Graph {
+        &self.0
+    }
+}
+
+impl From<Graph> for SharedDiagram {
+    fn from(graph: Graph) -> Self {
+        SharedDiagram::new(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_visualizer::to_dot_with_positions;
+    use quizx::graph::{GraphLike, VType};
+
+    #[test]
+    fn test_clone_shares_until_mutated() {
+        let mut g = Graph::new();
+        g.add_vertex(VType::Z);
+        let mut a = SharedDiagram::new(g);
+        let b = a.clone();
+        assert!(std::ptr::eq(a.graph(), b.graph()));
+
+        a.make_mut().add_vertex(VType::X);
+        assert_eq!(a.num_vertices(), 2);
+        assert_eq!(b.num_vertices(), 1);
+        assert_eq!(b.into_graph().num_vertices(), 1);
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let mut g = Graph::new();
+        let z = g.add_vertex(VType::Z);
+        let x = g.add_vertex(VType::X);
+        g.add_edge(z, x);
+        let diagram = SharedDiagram::from(g);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let diagram = diagram.clone();
+                std::thread::spawn(move || to_dot_with_positions(&*diagram, None, false))
+            })
+            .collect();
+        let dots: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(dots.windows(2).all(|w| w[0] == w[1]));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::graph_stats::{graph_stats, GraphStats};
+use quizx::hash_graph::{Graph, GraphLike};
+use quizx::simplify as qs;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::Instant;
+
+/// Simplification strategies that can be compared with [`run_strategy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Strategy {
+    /// Identity removal, spider fusion, pivoting, local complementation and pivots
+    /// next to the boundary, on the graph-like form
+    CliffordSimp,
+    /// CliffordSimp plus phase gadget fusion and gadget pivoting
+    FullReduce,
+    /// quizx's flow-preserving reduction: moves phases around like phase
+    /// teleportation without breaking extractability
+    TeleportReduce,
+}
+
+/// What a strategy did to a diagram
+#[derive(Debug, Clone, Serialize)]
+pub struct SimplifyMetrics {
+    pub strategy: Strategy,
+    /// Number of passes in which each rule made progress
+    pub rule_applications: BTreeMap<String, usize>,
+    pub before: GraphStats,
+    pub after: GraphStats,
+    pub duration_ms: f64,
+}
+
+type Rule = (&'static str, fn(&mut Graph) -> bool);
+
+fn rules(strategy: Strategy) -> Vec<Rule> {
+    let clifford: Vec<Rule> = vec![
+        ("id_simp", qs::id_simp),
+        ("spider_simp", qs::spider_simp),
+        ("pivot_simp", qs::pivot_simp),
+        ("local_comp_simp", qs::local_comp_simp),
+        ("boundary_pivot_simp", qs::boundary_pivot_simp),
+    ];
+    match strategy {
+        Strategy::CliffordSimp => clifford,
+        Strategy::FullReduce => {
+            let mut all = clifford;
+            all.push(("gadget_simp", qs::gadget_simp));
+            all.push(("pivot_gadget_simp", qs::pivot_gadget_simp));
+            all
+        }
+        Strategy::TeleportReduce => vec![("flow_simp", qs::flow_simp)],
+    }
+}
+
+/// Simplifies `graph` in place with `strategy`, recording what happened
+///
+/// The Clifford rules only match on graph-like diagrams, so for those strategies the
+/// graph is first made graph-like, as quizx's `clifford_simp` does: spiders are fused
+/// and X spiders turned into Z spiders. Rules are then tried in order and the loop
+/// restarts from the first rule whenever one makes progress, until none applies. Each quizx rule is exhaustive, so a count is
+/// the number of times the rule had to be re-run after other rules created matches.
+pub fn run_strategy(graph: &mut Graph, strategy: Strategy) -> SimplifyMetrics {
+    let before = graph_stats(graph);
+    let start = Instant::now();
+    let rules = rules(strategy);
+    let mut rule_applications: BTreeMap<String, usize> =
+        rules.iter().map(|(name, _)| (name.to_string(), 0)).collect();
+    if strategy != Strategy::TeleportReduce {
+        if qs::spider_simp(graph) {
+            *rule_applications.get_mut("spider_simp").unwrap() += 1;
+        }
+        graph.x_to_z();
+    }
+
+    'fixpoint: loop {
+        for (name, rule) in &rules {
+            if rule(graph) {
+                *rule_applications.get_mut(*name).unwrap() += 1;
+                continue 'fixpoint;
+            }
+        }
+        break;
+    }
+
+    SimplifyMetrics {
+        strategy,
+        rule_applications,
+        before,
+        after: graph_stats(graph),
+        duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+impl SimplifyMetrics {
+    /// Writes the metrics as pretty-printed JSON
+    pub fn write_json(&self, filename: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialise metrics: {}", e))?;
+        fs::write(filename, json).map_err(|e| format!("Failed to write {}: {}", filename, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_spider_chain;
+    use quizx::graph::VType;
+
+    #[test]
+    fn test_spider_chain_fuses() {
+        let mut g = create_spider_chain(5, VType::Z, true, true);
+        let metrics = run_strategy(&mut g, Strategy::CliffordSimp);
+
+        assert_eq!(metrics.before.z_spiders, 4);
+        assert!(metrics.after.z_spiders < metrics.before.z_spiders);
+        assert!(metrics.rule_applications["spider_simp"] >= 1);
+        assert!(metrics.rule_applications.contains_key("boundary_pivot_simp"));
+        assert!(!metrics.rule_applications.contains_key("gadget_simp"));
+    }
+
+    #[test]
+    fn test_write_metrics_json() {
+        let mut g = create_spider_chain(3, VType::X, false, true);
+        let metrics = run_strategy(&mut g, Strategy::FullReduce);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        metrics.write_json(path.to_str().unwrap()).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(json["strategy"], "FullReduce");
+        assert_eq!(json["before"]["x_spiders"], 2);
+        assert_eq!(json["after"]["x_spiders"], 0, "Simplified diagrams are graph-like");
+        assert!(json["rule_applications"]["pivot_gadget_simp"].is_number());
+    }
+}
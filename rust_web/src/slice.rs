@@ -0,0 +1,153 @@
+use quizx::hash_graph::{Graph, GraphLike};
+use quizx::graph::{VData, VType, V};
+use std::collections::HashMap;
+
+/// Extracts the sub-diagram with rows in `[r0, r1]`
+///
+/// Every edge leaving the slice is cut and replaced by a fresh boundary vertex placed
+/// on the slice border. Cuts towards earlier rows become inputs and cuts towards later
+/// rows become outputs (both sorted by qubit), so each slice is a diagram on its own.
+pub fn slice_by_row(graph: &Graph, r0: f64, r1: f64) -> Graph {
+    slice_by_row_with_map(graph, r0, r1).0
+}
+
+/// Same as [`slice_by_row`], also returning the map from original to sliced vertex ids
+pub fn slice_by_row_with_map(graph: &Graph, r0: f64, r1: f64) -> (Graph, HashMap<V, V>) {
+    let mut sliced = Graph::new();
+    let mut vertex_map = HashMap::new();
+
+    let mut vertices: Vec<V> = graph.vertices()
+        .filter(|&v| graph.row(v) >= r0 && graph.row(v) <= r1)
+        .collect();
+    vertices.sort();
+
+    for &v in &vertices {
+        let data = graph.vertex_data(v);
+        let new_v = sliced.add_vertex_with_data(VData {
+            ty: data.ty,
+            phase: data.phase,
+            qubit: data.qubit,
+            row: data.row,
+        });
+        vertex_map.insert(v, new_v);
+    }
+
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+
+    for &v in &vertices {
+        let mut neighbors: Vec<V> = graph.neighbors(v).collect();
+        neighbors.sort();
+        for n in neighbors {
+            let ety = graph.edge_type(v, n);
+            if let Some(&new_n) = vertex_map.get(&n) {
+                // Internal edge, add it once
+                if v < n {
+                    sliced.add_edge_with_type(vertex_map[&v], new_n, ety);
+                }
+                continue;
+            }
+
+            // Cut edge, terminate it on the slice border
+            let before = graph.row(n) < r0;
+            let boundary = sliced.add_vertex_with_data(VData {
+                ty: VType::B,
+                phase: 0.into(),
+                qubit: graph.qubit(v),
+                row: if before { r0 } else { r1 },
+            });
+            sliced.add_edge_with_type(boundary, vertex_map[&v], ety);
+            if before {
+                inputs.push(boundary);
+            } else {
+                outputs.push(boundary);
+            }
+        }
+    }
+
+    // Original boundaries inside the slice keep their role
+    inputs.extend(graph.inputs().iter().filter_map(|v| vertex_map.get(v)));
+    outputs.extend(graph.outputs().iter().filter_map(|v| vertex_map.get(v)));
+    inputs.sort_by(|&a, &b| sliced.qubit(a).total_cmp(&sliced.qubit(b)).then(a.cmp(&b)));
+    outputs.sort_by(|&a, &b| sliced.qubit(a).total_cmp(&sliced.qubit(b)).then(a.cmp(&b)));
+    sliced.set_inputs(inputs);
+    sliced.set_outputs(outputs);
+
+    (sliced, vertex_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::vertex_at;
+
+    /// Two qubit lines B-Z-X-Z-B with a Z-X coupling at row 2
+    fn two_lines() -> Graph {
+        let mut g = Graph::new();
+        let mut verts = Vec::new();
+        for q in 0..2 {
+            let qubit = q as f64;
+            let line = [
+                vertex_at(&mut g, VType::B, qubit, 0.0),
+                vertex_at(&mut g, VType::Z, qubit, 1.0),
+                vertex_at(&mut g, if q == 0 { VType::Z } else { VType::X }, qubit, 2.0),
+                vertex_at(&mut g, VType::Z, qubit, 3.0),
+                vertex_at(&mut g, VType::B, qubit, 4.0),
+            ];
+            for w in line.windows(2) {
+                g.add_edge(w[0], w[1]);
+            }
+            verts.push(line);
+        }
+        g.add_edge(verts[0][2], verts[1][2]);
+        g.set_inputs(vec![verts[0][0], verts[1][0]]);
+        g.set_outputs(vec![verts[0][4], verts[1][4]]);
+        g
+    }
+
+    #[test]
+    fn test_slice_middle() {
+        let g = two_lines();
+        let (s, map) = slice_by_row_with_map(&g, 1.5, 2.5);
+
+        // Two spiders from row 2 plus four cut boundaries
+        assert_eq!(map.len(), 2);
+        assert_eq!(s.num_vertices(), 6);
+        assert_eq!(s.num_edges(), 5);
+        assert_eq!(s.inputs().len(), 2);
+        assert_eq!(s.outputs().len(), 2);
+        for &b in s.inputs() {
+            assert_eq!(s.vertex_type(b), VType::B);
+            assert_eq!(s.row(b), 1.5);
+        }
+        for &b in s.outputs() {
+            assert_eq!(s.row(b), 2.5);
+        }
+        // Sorted by qubit
+        assert_eq!(s.qubit(s.inputs()[0]), 0.0);
+        assert_eq!(s.qubit(s.inputs()[1]), 1.0);
+    }
+
+    #[test]
+    fn test_slice_keeps_original_boundaries() {
+        let g = two_lines();
+        let s = slice_by_row(&g, 0.0, 1.0);
+
+        // Original inputs kept, edges to row 2 cut into outputs
+        assert_eq!(s.num_vertices(), 6);
+        assert_eq!(s.inputs().len(), 2);
+        assert_eq!(s.outputs().len(), 2);
+        assert!(s.inputs().iter().all(|&v| s.row(v) == 0.0));
+        assert!(s.outputs().iter().all(|&v| s.row(v) == 1.0));
+    }
+
+    #[test]
+    fn test_full_slice_is_copy() {
+        let g = two_lines();
+        let s = slice_by_row(&g, 0.0, 4.0);
+        assert_eq!(s.num_vertices(), g.num_vertices());
+        assert_eq!(s.num_edges(), g.num_edges());
+        assert_eq!(s.inputs().len(), 2);
+        assert_eq!(s.outputs().len(), 2);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::linalg::Mat2;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Longest side of a spy plot in pixels; larger matrices are scaled down
+pub const SPY_MAX_SIDE: usize = 4096;
+
+/// Spy plot of `mat` as 8-bit grayscale rows: black where the matrix has a one
+///
+/// Each pixel covers a block of `scale × scale` entries, with the smallest `scale` that
+/// keeps both sides within `max_side`, and is black if any entry of its block is set.
+/// Returns (width, height, pixels).
+pub fn spy_raster(mat: &Mat2, max_side: usize) -> (usize, usize, Vec<u8>) {
+    let longest = mat.rows().max(mat.cols());
+    let scale = longest.div_ceil(max_side.max(1)).max(1);
+    let width = mat.cols().div_ceil(scale).max(1);
+    let height = mat.rows().div_ceil(scale).max(1);
+
+    let mut pixels = vec![255u8; width * height];
+    for r in 0..mat.rows() {
+        for c in mat.row_ones(r) {
+            pixels[(r / scale) * width + c / scale] = 0;
+        }
+    }
+    (width, height, pixels)
+}
+
+/// Writes a spy plot of `mat` to `path` as a PNG, see [`spy_raster`]
+///
+/// Meant for matrices far too large to read in the debug log. Matrices with more than
+/// [`SPY_MAX_SIDE`] rows or columns are scaled down; an empty matrix gives one white pixel.
+pub fn draw_mat_png(mat: &Mat2, path: impl AsRef<Path>) -> Result<(), String> {
+    let path = path.as_ref();
+    let (width, height, pixels) = spy_raster(mat, SPY_MAX_SIDE);
+    let file = File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spy_plot() {
+        let mat = Mat2::from_u8(vec![
+            vec![1, 0, 0, 1, 0],
+            vec![0, 0, 0, 0, 0],
+            vec![0, 1, 0, 0, 1],
+        ]);
+        let (width, height, pixels) = spy_raster(&mat, 100);
+        assert_eq!((width, height), (5, 3));
+        assert_eq!(pixels, vec![
+            0, 255, 255, 0, 255,
+            255, 255, 255, 255, 255,
+            255, 0, 255, 255, 0,
+        ]);
+
+        // Two by two blocks, the last ones cut short
+        let (width, height, pixels) = spy_raster(&mat, 3);
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(pixels, vec![0, 0, 255, 0, 255, 0]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spy.png");
+        draw_mat_png(&mat, &path).unwrap();
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (5, 3));
+        assert_eq!(&buf[..info.buffer_size()], &spy_raster(&mat, SPY_MAX_SIDE).2[..]);
+
+        draw_mat_png(&Mat2::zeros(0, 0), &path).unwrap();
+        assert!(draw_mat_png(&mat, dir.path().join("missing/spy.png")).is_err());
+    }
+}
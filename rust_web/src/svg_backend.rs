@@ -0,0 +1,230 @@
+use crate::graph_visualizer::{edge_label, format_phase, web_edge_style, RenderOptions, GADGET_COLOR};
+use crate::layout::LayoutGraph;
+use crate::pauliweb::PauliWeb;
+use crate::phase_gadgets::find_phase_gadgets;
+use quizx::graph::{GraphLike, VType};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const MARGIN: f64 = 40.0;
+const RADIUS: f64 = 22.0;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders a graph to SVG without Graphviz
+///
+/// Uses the same layout and colours as [`crate::graph_visualizer::to_dot_with_options`]
+/// rendered with `neato -n2`, including [`RenderOptions::active_layout`]. Meant as a fallback
+/// where Graphviz is unavailable, so it skips arrowheads and fonts are left to the viewer.
+pub fn to_svg<G: GraphLike>(graph: &G, pauli_web: Option<&PauliWeb>, options: &RenderOptions) -> String {
+    let layout_graph = LayoutGraph::new(graph);
+    let layout = options.active_layout();
+    let positions = layout.positions(&layout_graph);
+
+    let bounds = |coord: &dyn Fn((f64, f64)) -> f64| {
+        if positions.is_empty() {
+            return (0.0, 0.0);
+        }
+        let min = positions.values().map(|&p| coord(p)).fold(f64::MAX, f64::min);
+        let max = positions.values().map(|&p| coord(p)).fold(f64::MIN, f64::max);
+        (min, max)
+    };
+    let (min_x, max_x) = bounds(&|(x, _)| x);
+    let (min_y, max_y) = bounds(&|(_, y)| y);
+    // SVG y points down, so the bottom of the diagram is at the largest y
+    let to_svg_point = |(x, y): (f64, f64)| (MARGIN + x - min_x, MARGIN + max_y - y);
+    let pos = |v: usize| to_svg_point(positions[&v]);
+    let width = 2.0 * MARGIN + (max_x - min_x);
+    let height = 2.0 * MARGIN + (max_y - min_y);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"Arial\">",
+        width, height
+    ).unwrap();
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n");
+
+    if options.qubit_lines {
+        for (y, x_start, x_end) in layout.qubit_lines(&layout_graph) {
+            let (x1, y) = to_svg_point((x_start, y));
+            let (x2, _) = to_svg_point((x_end, 0.0));
+            writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\" stroke=\"#cccccc\" stroke-dasharray=\"5,5\"/>",
+                x1, x2
+            ).unwrap();
+        }
+    }
+
+    let gadgets = if options.phase_gadgets { find_phase_gadgets(graph) } else { Vec::new() };
+    let gadget_vertices: HashSet<usize> = gadgets.iter().flat_map(|g| [g.hub, g.leaf]).collect();
+    let gadget_edges: HashSet<(usize, usize)> = gadgets.iter()
+        .map(|g| (g.hub.min(g.leaf), g.hub.max(g.leaf)))
+        .collect();
+
+    let mut edges: Vec<_> = graph.edges().map(|(a, b, _)| (a.min(b), a.max(b))).collect();
+    edges.sort();
+    for (a, b) in edges {
+        let web_style = pauli_web.and_then(|w| w.get_edge(a, b)).map(|pauli| web_edge_style(pauli, options.palette));
+        let is_gadget = gadget_edges.contains(&(a, b));
+        let (color, width) = match web_style {
+            Some((color, width)) => (color.to_string(), width.to_string()),
+            None if is_gadget => (GADGET_COLOR.to_string(), "1.5".to_string()),
+            None => ("#000000".to_string(), "1.5".to_string()),
+        };
+        let highlight = options.highlights.edges.get(&(a, b));
+        let (color, width) = match highlight {
+            Some(style) => (style.color.clone(), style.width.unwrap_or(4.0).to_string()),
+            None => (color, width),
+        };
+        let dashed = highlight.map_or(is_gadget, |style| style.dashed || is_gadget);
+        let dash = if dashed { " stroke-dasharray=\"6,4\"" } else { "" };
+        let ((x1, y1), (x2, y2)) = (pos(a), pos(b));
+        writeln!(
+            svg,
+            "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"{color}\" stroke-width=\"{width}\"{dash}/>"
+        ).unwrap();
+        if let Some(label) = edge_label(options, pauli_web, a, b) {
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"14\" text-anchor=\"middle\">{}</text>",
+                (x1 + x2) / 2.0, (y1 + y2) / 2.0 - 4.0, escape(&label)
+            ).unwrap();
+        }
+    }
+
+    let inputs: HashSet<usize> = graph.inputs().iter().copied().collect();
+    let colors = options.palette.colors();
+    for &v in &layout_graph.vertices {
+        let (x, y) = pos(v);
+        let ty = graph.vertex_type(v);
+        let (fill, text_color, label) = match ty {
+            VType::Z | VType::X => {
+                let fill = if ty == VType::Z { colors.z_spider } else { colors.x_spider };
+                let label = options.phase_labels.get(&v).cloned()
+                    .unwrap_or_else(|| format_phase(graph.phase(v).to_f64()));
+                (fill, "#000000", label)
+            }
+            VType::H => (colors.h_box, "#000000", String::new()),
+            VType::B if inputs.contains(&v) => ("#ffffff", "#000000", "B".to_string()),
+            VType::B => ("#000000", "#ffffff", "B".to_string()),
+            _ => ("#ffffff", "#000000", String::new()),
+        };
+        let stroke = if gadget_vertices.contains(&v) { GADGET_COLOR } else { "#000000" };
+
+        if let Some(style) = options.highlights.vertices.get(&v) {
+            let dash = if style.dashed { " stroke-dasharray=\"6,4\"" } else { "" };
+            writeln!(
+                svg,
+                "<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"{dash}/>",
+                RADIUS + 3.0, style.color, style.width.unwrap_or(5.0)
+            ).unwrap();
+            if !style.note.is_empty() {
+                writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"12\" fill=\"{}\">{}</text>",
+                    x + RADIUS + 6.0, y - RADIUS, style.color, escape(&style.note)
+                ).unwrap();
+            }
+        }
+        if gadget_vertices.contains(&v) {
+            writeln!(svg, "<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"none\" stroke=\"{stroke}\" stroke-width=\"1.5\"/>", RADIUS + 4.0).unwrap();
+        }
+        if ty == VType::H {
+            let side = 28.8;
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{side}\" height=\"{side}\" fill=\"{fill}\" stroke=\"{stroke}\" stroke-width=\"1.5\"/>",
+                x - side / 2.0, y - side / 2.0
+            ).unwrap();
+        } else {
+            writeln!(svg, "<circle cx=\"{x}\" cy=\"{y}\" r=\"{RADIUS}\" fill=\"{fill}\" stroke=\"{stroke}\" stroke-width=\"1.5\"/>").unwrap();
+        }
+
+        if options.show_node_ids {
+            writeln!(
+                svg,
+                "<text x=\"{x}\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\" fill=\"{text_color}\">{v}</text>",
+                if label.is_empty() { y + 4.0 } else { y - 6.0 }
+            ).unwrap();
+        }
+        if !label.is_empty() {
+            writeln!(
+                svg,
+                "<text x=\"{x}\" y=\"{}\" font-size=\"16\" text-anchor=\"middle\" fill=\"{text_color}\">{}</text>",
+                if options.show_node_ids { y + 12.0 } else { y + 6.0 },
+                escape(&label)
+            ).unwrap();
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_visualizer::{Style, HIGHLIGHT_COLOR};
+    use crate::pauliweb::Pauli;
+    use quizx::hash_graph::Graph;
+    use quizx::phase::Phase;
+
+    #[test]
+    fn test_native_svg() {
+        let mut g = Graph::new();
+        let b = g.add_vertex(VType::B);
+        let z = g.add_vertex_with_phase(VType::Z, Phase::from(0.5));
+        let x = g.add_vertex(VType::X);
+        g.set_row(z, 1.0);
+        g.set_row(x, 2.0);
+        g.set_qubit(x, 1.0);
+        g.add_edge(b, z);
+        g.add_edge(z, x);
+        g.set_inputs(vec![b]);
+
+        let mut web = PauliWeb::new();
+        web.set_edge(z, x, Pauli::Z);
+        let options = RenderOptions {
+            show_node_ids: true,
+            phase_labels: [(x, "α < β".to_string())].into(),
+            ..RenderOptions::default()
+        };
+        let svg = to_svg(&g, Some(&web), &options);
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert!(svg.contains("width=\"380\" height=\"180\""));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 2);
+        // Web edge in the web colour, the other one plain
+        assert!(svg.contains("x1=\"190\" y1=\"140\" x2=\"340\" y2=\"40\" stroke=\"#00aa00\" stroke-width=\"2.5\""));
+        assert!(svg.contains("stroke=\"#000000\" stroke-width=\"1.5\"/>"));
+        // Input boundary is an open circle
+        assert!(svg.contains("cx=\"40\" cy=\"140\" r=\"22\" fill=\"#ffffff\""));
+        assert!(svg.contains(">π/2</text>"));
+        assert!(svg.contains(">α &lt; β</text>"));
+        assert!(svg.contains(&format!(">{}</text>", z)));
+        assert!(!svg.contains(HIGHLIGHT_COLOR));
+
+        let mut highlighted = options.clone();
+        highlighted.highlights.vertices.insert(z, Style::note("odd parity"));
+        highlighted.highlights.add_edge(b, z, Style::default());
+        let svg = to_svg(&g, Some(&web), &highlighted);
+        assert!(svg.contains("cx=\"190\" cy=\"140\" r=\"25\" fill=\"none\" stroke=\"#ff8800\""));
+        assert!(svg.contains(">odd parity</text>"));
+        assert!(svg.contains("x1=\"40\" y1=\"140\" x2=\"190\" y2=\"140\" stroke=\"#ff8800\" stroke-width=\"4\""));
+
+        let flipped = RenderOptions {
+            layout: crate::layout::FixedCoords { flip_y: true, ..Default::default() },
+            ..RenderOptions::default()
+        };
+        let svg = to_svg(&g, None, &flipped);
+        assert!(svg.contains("x1=\"190\" y1=\"40\" x2=\"340\" y2=\"140\""));
+    }
+}
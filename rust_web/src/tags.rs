@@ -0,0 +1,301 @@
+//! Designer-provided metadata from the annotations of a .zxg file
+//!
+//! Annotation fields other than `coord` and `boundary`, e.g. `"detector_round": 3` or
+//! `"is_ancilla": true`, are kept as tags of their vertex or edge. They let analyses use
+//! what the designer knows instead of guessing from coordinates: PyZX's `input` and
+//! `output` indices give the boundary roles ([`Tags::boundary_roles`]), tags select
+//! parts of a diagram ([`Tags::select`]) and describe what a web touches
+//! ([`Tags::value_counts`]).
+
+use crate::connectivity::subgraph;
+use crate::detection_webs::BoundaryRoles;
+use crate::logging::LOADER;
+use quizx::graph::{VType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Annotation fields the loader reads itself, which aren't tags
+const RESERVED: [&str; 2] = ["coord", "boundary"];
+
+/// Value of a tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TagValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl fmt::Display for TagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagValue::Bool(b) => write!(f, "{}", b),
+            TagValue::Int(i) => write!(f, "{}", i),
+            TagValue::Float(x) => write!(f, "{}", x),
+            TagValue::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+impl TagValue {
+    fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(TagValue::Bool(*b)),
+            Value::Number(n) => Some(n.as_i64().map_or_else(|| TagValue::Float(n.as_f64().unwrap_or(f64::NAN)), TagValue::Int)),
+            Value::String(s) => Some(TagValue::Str(s.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Types a tag can be read as, see [`Tags::vertex`]
+pub trait FromTag: Sized {
+    /// Name of the type in error messages
+    const EXPECTED: &'static str;
+
+    fn from_tag(value: &TagValue) -> Option<Self>;
+}
+
+impl FromTag for bool {
+    const EXPECTED: &'static str = "a boolean";
+
+    fn from_tag(value: &TagValue) -> Option<Self> {
+        match value {
+            TagValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl FromTag for i64 {
+    const EXPECTED: &'static str = "an integer";
+
+    fn from_tag(value: &TagValue) -> Option<Self> {
+        match value {
+            TagValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl FromTag for usize {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn from_tag(value: &TagValue) -> Option<Self> {
+        i64::from_tag(value).and_then(|i| usize::try_from(i).ok())
+    }
+}
+
+impl FromTag for f64 {
+    const EXPECTED: &'static str = "a number";
+
+    fn from_tag(value: &TagValue) -> Option<Self> {
+        match value {
+            TagValue::Int(i) => Some(*i as f64),
+            TagValue::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
+
+impl FromTag for String {
+    const EXPECTED: &'static str = "a string";
+
+    fn from_tag(value: &TagValue) -> Option<Self> {
+        match value {
+            TagValue::Str(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Tags by name of one vertex or edge
+pub type TagMap = BTreeMap<String, TagValue>;
+
+/// The tags of a loaded diagram, by vertex and by edge
+///
+/// Edges are keyed by their endpoints, smaller first. Vertices and edges without tags
+/// are left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tags {
+    pub vertices: HashMap<V, TagMap>,
+    pub edges: HashMap<(V, V), TagMap>,
+}
+
+/// Reads the tags of one annotation; fields that aren't booleans, numbers or strings are
+/// skipped with a warning
+pub(crate) fn parse_tags(key: &str, annotation: &Value) -> TagMap {
+    let Some(fields) = annotation.as_object() else {
+        return TagMap::new();
+    };
+    fields.iter()
+        .filter(|(name, _)| !RESERVED.contains(&name.as_str()))
+        .filter_map(|(name, value)| {
+            let tag = TagValue::from_json(value);
+            if tag.is_none() {
+                log::warn!(target: LOADER, "Skipping annotation {} of {}: {} is not a boolean, number or string", name, key, value);
+            }
+            Some((name.clone(), tag?))
+        })
+        .collect()
+}
+
+fn typed<T: FromTag>(what: String, name: &str, tags: Option<&TagMap>) -> Result<Option<T>, String> {
+    match tags.and_then(|tags| tags.get(name)) {
+        None => Ok(None),
+        Some(value) => T::from_tag(value)
+            .map(Some)
+            .ok_or_else(|| format!("{} has tag {} = {}, expected {}", what, name, value, T::EXPECTED)),
+    }
+}
+
+impl Tags {
+    /// Tag `name` of vertex `v` as a `T`, `None` if it has no such tag
+    ///
+    /// Fails if the tag has another type, e.g. `"detector_round": "three"` read as a
+    /// `usize`.
+    pub fn vertex<T: FromTag>(&self, v: V, name: &str) -> Result<Option<T>, String> {
+        typed(format!("Vertex {}", v), name, self.vertices.get(&v))
+    }
+
+    /// Tag `name` of the edge between `a` and `b` as a `T`, see [`Tags::vertex`]
+    pub fn edge<T: FromTag>(&self, a: V, b: V, name: &str) -> Result<Option<T>, String> {
+        typed(format!("Edge {}-{}", a, b), name, self.edges.get(&(a.min(b), a.max(b))))
+    }
+
+    /// The vertices whose tag `name` equals `value`, sorted
+    pub fn vertices_where<T: FromTag + PartialEq>(&self, name: &str, value: &T) -> Vec<V> {
+        let mut vertices: Vec<V> = self.vertices.iter()
+            .filter(|(_, tags)| tags.get(name).and_then(T::from_tag).as_ref() == Some(value))
+            .map(|(&v, _)| v)
+            .collect();
+        vertices.sort();
+        vertices
+    }
+
+    /// The part of `g` whose vertices have tag `name` equal to `value`, with the original
+    /// vertex of each of its vertices, see [`subgraph`]
+    pub fn select<T: FromTag + PartialEq>(&self, g: &Graph, name: &str, value: &T) -> (Graph, HashMap<V, V>) {
+        subgraph(g, &self.vertices_where(name, value))
+    }
+
+    /// How often each value of tag `name` occurs among `vertices`, e.g. the detector
+    /// rounds the fired spiders of a web belong to
+    pub fn value_counts(&self, vertices: impl IntoIterator<Item = V>, name: &str) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for v in vertices {
+            if let Some(value) = self.vertices.get(&v).and_then(|tags| tags.get(name)) {
+                *counts.entry(value.to_string()).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Tag names in use on vertices, sorted
+    pub fn vertex_tag_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.vertices.values().flat_map(|tags| tags.keys().map(String::as_str)).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Boundary roles from the `input` and `output` indices PyZX writes on boundaries
+    ///
+    /// `None` unless every boundary of `g` has exactly one of the two, in which case the
+    /// inputs and outputs are ordered by index rather than by qubit coordinate. Fails if
+    /// an index is not a non-negative integer or appears twice.
+    pub fn boundary_roles(&self, g: &Graph) -> Result<Option<BoundaryRoles>, String> {
+        let mut boundaries: Vec<V> = g.vertices().filter(|&v| g.vertex_type(v) == VType::B).collect();
+        boundaries.sort();
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for &v in &boundaries {
+            match (self.vertex::<usize>(v, "input")?, self.vertex::<usize>(v, "output")?) {
+                (Some(i), None) => inputs.push((i, v)),
+                (None, Some(i)) => outputs.push((i, v)),
+                _ => return Ok(None),
+            }
+        }
+        if boundaries.is_empty() {
+            return Ok(None);
+        }
+        let ordered = |mut indexed: Vec<(usize, V)>, role: &str| {
+            indexed.sort();
+            if let Some(pair) = indexed.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+                return Err(format!("Boundaries {} and {} are both {} {}", pair[0].1, pair[1].1, role, pair[0].0));
+            }
+            Ok(indexed.into_iter().map(|(_, v)| v).collect())
+        };
+        Ok(Some(BoundaryRoles::Explicit { inputs: ordered(inputs, "input")?, outputs: ordered(outputs, "output")? }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_loader::{load_graph_from_str_tagged, LoadOptions};
+    use serde_json::json;
+
+    #[test]
+    fn test_tags() {
+        // PyZX indices order the inputs against their qubit order
+        let boundary = |x: f64, y: f64, role: &str, index: usize| json!({ "annotation": { "boundary": true, "coord": [x, y], role: index } });
+        let spider = |x: f64, y: f64| json!({ "annotation": { "coord": [x, y] }, "data": { "type": "Z" } });
+        let edge = |src: &str, tgt: &str| json!({ "src": src, "tgt": tgt });
+        let indexed = json!({
+            "wire_vertices": {
+                "b0": boundary(-4.0, 2.0, "input", 0), "b1": boundary(-4.0, 0.0, "input", 1),
+                "b2": boundary(4.0, 2.0, "output", 0), "b3": boundary(4.0, 0.0, "output", 1)
+            },
+            "node_vertices": { "v0": spider(0.0, 2.0), "v1": spider(0.0, 0.0) },
+            "undir_edges": {
+                "e0": edge("b0", "v0"), "e1": edge("v0", "b2"), "e2": edge("b1", "v1"), "e3": edge("v1", "b3"), "e4": edge("v0", "v1")
+            }
+        });
+        let (g, tags) = load_graph_from_str_tagged(&indexed.to_string(), &LoadOptions::default()).unwrap();
+        let Some(BoundaryRoles::Explicit { inputs, outputs }) = tags.boundary_roles(&g).unwrap() else { panic!() };
+        assert_eq!(inputs.iter().map(|&v| g.qubit(v)).collect::<Vec<_>>(), [2.0, 0.0]);
+        assert_eq!(outputs.iter().map(|&v| g.qubit(v)).collect::<Vec<_>>(), [2.0, 0.0]);
+
+        let zxg = json!({
+            "wire_vertices": {
+                "in": { "annotation": { "boundary": true, "coord": [0, 0] } },
+                "out": { "annotation": { "boundary": true, "coord": [3, 0] } }
+            },
+            "node_vertices": {
+                "a": { "annotation": { "coord": [1, 0], "detector_round": 1, "is_ancilla": false }, "data": { "type": "Z" } },
+                "b": { "annotation": { "coord": [2, 0], "detector_round": 2, "label": "flag", "shape": [1, 2] }, "data": { "type": "X" } }
+            },
+            "undir_edges": {
+                "e0": { "src": "in", "tgt": "a" },
+                "e1": { "src": "a", "tgt": "b", "annotation": { "weight": 0.5 } },
+                "e2": { "src": "b", "tgt": "out" }
+            }
+        });
+        let (g, tags) = load_graph_from_str_tagged(&zxg.to_string(), &LoadOptions::default()).unwrap();
+        let by_row = |row: f64| g.vertices().find(|&v| g.row(v) == row).unwrap();
+        let (a, b) = (by_row(1.0), by_row(2.0));
+
+        assert_eq!(tags.vertex::<usize>(a, "detector_round"), Ok(Some(1)));
+        assert_eq!(tags.vertex::<bool>(a, "is_ancilla"), Ok(Some(false)));
+        assert_eq!(tags.vertex::<String>(b, "label"), Ok(Some("flag".to_string())));
+        assert_eq!(tags.vertex::<bool>(b, "is_ancilla"), Ok(None));
+        assert_eq!(tags.edge::<f64>(b, a, "weight"), Ok(Some(0.5)));
+        let error = tags.vertex::<bool>(b, "label").unwrap_err();
+        assert_eq!(error, format!("Vertex {} has tag label = flag, expected a boolean", b));
+        // Lists aren't tags, and the reserved fields never are
+        assert_eq!(tags.vertex_tag_names(), ["detector_round", "is_ancilla", "label"]);
+        assert!(tags.boundary_roles(&g).unwrap().is_none());
+
+        assert_eq!(tags.vertices_where("detector_round", &2usize), [b]);
+        let (round, old_of) = tags.select(&g, "detector_round", &1usize);
+        assert_eq!(round.num_vertices(), 1);
+        assert_eq!(old_of.values().copied().collect::<Vec<_>>(), [a]);
+        let counts = tags.value_counts(g.vertices(), "detector_round");
+        assert_eq!(counts, BTreeMap::from([("1".to_string(), 1), ("2".to_string(), 1)]));
+    }
+}
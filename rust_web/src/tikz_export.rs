@@ -1,3 +1,7 @@
+use crate::graph_visualizer::RenderOptions;
+use crate::pauliweb::{Pauli, PauliWeb};
+use crate::palette::{tikz_color, Palette};
+use zxcore::tikz::{SpiderFills, WireStyle};
 use quizx::hash_graph::*;
 use std::fs::File;
 use std::io::{Write, Result};
@@ -6,59 +10,106 @@ use std::io::{Write, Result};
 #[allow(dead_code)] // Remove once used
 pub fn export_to_tikz(g: &Graph, filename: &str) -> Result<()> {
     let mut file = File::create(filename)?;
+    tikz_to_writer(g, &mut file)
+}
+
+/// Same as [`export_to_tikz`], returning the document as a string
+pub fn tikz_to_string(g: &Graph) -> String {
+    zxcore::tikz::tikz_to_string(g)
+}
 
-    writeln!(file, "\\documentclass{{standalone}}")?;
-    writeln!(file, "\\usepackage{{tikz}}")?;
-    writeln!(file, "\\begin{{document}}")?;
-    writeln!(file, "\\begin{{tikzpicture}}[scale=1]")?;
+/// Same as [`export_to_tikz`], writing the document to any writer
+pub fn tikz_to_writer<W: Write>(g: &Graph, file: &mut W) -> Result<()> {
+    zxcore::tikz::tikz_to_writer(g, file)
+}
 
-    // Define styles for X, Z and boundary spiders
-    writeln!(file, "\\tikzstyle{{xspider}}=[draw,circle,fill=red!20]")?;
-    writeln!(file, "\\tikzstyle{{zspider}}=[draw,circle,fill=green!20]")?;
-    writeln!(file, "\\tikzstyle{{boundary}}=[draw,circle,fill=black!20]")?;
+/// Same as [`tikz_to_string`], with the edges of `web` drawn thick in the colours of the
+/// DOT output: red for X, green for Z and blue for Y
+pub fn tikz_to_string_with_web(g: &Graph, web: &PauliWeb) -> String {
+    tikz_to_string_with_options(g, web, &RenderOptions::default())
+}
 
-    let mut positions = vec![];
+/// Same as [`tikz_to_string_with_web`], in the colours of [`RenderOptions::palette`] and
+/// writing the Pauli letter on each web edge if [`RenderOptions::pauli_labels`] is set;
+/// other options don't apply to TikZ
+pub fn tikz_to_string_with_options(g: &Graph, web: &PauliWeb, options: &RenderOptions) -> String {
+    // The classic palette keeps xcolor's named colours, which read better in LaTeX sources
+    let (fills, [web_x, web_z, web_y]) = match options.palette {
+        Palette::Classic => (SpiderFills::default(), ["red".to_string(), "green!66!black".to_string(), "blue".to_string()]),
+        palette => {
+            let colors = palette.colors();
+            let fills = SpiderFills {
+                x: tikz_color(colors.x_spider),
+                z: tikz_color(colors.z_spider),
+                ..SpiderFills::default()
+            };
+            (fills, [tikz_color(colors.web_x), tikz_color(colors.web_z), tikz_color(colors.web_y)])
+        }
+    };
+    let mut buffer = Vec::new();
+    zxcore::tikz::tikz_to_writer_themed(g, &mut buffer, &fills, |a, b| {
+        web.get_edge(a, b).map(|pauli| WireStyle {
+            options: format!("draw={}, very thick", match pauli {
+                Pauli::X => &web_x,
+                Pauli::Z => &web_z,
+                Pauli::Y => &web_y,
+            }),
+            label: options.pauli_labels.then(|| pauli.to_string()),
+        })
+    }).expect("writing to a Vec can't fail");
+    String::from_utf8(buffer).expect("TikZ output is valid UTF-8")
+}
 
-    // Assign simple horizontal positions to each vertex
-    for (i, v) in g.vertices().enumerate() {
-        let x = i as f64 * 1.5; // horizontal spacing
-        let (style, label) = match g.vertex_type(v) {
-            VType::X => {
-                let phase = g.phase(v);
-                let phase_str = if phase.to_string() == "0" {
-                    String::from("")
-                } else {
-                    format!("{}π", phase)
-                };
-                ("xspider", phase_str)
-            },
-            VType::Z => {
-                let phase = g.phase(v);
-                let phase_str = if phase.to_string() == "0" {
-                    String::from("")
-                } else {
-                    format!("{}π", phase)
-                };
-                ("zspider", phase_str)
-            },
-            _ => ("boundary", String::from("B")),
-        };
-        
-        writeln!(
-            file,
-            "\\node[{}] (v{}) at ({},0) {{{}}};",
-            style, v, x, label
-        )?;
-        positions.push((v, x));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::{GraphLike, VType};
+
+    /// A Z spider with an X neighbour and a boundary, Y on the spider edge and X on the leg
+    fn y_web() -> (Graph, PauliWeb, usize, usize, usize) {
+        let mut g = Graph::new();
+        let z = g.add_vertex(VType::Z);
+        let x = g.add_vertex(VType::X);
+        let b = g.add_vertex(VType::B);
+        g.add_edge(z, x);
+        g.add_edge(z, b);
+        let mut web = PauliWeb::new();
+        web.set_edge(z, x, Pauli::Y);
+        web.set_edge(z, b, Pauli::X);
+        (g, web, z, x, b)
+    }
+
+    #[test]
+    fn test_web_edge_colors() {
+        let (g, web, z, x, b) = y_web();
+        let tikz = tikz_to_string_with_web(&g, &web);
+        assert!(tikz.contains(&format!("\\draw[draw=blue, very thick] (v{}) -- (v{});", z, x)));
+        assert!(tikz.contains(&format!("\\draw[draw=red, very thick] (v{}) -- (v{});", z, b)));
     }
 
-    // Draw edges
-    for (v0, v1, _) in g.edges() {
-        writeln!(file, "\\draw (v{}) -- (v{});", v0, v1)?;
+    #[test]
+    fn test_pauli_labels() {
+        let (g, web, z, x, _) = y_web();
+        let options = RenderOptions { pauli_labels: true, ..RenderOptions::default() };
+        let tikz = tikz_to_string_with_options(&g, &web, &options);
+        assert!(tikz.contains(&format!(
+            "\\draw[draw=blue, very thick] (v{}) -- node[midway, fill=white, inner sep=1pt, font=\\small] {{Y}} (v{});",
+            z, x
+        )));
+        assert!(!tikz_to_string_with_web(&g, &web).contains("node[midway"));
     }
 
-    writeln!(file, "\\end{{tikzpicture}}")?;
-    writeln!(file, "\\end{{document}}")?;
-    
-    Ok(())
+    #[test]
+    fn test_palettes() {
+        let (g, web, z, x, b) = y_web();
+        // Classic keeps xcolor's named colours
+        let tikz = tikz_to_string_with_options(&g, &web, &RenderOptions::default());
+        assert!(tikz.contains("\\tikzstyle{xspider}=[draw,circle,fill=red!20]"));
+        assert!(tikz.contains(&format!("\\draw[draw=red, very thick] (v{}) -- (v{});", z, b)));
+
+        let options = RenderOptions { palette: Palette::Viridis, ..RenderOptions::default() };
+        let tikz = tikz_to_string_with_options(&g, &web, &options);
+        assert!(tikz.contains("\\tikzstyle{xspider}=[draw,circle,fill={rgb,255:red,161;green,128;blue,170}]"));
+        assert!(tikz.contains(&format!("\\draw[draw={{rgb,255:red,33;green,145;blue,140}}, very thick] (v{}) -- (v{});", z, x)));
+    }
 }
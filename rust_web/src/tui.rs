@@ -0,0 +1,351 @@
+//! Terminal browser for diagrams and their Pauli webs (`tui` feature)
+//!
+//! Keys: Tab/Shift-Tab switch pane, j/k or arrows move, g + id + Enter jumps to a
+//! vertex, Space/Enter on a web toggles its overlay, r re-renders to the output
+//! image, q quits.
+
+use crate::graph_visualizer::draw_graph_with_pauliweb;
+use crate::pauliweb::PauliWeb;
+use quizx::graph::{EType, V};
+use quizx::hash_graph::{Graph, GraphLike};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs};
+use ratatui::Frame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Vertices,
+    Edges,
+    Webs,
+}
+
+impl Pane {
+    const ALL: [Pane; 3] = [Pane::Vertices, Pane::Edges, Pane::Webs];
+
+    fn index(self) -> usize {
+        Pane::ALL.iter().position(|&p| p == self).unwrap()
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Pane::Vertices => "Vertices",
+            Pane::Edges => "Edges",
+            Pane::Webs => "Webs",
+        }
+    }
+}
+
+/// State of the browser, independent of the terminal so it can be driven in tests
+pub struct App {
+    graph: Graph,
+    webs: Vec<PauliWeb>,
+    vertices: Vec<V>,
+    edges: Vec<(V, V, EType)>,
+    pane: Pane,
+    selected: [usize; 3],
+    overlay: Option<usize>,
+    jump: Option<String>,
+    render_path: String,
+    status: String,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(graph: Graph, webs: Vec<PauliWeb>, render_path: &str) -> Self {
+        let mut vertices: Vec<V> = graph.vertices().collect();
+        vertices.sort();
+        let mut edges: Vec<(V, V, EType)> = graph.edges()
+            .map(|(a, b, ety)| (a.min(b), a.max(b), ety))
+            .collect();
+        edges.sort();
+        App {
+            graph,
+            webs,
+            vertices,
+            edges,
+            pane: Pane::Vertices,
+            selected: [0; 3],
+            overlay: None,
+            jump: None,
+            render_path: render_path.to_string(),
+            status: String::from("Press q to quit, g to jump to a vertex, r to render"),
+            quit: false,
+        }
+    }
+
+    pub fn pane(&self) -> Pane {
+        self.pane
+    }
+
+    pub fn overlay(&self) -> Option<usize> {
+        self.overlay
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn selected_vertex(&self) -> Option<V> {
+        self.vertices.get(self.selected[Pane::Vertices.index()]).copied()
+    }
+
+    fn len(&self, pane: Pane) -> usize {
+        match pane {
+            Pane::Vertices => self.vertices.len(),
+            Pane::Edges => self.edges.len(),
+            Pane::Webs => self.webs.len(),
+        }
+    }
+
+    fn active_web(&self) -> Option<&PauliWeb> {
+        self.overlay.and_then(|i| self.webs.get(i))
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if let Some(input) = self.jump.as_mut() {
+            match key {
+                KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let input = self.jump.take().unwrap();
+                    match input.parse::<V>() {
+                        Ok(v) => self.jump_to(v),
+                        Err(_) => self.status = format!("Not a vertex id: '{}'", input),
+                    }
+                }
+                KeyCode::Esc => self.jump = None,
+                _ => {}
+            }
+            return;
+        }
+
+        let pane = self.pane.index();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab => self.pane = Pane::ALL[(pane + 1) % 3],
+            KeyCode::BackTab => self.pane = Pane::ALL[(pane + 2) % 3],
+            KeyCode::Down | KeyCode::Char('j') if self.selected[pane] + 1 < self.len(self.pane) => {
+                self.selected[pane] += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected[pane] = self.selected[pane].saturating_sub(1);
+            }
+            KeyCode::Char('g') => self.jump = Some(String::new()),
+            KeyCode::Char(' ') | KeyCode::Enter if self.pane == Pane::Webs && !self.webs.is_empty() => {
+                let web = self.selected[pane];
+                self.overlay = if self.overlay == Some(web) { None } else { Some(web) };
+                self.status = match self.overlay {
+                    Some(i) => format!("Showing web {}", i),
+                    None => String::from("Overlay off"),
+                };
+            }
+            KeyCode::Char('r') => self.render_image(),
+            _ => {}
+        }
+    }
+
+    fn jump_to(&mut self, v: V) {
+        match self.vertices.iter().position(|&u| u == v) {
+            Some(i) => {
+                self.pane = Pane::Vertices;
+                self.selected[Pane::Vertices.index()] = i;
+                self.status = format!("Jumped to vertex {}", v);
+            }
+            None => self.status = format!("No vertex {}", v),
+        }
+    }
+
+    fn render_image(&mut self) {
+        let empty = PauliWeb::new();
+        let web = self.active_web().unwrap_or(&empty);
+        self.status = match draw_graph_with_pauliweb(&self.graph, web, &self.render_path) {
+            Ok(()) => format!("Rendered to {}", self.render_path),
+            Err(e) => format!("Render failed: {}", e),
+        };
+    }
+
+    fn vertex_line(&self, v: V) -> String {
+        let data = self.graph.vertex_data(v);
+        format!("{:>4} {:?} phase={} q={} r={}", v, data.ty, data.phase.to_f64(), data.qubit, data.row)
+    }
+
+    fn edge_line(&self, a: V, b: V, ety: EType) -> String {
+        let pauli = self.active_web()
+            .and_then(|web| web.get_edge(a, b))
+            .map(|p| format!(" [{:?}]", p))
+            .unwrap_or_default();
+        format!("{}-{} {:?}{}", a, b, ety, pauli)
+    }
+
+    fn details(&self) -> Vec<String> {
+        let index = self.selected[self.pane.index()];
+        match self.pane {
+            Pane::Vertices => {
+                let Some(&v) = self.vertices.get(index) else { return Vec::new() };
+                let mut lines = vec![self.vertex_line(v), String::from("Neighbours:")];
+                let mut neighbors: Vec<V> = self.graph.neighbors(v).collect();
+                neighbors.sort();
+                lines.extend(neighbors.into_iter().map(|n| {
+                    format!("  {}", self.edge_line(v, n, self.graph.edge_type(v, n)))
+                }));
+                lines
+            }
+            Pane::Edges => {
+                let Some(&(a, b, ety)) = self.edges.get(index) else { return Vec::new() };
+                vec![self.edge_line(a, b, ety), self.vertex_line(a), self.vertex_line(b)]
+            }
+            Pane::Webs => {
+                let Some(web) = self.webs.get(index) else { return Vec::new() };
+                let mut edges: Vec<_> = web.edge_operators.iter().collect();
+                edges.sort_by_key(|&(&edge, _)| edge);
+                edges.into_iter().map(|((a, b), p)| format!("{}-{} {:?}", a, b, p)).collect()
+            }
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [tabs_area, main_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+        let [list_area, details_area] = Layout::horizontal([
+            Constraint::Percentage(60),
+            Constraint::Percentage(40),
+        ]).areas(main_area);
+
+        let tabs = Tabs::new(Pane::ALL.iter().map(|p| p.title()))
+            .select(self.pane.index())
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(tabs, tabs_area);
+
+        let items: Vec<ListItem> = match self.pane {
+            Pane::Vertices => self.vertices.iter().map(|&v| ListItem::new(self.vertex_line(v))).collect(),
+            Pane::Edges => self.edges.iter().map(|&(a, b, ety)| ListItem::new(self.edge_line(a, b, ety))).collect(),
+            Pane::Webs => self.webs.iter().enumerate().map(|(i, web)| {
+                let marker = if self.overlay == Some(i) { " *" } else { "" };
+                ListItem::new(format!("web {}: {} edges{}", i, web.edge_operators.len(), marker))
+            }).collect(),
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(self.pane.title()))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected[self.pane.index()]));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        let details = Paragraph::new(self.details().join("\n"))
+            .block(Block::default().borders(Borders::ALL).title("Details"));
+        frame.render_widget(details, details_area);
+
+        let status = match &self.jump {
+            Some(input) => format!("Jump to vertex: {}", input),
+            None => self.status.clone(),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+    }
+}
+
+/// Runs the browser in the current terminal until the user quits
+pub fn run(graph: Graph, webs: Vec<PauliWeb>, render_path: &str) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut app = App::new(graph, webs, render_path);
+    let result = (|| {
+        while !app.should_quit() {
+            terminal.draw(|frame| app.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                app.handle_key(key.code);
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::Pauli;
+    use quizx::graph::VType;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn app() -> App {
+        let mut g = Graph::new();
+        let vs: Vec<V> = (0..4).map(|_| g.add_vertex(VType::Z)).collect();
+        for w in vs.windows(2) {
+            g.add_edge(w[0], w[1]);
+        }
+        let mut web = PauliWeb::new();
+        web.set_edge(vs[0], vs[1], Pauli::X);
+        App::new(g, vec![web, PauliWeb::new()], "unused.svg")
+    }
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_navigation_and_jump() {
+        let mut app = app();
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.selected_vertex(), Some(2));
+
+        app.handle_key(KeyCode::Tab);
+        assert_eq!(app.pane(), Pane::Edges);
+        for c in ['g', '3'] {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.pane(), Pane::Vertices);
+        assert_eq!(app.selected_vertex(), Some(3));
+
+        for key in [KeyCode::Char('g'), KeyCode::Char('9'), KeyCode::Enter] {
+            app.handle_key(key);
+        }
+        assert_eq!(app.status(), "No vertex 9");
+        assert_eq!(app.selected_vertex(), Some(3));
+    }
+
+    #[test]
+    fn test_toggle_overlay() {
+        let mut app = app();
+        app.handle_key(KeyCode::BackTab);
+        assert_eq!(app.pane(), Pane::Webs);
+        app.handle_key(KeyCode::Char(' '));
+        assert_eq!(app.overlay(), Some(0));
+
+        app.handle_key(KeyCode::BackTab);
+        assert!(screen(&app).contains("0-1 N [X]"));
+
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char(' '));
+        assert_eq!(app.overlay(), None);
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_draw_lists_vertices() {
+        let app = app();
+        let text = screen(&app);
+        assert!(text.contains("Vertices"));
+        assert!(text.contains("Z phase=0"));
+        assert!(text.contains("Neighbours:"));
+    }
+}
@@ -0,0 +1,320 @@
+use crate::graph_visualizer::{
+    render_image, to_dot_with_options, Highlights, RenderOptions, RenderedImage, RetryPolicy, Style,
+};
+use crate::pauliweb::{Pauli, PauliWeb};
+use quizx::graph::{EType, GraphLike, VType, V};
+use std::fmt;
+
+/// Why a web can't be checked against a graph at all
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The web uses an edge the graph doesn't have
+    MissingEdge(V, V),
+    /// The web touches an H-box, which has no local web rule here
+    HBox(V),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::MissingEdge(a, b) => write!(f, "Edge ({}, {}) is not in the graph", a, b),
+            Issue::HBox(v) => write!(f, "Vertex {} is an H-box", v),
+        }
+    }
+}
+
+/// A local web condition that fails at one vertex
+///
+/// For a Z spider the opposite colour is X, its own colour Z; an X spider is the reverse.
+/// A Y leg carries both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// Some but not all legs carry the opposite colour; `edges` are the legs without it
+    PartialLegs { vertex: V, edges: Vec<(V, V)> },
+    /// An odd number of legs carry the spider's own colour; `edges` are those legs
+    OddParity { vertex: V, edges: Vec<(V, V)> },
+    /// The opposite colour passes through a spider whose phase isn't 0 or π
+    NonPauliPhase { vertex: V },
+    /// The web carries a Pauli on the wire of an input or output; `edges` are those wires
+    BoundarySupport { vertex: V, edges: Vec<(V, V)> },
+}
+
+impl Violation {
+    pub fn vertex(&self) -> V {
+        match self {
+            Violation::PartialLegs { vertex, .. }
+            | Violation::OddParity { vertex, .. }
+            | Violation::NonPauliPhase { vertex }
+            | Violation::BoundarySupport { vertex, .. } => *vertex,
+        }
+    }
+
+    /// Short description of the failed condition
+    pub fn condition(&self) -> &'static str {
+        match self {
+            Violation::PartialLegs { .. } => "opposite colour on only some legs",
+            Violation::OddParity { .. } => "odd number of own-colour legs",
+            Violation::NonPauliPhase { .. } => "opposite colour through a non-Pauli phase",
+            Violation::BoundarySupport { .. } => "Pauli on an input or output",
+        }
+    }
+
+    /// Edges involved in the violation, each as (smaller, larger)
+    pub fn edges(&self) -> Vec<(V, V)> {
+        match self {
+            Violation::PartialLegs { edges, .. }
+            | Violation::OddParity { edges, .. }
+            | Violation::BoundarySupport { edges, .. } => edges.clone(),
+            Violation::NonPauliPhase { .. } => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if matches!(self, Violation::BoundarySupport { .. }) { "Boundary" } else { "Spider" };
+        write!(f, "{} {}: {}", kind, self.vertex(), self.condition())
+    }
+}
+
+/// Checks the local web conditions at every vertex the web touches
+///
+/// The Pauli on a Hadamard edge is read as seen from its smaller endpoint, so the
+/// larger endpoint sees X and Z swapped.
+/// Z spiders need X on all legs or none and Z on an even number of legs, X spiders
+/// the same with colours swapped. A spider the opposite colour passes through must
+/// have phase 0 or π. A detection web is trivial on inputs and outputs, as the solver
+/// pins their bits to zero, so any Pauli on a boundary's wire is a violation too.
+/// Violations are sorted by vertex.
+pub fn web_violations<G: GraphLike>(g: &G, web: &PauliWeb) -> Result<Vec<Violation>, Issue> {
+    for ((a, b), _) in web.sorted_edges() {
+        if !g.connected(a, b) {
+            return Err(Issue::MissingEdge(a, b));
+        }
+    }
+
+    let mut touched: Vec<V> = web.edge_operators.keys().flat_map(|&(a, b)| [a, b]).collect();
+    touched.sort();
+    touched.dedup();
+
+    let mut violations = Vec::new();
+    for v in touched {
+        let mut legs: Vec<(V, V)> = g.neighbors(v).map(|n| (v.min(n), v.max(n))).collect();
+        legs.sort();
+        let (own, opposite) = match g.vertex_type(v) {
+            VType::Z => (Pauli::Z, Pauli::X),
+            VType::X => (Pauli::X, Pauli::Z),
+            VType::H => return Err(Issue::HBox(v)),
+            VType::B => {
+                let edges: Vec<_> = legs.into_iter().filter(|&(a, b)| web.get_edge(a, b).is_some()).collect();
+                violations.push(Violation::BoundarySupport { vertex: v, edges });
+                continue;
+            }
+            _ => continue,
+        };
+        // Paulis on Hadamard edges are stored as seen from the smaller end
+        let carries = |&(a, b): &(V, V), pauli: Pauli| {
+            web.get_edge(a, b)
+                .map(|p| if v == b && g.edge_type(a, b) == EType::H { p.through_hadamard() } else { p })
+                .is_some_and(|p| p == pauli || p == Pauli::Y)
+        };
+
+        let lacking: Vec<_> = legs.iter().copied().filter(|e| !carries(e, opposite)).collect();
+        if !lacking.is_empty() && lacking.len() < legs.len() {
+            violations.push(Violation::PartialLegs { vertex: v, edges: lacking.clone() });
+        }
+        if lacking.is_empty() && !g.phase(v).to_rational().is_integer() {
+            violations.push(Violation::NonPauliPhase { vertex: v });
+        }
+        let own_legs: Vec<_> = legs.iter().copied().filter(|e| carries(e, own)).collect();
+        if own_legs.len() % 2 == 1 {
+            violations.push(Violation::OddParity { vertex: v, edges: own_legs });
+        }
+    }
+    Ok(violations)
+}
+
+/// Whether `web` is a detection web of `g`, checked locally without computing the nullspace
+///
+/// Useful for webs drawn by hand, e.g. in ZXLive. See [`web_violations`] for the conditions.
+pub fn is_detection_web<G: GraphLike>(g: &G, web: &PauliWeb) -> Result<bool, Issue> {
+    web_violations(g, web).map(|violations| violations.is_empty())
+}
+
+/// Highlights for drawing `violations`, with the failed conditions as vertex notes
+pub fn violation_highlights(violations: &[Violation]) -> Highlights {
+    let mut highlights = Highlights::default();
+    for violation in violations {
+        let note = &mut highlights.vertices.entry(violation.vertex()).or_default().note;
+        if !note.is_empty() {
+            note.push_str("; ");
+        }
+        note.push_str(violation.condition());
+        for (a, b) in violation.edges() {
+            highlights.add_edge(a, b, Style::default());
+        }
+    }
+    highlights
+}
+
+/// DOT for `g` with `web` overlaid and the places where it breaks the web rules highlighted
+///
+/// Returns the violations alongside the DOT, which is a plain rendering if there are none.
+pub fn to_dot_with_violations<G: GraphLike>(
+    g: &G,
+    web: &PauliWeb,
+    options: &RenderOptions,
+) -> Result<(String, Vec<Violation>), Issue> {
+    let violations = web_violations(g, web)?;
+    let options = RenderOptions { highlights: violation_highlights(&violations), ..options.clone() };
+    Ok((to_dot_with_options(g, Some(web), &options), violations))
+}
+
+/// Same as [`to_dot_with_violations`], rendered through [`render_image`]
+pub fn render_violations<G: GraphLike>(
+    g: &G,
+    web: &PauliWeb,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> Result<(RenderedImage, Vec<Violation>), Issue> {
+    let violations = web_violations(g, web)?;
+    let options = RenderOptions { highlights: violation_highlights(&violations), ..options.clone() };
+    Ok((render_image(g, Some(web), &options, policy), violations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection_webs::get_detection_webs;
+    use crate::fixtures::fixture;
+    use quizx::hash_graph::Graph;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_computed_webs_are_valid() {
+        let mut g = fixture("steane_2_rounds").unwrap();
+        let webs = get_detection_webs(&mut g);
+        assert!(!webs.is_empty());
+        for web in &webs {
+            assert_eq!(web_violations(&g, web), Ok(Vec::new()));
+        }
+    }
+
+    /// b - z - x - z2 = h, with a π/4 phase on z2 and a Hadamard edge to h
+    fn chain() -> (Graph, [V; 5]) {
+        let mut g = Graph::new();
+        let b = g.add_vertex(VType::B);
+        let z = g.add_vertex(VType::Z);
+        let x = g.add_vertex(VType::X);
+        let z2 = g.add_vertex_with_phase(VType::Z, quizx::phase::Phase::from(0.25));
+        let h = g.add_vertex(VType::Z);
+        g.add_edge(b, z);
+        g.add_edge(z, x);
+        g.add_edge(x, z2);
+        g.add_edge_with_type(z2, h, EType::H);
+        (g, [b, z, x, z2, h])
+    }
+
+    #[test]
+    fn test_partial_legs() {
+        let (g, [b, z, x, z2, h]) = chain();
+        let mut web = PauliWeb::new();
+        web.set_edge(z, x, Pauli::X);
+        assert_eq!(web_violations(&g, &web).unwrap(), vec![
+            Violation::PartialLegs { vertex: z, edges: vec![(b, z)] },
+            Violation::OddParity { vertex: x, edges: vec![(z, x)] },
+        ]);
+        assert_eq!(is_detection_web(&g, &web), Ok(false));
+
+        web.set_edge(x, z2, Pauli::X);
+        assert_eq!(web_violations(&g, &web).unwrap(), vec![
+            Violation::PartialLegs { vertex: z, edges: vec![(b, z)] },
+            Violation::PartialLegs { vertex: z2, edges: vec![(z2, h)] },
+        ]);
+    }
+
+    #[test]
+    fn test_boundary_support() {
+        // b1 - z - b2: X on both legs satisfies z, but the web ends on the boundaries
+        let mut g = Graph::new();
+        let b1 = g.add_vertex(VType::B);
+        let z = g.add_vertex(VType::Z);
+        let b2 = g.add_vertex(VType::B);
+        g.add_edge(b1, z);
+        g.add_edge(z, b2);
+        let mut web = PauliWeb::new();
+        web.set_edge(b1, z, Pauli::X);
+        web.set_edge(z, b2, Pauli::X);
+        assert_eq!(web_violations(&g, &web).unwrap(), vec![
+            Violation::BoundarySupport { vertex: b1, edges: vec![(b1, z)] },
+            Violation::BoundarySupport { vertex: b2, edges: vec![(z, b2)] },
+        ]);
+        assert_eq!(is_detection_web(&g, &web), Ok(false));
+        assert_eq!(web_violations(&g, &web).unwrap()[0].to_string(), format!("Boundary {}: Pauli on an input or output", b1));
+    }
+
+    #[test]
+    fn test_odd_parity() {
+        let (g, [_, z, x, z2, _]) = chain();
+        let mut parity = PauliWeb::new();
+        parity.set_edge(x, z2, Pauli::Z);
+        parity.set_edge(z, x, Pauli::Z);
+        assert_eq!(web_violations(&g, &parity).unwrap(), vec![
+            Violation::OddParity { vertex: z, edges: vec![(z, x)] },
+            Violation::OddParity { vertex: z2, edges: vec![(x, z2)] },
+        ]);
+    }
+
+    #[test]
+    fn test_non_pauli_phase() {
+        // X through the π/4 spider, on all of its legs
+        let (mut g, [_, _, x, z2, h]) = chain();
+        let x2 = g.add_vertex(VType::X);
+        g.remove_edge(z2, h);
+        g.add_edge(z2, x2);
+        let mut phase = PauliWeb::new();
+        phase.set_edge(x, z2, Pauli::X);
+        phase.set_edge(z2, x2, Pauli::X);
+        assert!(web_violations(&g, &phase).unwrap().contains(&Violation::NonPauliPhase { vertex: z2 }));
+    }
+
+    #[test]
+    fn test_hadamard_edge() {
+        // X at z2's end of the Hadamard edge is Z at h's end
+        let (g, [_, _, x, z2, h]) = chain();
+        let mut hadamard = PauliWeb::new();
+        hadamard.set_edge(z2, h, Pauli::X);
+        assert_eq!(web_violations(&g, &hadamard).unwrap(), vec![
+            Violation::PartialLegs { vertex: z2, edges: vec![(x, z2)] },
+            Violation::OddParity { vertex: h, edges: vec![(z2, h)] },
+        ]);
+    }
+
+    #[test]
+    fn test_missing_edge() {
+        let (g, [b, _, x, _, _]) = chain();
+        let mut missing = PauliWeb::new();
+        missing.set_edge(b, x, Pauli::X);
+        assert_eq!(is_detection_web(&g, &missing), Err(Issue::MissingEdge(b, x)));
+    }
+
+    #[test]
+    fn test_violation_rendering() {
+        let (g, [b, z, x, z2, _]) = chain();
+        let mut web = PauliWeb::new();
+        for (a, c) in [(b, z), (z, x), (x, z2)] {
+            web.set_edge(a, c, Pauli::X);
+        }
+        let (dot, violations) = to_dot_with_violations(&g, &web, &RenderOptions::default()).unwrap();
+        assert_eq!(violations.len(), 2);
+        assert!(dot.contains("forcelabels=true"));
+        assert!(dot.contains("xlabel=\"opposite colour on only some legs\""));
+        assert!(dot.contains("xlabel=\"Pauli on an input or output\""));
+        let highlights = violation_highlights(&[
+            Violation::OddParity { vertex: x, edges: vec![(z, x)] },
+            Violation::NonPauliPhase { vertex: x },
+        ]);
+        assert_eq!(highlights.vertices[&x].note, "odd number of own-colour legs; opposite colour through a non-Pauli phase");
+        assert_eq!(highlights.edges.keys().copied().collect::<HashSet<_>>(), HashSet::from([(z, x)]));
+    }
+}
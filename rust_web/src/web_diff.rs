@@ -0,0 +1,162 @@
+use crate::graph_visualizer::{
+    render_image, to_dot_with_options, Highlights, RenderOptions, RenderedImage, RetryPolicy, Style,
+};
+use crate::web_set::WebSet;
+use crate::web_space::WebSpace;
+use quizx::graph::GraphLike;
+use std::collections::BTreeMap;
+
+/// Colour of edges only the webs in [`WebDiff::removed`] use
+pub const REMOVED_COLOR: &str = "#cc0000";
+/// Colour of edges only the webs in [`WebDiff::added`] use
+pub const ADDED_COLOR: &str = "#00aa00";
+
+/// How a second set of webs differs from a first, e.g. after an algorithm change or a
+/// graph edit
+///
+/// Webs are compared by the spaces they span, so a different basis of the same space
+/// is no difference at all. Indices refer to the webs in the respective set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebDiff {
+    /// Webs of the first set outside the span of the second
+    pub removed: Vec<usize>,
+    /// Webs of the second set outside the span of the first
+    pub added: Vec<usize>,
+    /// Webs of the first set missing from the second as written, but products of its webs
+    pub changed: Vec<usize>,
+}
+
+impl WebDiff {
+    /// Whether both sets span the same space
+    pub fn same_span(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+/// Compares the webs of `b` against those of `a`, see [`WebDiff`]
+pub fn diff_webs(a: &WebSet, b: &WebSet) -> WebDiff {
+    let empty = WebSpace::over(a.webs.iter().chain(&b.webs));
+    let span = |set: &WebSet| {
+        let mut space = empty.clone();
+        for web in &set.webs {
+            space.add(web).expect("the space has every edge of both sets");
+        }
+        space
+    };
+    let (span_a, span_b) = (span(a), span(b));
+
+    let mut diff = WebDiff::default();
+    for (i, web) in a.webs.iter().enumerate() {
+        if !span_b.contains(web) {
+            diff.removed.push(i);
+        } else if !b.webs.contains(web) {
+            diff.changed.push(i);
+        }
+    }
+    diff.added = (0..b.webs.len()).filter(|&j| !span_a.contains(&b.webs[j])).collect();
+    diff
+}
+
+/// Highlights for drawing `diff`: edges used by removed webs in red, by added webs in
+/// green, by both in the default orange
+///
+/// Each edge is noted with the webs using it, as `a<i>` for removed and `b<j>` for
+/// added ones. Changed webs aren't drawn, since they span nothing new.
+pub fn diff_highlights(a: &WebSet, b: &WebSet, diff: &WebDiff) -> Highlights {
+    let mut users: BTreeMap<(usize, usize), (Vec<String>, Vec<String>)> = BTreeMap::new();
+    for &i in &diff.removed {
+        for (edge, _) in a.webs[i].sorted_edges() {
+            users.entry(edge).or_default().0.push(format!("a{}", i));
+        }
+    }
+    for &j in &diff.added {
+        for (edge, _) in b.webs[j].sorted_edges() {
+            users.entry(edge).or_default().1.push(format!("b{}", j));
+        }
+    }
+
+    let mut highlights = Highlights::default();
+    for ((x, y), (removed, added)) in users {
+        let style = match (removed.is_empty(), added.is_empty()) {
+            (false, true) => Style::color(REMOVED_COLOR),
+            (true, false) => Style::color(ADDED_COLOR),
+            _ => Style::default(),
+        };
+        let note = removed.into_iter().chain(added).collect::<Vec<_>>().join(",");
+        highlights.add_edge(x, y, Style { note, ..style });
+    }
+    highlights
+}
+
+/// DOT for `g` with the symmetric difference of the spans of `a` and `b` highlighted
+///
+/// Returns the diff alongside the DOT, which is a plain rendering if both sets span the
+/// same space.
+pub fn to_dot_with_diff<G: GraphLike>(
+    g: &G,
+    a: &WebSet,
+    b: &WebSet,
+    options: &RenderOptions,
+) -> (String, WebDiff) {
+    let diff = diff_webs(a, b);
+    let options = RenderOptions { highlights: diff_highlights(a, b, &diff), ..options.clone() };
+    (to_dot_with_options(g, None, &options), diff)
+}
+
+/// Same as [`to_dot_with_diff`], rendered through [`render_image`]
+pub fn render_diff<G: GraphLike>(
+    g: &G,
+    a: &WebSet,
+    b: &WebSet,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> (RenderedImage, WebDiff) {
+    let diff = diff_webs(a, b);
+    let options = RenderOptions { highlights: diff_highlights(a, b, &diff), ..options.clone() };
+    (render_image(g, None, &options, policy), diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::web;
+    use crate::pauliweb::Pauli;
+    use quizx::graph::VType;
+    use quizx::hash_graph::Graph;
+
+    #[test]
+    fn test_diff_webs() {
+        let x = web(&[((0, 1), Pauli::X)]);
+        let z = web(&[((1, 2), Pauli::Z)]);
+        let xz = web(&[((0, 1), Pauli::X), ((1, 2), Pauli::Z)]);
+        let y = web(&[((2, 3), Pauli::Y)]);
+
+        // Another basis of the same space
+        let before = WebSet::new(vec![x.clone(), z.clone()]);
+        let diff = diff_webs(&before, &WebSet::new(vec![xz, z]));
+        assert_eq!(diff, WebDiff { removed: vec![], added: vec![], changed: vec![0] });
+        assert!(diff.same_span());
+
+        // z dropped, y new
+        let after = WebSet::new(vec![x, y]);
+        let diff = diff_webs(&before, &after);
+        assert_eq!(diff, WebDiff { removed: vec![1], added: vec![1], changed: vec![] });
+        assert!(!diff.same_span());
+
+        let highlights = diff_highlights(&before, &after, &diff);
+        assert_eq!(highlights.edges.len(), 2);
+        assert_eq!(highlights.edges[&(1, 2)], Style { note: "a1".to_string(), ..Style::color(REMOVED_COLOR) });
+        assert_eq!(highlights.edges[&(2, 3)], Style { note: "b1".to_string(), ..Style::color(ADDED_COLOR) });
+
+        let mut g = Graph::new();
+        let vertices: Vec<_> = (0..4).map(|_| g.add_vertex(VType::Z)).collect();
+        for pair in vertices.windows(2) {
+            g.add_edge(pair[0], pair[1]);
+        }
+        let (dot, _) = to_dot_with_diff(&g, &before, &after, &RenderOptions::default());
+        assert!(dot.contains(&format!("color=\"{}\"", REMOVED_COLOR)));
+        assert!(dot.contains(&format!("color=\"{}\"", ADDED_COLOR)));
+        let (dot, _) = to_dot_with_diff(&g, &before, &before, &RenderOptions::default());
+        assert!(!dot.contains(REMOVED_COLOR) && !dot.contains(ADDED_COLOR));
+    }
+}
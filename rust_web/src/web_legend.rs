@@ -0,0 +1,245 @@
+use crate::graph_visualizer::{run_graphviz_with_retry, to_dot_with_options, RenderOptions, RetryPolicy};
+use crate::palette::Palette;
+use crate::pauliweb::{Pauli, PauliWeb};
+use quizx::graph::GraphLike;
+use std::fs;
+
+const ROW_HEIGHT: f64 = 18.0;
+
+/// One line of the legend
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    pub index: usize,
+    pub color: &'static str,
+    /// Pauli string on the boundary, inputs then outputs, `I` where the web is absent
+    pub support: String,
+    /// Number of edges the web covers
+    pub weight: usize,
+}
+
+/// Legend entries for a list of webs on `graph`, coloured from `palette`'s series
+pub fn legend_entries<G: GraphLike>(graph: &G, webs: &[PauliWeb], palette: Palette) -> Vec<LegendEntry> {
+    webs.iter()
+        .enumerate()
+        .map(|(index, web)| LegendEntry {
+            index,
+            color: palette.series_color(index),
+            support: web.project_to_boundary(graph)
+                .into_iter()
+                .map(|p| match p {
+                    Some(Pauli::X) => 'X',
+                    Some(Pauli::Y) => 'Y',
+                    Some(Pauli::Z) => 'Z',
+                    None => 'I',
+                })
+                .collect(),
+            weight: web.edge_operators.len(),
+        })
+        .collect()
+}
+
+/// Legend as a CSV table
+pub fn legend_csv(entries: &[LegendEntry]) -> String {
+    let mut csv = String::from("web,color,support,weight\n");
+    for e in entries {
+        csv.push_str(&format!("{},{},{},{}\n", e.index, e.color, e.support, e.weight));
+    }
+    csv
+}
+
+/// Legend rows as an SVG group, without the enclosing <svg> element
+fn legend_group(entries: &[LegendEntry], x: f64, y: f64) -> String {
+    let mut group = format!("<g id=\"legend\" transform=\"translate({},{})\" font-family=\"Arial\" font-size=\"12\">\n", x, y);
+    for (i, e) in entries.iter().enumerate() {
+        let row_y = (i as f64 + 0.5) * ROW_HEIGHT;
+        group.push_str(&format!(
+            "<line x1=\"4\" y1=\"{0}\" x2=\"28\" y2=\"{0}\" stroke=\"{1}\" stroke-width=\"3\"/>\n",
+            row_y, e.color
+        ));
+        group.push_str(&format!(
+            "<text x=\"34\" y=\"{}\" dominant-baseline=\"middle\">web {}  support {}  weight {}</text>\n",
+            row_y, e.index, e.support, e.weight
+        ));
+    }
+    group.push_str("</g>\n");
+    group
+}
+
+fn legend_width(entries: &[LegendEntry]) -> f64 {
+    let chars = entries.iter()
+        .map(|e| format!("web {}  support {}  weight {}", e.index, e.support, e.weight).len())
+        .max()
+        .unwrap_or(0);
+    40.0 + chars as f64 * 7.0
+}
+
+/// Standalone SVG image of the legend
+pub fn legend_svg(entries: &[LegendEntry]) -> String {
+    let width = legend_width(entries);
+    let height = entries.len() as f64 * ROW_HEIGHT;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n{2}</svg>\n",
+        width, height, legend_group(entries, 0.0, 0.0)
+    )
+}
+
+/// Appends the legend below the content of an existing SVG image
+///
+/// The root element's viewBox and height are grown to make room. Fails if the SVG
+/// has no viewBox to extend.
+pub fn embed_legend_in_svg(svg: &str, entries: &[LegendEntry]) -> Result<String, String> {
+    let (start, end) = attribute_span(svg, "viewBox").ok_or("SVG has no viewBox")?;
+    let view_box: Vec<f64> = svg[start..end]
+        .split_whitespace()
+        .map(|n| n.parse::<f64>().map_err(|e| format!("Invalid viewBox '{}': {}", &svg[start..end], e)))
+        .collect::<Result<_, _>>()?;
+    let [min_x, min_y, width, height] = view_box[..] else {
+        return Err(format!("Invalid viewBox '{}'", &svg[start..end]));
+    };
+    let extra = entries.len() as f64 * ROW_HEIGHT;
+    let new_width = width.max(legend_width(entries));
+
+    let mut result = String::with_capacity(svg.len() + 256);
+    result.push_str(&svg[..start]);
+    result.push_str(&format!("{} {} {} {}", min_x, min_y, new_width, height + extra));
+    result.push_str(&svg[end..]);
+
+    // Graphviz writes the size in points ("123pt"), keep whatever unit is there
+    for (attr, grow) in [("height", extra), ("width", new_width - width)] {
+        if let Some((s, e)) = attribute_span(&result, attr) {
+            let value = &result[s..e];
+            let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            if let Ok(n) = digits.parse::<f64>() {
+                let unit = &value[digits.len()..];
+                result.replace_range(s..e, &format!("{}{}", n + grow, unit));
+            }
+        }
+    }
+
+    let close = result.rfind("</svg>").ok_or("SVG has no closing tag")?;
+    result.insert_str(close, &legend_group(entries, min_x, min_y + height));
+    Ok(result)
+}
+
+/// Byte range of the value of the first `name="..."` attribute in the root <svg> tag
+fn attribute_span(svg: &str, name: &str) -> Option<(usize, usize)> {
+    let tag_start = svg.find("<svg")?;
+    let tag_end = tag_start + svg[tag_start..].find('>')?;
+    let needle = format!(" {}=\"", name);
+    let start = tag_start + svg[tag_start..tag_end].find(&needle)? + needle.len();
+    let end = start + svg[start..].find('"')?;
+    Some((start, end))
+}
+
+/// DOT of `graph` with every web drawn on top in its own colour of the options' palette
+pub fn to_dot_composite<G: GraphLike>(graph: &G, webs: &[PauliWeb], options: &RenderOptions) -> String {
+    let mut dot = to_dot_with_options(graph, None, options);
+    let close = dot.rfind('}').unwrap();
+    let mut overlay = String::new();
+    for (i, web) in webs.iter().enumerate() {
+        let color = options.palette.series_color(i);
+        let mut edges: Vec<_> = web.edge_operators.iter().collect();
+        edges.sort_by_key(|&(&edge, _)| edge);
+        for ((a, b), pauli) in edges {
+            overlay.push_str(&format!(
+                "  {} -- {} [color=\"{}\",penwidth=2.5,style=bold,label=\"{:?}\",fontcolor=\"{}\"]\n",
+                a, b, color, pauli, color
+            ));
+        }
+    }
+    dot.insert_str(close, &overlay);
+    dot
+}
+
+/// Draws all webs on one SVG figure with their legend
+///
+/// With `embed_legend` the legend is drawn below the diagram, otherwise it is written
+/// next to the output as `<output>.legend.svg` and `<output>.legend.csv`. Neato is
+/// retried and timed out according to `policy`.
+pub fn draw_webs_with_legend<G: GraphLike>(
+    graph: &G,
+    webs: &[PauliWeb],
+    output_path: &str,
+    embed_legend: bool,
+    options: &RenderOptions,
+    policy: &RetryPolicy,
+) -> Result<(), String> {
+    let dot = to_dot_composite(graph, webs, options);
+    let svg = run_graphviz_with_retry("neato", &["-n2", "-Tsvg"], &dot, policy)
+        .map_err(|e| format!("Graphviz failed: {}", e))?;
+    let svg = String::from_utf8_lossy(&svg).into_owned();
+
+    let entries = legend_entries(graph, webs, options.palette);
+    let write = |path: String, content: String| {
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))
+    };
+    if embed_legend {
+        write(output_path.to_string(), embed_legend_in_svg(&svg, &entries)?)
+    } else {
+        write(output_path.to_string(), svg)?;
+        write(format!("{}.legend.svg", output_path), legend_svg(&entries))?;
+        write(format!("{}.legend.csv", output_path), legend_csv(&entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quizx::graph::VType;
+    use quizx::hash_graph::Graph;
+
+    fn graph_and_webs() -> (Graph, Vec<PauliWeb>) {
+        let mut g = Graph::new();
+        let b0 = g.add_vertex(VType::B);
+        let z = g.add_vertex(VType::Z);
+        let b1 = g.add_vertex(VType::B);
+        g.add_edge(b0, z);
+        g.add_edge(z, b1);
+        g.set_inputs(vec![b0]);
+        g.set_outputs(vec![b1]);
+
+        let mut through = PauliWeb::new();
+        through.set_edge(b0, z, Pauli::X);
+        through.set_edge(z, b1, Pauli::X);
+        let mut half = PauliWeb::new();
+        half.set_edge(z, b1, Pauli::Z);
+        (g, vec![through, half])
+    }
+
+    #[test]
+    fn test_entries_and_csv() {
+        let (g, webs) = graph_and_webs();
+        let entries = legend_entries(&g, &webs, Palette::Classic);
+        assert_eq!(entries[0].support, "XX");
+        assert_eq!(entries[0].weight, 2);
+        assert_eq!(entries[1].support, "IZ");
+        assert_ne!(entries[0].color, entries[1].color);
+        assert_eq!(legend_csv(&entries).lines().nth(2).unwrap(), format!("1,{},IZ,1", Palette::Classic.series_color(1)));
+    }
+
+    #[test]
+    fn test_embed_legend() {
+        let (g, webs) = graph_and_webs();
+        let entries = legend_entries(&g, &webs, Palette::Classic);
+        let svg = "<?xml version=\"1.0\"?>\n<svg width=\"300pt\" height=\"100pt\" viewBox=\"0.00 0.00 300.00 100.00\">\n<g id=\"graph0\"></g>\n</svg>\n";
+
+        let embedded = embed_legend_in_svg(svg, &entries).unwrap();
+        assert!(embedded.contains("viewBox=\"0 0 300 136\""));
+        assert!(embedded.contains("height=\"136pt\""));
+        assert!(embedded.contains("web 1  support IZ  weight 1"));
+        assert!(embedded.find("id=\"legend\"").unwrap() < embedded.find("</svg>").unwrap());
+
+        assert!(embed_legend_in_svg("<svg/>", &entries).is_err());
+    }
+
+    #[test]
+    fn test_composite_dot_colours_each_web() {
+        let (g, webs) = graph_and_webs();
+        let options = RenderOptions { palette: Palette::OkabeIto, ..RenderOptions::default() };
+        let dot = to_dot_composite(&g, &webs, &options);
+        let series = Palette::OkabeIto.series();
+        assert!(dot.contains(&format!("color=\"{}\",penwidth=2.5,style=bold,label=\"X\"", series[0])));
+        assert!(dot.contains(&format!("color=\"{}\",penwidth=2.5,style=bold,label=\"Z\"", series[1])));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::pauliweb::PauliWeb;
+use crate::web_space::WebSpace;
+use std::collections::BTreeMap;
+
+/// A list of Pauli webs on the same diagram, compared as elements of the
+/// [`WebSpace`] over the edges they use
+///
+/// Multiplying webs edge by edge is adding their vectors there. Phases are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct WebSet {
+    pub webs: Vec<PauliWeb>,
+}
+
+/// The span of `webs` within `space`, which has every edge they use
+fn span<'a>(space: &WebSpace, webs: impl IntoIterator<Item = &'a PauliWeb>) -> WebSpace {
+    let mut space = space.clone();
+    for web in webs {
+        space.add(web).expect("the space has every edge of the webs");
+    }
+    space
+}
+
+impl WebSet {
+    pub fn new(webs: Vec<PauliWeb>) -> Self {
+        WebSet { webs }
+    }
+
+    /// Groups the webs that differ by an element of the span of `stabilizers`
+    ///
+    /// Returns the indices of the webs in each class. Classes are ordered by their
+    /// first member, members by index.
+    pub fn equivalence_classes(&self, stabilizers: &[PauliWeb]) -> Vec<Vec<usize>> {
+        let coords = WebSpace::over(self.webs.iter().chain(stabilizers));
+        // Reducing against the subgroup gives the same web for every member of a coset
+        let subgroup = span(&coords, stabilizers);
+
+        let mut classes: Vec<Vec<usize>> = Vec::new();
+        let mut class_of = BTreeMap::new();
+        for (i, web) in self.webs.iter().enumerate() {
+            let reduced = subgroup.reduced(web).expect("the space has every edge of the webs");
+            let class = *class_of.entry(reduced).or_insert_with(|| {
+                classes.push(Vec::new());
+                classes.len() - 1
+            });
+            classes[class].push(i);
+        }
+        classes
+    }
+
+    /// Whether both sets generate the same group once `stabilizers` are quotiented out
+    ///
+    /// Use this to compare webs from another tool, which may pick a different basis or
+    /// different representatives.
+    pub fn same_span_modulo(&self, other: &WebSet, stabilizers: &[PauliWeb]) -> bool {
+        let coords = WebSpace::over(self.webs.iter().chain(&other.webs).chain(stabilizers));
+        let ours = span(&coords, self.webs.iter().chain(stabilizers));
+        let theirs = span(&coords, other.webs.iter().chain(stabilizers));
+        ours.dim() == theirs.dim() && theirs.basis().iter().all(|web| ours.contains(web))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::web;
+    use crate::pauliweb::Pauli;
+
+    #[test]
+    fn test_equivalence_classes() {
+        let stabilizer = web(&[((1, 2), Pauli::Z), ((2, 3), Pauli::Z)]);
+        let a = web(&[((0, 1), Pauli::X)]);
+        // a times the stabilizer
+        let a2 = web(&[((0, 1), Pauli::X), ((1, 2), Pauli::Z), ((2, 3), Pauli::Z)]);
+        let b = web(&[((0, 1), Pauli::X), ((1, 2), Pauli::Z)]);
+        let c = web(&[((0, 1), Pauli::X), ((1, 2), Pauli::Y), ((2, 3), Pauli::Z)]);
+        // c times the stabilizer, Y·Z = X up to phase
+        let c2 = web(&[((0, 1), Pauli::X), ((1, 2), Pauli::X)]);
+
+        let set = WebSet::new(vec![a, b, a2, c, c2]);
+        assert_eq!(set.equivalence_classes(&[]), vec![vec![0], vec![1], vec![2], vec![3], vec![4]]);
+        assert_eq!(set.equivalence_classes(&[stabilizer]), vec![vec![0, 2], vec![1], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_same_span_modulo() {
+        let stabilizer = web(&[((1, 2), Pauli::Z), ((2, 3), Pauli::Z)]);
+        let a = web(&[((0, 1), Pauli::X)]);
+        let b = web(&[((3, 4), Pauli::Z)]);
+        let ours = WebSet::new(vec![a.clone(), b]);
+
+        // Another basis of the same space, with a stabilizer multiplied in
+        let ab = web(&[((0, 1), Pauli::X), ((3, 4), Pauli::Z)]);
+        let b_stab = web(&[((1, 2), Pauli::Z), ((2, 3), Pauli::Z), ((3, 4), Pauli::Z)]);
+        let theirs = WebSet::new(vec![ab, b_stab]);
+
+        assert!(!ours.same_span_modulo(&theirs, &[]));
+        assert!(ours.same_span_modulo(&theirs, std::slice::from_ref(&stabilizer)));
+        assert!(!ours.same_span_modulo(&WebSet::new(vec![a]), &[stabilizer]));
+    }
+}
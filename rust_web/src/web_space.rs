@@ -0,0 +1,369 @@
+use crate::detection_webs::{get_detection_webs_lenient, WebOptions};
+use crate::linalg::Mat2;
+use crate::logging::WEBS;
+use crate::pauliweb::{Pauli, PauliWeb};
+use bitvec::prelude::*;
+use quizx::graph::GraphLike;
+use quizx::hash_graph::Graph;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Most basis combinations [`lowest_weight_webs`] tries
+pub const SEARCH_BUDGET: usize = 1 << 16;
+
+/// A subspace of the Pauli webs on a fixed set of edges, as an F2 vector space
+///
+/// Edge `i` owns columns `2i` (X) and `2i + 1` (Z), Y setting both, so multiplying webs is
+/// adding vectors. The basis is kept as the rows of a [`Mat2`] in reduced row echelon form.
+/// Phases are ignored.
+#[derive(Debug, Clone)]
+pub struct WebSpace {
+    edges: Vec<(usize, usize)>,
+    index: HashMap<(usize, usize), usize>,
+    basis: Mat2,
+    pivots: Vec<usize>,
+}
+
+type Vector = BitVec<usize, Lsb0>;
+
+impl WebSpace {
+    /// The zero space over `edges`, given as vertex pairs in either order
+    pub fn new(edges: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut edges: Vec<_> = edges.into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect();
+        edges.sort();
+        edges.dedup();
+        let index = edges.iter().enumerate().map(|(i, &e)| (e, i)).collect();
+        let basis = Mat2::zeros(0, 2 * edges.len());
+        WebSpace { edges, index, basis, pivots: Vec::new() }
+    }
+
+    /// The zero space over the edges `webs` use
+    pub fn over<'a>(webs: impl IntoIterator<Item = &'a PauliWeb>) -> Self {
+        WebSpace::new(webs.into_iter().flat_map(|web| web.edge_operators.keys().copied()))
+    }
+
+    /// The span of `webs` over the edges of `graph`
+    ///
+    /// Fails if a web uses an edge the graph doesn't have.
+    pub fn spanned_by<G: GraphLike>(graph: &G, webs: &[PauliWeb]) -> Result<Self, String> {
+        let mut space = WebSpace::new(graph.edges().map(|(a, b, _)| (a, b)));
+        for web in webs {
+            space.add(web)?;
+        }
+        Ok(space)
+    }
+
+    /// Edges of the coordinate system, sorted
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    pub fn dim(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// The reduced basis, one web per row
+    pub fn basis(&self) -> Vec<PauliWeb> {
+        (0..self.dim()).map(|r| self.to_web(&self.row(r))).collect()
+    }
+
+    /// Whether `web` is a product of basis webs. Webs on edges outside the coordinate
+    /// system are never contained.
+    pub fn contains(&self, web: &PauliWeb) -> bool {
+        self.vector(web).is_some_and(|v| self.reduce(v).not_any())
+    }
+
+    /// `web` times the basis webs clearing the pivot columns of the basis, which is the
+    /// same web for all webs differing by an element of the space. `None` for a web on
+    /// edges outside the coordinate system.
+    pub fn reduced(&self, web: &PauliWeb) -> Option<PauliWeb> {
+        self.vector(web).map(|v| self.to_web(&self.reduce(v)))
+    }
+
+    /// Adds `web` to the span, returning whether the dimension grew
+    pub fn add(&mut self, web: &PauliWeb) -> Result<bool, String> {
+        let v = self.vector(web).ok_or_else(|| {
+            let (a, b) = web.sorted_edges().into_iter()
+                .map(|(e, _)| e)
+                .find(|e| !self.index.contains_key(e))
+                .unwrap();
+            format!("Edge ({}, {}) is not part of the web space", a, b)
+        })?;
+        let v = self.reduce(v);
+        if v.not_any() {
+            return Ok(false);
+        }
+        let mut rows: Vec<Vector> = (0..self.dim()).map(|r| self.row(r)).collect();
+        rows.push(v);
+        self.set_basis(rows);
+        Ok(true)
+    }
+
+    /// The webs contained in both spaces
+    pub fn intersect(&self, other: &WebSpace) -> Result<WebSpace, String> {
+        self.check_same_edges(other)?;
+        let (m, n) = (self.dim(), other.dim());
+        let mut stacked = self.basis.vstack(&other.basis);
+        let mut ops = Mat2::id(m + n);
+        let rank = stacked.gauss(false, Some(&mut ops), None, 0, &mut Vec::new());
+
+        // Each zero row past the rank is a combination a·self = b·other; a·self lies in both
+        let rows = (rank..m + n).map(|r| {
+            let mut v = bitvec![0; self.basis.cols()];
+            for i in (0..m).filter(|&i| ops.get(r, i)) {
+                v ^= self.row(i);
+            }
+            v
+        }).collect();
+        let mut space = self.clone();
+        space.set_basis(rows);
+        Ok(space)
+    }
+
+    /// Representatives of a basis of this space modulo `sub`
+    ///
+    /// Returns basis webs of `self` that stay independent once `sub` is quotiented out, so
+    /// their number is `dim(self) - dim(self ∩ sub)`.
+    pub fn quotient(&self, sub: &WebSpace) -> Result<Vec<PauliWeb>, String> {
+        self.check_same_edges(sub)?;
+        let mut span = sub.clone();
+        Ok(self.basis().into_iter()
+            .filter(|web| span.add(web).expect("same edges"))
+            .collect())
+    }
+
+    /// The `k` lowest-weight nonzero webs among the products of few basis webs, lightest
+    /// first
+    ///
+    /// Weight is the number of edges a web touches. Products of one basis web, then two,
+    /// and so on are tried while the number of them stays within `budget`, which covers
+    /// the whole space when it has at most `budget` nonzero webs; otherwise the result is
+    /// the lightest found, not necessarily the lightest there are. Ties go to the product
+    /// of earlier basis webs.
+    pub fn lowest_weight(&self, k: usize, budget: usize) -> Vec<PauliWeb> {
+        let rows: Vec<Vector> = (0..self.dim()).map(|r| self.row(r)).collect();
+        // Products of up to `depth` basis webs fit into the budget
+        let mut depth = 0;
+        let mut tried = 0usize;
+        let mut choose = 1usize;
+        while depth < rows.len() {
+            choose = choose.saturating_mul(rows.len() - depth) / (depth + 1);
+            if depth > 0 && tried.saturating_add(choose) > budget {
+                break;
+            }
+            tried = tried.saturating_add(choose);
+            depth += 1;
+        }
+        log::debug!(
+            target: WEBS,
+            "Searching products of up to {} of {} basis webs, {} in all{}",
+            depth, rows.len(), tried, if depth == rows.len() { "" } else { ", not the whole space" }
+        );
+
+        let zero = bitvec![0; self.basis.cols()];
+        let mut search = Search { rows: &rows, edges: self.edges.len(), k, combination: Vec::new(), best: BinaryHeap::new() };
+        search.visit(0, depth, &zero);
+
+        search.best.into_sorted_vec().into_iter().map(|(_, combination)| {
+            let mut v = zero.clone();
+            for i in combination {
+                v ^= &rows[i];
+            }
+            self.to_web(&v)
+        }).collect()
+    }
+
+    fn check_same_edges(&self, other: &WebSpace) -> Result<(), String> {
+        if self.edges == other.edges {
+            Ok(())
+        } else {
+            Err("Web spaces are over different edges".to_string())
+        }
+    }
+
+    fn vector(&self, web: &PauliWeb) -> Option<Vector> {
+        let mut v = bitvec![0; 2 * self.edges.len()];
+        for (edge, &pauli) in &web.edge_operators {
+            let i = *self.index.get(edge)?;
+            v.set(2 * i, matches!(pauli, Pauli::X | Pauli::Y));
+            v.set(2 * i + 1, matches!(pauli, Pauli::Z | Pauli::Y));
+        }
+        Some(v)
+    }
+
+    fn to_web(&self, v: &Vector) -> PauliWeb {
+        let mut web = PauliWeb::new();
+        for (i, &(a, b)) in self.edges.iter().enumerate() {
+            match (v[2 * i], v[2 * i + 1]) {
+                (true, false) => web.set_edge(a, b, Pauli::X),
+                (false, true) => web.set_edge(a, b, Pauli::Z),
+                (true, true) => web.set_edge(a, b, Pauli::Y),
+                (false, false) => {}
+            }
+        }
+        web
+    }
+
+    fn row(&self, r: usize) -> Vector {
+        (0..self.basis.cols()).map(|c| self.basis.get(r, c)).collect()
+    }
+
+    /// Clears the pivot columns of the basis from `v`
+    fn reduce(&self, mut v: Vector) -> Vector {
+        for (r, &col) in self.pivots.iter().enumerate() {
+            if v[col] {
+                v ^= self.row(r);
+            }
+        }
+        v
+    }
+
+    /// Replaces the basis by the reduced, nonzero rows spanning `rows`
+    fn set_basis(&mut self, rows: Vec<Vector>) {
+        let cols = 2 * self.edges.len();
+        let mut mat = Mat2::zeros(rows.len(), cols);
+        for (r, row) in rows.iter().enumerate() {
+            for c in row.iter_ones() {
+                mat.set(r, c, true);
+            }
+        }
+        let mut pivots = Vec::new();
+        let rank = mat.gauss(false, None, None, 0, &mut pivots);
+        let mut basis = Mat2::zeros(rank, cols);
+        for r in 0..rank {
+            for c in 0..cols {
+                basis.set(r, c, mat.get(r, c));
+            }
+        }
+        self.basis = basis;
+        self.pivots = pivots;
+    }
+}
+
+/// State of the search in [`WebSpace::lowest_weight`]
+struct Search<'a> {
+    rows: &'a [Vector],
+    edges: usize,
+    k: usize,
+    /// Indices of the rows in the current product
+    combination: Vec<usize>,
+    /// Max-heap of the `k` best products so far, by weight and then row indices
+    best: BinaryHeap<(usize, Vec<usize>)>,
+}
+
+impl Search<'_> {
+    /// Visits each product extending the current one by at most `depth` rows from `start`
+    /// on, `acc` being the current product
+    fn visit(&mut self, start: usize, depth: usize, acc: &Vector) {
+        if depth == 0 || self.k == 0 {
+            return;
+        }
+        for i in start..self.rows.len() {
+            let v = acc.clone() ^ &self.rows[i];
+            self.combination.push(i);
+            let weight = (0..self.edges).filter(|&e| v[2 * e] || v[2 * e + 1]).count();
+            let candidate = (weight, self.combination.clone());
+            if self.best.len() < self.k {
+                self.best.push(candidate);
+            } else if self.best.peek().is_some_and(|worst| candidate < *worst) {
+                self.best.pop();
+                self.best.push(candidate);
+            }
+            self.visit(i + 1, depth - 1, &v);
+            self.combination.pop();
+        }
+    }
+}
+
+/// The `k` lowest-weight detection webs of `g`, for when a handful of small detectors is
+/// all that's needed, e.g. for a figure
+///
+/// Solves a copy of `g` like [`get_detection_webs_lenient`] with the default
+/// [`WebOptions`], so its size limits apply, and runs [`WebSpace::lowest_weight`] over the
+/// span of the webs with [`SEARCH_BUDGET`]; that skips building and sorting every product
+/// of them. The webs are over the rg form of `g`, which is `g` itself if it is in rg form
+/// already, like after [`crate::detection_webs::get_detection_webs`].
+pub fn lowest_weight_webs(g: &Graph, k: usize) -> Result<Vec<PauliWeb>, String> {
+    let mut g = g.clone();
+    let webs = get_detection_webs_lenient(&mut g, &WebOptions::default())?;
+    Ok(WebSpace::spanned_by(&g, &webs)?.lowest_weight(k, SEARCH_BUDGET))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pauliweb::testing::web;
+    use quizx::graph::VType;
+    use quizx::hash_graph::Graph;
+
+    #[test]
+    fn test_span_operations() {
+        let mut g = Graph::new();
+        let vs: Vec<_> = (0..4).map(|_| g.add_vertex(VType::Z)).collect();
+        for w in vs.windows(2) {
+            g.add_edge(w[0], w[1]);
+        }
+        let (e01, e12, e23) = ((vs[0], vs[1]), (vs[1], vs[2]), (vs[2], vs[3]));
+
+        let a = web(&[(e01, Pauli::X), (e12, Pauli::X)]);
+        let b = web(&[(e12, Pauli::Z), (e23, Pauli::Z)]);
+        let space = WebSpace::spanned_by(&g, &[a.clone(), b.clone()]).unwrap();
+        assert_eq!(space.dim(), 2);
+        assert_eq!(space.edges().len(), 3);
+        // X·Z = Y on the shared edge
+        assert!(space.contains(&web(&[(e01, Pauli::X), (e12, Pauli::Y), (e23, Pauli::Z)])));
+        assert!(space.contains(&PauliWeb::new()));
+        assert!(!space.contains(&web(&[(e01, Pauli::X)])));
+        assert!(!space.contains(&web(&[((vs[0], vs[3]), Pauli::X)])));
+        assert!(space.basis().iter().all(|w| space.contains(w)));
+
+        let mut grown = space.clone();
+        assert!(!grown.add(&web(&[(e01, Pauli::X), (e12, Pauli::Y), (e23, Pauli::Z)])).unwrap());
+        let c = web(&[(e23, Pauli::X)]);
+        assert!(grown.add(&c).unwrap());
+        assert_eq!(grown.dim(), 3);
+        assert!(grown.add(&web(&[((vs[0], vs[3]), Pauli::X)])).unwrap_err().contains("not part"));
+
+        let other = WebSpace::spanned_by(&g, &[b.clone(), c.clone(), web(&[(e01, Pauli::Z)])]).unwrap();
+        let both = grown.intersect(&other).unwrap();
+        assert_eq!(both.dim(), 2);
+        assert!(both.contains(&b) && both.contains(&c) && !both.contains(&a));
+
+        let quotient = grown.quotient(&space).unwrap();
+        assert_eq!(quotient.len(), 1);
+        let mut together = space.clone();
+        assert!(together.add(&quotient[0]).unwrap());
+        assert!(together.contains(&c));
+
+        // Webs differing by an element of the space reduce to the same web
+        let ca = web(&[(e01, Pauli::X), (e12, Pauli::X), (e23, Pauli::X)]);
+        assert_eq!(space.reduced(&ca), space.reduced(&c));
+        assert_eq!(space.reduced(&a), Some(PauliWeb::new()));
+        assert_eq!(space.reduced(&web(&[((vs[0], vs[3]), Pauli::X)])), None);
+        assert_eq!(WebSpace::over([&a, &b]).edges(), space.edges());
+
+        assert!(space.intersect(&WebSpace::new([(0, 1)])).is_err());
+    }
+
+    #[test]
+    fn test_lowest_weight() {
+        let mut g = crate::create_graph::create_steane_rounds(2);
+        let webs = crate::detection_webs::get_detection_webs(&mut g);
+        let space = WebSpace::spanned_by(&g, &webs).unwrap();
+        let weight = |web: &PauliWeb| web.edge_operators.len();
+
+        // The whole space fits into the budget, so the search is exhaustive
+        assert!(space.dim() < 16);
+        let lowest = lowest_weight_webs(&g, 3).unwrap();
+        assert_eq!(lowest.len(), 3);
+        assert!(lowest.windows(2).all(|w| weight(&w[0]) <= weight(&w[1])));
+        assert!(lowest.iter().all(|web| space.contains(web) && weight(web) > 0));
+        let all = space.lowest_weight(usize::MAX, SEARCH_BUDGET);
+        assert_eq!(all.len(), (1 << space.dim()) - 1);
+        assert_eq!(all[..3].iter().map(weight).collect::<Vec<_>>(), lowest.iter().map(weight).collect::<Vec<_>>());
+        assert!(webs.iter().all(|web| weight(web) >= weight(&lowest[0])));
+
+        // A budget of one only tries the basis webs themselves
+        assert_eq!(space.lowest_weight(usize::MAX, 1).len(), space.dim());
+        assert!(space.lowest_weight(0, SEARCH_BUDGET).is_empty());
+    }
+}
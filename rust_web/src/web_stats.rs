@@ -0,0 +1,120 @@
+//! Distributions of detection web weights and boundary supports
+//!
+//! How heavy the webs of a diagram are, and how many boundaries they reach, is what
+//! we compare across code families. `use_detection_webs` writes the histograms of every
+//! run to `web_distributions.json` in its output directory, and draws them as PNGs when
+//! built with the `plots` feature.
+
+use crate::pauliweb::PauliWeb;
+use quizx::graph::GraphLike;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Number of webs by value, e.g. by weight
+pub type Histogram = BTreeMap<usize, usize>;
+
+/// The histograms of one run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WebDistributions {
+    /// Webs by weight, the number of edges they touch
+    pub weights: Histogram,
+    /// Webs by boundary support size, the number of boundaries they reach
+    pub boundary_support: Histogram,
+}
+
+impl WebDistributions {
+    pub fn new<G: GraphLike>(graph: &G, webs: &[PauliWeb]) -> Self {
+        let mut distributions = WebDistributions::default();
+        for web in webs {
+            *distributions.weights.entry(web.edge_operators.len()).or_default() += 1;
+            let support = web.project_to_boundary(graph).iter().filter(|pauli| pauli.is_some()).count();
+            *distributions.boundary_support.entry(support).or_default() += 1;
+        }
+        distributions
+    }
+}
+
+/// Writes the distributions of `webs` to `web_distributions.json` in `dir`, and with the
+/// `plots` feature draws them to `web_weights.png` and `boundary_support.png`
+///
+/// Returns the paths written.
+pub fn write_distributions<G: GraphLike>(graph: &G, webs: &[PauliWeb], dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let distributions = WebDistributions::new(graph, webs);
+    let path = dir.join("web_distributions.json");
+    let json = serde_json::to_string_pretty(&distributions).expect("histograms always serialise");
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[allow(unused_mut)]
+    let mut paths = vec![path];
+    #[cfg(feature = "plots")]
+    for (histogram, x_desc, name) in [
+        (&distributions.weights, "web weight", "web_weights.png"),
+        (&distributions.boundary_support, "boundary support size", "boundary_support.png"),
+    ] {
+        let path = dir.join(name);
+        plot_histogram(histogram, x_desc, &path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Draws `histogram` as a bar chart into a PNG at `path`, with `x_desc` under the axis
+/// of values
+#[cfg(feature = "plots")]
+pub fn plot_histogram(histogram: &Histogram, x_desc: &str, path: &Path) -> Result<(), String> {
+    use plotters::prelude::*;
+
+    let failed = |e: &dyn std::fmt::Display| format!("Failed to plot {}: {}", path.display(), e);
+    let max_value = histogram.keys().last().copied().unwrap_or(0);
+    let max_count = histogram.values().max().copied().unwrap_or(0);
+
+    let root = BitMapBackend::new(path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| failed(&e))?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(format!("webs by {}", x_desc), ("sans-serif", 24))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(-0.5..max_value as f64 + 0.5, 0.0..max_count as f64 * 1.1 + 1.0)
+        .map_err(|e| failed(&e))?;
+    chart.configure_mesh()
+        .disable_x_mesh()
+        .x_label_formatter(&|&x| if x.fract() == 0.0 { format!("{}", x) } else { String::new() })
+        .y_label_formatter(&|&y| format!("{}", y.round()))
+        .x_desc(x_desc)
+        .y_desc("webs")
+        .draw()
+        .map_err(|e| failed(&e))?;
+    let color = Palette99::pick(0).to_rgba();
+    chart.draw_series(histogram.iter().map(|(&value, &count)| {
+        let x = value as f64;
+        Rectangle::new([(x - 0.4, 0.0), (x + 0.4, count as f64)], color.filled())
+    }))
+    .map_err(|e| failed(&e))?;
+    root.present().map_err(|e| failed(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_graph::create_steane_rounds;
+    use crate::detection_webs::get_detection_webs;
+
+    #[test]
+    fn test_web_distributions() {
+        let mut g = create_steane_rounds(2);
+        let webs = get_detection_webs(&mut g);
+        let distributions = WebDistributions::new(&g, &webs);
+        assert_eq!(distributions.weights.values().sum::<usize>(), webs.len());
+        assert_eq!(distributions.boundary_support.values().sum::<usize>(), webs.len());
+        // Detection webs stay inside the diagram
+        assert_eq!(distributions.boundary_support, Histogram::from([(0, 6)]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_distributions(&g, &webs, dir.path()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        assert_eq!(json["boundary_support"]["0"], distributions.boundary_support[&0]);
+        assert_eq!(paths.len(), if cfg!(feature = "plots") { 3 } else { 1 });
+        assert!(paths.iter().all(|path| path.exists()));
+    }
+}
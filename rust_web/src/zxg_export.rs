@@ -0,0 +1,110 @@
+use crate::graph_loader::TimeCoords;
+use quizx::graph::VType;
+use quizx::hash_graph::{Graph, GraphLike};
+use std::path::Path;
+
+/// quizx vertex types .zxg can store, see [`zxcore::zxg::node_type`]
+const STORABLE: [VType; 7] = [VType::B, VType::Z, VType::X, VType::H, VType::ZBox, VType::WInput, VType::WOutput];
+
+/// Serialises a graph to the .zxg JSON read by [`crate::graph_loader`]
+///
+/// Written by [`zxcore::zxg::to_zxg`], the writer shared with rustzx: vertices keep
+/// their `[row, qubit]` coordinates, Hadamard edges get an H node halfway along, and
+/// inputs and outputs, if set, are listed in order. The file is stamped with
+/// [`FORMAT_VERSION`](crate::graph_loader::FORMAT_VERSION), so the loader can migrate it
+/// once the schema changes.
+///
+/// Fails on a vertex type .zxg has no name for.
+pub fn to_zxg(g: &Graph) -> Result<String, String> {
+    to_zxg_3d(g, &TimeCoords::new())
+}
+
+/// Same as [`to_zxg`] for a 3D diagram, writing `[row, qubit, t]` coordinates for the
+/// vertices in `times`
+///
+/// An H node gets the mean `t` of its ends if both have one.
+pub fn to_zxg_3d(g: &Graph, times: &TimeCoords) -> Result<String, String> {
+    if let Some(v) = g.vertices().find(|&v| !STORABLE.contains(&g.vertex_type(v))) {
+        return Err(format!("Vertex {} has type {:?}, which .zxg has no name for", v, g.vertex_type(v)));
+    }
+    Ok(zxcore::zxg::to_zxg_3d(g, times))
+}
+
+/// Writes [`to_zxg`] of `g` to `path`
+pub fn write_zxg(g: &Graph, path: &Path) -> Result<(), String> {
+    write_zxg_3d(g, &TimeCoords::new(), path)
+}
+
+/// Writes [`to_zxg_3d`] of `g` to `path`
+pub fn write_zxg_3d(g: &Graph, times: &TimeCoords, path: &Path) -> Result<(), String> {
+    std::fs::write(path, to_zxg_3d(g, times)?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_loader::{load_graph_from_str, FORMAT_VERSION};
+    use quizx::graph::EType;
+    use quizx::phase::Phase;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_zxg_roundtrip() {
+        let mut g = Graph::new();
+        let b0 = g.add_vertex_with_data(quizx::graph::VData { ty: VType::B, phase: Phase::from_f64(0.0), qubit: 0.0, row: 0.0 });
+        let z = g.add_vertex_with_data(quizx::graph::VData { ty: VType::Z, phase: Phase::from_f64(0.5), qubit: 0.0, row: 1.0 });
+        let x = g.add_vertex_with_data(quizx::graph::VData { ty: VType::X, phase: Phase::from_f64(0.0), qubit: 0.0, row: 3.0 });
+        let b1 = g.add_vertex_with_data(quizx::graph::VData { ty: VType::B, phase: Phase::from_f64(0.0), qubit: 0.0, row: 4.0 });
+        g.add_edge(b0, z);
+        g.add_edge_with_type(z, x, EType::H);
+        g.add_edge(x, b1);
+
+        let zxg = to_zxg(&g).unwrap();
+        let json: Value = serde_json::from_str(&zxg).unwrap();
+        assert_eq!(json["node_vertices"][format!("v{}", z)]["data"]["value"], "1/2");
+        assert!(json["node_vertices"][format!("v{}", x)]["data"].get("value").is_none());
+        assert_eq!(json["node_vertices"][format!("h{}_{}", z, x)]["annotation"]["coord"], json!([2.0, 0.0]));
+        assert_eq!(json["format_version"], FORMAT_VERSION);
+
+        let loaded = load_graph_from_str(&zxg).unwrap();
+        assert_eq!(loaded.num_vertices(), 5);
+        assert_eq!(loaded.num_edges(), 4);
+        assert_eq!(loaded.inputs().len(), 1);
+        assert_eq!(loaded.outputs().len(), 1);
+        let phases: Vec<f64> = loaded.vertices()
+            .filter(|&v| loaded.vertex_type(v) == VType::Z)
+            .map(|v| loaded.phase(v).to_f64())
+            .collect();
+        assert_eq!(phases, vec![0.5]);
+
+        // Listed boundaries come back in order, even against the row order
+        let mut swapped = g.clone();
+        let (b2, b3) = (swapped.add_vertex(VType::B), swapped.add_vertex(VType::B));
+        swapped.set_qubit(b2, 1.0);
+        swapped.set_qubit(b3, 1.0);
+        swapped.set_row(b3, 4.0);
+        swapped.add_edge(b2, b3);
+        swapped.set_inputs(vec![b2, b0]);
+        swapped.set_outputs(vec![b1, b3]);
+        let zxg = to_zxg(&swapped).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&zxg).unwrap()["inputs"], json!([format!("b{}", b2), format!("b{}", b0)]));
+        let loaded = load_graph_from_str(&zxg).unwrap();
+        let qubits = |vs: &[usize]| vs.iter().map(|&v| loaded.qubit(v)).collect::<Vec<_>>();
+        assert_eq!((qubits(loaded.inputs()), qubits(loaded.outputs())), (vec![1.0, 0.0], vec![0.0, 1.0]));
+        assert!(serde_json::from_str::<Value>(&to_zxg(&g).unwrap()).unwrap().get("inputs").is_none());
+
+        // A 3D diagram keeps its third coordinate through a file
+        let times: TimeCoords = [(b0, 0.0), (z, 2.0), (x, 4.0), (b1, 4.0)].into_iter().collect();
+        let zxg = to_zxg_3d(&g, &times).unwrap();
+        let json: Value = serde_json::from_str(&zxg).unwrap();
+        assert_eq!(json["node_vertices"][format!("h{}_{}", z, x)]["annotation"]["coord"], json!([2.0, 0.0, 3.0]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("3d.zxg");
+        write_zxg_3d(&g, &times, &path).unwrap();
+        let (loaded, loaded_times) = crate::graph_loader::load_graph_3d(path.to_str().unwrap(), &Default::default()).unwrap();
+        let mut ts: Vec<(f64, f64)> = loaded.vertices().map(|v| (loaded.row(v), loaded_times[&v])).collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(ts, vec![(0.0, 0.0), (1.0, 2.0), (2.0, 3.0), (3.0, 4.0), (4.0, 4.0)]);
+    }
+}
@@ -3,14 +3,14 @@
 fn test_from_file() {
     use std::fs;
 
+    use rust_web::fixtures::fixture;
     use rust_web::graph_loader;
     use rust_web::graph_visualizer;
+    use rust_web::zxg_export::write_zxg;
 
-    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
-    let path = std::path::Path::new(&manifest_dir)
-        .join("tests")
-        .join("zxgs")
-        .join("xxx_final.zxg");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("xx_stab.zxg");
+    write_zxg(&fixture("xx_stab_2_rounds").unwrap(), &path).unwrap();
     
     // Load the graph
     let g = graph_loader::load_graph(path.to_str().unwrap()).unwrap();
@@ -1,6 +1,8 @@
+use rust_web::fixtures::fixture;
 use rust_web::graph_loader;
 use rust_web::graph_visualizer;
 use rust_web::pauliweb::{PauliWeb, Pauli};
+use rust_web::zxg_export::write_zxg;
 use quizx::graph::GraphLike;
 use std::path::Path;
 
@@ -11,7 +13,10 @@ fn test_load_and_pauliweb() -> Result<(), String> {
     std::fs::create_dir_all(output_dir).map_err(|e| e.to_string())?;
     
     // Load the graph
-    let graph = graph_loader::load_graph("tests/zxgs/xxx_final.zxg")?;
+    let dir = tempfile::tempdir().map_err(|e| e.to_string())?;
+    let path = dir.path().join("xx_stab.zxg");
+    write_zxg(&fixture("xx_stab_2_rounds")?, &path)?;
+    let graph = graph_loader::load_graph(path.to_str().unwrap())?;
     
     // Find three connected vertices in the graph
     let mut found_vertices = None;
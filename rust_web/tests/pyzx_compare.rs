@@ -0,0 +1,124 @@
+//! Golden comparison of the detection webs against the Python reference implementation
+//!
+//! `python/golden_webs.py` computes the webs of each fixture in [`rust_web::fixtures`],
+//! written out as .zxg, with the original PyZX-based code. Both sides name vertices by their .zxg keys, so the webs can be compared as
+//! spans: the bases may differ, the spaces must not. Needs a Python with the packages in
+//! `python/requirements.txt`, picked with `PYTHON` (default `python3`):
+//!
+//!     cargo test -p rust_web --features pyzx-compare --test pyzx_compare
+#![cfg(feature = "pyzx-compare")]
+
+use quizx::graph::GraphLike;
+use quizx::hash_graph::Graph;
+use rust_web::detection_webs::get_detection_webs;
+use rust_web::fixtures::write_fixtures;
+use rust_web::graph_loader::{load_graph_with_keys, LoadOptions};
+use rust_web::pauliweb::{Pauli, PauliWeb};
+use rust_web::web_space::WebSpace;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+/// A web as (name, name, Pauli) edges, names sorted within each edge
+type NamedWeb = BTreeSet<(String, String, Pauli)>;
+
+fn pauli(letter: &str) -> Pauli {
+    match letter {
+        "X" => Pauli::X,
+        "Y" => Pauli::Y,
+        "Z" => Pauli::Z,
+        other => panic!("Unexpected Pauli {:?} from golden_webs.py", other),
+    }
+}
+
+fn python_webs(manifest: &Path, zxg: &Path) -> Vec<NamedWeb> {
+    let python = std::env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let output = Command::new(&python)
+        .arg(manifest.join("python").join("golden_webs.py"))
+        .arg(zxg)
+        .output()
+        .unwrap_or_else(|e| panic!("Failed to run {}: {}", python, e));
+    assert!(output.status.success(), "golden_webs.py failed: {}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("golden_webs.py printed invalid JSON");
+    json["webs"].as_array().expect("missing webs").iter()
+        .map(|web| web.as_array().unwrap().iter()
+            .map(|edge| {
+                let field = |i: usize| edge[i].as_str().unwrap().to_string();
+                (field(0), field(1), pauli(&field(2)))
+            })
+            .collect())
+        .collect()
+}
+
+/// Names a vertex like golden_webs.py: by its key, or `rg(a,b)` if make_rg added it
+fn vertex_name(g: &Graph, keys: &HashMap<usize, String>, v: usize) -> String {
+    if let Some(key) = keys.get(&v) {
+        return key.clone();
+    }
+    let mut neighbours: Vec<&str> = g.neighbors(v).map(|n| keys[&n].as_str()).collect();
+    neighbours.sort();
+    format!("rg({})", neighbours.join(","))
+}
+
+fn rust_webs(zxg: &Path) -> Vec<NamedWeb> {
+    let (mut g, keys) = load_graph_with_keys(zxg.to_str().unwrap(), &LoadOptions::default()).unwrap();
+    let webs = get_detection_webs(&mut g);
+    webs.iter()
+        .map(|web| web.sorted_edges().into_iter()
+            .map(|((a, b), p)| {
+                let (a, b) = (vertex_name(&g, &keys, a), vertex_name(&g, &keys, b));
+                (a.clone().min(b.clone()), a.max(b), p)
+            })
+            .collect())
+        .collect()
+}
+
+/// Spans of both sets of webs over a shared numbering of the vertex names
+fn spans(left: &[NamedWeb], right: &[NamedWeb]) -> (WebSpace, WebSpace, Vec<PauliWeb>, Vec<PauliWeb>) {
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let mut to_web = |web: &NamedWeb| {
+        let mut pw = PauliWeb::new();
+        for (a, b, p) in web {
+            let n = ids.len();
+            let a = *ids.entry(a.clone()).or_insert(n);
+            let n = ids.len();
+            let b = *ids.entry(b.clone()).or_insert(n);
+            pw.set_edge(a, b, *p);
+        }
+        pw
+    };
+    let left: Vec<PauliWeb> = left.iter().map(&mut to_web).collect();
+    let right: Vec<PauliWeb> = right.iter().map(&mut to_web).collect();
+    let edges: BTreeSet<(usize, usize)> = left.iter().chain(&right)
+        .flat_map(|w| w.sorted_edges().into_iter().map(|(e, _)| e))
+        .collect();
+    let span = |webs: &[PauliWeb]| {
+        let mut space = WebSpace::new(edges.iter().copied());
+        for web in webs {
+            space.add(web).unwrap();
+        }
+        space
+    };
+    (span(&left), span(&right), left, right)
+}
+
+#[test]
+fn test_webs_match_pyzx() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir = tempfile::tempdir().unwrap();
+    let mut failures = Vec::new();
+    for zxg in write_fixtures(dir.path()).unwrap() {
+        let name = zxg.file_name().unwrap().to_string_lossy();
+        let (python, rust) = (python_webs(manifest, &zxg), rust_webs(&zxg));
+        let (python_span, rust_span, python, rust) = spans(&python, &rust);
+        let missing = python.iter().filter(|w| !rust_span.contains(w)).count();
+        let extra = rust.iter().filter(|w| !python_span.contains(w)).count();
+        if missing > 0 || extra > 0 {
+            failures.push(format!(
+                "{}: PyZX spans {} dimensions, Rust {}; {} PyZX webs outside the Rust span, {} Rust webs outside the PyZX span",
+                name, python_span.dim(), rust_span.dim(), missing, extra
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "Detection webs differ from PyZX:\n{}", failures.join("\n"));
+}
@@ -0,0 +1,113 @@
+use quizx::graph::{GraphLike, VData, VType};
+use quizx::hash_graph::Graph;
+use quizx::phase::Phase;
+use rust_web::create_graph::create_spider_chain;
+use rust_web::graph_visualizer::to_dot_with_positions;
+use rust_web::pauliweb::{Pauli, PauliWeb};
+use rust_web::tikz_export::export_to_tikz;
+
+// Small canonical graphs used for the golden-file snapshots below.
+// Keep these tiny: the point is to catch attribute/escaping changes, not layout.
+
+fn add_vertex(g: &mut Graph, ty: VType, phase: f64, qubit: f64, row: f64) -> usize {
+    g.add_vertex_with_data(VData {
+        ty,
+        phase: Phase::from_f64(phase),
+        qubit,
+        row,
+    })
+}
+
+/// B - Z - X - B on a single qubit line
+fn z_x_line() -> Graph {
+    let mut g = Graph::new();
+    let b0 = add_vertex(&mut g, VType::B, 0.0, 0.0, 0.0);
+    let z = add_vertex(&mut g, VType::Z, 0.0, 0.0, 1.0);
+    let x = add_vertex(&mut g, VType::X, 0.0, 0.0, 2.0);
+    let b1 = add_vertex(&mut g, VType::B, 0.0, 0.0, 3.0);
+    g.add_edge(b0, z);
+    g.add_edge(z, x);
+    g.add_edge(x, b1);
+    g
+}
+
+/// Two Z spiders joined through an H-box, with phases that exercise format_phase
+fn h_box_pair() -> Graph {
+    let mut g = Graph::new();
+    let z0 = add_vertex(&mut g, VType::Z, 0.5, 0.0, 0.0);
+    let h = add_vertex(&mut g, VType::H, 0.0, 0.5, 1.0);
+    let z1 = add_vertex(&mut g, VType::Z, -0.25, 1.0, 2.0);
+    g.add_edge(z0, h);
+    g.add_edge(h, z1);
+    g
+}
+
+/// Z-X-Z triangle carrying a web with one edge of each Pauli
+fn triangle_with_web() -> (Graph, PauliWeb) {
+    let mut g = Graph::new();
+    let z0 = add_vertex(&mut g, VType::Z, 0.0, 0.0, 0.0);
+    let x = add_vertex(&mut g, VType::X, 0.0, 1.0, 1.0);
+    let z1 = add_vertex(&mut g, VType::Z, 0.0, 0.0, 2.0);
+    g.add_edge(z0, x);
+    g.add_edge(x, z1);
+    g.add_edge(z0, z1);
+
+    let mut pw = PauliWeb::new();
+    pw.set_edge(z0, x, Pauli::X);
+    pw.set_edge(x, z1, Pauli::Z);
+    pw.set_edge(z0, z1, Pauli::Y);
+    (g, pw)
+}
+
+fn tikz_string(g: &Graph) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("graph.tex");
+    export_to_tikz(g, path.to_str().unwrap()).unwrap();
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn dot_z_x_line() {
+    let g = z_x_line();
+    insta::assert_snapshot!("dot_z_x_line", to_dot_with_positions(&g, None, false));
+    insta::assert_snapshot!("dot_z_x_line_ids", to_dot_with_positions(&g, None, true));
+}
+
+#[test]
+fn dot_z_x_line_with_io() {
+    let mut g = z_x_line();
+    g.set_inputs(vec![0]);
+    g.set_outputs(vec![3]);
+    insta::assert_snapshot!("dot_z_x_line_with_io", to_dot_with_positions(&g, None, false));
+}
+
+#[test]
+fn dot_h_box_pair() {
+    let g = h_box_pair();
+    insta::assert_snapshot!("dot_h_box_pair", to_dot_with_positions(&g, None, true));
+}
+
+#[test]
+fn dot_spider_chain_with_phases() {
+    let g = create_spider_chain(4, VType::Z, true, true);
+    insta::assert_snapshot!("dot_spider_chain_with_phases", to_dot_with_positions(&g, None, false));
+}
+
+#[test]
+fn dot_triangle_with_web() {
+    let (g, pw) = triangle_with_web();
+    insta::assert_snapshot!("dot_triangle_with_web", to_dot_with_positions(&g, Some(&pw), false));
+}
+
+#[test]
+fn tikz_z_x_line() {
+    insta::assert_snapshot!("tikz_z_x_line", tikz_string(&z_x_line()));
+}
+
+#[test]
+fn tikz_spider_chains() {
+    let gx = create_spider_chain(3, VType::X, false, true);
+    let gz = create_spider_chain(3, VType::Z, false, false);
+    insta::assert_snapshot!("tikz_x_chain_with_boundaries", tikz_string(&gx));
+    insta::assert_snapshot!("tikz_z_chain", tikz_string(&gz));
+}
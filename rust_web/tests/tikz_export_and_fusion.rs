@@ -2,7 +2,7 @@ use quizx::hash_graph::*;
 use quizx::basic_rules::*;
 use rust_web::create_graph::*;
 use quizx::graph::VType::{X,Z};
-use rust_web::tikz_export::export_to_tikz;
+use rust_web::tikz_export::{export_to_tikz, tikz_to_string, tikz_to_writer};
 
 
 pub fn compress_graph(mut g:Graph)->Graph {
@@ -26,7 +26,23 @@ fn compression_simple() {
 pub fn compression_spider() -> Result<(), std::io::Error> {
     let gx: Graph = create_spider_chain(10, X, false,true);
     let gz: Graph = create_spider_chain(10, Z, false,true);
+    // The workspace target dir lives at the repo root, so this one may not exist
+    std::fs::create_dir_all("./target/debug/examples")?;
     export_to_tikz(&gx, "./target/debug/examples/gx.tex")?;
     export_to_tikz(&gz, "./target/debug/examples/gz.tex")?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn tikz_in_memory_matches_file() -> Result<(), std::io::Error> {
+    let g: Graph = create_spider_chain(4, Z, false, true);
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("chain.tex");
+    export_to_tikz(&g, path.to_str().unwrap())?;
+
+    let mut buffer = Vec::new();
+    tikz_to_writer(&g, &mut buffer)?;
+    assert_eq!(buffer, std::fs::read(&path)?);
+    assert_eq!(tikz_to_string(&g).into_bytes(), buffer);
+    Ok(())
+}
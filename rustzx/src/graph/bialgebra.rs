@@ -0,0 +1,102 @@
+use super::{Graph, NodeType, Phase};
+use num::Zero;
+
+#[allow(dead_code)]
+impl Graph {
+    /// Applies the bialgebra rule to a phase-free Z spider `z` and X spider `x` joined by an edge
+    ///
+    /// The pair is replaced by a complete bipartite graph: one new X spider per other
+    /// neighbour of `z` and one new Z spider per other neighbour of `x`. The rule holds
+    /// up to √2^((m-1)(n-1)) for m and n such neighbours, which goes into the scalar.
+    /// Returns the new X spiders followed by the new Z spiders.
+    pub fn bialgebra(&mut self, z: usize, x: usize) -> Result<Vec<usize>, String> {
+        match (self.nodes.get(&z).map(|n| &n.node_type), self.nodes.get(&x).map(|n| &n.node_type)) {
+            (Some(NodeType::ZSpider(pz)), Some(NodeType::XSpider(px))) => {
+                if !pz.is_zero() || !px.is_zero() {
+                    return Err("Bialgebra needs phase-free spiders".to_string());
+                }
+            }
+            (None, _) | (_, None) => return Err("Node not found".to_string()),
+            _ => return Err("Bialgebra needs a Z spider and an X spider".to_string()),
+        }
+        if !self.has_edge(z, x) {
+            return Err("Spiders are not connected".to_string());
+        }
+
+        let mut z_side: Vec<usize> = self.neighbors(z).into_iter().filter(|&n| n != x).collect();
+        let mut x_side: Vec<usize> = self.neighbors(x).into_iter().filter(|&n| n != z).collect();
+        if z_side.is_empty() || x_side.is_empty() {
+            return Err("Both spiders need another neighbour".to_string());
+        }
+        z_side.sort();
+        x_side.sort();
+
+        self.remove_node(z);
+        self.remove_node(x);
+        let new_x: Vec<usize> = z_side.iter()
+            .map(|&n| {
+                let id = self.add_node(NodeType::XSpider(Phase::zero()));
+                self.add_edge(id, n);
+                id
+            })
+            .collect();
+        let new_z: Vec<usize> = x_side.iter()
+            .map(|&n| {
+                let id = self.add_node(NodeType::ZSpider(Phase::zero()));
+                self.add_edge(id, n);
+                id
+            })
+            .collect();
+        for &a in &new_x {
+            for &b in &new_z {
+                self.add_edge(a, b);
+            }
+        }
+
+        let (m, n) = (z_side.len() as i32, x_side.len() as i32);
+        self.scalar.mul_sqrt2_power((m - 1) * (n - 1));
+        Ok(new_x.into_iter().chain(new_z).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bialgebra_scalar() {
+        // Three inputs into Z, X out to two outputs
+        let mut g = Graph::new();
+        let ins: Vec<usize> = (0..3).map(|_| g.add_node(NodeType::Boundary)).collect();
+        let z = g.add_node(NodeType::ZSpider(Phase::zero()));
+        let x = g.add_node(NodeType::XSpider(Phase::zero()));
+        let outs: Vec<usize> = (0..2).map(|_| g.add_node(NodeType::Boundary)).collect();
+        for &i in &ins {
+            g.add_edge(i, z);
+        }
+        g.add_edge(z, x);
+        for &o in &outs {
+            g.add_edge(x, o);
+        }
+
+        let new = g.bialgebra(z, x).unwrap();
+        assert_eq!(new.len(), 5);
+        assert_eq!(g.nodes.len(), 10);
+        assert_eq!(g.edges.len(), 3 + 2 + 3 * 2);
+        assert_eq!(g.scalar.power, 2);
+    }
+
+    #[test]
+    fn test_bialgebra_preconditions() {
+        let mut g = Graph::new();
+        let b = g.add_node(NodeType::Boundary);
+        let z = g.add_node(NodeType::ZSpider(Phase::new(1, 2)));
+        let x = g.add_node(NodeType::XSpider(Phase::zero()));
+        g.add_edge(b, z);
+        g.add_edge(z, x);
+
+        assert!(g.bialgebra(z, x).is_err());
+        assert!(g.bialgebra(x, z).is_err());
+        assert!(g.scalar.is_one());
+    }
+}
@@ -1,29 +1,91 @@
-use std::fmt::Write;
 use super::structure::Graph;
-use super::types::NodeType;
+use super::types::{normalize_phase, NodeType, Phase};
+use zxcore::{DiagramBuilder, DiagramView, NodeKind, WireKind};
 
-impl Graph {
-    pub fn to_dot(&self) -> String {
-        let mut output = String::from("graph ZX {\n");
-
-        for node in self.nodes.values() {
-            let label = match &node.node_type {
-                NodeType::ZSpider(phase) => format!("Z\\n{:.2}", phase),
-                NodeType::XSpider(phase) => format!("X\\n{:.2}", phase),
-                NodeType::Boundary => "B".to_string(),
-            };
-            let _ = writeln!(
-                output,
-                "    {} [label=\"{}\", shape=circle];",
-                node.id, label
-            );
+/// zxcore phases are in units of π, ours in turns
+impl DiagramView for Graph {
+    fn node_ids(&self) -> Vec<usize> {
+        self.nodes.values().map(|node| node.id).collect()
+    }
+
+    fn node_kind(&self, id: usize) -> NodeKind {
+        match self.nodes[&id].node_type {
+            NodeType::ZSpider(_) => NodeKind::Z,
+            NodeType::XSpider(_) => NodeKind::X,
+            NodeType::Boundary => NodeKind::Boundary,
         }
+    }
+
+    fn node_phase(&self, id: usize) -> zxcore::Phase {
+        match self.nodes[&id].node_type {
+            NodeType::ZSpider(p) | NodeType::XSpider(p) => p * 2,
+            NodeType::Boundary => zxcore::Phase::new(0, 1),
+        }
+    }
+
+    fn wires(&self) -> Vec<(usize, usize, WireKind)> {
+        self.edges.keys().map(|&(a, b)| (a, b, WireKind::Plain)).collect()
+    }
+
+    fn input_ids(&self) -> Vec<usize> {
+        self.inputs().to_vec()
+    }
+
+    fn output_ids(&self) -> Vec<usize> {
+        self.outputs().to_vec()
+    }
+
+    fn global_scalar(&self) -> (i32, zxcore::Phase) {
+        (self.scalar.power, self.scalar.phase * 2)
+    }
+}
 
-        for ((a, b), _) in &self.edges {
-            let _ = writeln!(output, "    {} -- {};", a, b);
+/// Panics on H-boxes, Z-boxes, W nodes and Hadamard wires, which this graph can't represent
+impl DiagramBuilder for Graph {
+    fn add_node(&mut self, kind: NodeKind, phase: zxcore::Phase, _qubit: f64, _row: f64) -> usize {
+        let phase: Phase = normalize_phase(phase / 2);
+        match kind {
+            NodeKind::Z => self.add_node(NodeType::ZSpider(phase)),
+            NodeKind::X => self.add_node(NodeType::XSpider(phase)),
+            NodeKind::Boundary => self.add_node(NodeType::Boundary),
+            NodeKind::H => panic!("rustzx graphs have no H-boxes"),
+            NodeKind::ZBox | NodeKind::WInput | NodeKind::WOutput => panic!("rustzx graphs only have Z and X spiders"),
         }
+    }
+
+    fn add_wire(&mut self, a: usize, b: usize, kind: WireKind) {
+        assert_eq!(kind, WireKind::Plain, "rustzx graphs have no Hadamard wires");
+        self.add_edge(a, b);
+    }
+}
+
+#[allow(dead_code)]
+impl Graph {
+    pub fn to_dot(&self) -> String {
+        zxcore::dot::to_dot(self)
+    }
+
+    pub fn to_tikz(&self) -> String {
+        zxcore::tikz::tikz_to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_builders_and_export() {
+        let mut g = Graph::new();
+        zxcore::build::spider_chain(&mut g, 3, NodeKind::Z, true, true);
+        assert_eq!(g.nodes.len(), 4);
+        assert_eq!(g.edges.len(), 3);
+        // Spider 1 gets π/2, i.e. a quarter turn
+        assert!(matches!(g.nodes[&1].node_type, NodeType::ZSpider(p) if p == Phase::new(1, 4)));
 
-        output.push_str("}\n");
-        output
+        let dot = g.to_dot();
+        assert!(dot.contains("1 [label=\"Z\\n1/2π\", shape=circle];"));
+        assert!(dot.contains("    0 -- 1;"));
+        assert!(g.to_tikz().contains("\\node[zspider] (v2) at (3,0) {2/3π};"));
     }
 }
@@ -1,4 +1,5 @@
 use super::{Graph, NodeType};
+use super::types::{normalize_phase, Phase};
 
 impl Graph {
     pub fn neighbors(&self, id: usize) -> Vec<usize> {
@@ -16,24 +17,30 @@ impl Graph {
             .collect()
     }
 
+    /// Fuses two spiders of the same colour, adding their phases
+    ///
+    /// Fusion is exact for unnormalised spiders, so the scalar is left alone.
     pub fn fuse_spiders(&mut self, a: usize, b: usize) -> Result<(), String> {
         use NodeType::*;
 
         let node_a = self.nodes.get(&a).ok_or("Node a not found")?.clone();
         let node_b = self.nodes.get(&b).ok_or("Node b not found")?.clone();
+        if self.is_interface(a) || self.is_interface(b) {
+            return Err("Cannot fuse an input or output".to_string());
+        }
 
         match (&node_a.node_type, &node_b.node_type) {
-            (ZSpider(pa), ZSpider(pb)) => self.merge_spiders(a, b, (pa + pb)%1.0, ZSpider),
-            (XSpider(pa), XSpider(pb)) => self.merge_spiders(a, b, (pa + pb)%1.0, XSpider),
+            (ZSpider(pa), ZSpider(pb)) => self.merge_spiders(a, b, normalize_phase(pa + pb), ZSpider),
+            (XSpider(pa), XSpider(pb)) => self.merge_spiders(a, b, normalize_phase(pa + pb), XSpider),
             _ => return Err("Mismatched spider types".to_string()),
         }
 
         Ok(())
     }
 
-    fn merge_spiders<F>(&mut self, target: usize, to_remove: usize, new_phase: f64, ctor: F)
+    fn merge_spiders<F>(&mut self, target: usize, to_remove: usize, new_phase: Phase, ctor: F)
     where
-        F: Fn(f64) -> NodeType,
+        F: Fn(Phase) -> NodeType,
     {
         self.nodes.insert(
             target,
@@ -54,3 +61,46 @@ impl Graph {
         self.remove_edge(target, to_remove);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::Zero;
+
+    fn fused_phase(pa: Phase, pb: Phase) -> Phase {
+        let mut g = Graph::new();
+        let a = g.add_node(NodeType::ZSpider(pa));
+        let b = g.add_node(NodeType::ZSpider(pb));
+        g.add_edge(a, b);
+        g.fuse_spiders(a, b).unwrap();
+        match g.nodes[&a].node_type {
+            NodeType::ZSpider(p) => p,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_fuse_wraps_around() {
+        // 0.1 + 0.9 is not exactly 1.0 in floating point
+        assert!(fused_phase(Phase::new(1, 10), Phase::new(9, 10)).is_zero());
+        assert_eq!(fused_phase(Phase::new(3, 4), Phase::new(1, 2)), Phase::new(1, 4));
+        assert_eq!(fused_phase(Phase::new(-1, 3), Phase::zero()), Phase::new(2, 3));
+    }
+
+    #[test]
+    fn test_fuse_keeps_neighbours() {
+        let mut g = Graph::new();
+        let a = g.add_node(NodeType::XSpider(Phase::new(1, 8)));
+        let b = g.add_node(NodeType::XSpider(Phase::new(1, 8)));
+        let c = g.add_node(NodeType::Boundary);
+        g.add_edge(a, b);
+        g.add_edge(b, c);
+        g.fuse_spiders(a, b).unwrap();
+
+        assert!(!g.nodes.contains_key(&b));
+        assert!(g.has_edge(a, c));
+        assert!(matches!(g.nodes[&a].node_type, NodeType::XSpider(p) if p == Phase::new(1, 4)));
+        assert!(g.fuse_spiders(a, c).is_err());
+        assert!(g.scalar.is_one());
+    }
+}
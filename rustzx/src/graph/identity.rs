@@ -1,19 +1,26 @@
 use super::{Graph, NodeType};
+use super::types::{normalize_phase, Phase};
+use num::Zero;
 
 impl Graph {
+    /// Removes a phase-free spider of degree 2, joining its neighbours
+    ///
+    /// Such a spider is exactly the identity, so the scalar is left alone.
     pub fn remove_identity_spider(&mut self, id: usize) -> Result<(), String> {
         use NodeType::*;
 
         let node = self.nodes.get(&id).ok_or("Node not found")?.clone();
+        if self.is_interface(id) {
+            return Err("Cannot remove an input or output".to_string());
+        }
 
-        let (_, phase): (fn(f64) -> NodeType, f64) = match node.node_type {
-            ZSpider(p) => (ZSpider as fn(f64) -> NodeType, p),
-            XSpider(p) => (XSpider as fn(f64) -> NodeType, p),
+        let (_, phase): (fn(Phase) -> NodeType, Phase) = match node.node_type {
+            ZSpider(p) => (ZSpider as fn(Phase) -> NodeType, p),
+            XSpider(p) => (XSpider as fn(Phase) -> NodeType, p),
             _ => return Err("Not a spider".to_string()),
         };
         
-        let phase = phase%1.0;
-        if phase != 0.0 {
+        if !normalize_phase(phase).is_zero() {
             return Err("Spider phase is not 0".to_string());
         }
 
@@ -21,6 +28,11 @@ impl Graph {
         if neighbors.len() != 2 {
             return Err("Spider does not have degree 2".to_string());
         }
+        // The graph has no parallel edges, so the two wires would collapse into one
+        // and a boundary on either end could lose its connection
+        if self.has_edge(neighbors[0], neighbors[1]) {
+            return Err("Neighbours are already connected".to_string());
+        }
 
         // Remove the spider and reconnect its neighbors
         self.remove_node(id);
@@ -29,3 +41,53 @@ impl Graph {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_turn_is_identity() {
+        let mut g = Graph::new();
+        let a = g.add_node(NodeType::Boundary);
+        let z = g.add_node(NodeType::ZSpider(Phase::new(1, 1)));
+        let b = g.add_node(NodeType::Boundary);
+        g.add_edge(a, z);
+        g.add_edge(z, b);
+
+        g.remove_identity_spider(z).unwrap();
+        assert!(g.has_edge(a, b));
+    }
+
+    #[test]
+    fn test_phase_blocks_removal() {
+        let mut g = Graph::new();
+        let a = g.add_node(NodeType::Boundary);
+        let x = g.add_node(NodeType::XSpider(Phase::new(1, 2)));
+        let b = g.add_node(NodeType::Boundary);
+        g.add_edge(a, x);
+        g.add_edge(x, b);
+
+        assert!(g.remove_identity_spider(x).is_err());
+        assert!(g.nodes.contains_key(&x));
+    }
+
+    #[test]
+    fn test_keeps_interface_wires() {
+        // Triangle input - Z - output with a direct input-output wire
+        let mut g = Graph::new();
+        let i = g.add_node(NodeType::Boundary);
+        let z = g.add_node(NodeType::ZSpider(Phase::new(0, 1)));
+        let o = g.add_node(NodeType::Boundary);
+        g.add_edge(i, z);
+        g.add_edge(z, o);
+        g.add_edge(i, o);
+        g.set_inputs(vec![i]).unwrap();
+        g.set_outputs(vec![o]).unwrap();
+
+        assert!(g.remove_identity_spider(z).is_err());
+        assert_eq!(g.edges.len(), 3);
+        assert!(g.remove_identity_spider(i).is_err());
+        assert_eq!(g.inputs(), &[i]);
+    }
+}
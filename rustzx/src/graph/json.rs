@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::fs;
+use num::{FromPrimitive, Zero};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Deserializer};
+use zxcore::zxg::FORMAT_VERSION;
+use super::types::normalize_phase;
+use super::{Graph, NodeType, Phase, Scalar};
+
+/// A spider, written as node vertex `v<id>`; the graph has no layout, so its
+/// coordinates are ignored
+#[derive(Deserialize)]
+struct NodeVertex {
+    data: NodeData,
+}
+
+/// Type and phase of a spider; phases are in units of π like PyZX's, e.g. "1/2" for a
+/// quarter turn, and left out when zero
+#[derive(Deserialize)]
+struct NodeData {
+    #[serde(rename = "type")]
+    kind: Kind,
+    #[serde(default)]
+    value: Option<PhaseValue>,
+}
+
+#[derive(Deserialize)]
+enum Kind {
+    Z,
+    X,
+}
+
+/// A phase as written by us, or as a number by other tools
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PhaseValue {
+    Text(String),
+    Number(f64),
+}
+
+#[derive(Deserialize)]
+struct EdgeData {
+    src: String,
+    tgt: String,
+}
+
+/// PyZX's scalar, stored as a JSON string inside the file
+#[derive(Deserialize)]
+struct ScalarData {
+    power2: i32,
+    phase: String,
+}
+
+/// On-disk form of a graph in the .zxg schema, as written by [`zxcore::zxg::to_zxg`];
+/// boundaries are wire vertices `b<id>`
+#[derive(Deserialize)]
+struct GraphData {
+    #[serde(default)]
+    format_version: u64,
+    wire_vertices: BTreeMap<String, IgnoredAny>,
+    node_vertices: BTreeMap<String, NodeVertex>,
+    undir_edges: BTreeMap<String, EdgeData>,
+    #[serde(default)]
+    inputs: Vec<String>,
+    #[serde(default)]
+    outputs: Vec<String>,
+    #[serde(default)]
+    scalar: Option<String>,
+}
+
+/// Id of the node named `key`, `b<id>` for a boundary and `v<id>` for a spider
+fn id(key: &str, prefix: char) -> Result<usize, String> {
+    key.strip_prefix(prefix)
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| format!("Vertex '{}' isn't named {}<id>", key, prefix))
+}
+
+/// A phase in units of π, as a fraction or a number, in turns
+fn phase_from_pi(value: &PhaseValue) -> Result<Phase, String> {
+    let pi = match value {
+        PhaseValue::Text(text) => text.parse::<Phase>().map_err(|e| e.to_string())?,
+        PhaseValue::Number(x) => Phase::from_f64(*x).ok_or("not a finite number")?,
+    };
+    Ok(normalize_phase(pi / 2))
+}
+
+impl TryFrom<GraphData> for Graph {
+    type Error = String;
+
+    fn try_from(data: GraphData) -> Result<Self, String> {
+        if data.format_version > FORMAT_VERSION {
+            return Err(format!("Unsupported format version {}", data.format_version));
+        }
+        let mut g = Graph::new();
+        let mut ids = BTreeMap::new();
+        for name in data.wire_vertices.keys() {
+            let node = id(name, 'b')?;
+            g.add_node_with_id(node, NodeType::Boundary)?;
+            ids.insert(name.as_str(), node);
+        }
+        for (name, vertex) in &data.node_vertices {
+            let node = id(name, 'v')?;
+            let phase = match &vertex.data.value {
+                Some(value) => phase_from_pi(value)
+                    .map_err(|e| format!("Invalid phase of vertex {}: {}", name, e))?,
+                None => Phase::zero(),
+            };
+            let node_type = match vertex.data.kind {
+                Kind::Z => NodeType::ZSpider(phase),
+                Kind::X => NodeType::XSpider(phase),
+            };
+            g.add_node_with_id(node, node_type)?;
+            ids.insert(name.as_str(), node);
+        }
+        let lookup = |name: &str| ids.get(name).copied()
+            .ok_or_else(|| format!("Edge refers to a missing vertex {}", name));
+        for edge in data.undir_edges.values() {
+            g.add_edge(lookup(&edge.src)?, lookup(&edge.tgt)?);
+        }
+        g.set_inputs(data.inputs.iter().map(|name| lookup(name)).collect::<Result<_, _>>()?)?;
+        g.set_outputs(data.outputs.iter().map(|name| lookup(name)).collect::<Result<_, _>>()?)?;
+        if let Some(scalar) = data.scalar {
+            let scalar: ScalarData = serde_json::from_str(&scalar)
+                .map_err(|e| format!("Invalid scalar '{}': {}", scalar, e))?;
+            let phase = phase_from_pi(&PhaseValue::Text(scalar.phase.clone()))
+                .map_err(|e| format!("Invalid scalar phase '{}': {}", scalar.phase, e))?;
+            g.scalar = Scalar::one();
+            g.scalar.mul_sqrt2_power(scalar.power2);
+            g.scalar.add_phase(phase);
+        }
+        Ok(g)
+    }
+}
+
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = GraphData::deserialize(deserializer)?;
+        Graph::try_from(data).map_err(serde::de::Error::custom)
+    }
+}
+
+#[allow(dead_code)]
+impl Graph {
+    /// The graph as .zxg, written by zxcore like rust_web's
+    pub fn to_json(&self) -> String {
+        zxcore::zxg::to_zxg(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Graph, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse graph: {}", e))
+    }
+
+    pub fn save_json(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_json()).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    pub fn load_json(path: &str) -> Result<Graph, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Graph::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circuit() -> (Graph, [usize; 4]) {
+        let mut g = Graph::new();
+        let i = g.add_node(NodeType::Boundary);
+        let z = g.add_node(NodeType::ZSpider(Phase::new(1, 4)));
+        let x = g.add_node(NodeType::XSpider(Phase::new(0, 1)));
+        let o = g.add_node(NodeType::Boundary);
+        g.add_edge(i, z);
+        g.add_edge(z, x);
+        g.add_edge(x, o);
+        g.set_inputs(vec![i]).unwrap();
+        g.set_outputs(vec![o]).unwrap();
+        (g, [i, z, x, o])
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (g, [i, z, x, o]) = circuit();
+        let json = g.to_json();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        // A quarter turn is π/2
+        assert_eq!(data["node_vertices"][format!("v{}", z)]["data"]["value"], "1/2");
+        assert!(data["node_vertices"][format!("v{}", x)]["data"].get("value").is_none());
+        assert_eq!(data["wire_vertices"][format!("b{}", i)]["annotation"]["boundary"], true);
+        assert_eq!(data["inputs"], serde_json::json!([format!("b{}", i)]));
+        assert_eq!(data["format_version"], FORMAT_VERSION);
+
+        let loaded = Graph::from_json(&json).unwrap();
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(loaded.outputs(), &[o]);
+
+        // Fresh nodes don't collide with loaded ids
+        let mut loaded = loaded;
+        assert_eq!(loaded.add_node(NodeType::Boundary), 4);
+
+        loaded.scalar.mul_sqrt2_power(-3);
+        loaded.scalar.add_phase(Phase::new(1, 8));
+        let reloaded = Graph::from_json(&loaded.to_json()).unwrap();
+        assert_eq!(reloaded.scalar, loaded.scalar);
+
+        // Other tools may write the phase as a number
+        let numeric = r#"{"wire_vertices": {}, "node_vertices": {"v0": {"annotation": {"coord": [0, 0]}, "data": {"type": "X", "value": 1.5}}}, "undir_edges": {}}"#;
+        let g = Graph::from_json(numeric).unwrap();
+        assert!(matches!(g.nodes[&0].node_type, NodeType::XSpider(p) if p == Phase::new(3, 4)));
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let spider = |value: &str| format!(
+            r#"{{"wire_vertices": {{}}, "node_vertices": {{"v0": {{"annotation": {{"coord": [0, 0]}}, "data": {{"type": "Z", "value": {}}}}}}}, "undir_edges": {{}}}}"#,
+            value
+        );
+        assert!(Graph::from_json(&spider("\"one\"")).unwrap_err().contains("Invalid phase of vertex v0"));
+
+        let dangling = r#"{"wire_vertices": {"b0": {"annotation": {"boundary": true}}}, "node_vertices": {}, "undir_edges": {"e0": {"src": "b0", "tgt": "b1"}}}"#;
+        assert!(Graph::from_json(dangling).unwrap_err().contains("missing vertex b1"));
+
+        let unnamed = r#"{"wire_vertices": {"in": {"annotation": {"boundary": true}}}, "node_vertices": {}, "undir_edges": {}}"#;
+        assert!(Graph::from_json(unnamed).unwrap_err().contains("isn't named b<id>"));
+
+        let spider_input = r#"{"wire_vertices": {}, "node_vertices": {"v0": {"annotation": {"coord": [0, 0]}, "data": {"type": "X"}}}, "undir_edges": {}, "inputs": ["v0"]}"#;
+        assert!(Graph::from_json(spider_input).is_err());
+    }
+}
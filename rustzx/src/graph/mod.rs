@@ -3,6 +3,10 @@ pub mod structure;
 pub mod export;
 pub mod fuse;
 pub mod identity;
+pub mod json;
+pub mod scalar;
+pub mod bialgebra;
 
-pub use types::NodeType;//{Node, Edge, NodeType};
-pub use structure::Graph;
\ No newline at end of file
+pub use types::{NodeType, Phase};//{Node, Edge, NodeType};
+pub use structure::Graph;
+pub use scalar::Scalar;
\ No newline at end of file
@@ -0,0 +1,63 @@
+use num::complex::Complex64;
+use num::Zero;
+use super::types::{normalize_phase, Phase};
+
+/// Global factor √2^power · e^(2πi·phase) that rewrites pull out of the diagram
+///
+/// The diagram a graph stands for is its spiders times this scalar, so rules that
+/// only hold up to a constant keep the represented linear map unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scalar {
+    pub power: i32,
+    pub phase: Phase,
+}
+
+impl Default for Scalar {
+    fn default() -> Self {
+        Scalar::one()
+    }
+}
+
+#[allow(dead_code)]
+impl Scalar {
+    pub fn one() -> Self {
+        Scalar { power: 0, phase: Phase::zero() }
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.power == 0 && self.phase.is_zero()
+    }
+
+    /// Multiplies by √2^k
+    pub fn mul_sqrt2_power(&mut self, k: i32) {
+        self.power += k;
+    }
+
+    /// Multiplies by e^(2πi·phase)
+    pub fn add_phase(&mut self, phase: Phase) {
+        self.phase = normalize_phase(self.phase + phase);
+    }
+
+    pub fn to_complex(self) -> Complex64 {
+        let turns = *self.phase.numer() as f64 / *self.phase.denom() as f64;
+        Complex64::from_polar(2f64.sqrt().powi(self.power), 2.0 * std::f64::consts::PI * turns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_value() {
+        let mut s = Scalar::one();
+        assert!(s.is_one());
+        s.mul_sqrt2_power(2);
+        s.add_phase(Phase::new(3, 4));
+        s.add_phase(Phase::new(1, 2));
+        assert_eq!(s.phase, Phase::new(1, 4));
+
+        let value = s.to_complex();
+        assert!((value - Complex64::new(0.0, 2.0)).norm() < 1e-12);
+    }
+}
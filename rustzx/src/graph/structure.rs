@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 use super::types::{Node, Edge, NodeType};
+use super::scalar::Scalar;
 
 #[derive(Debug)]
 pub struct Graph {
     pub nodes: HashMap<usize, Node>,
     pub edges: HashMap<(usize, usize), Edge>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    /// Global factor the rewrites have pulled out of the diagram
+    pub scalar: Scalar,
     next_id: usize,
 }
 #[allow(dead_code)]
@@ -13,6 +18,9 @@ impl Graph {
         Self {
             nodes: HashMap::new(),
             edges: HashMap::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            scalar: Scalar::one(),
             next_id: 0,
         }
     }
@@ -25,6 +33,16 @@ impl Graph {
         id
     }    
 
+    /// Adds a node under a given id, e.g. when loading a saved diagram
+    pub fn add_node_with_id(&mut self, id: usize, node_type: NodeType) -> Result<(), String> {
+        if self.nodes.contains_key(&id) {
+            return Err(format!("Node {} already exists", id));
+        }
+        self.nodes.insert(id, Node { id, node_type });
+        self.next_id = self.next_id.max(id + 1);
+        Ok(())
+    }
+
     pub fn add_edge(&mut self, source: usize, target: usize) {
         if self.nodes.contains_key(&source) && self.nodes.contains_key(&target) {
             self.edges.insert(Self::edge_key(source, target), Edge { source, target });
@@ -46,9 +64,74 @@ impl Graph {
             panic!("Node {} does not exist", id);
         }
         self.edges.retain(|&(a, b), _| a != id && b != id);
+        self.inputs.retain(|&v| v != id);
+        self.outputs.retain(|&v| v != id);
+    }
+
+    pub fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    /// Marks the given boundary nodes as the diagram's inputs, in order
+    pub fn set_inputs(&mut self, inputs: Vec<usize>) -> Result<(), String> {
+        self.check_boundaries(&inputs)?;
+        self.inputs = inputs;
+        Ok(())
+    }
+
+    /// Marks the given boundary nodes as the diagram's outputs, in order
+    pub fn set_outputs(&mut self, outputs: Vec<usize>) -> Result<(), String> {
+        self.check_boundaries(&outputs)?;
+        self.outputs = outputs;
+        Ok(())
+    }
+
+    /// Whether the node is one of the diagram's inputs or outputs
+    pub fn is_interface(&self, id: usize) -> bool {
+        self.inputs.contains(&id) || self.outputs.contains(&id)
+    }
+
+    fn check_boundaries(&self, ids: &[usize]) -> Result<(), String> {
+        for &id in ids {
+            match self.nodes.get(&id) {
+                Some(node) if matches!(node.node_type, NodeType::Boundary) => {}
+                Some(_) => return Err(format!("Node {} is not a boundary", id)),
+                None => return Err(format!("Node {} does not exist", id)),
+            }
+        }
+        Ok(())
     }
 
     fn edge_key(a: usize, b: usize) -> (usize, usize) {
         if a < b { (a, b) } else { (b, a) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Phase;
+
+    #[test]
+    fn test_interface_lists() {
+        let mut g = Graph::new();
+        let i = g.add_node(NodeType::Boundary);
+        let z = g.add_node(NodeType::ZSpider(Phase::new(1, 2)));
+        let o = g.add_node(NodeType::Boundary);
+        g.add_edge(i, z);
+        g.add_edge(z, o);
+
+        assert!(g.set_inputs(vec![z]).is_err());
+        assert!(g.set_outputs(vec![7]).is_err());
+        g.set_inputs(vec![i]).unwrap();
+        g.set_outputs(vec![o]).unwrap();
+        assert!(g.is_interface(i) && g.is_interface(o) && !g.is_interface(z));
+
+        g.remove_node(o);
+        assert!(g.outputs().is_empty());
+    }
+}
@@ -1,9 +1,18 @@
+use num::Rational64;
+
+/// Spider phase in turns (multiples of 2π), kept in [0, 1)
+pub type Phase = Rational64;
+
+/// Reduces a phase modulo one full turn into [0, 1)
+pub fn normalize_phase(phase: Phase) -> Phase {
+    phase - phase.floor()
+}
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum NodeType {
-    ZSpider(f64),
-    XSpider(f64),
+    ZSpider(Phase),
+    XSpider(Phase),
     Boundary,
 }
 #[derive(Debug, Clone)]
@@ -17,4 +26,4 @@ pub struct Node {
 pub struct Edge {
     pub source: usize,
     pub target: usize,
-}
\ No newline at end of file
+}
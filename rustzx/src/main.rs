@@ -1,21 +1,22 @@
 mod graph;
 
-use std::fs::write;
+use std::fs::{create_dir_all, write};
 
-use graph::{Graph, NodeType};
+use graph::{Graph, NodeType, Phase};
 
 fn main() {
     let mut g = Graph::new();
-    let a = g.add_node(NodeType::ZSpider(0.2));
-    let b = g.add_node(NodeType::ZSpider(0.9));
+    let a = g.add_node(NodeType::ZSpider(Phase::new(1, 5)));
+    let b = g.add_node(NodeType::ZSpider(Phase::new(9, 10)));
     let c = g.add_node(NodeType::Boundary);
-    let d = g.add_node(NodeType::XSpider(0.0));
+    let d = g.add_node(NodeType::XSpider(Phase::new(0, 1)));
     g.add_edge(b, d);
     g.add_edge(a, b);
     g.add_edge(b, c);
     g.add_edge(d, a);
 
     let dot1 = g.to_dot();
+    create_dir_all("target/debug/examples").unwrap();
     write("target/debug/examples/graphBeforeRules.dot", dot1).unwrap();
 
     if let Err(e) = g.remove_identity_spider(d) {
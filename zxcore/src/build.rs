@@ -0,0 +1,94 @@
+use crate::diagram::{DiagramBuilder, NodeKind, Phase, WireKind};
+
+/// Builds a line of `n` spiders of one kind, optionally between two boundaries
+///
+/// With boundaries the line holds n-1 spiders at qubit coordinates 1 to n-1 and
+/// the boundaries at 0 and n. With `with_phases` spider i gets phase i/(i+1)·π (the
+/// first one π/4 when there is no boundary), otherwise all phases are 0.
+pub fn spider_chain<B: DiagramBuilder>(
+    g: &mut B,
+    n: usize,
+    kind: NodeKind,
+    with_phases: bool,
+    with_boundaries: bool,
+) {
+    let zero = Phase::new(0, 1);
+    let mut prev = if with_boundaries {
+        g.add_node(NodeKind::Boundary, zero, 0.0, 0.0)
+    } else {
+        g.add_node(kind, if with_phases { Phase::new(1, 4) } else { zero }, 0.0, 0.0)
+    };
+
+    for i in 1..n {
+        let phase = if with_phases { Phase::new(i as i64, (i + 1) as i64) } else { zero };
+        let current = g.add_node(kind, phase, i as f64, 0.0);
+        g.add_wire(prev, current, WireKind::Plain);
+        prev = current;
+    }
+
+    if with_boundaries {
+        let last = g.add_node(NodeKind::Boundary, zero, n as f64, 0.0);
+        g.add_wire(prev, last, WireKind::Plain);
+    }
+}
+
+/// Builds a line of `n + 1` phase-free nodes of one kind, all at the origin
+pub fn chain<B: DiagramBuilder>(g: &mut B, n: usize, kind: NodeKind) {
+    let zero = Phase::new(0, 1);
+    let mut prev = g.add_node(kind, zero, 0.0, 0.0);
+    for _ in 0..n {
+        let current = g.add_node(kind, zero, 0.0, 0.0);
+        g.add_wire(prev, current, WireKind::Plain);
+        prev = current;
+    }
+}
+
+/// Builds `rounds` rounds of stabilizer measurements on `qubits` data qubits, returning
+/// the input and output boundaries in qubit order
+///
+/// Each stabilizer is the spider kind it puts on the data (X for an X-type stabilizer)
+/// and its support. Every round measures the stabilizers in order, the k-th measurement
+/// overall putting one spider per supported qubit on row 2k+2, all joined to an ancilla
+/// spider of the other colour on row 2k+3. Data qubit q runs along qubit coordinate 2q
+/// and the ancillas along -2; inputs sit on row 0 and outputs on the row after the last
+/// measurement.
+pub fn stabilizer_rounds<B: DiagramBuilder>(
+    g: &mut B,
+    qubits: usize,
+    stabilizers: &[(NodeKind, &[usize])],
+    rounds: usize,
+) -> (Vec<usize>, Vec<usize>) {
+    let zero = Phase::new(0, 1);
+    let qubit_coord = |q: usize| 2.0 * q as f64;
+    let inputs: Vec<usize> = (0..qubits)
+        .map(|q| g.add_node(NodeKind::Boundary, zero, qubit_coord(q), 0.0))
+        .collect();
+    let mut last = inputs.clone();
+
+    let measurements = stabilizers.iter().cycle().take(stabilizers.len() * rounds);
+    for (k, &(kind, support)) in measurements.enumerate() {
+        let row = 2.0 * k as f64 + 2.0;
+        let ancilla_kind = match kind {
+            NodeKind::X => NodeKind::Z,
+            NodeKind::Z => NodeKind::X,
+            other => panic!("Stabilizers put X or Z spiders on the data, not {:?}", other),
+        };
+        let ancilla = g.add_node(ancilla_kind, zero, -2.0, row + 1.0);
+        for &q in support {
+            let spider = g.add_node(kind, zero, qubit_coord(q), row);
+            g.add_wire(last[q], spider, WireKind::Plain);
+            g.add_wire(spider, ancilla, WireKind::Plain);
+            last[q] = spider;
+        }
+    }
+
+    let out_row = 2.0 * (stabilizers.len() * rounds) as f64 + 2.0;
+    let outputs = (0..qubits)
+        .map(|q| {
+            let output = g.add_node(NodeKind::Boundary, zero, qubit_coord(q), out_row);
+            g.add_wire(last[q], output, WireKind::Plain);
+            output
+        })
+        .collect();
+    (inputs, outputs)
+}
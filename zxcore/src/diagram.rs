@@ -0,0 +1,76 @@
+use num::{Rational64, Zero};
+
+/// Spider phase in units of π
+pub type Phase = Rational64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Boundary,
+    Z,
+    X,
+    H,
+    ZBox,
+    WInput,
+    WOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireKind {
+    Plain,
+    Hadamard,
+}
+
+/// Read access to a ZX diagram, enough to draw or export it
+pub trait DiagramView {
+    /// All node ids, in any order
+    fn node_ids(&self) -> Vec<usize>;
+    fn node_kind(&self, id: usize) -> NodeKind;
+    /// Phase in units of π
+    fn node_phase(&self, id: usize) -> Phase;
+    /// All wires, each listed once in any orientation
+    fn wires(&self) -> Vec<(usize, usize, WireKind)>;
+
+    /// Where a node is drawn, as (qubit, row); by default all nodes sit in one row, by id
+    fn node_position(&self, id: usize) -> (f64, f64) {
+        (0.0, id as f64)
+    }
+
+    /// Boundary nodes that are inputs, in order, or none if the diagram doesn't say
+    fn input_ids(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// Boundary nodes that are outputs, in order, or none if the diagram doesn't say
+    fn output_ids(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// Global scalar as a power of √2 and a phase in units of π
+    fn global_scalar(&self) -> (i32, Phase) {
+        (0, Phase::zero())
+    }
+
+    /// Node ids in increasing order
+    fn sorted_node_ids(&self) -> Vec<usize> {
+        let mut ids = self.node_ids();
+        ids.sort();
+        ids
+    }
+
+    /// Wires as (smaller, larger) id pairs in increasing order
+    fn sorted_wires(&self) -> Vec<(usize, usize, WireKind)> {
+        let mut wires: Vec<_> = self.wires()
+            .into_iter()
+            .map(|(a, b, kind)| (a.min(b), a.max(b), kind))
+            .collect();
+        wires.sort_by_key(|&(a, b, _)| (a, b));
+        wires
+    }
+}
+
+/// Write access to a ZX diagram, enough to build one up node by node
+pub trait DiagramBuilder {
+    /// Adds a node with a phase in units of π at the given coordinates
+    fn add_node(&mut self, kind: NodeKind, phase: Phase, qubit: f64, row: f64) -> usize;
+    fn add_wire(&mut self, a: usize, b: usize, kind: WireKind);
+}
@@ -0,0 +1,34 @@
+use crate::diagram::{DiagramView, NodeKind, WireKind};
+use num::Zero;
+use std::fmt::Write;
+
+/// Plain Graphviz drawing of the diagram, without positions or styling
+///
+/// Hadamard wires are drawn dashed and blue.
+pub fn to_dot<D: DiagramView>(g: &D) -> String {
+    let mut output = String::from("graph ZX {\n");
+
+    for v in g.sorted_node_ids() {
+        let phase = g.node_phase(v);
+        let phase_label = if phase.is_zero() { String::new() } else { format!("\\n{}π", phase) };
+        let label = match g.node_kind(v) {
+            NodeKind::Z => format!("Z{}", phase_label),
+            NodeKind::X => format!("X{}", phase_label),
+            NodeKind::H => "H".to_string(),
+            NodeKind::ZBox => format!("Z box{}", phase_label),
+            NodeKind::WInput | NodeKind::WOutput => "W".to_string(),
+            NodeKind::Boundary => "B".to_string(),
+        };
+        let _ = writeln!(output, "    {} [label=\"{}\", shape=circle];", v, label);
+    }
+
+    for (a, b, kind) in g.sorted_wires() {
+        match kind {
+            WireKind::Plain => { let _ = writeln!(output, "    {} -- {};", a, b); }
+            WireKind::Hadamard => { let _ = writeln!(output, "    {} -- {} [style=dashed, color=blue];", a, b); }
+        }
+    }
+
+    output.push_str("}\n");
+    output
+}
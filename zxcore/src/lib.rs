@@ -0,0 +1,88 @@
+//! Diagram-agnostic pieces shared by `rust_web` (quizx graphs) and `rustzx` (toy graphs)
+//!
+//! Exporters and builders are written against the [`DiagramView`] and
+//! [`DiagramBuilder`] traits, so each graph type only has to describe itself once.
+
+pub mod diagram;
+#[cfg(feature = "tikz")]
+pub mod tikz;
+#[cfg(feature = "dot")]
+pub mod dot;
+#[cfg(feature = "build")]
+pub mod build;
+#[cfg(feature = "zxg")]
+pub mod zxg;
+#[cfg(feature = "quizx")]
+mod quizx_impl;
+
+pub use diagram::{DiagramBuilder, DiagramView, NodeKind, Phase, WireKind};
+
+#[cfg(all(test, feature = "build", feature = "dot", feature = "tikz", feature = "zxg"))]
+mod tests {
+    use super::*;
+
+    /// Minimal diagram storing nodes and wires in insertion order
+    #[derive(Default)]
+    struct Toy {
+        nodes: Vec<(NodeKind, Phase)>,
+        wires: Vec<(usize, usize, WireKind)>,
+    }
+
+    impl DiagramView for Toy {
+        fn node_ids(&self) -> Vec<usize> {
+            (0..self.nodes.len()).rev().collect()
+        }
+        fn node_kind(&self, id: usize) -> NodeKind {
+            self.nodes[id].0
+        }
+        fn node_phase(&self, id: usize) -> Phase {
+            self.nodes[id].1
+        }
+        fn wires(&self) -> Vec<(usize, usize, WireKind)> {
+            self.wires.iter().map(|&(a, b, kind)| (b, a, kind)).collect()
+        }
+    }
+
+    impl DiagramBuilder for Toy {
+        fn add_node(&mut self, kind: NodeKind, phase: Phase, _qubit: f64, _row: f64) -> usize {
+            self.nodes.push((kind, phase));
+            self.nodes.len() - 1
+        }
+        fn add_wire(&mut self, a: usize, b: usize, kind: WireKind) {
+            self.wires.push((a, b, kind));
+        }
+    }
+
+    #[test]
+    fn test_build_and_export() {
+        let mut g = Toy::default();
+        build::spider_chain(&mut g, 3, NodeKind::X, true, true);
+        let h = g.add_node(NodeKind::H, Phase::new(0, 1), 0.0, 0.0);
+        g.add_wire(h, 1, WireKind::Hadamard);
+
+        let dot = dot::to_dot(&g);
+        let expected_nodes = [
+            "    0 [label=\"B\", shape=circle];",
+            "    1 [label=\"X\\n1/2π\", shape=circle];",
+            "    2 [label=\"X\\n2/3π\", shape=circle];",
+        ];
+        for line in expected_nodes {
+            assert!(dot.contains(line), "{}", dot);
+        }
+        // Wires come out sorted with the smaller id first
+        assert!(dot.contains("    0 -- 1;\n    1 -- 2;\n    1 -- 4 [style=dashed, color=blue];\n    2 -- 3;\n"));
+
+        let tikz = tikz::tikz_to_string(&g);
+        assert!(tikz.contains("\\node[xspider] (v1) at (1.5,0) {1/2π};"));
+        assert!(tikz.contains("\\draw (v1) -- (v4);"));
+
+        let zxg: serde_json::Value = serde_json::from_str(&zxg::to_zxg(&g)).unwrap();
+        assert_eq!(zxg["wire_vertices"]["b0"]["annotation"]["coord"], serde_json::json!([0.0, 0.0]));
+        assert_eq!(zxg["node_vertices"]["v1"]["data"], serde_json::json!({ "type": "X", "value": "1/2" }));
+        assert_eq!(zxg["node_vertices"]["v4"]["data"], serde_json::json!({ "type": "hadamard" }));
+        // The Hadamard wire gets an H node of its own
+        assert_eq!(zxg["node_vertices"]["h1_4"]["data"]["type"], "hadamard");
+        assert_eq!(zxg["undir_edges"].as_object().unwrap().len(), 5);
+        assert_eq!(zxg["format_version"], zxg::FORMAT_VERSION);
+    }
+}
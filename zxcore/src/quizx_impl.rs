@@ -0,0 +1,69 @@
+use crate::diagram::{DiagramBuilder, DiagramView, NodeKind, Phase, WireKind};
+use quizx::graph::{EType, GraphLike, VData, VType};
+use quizx::hash_graph::Graph;
+
+impl DiagramView for Graph {
+    fn node_ids(&self) -> Vec<usize> {
+        self.vertices().collect()
+    }
+
+    fn node_kind(&self, id: usize) -> NodeKind {
+        match self.vertex_type(id) {
+            VType::Z => NodeKind::Z,
+            VType::X => NodeKind::X,
+            VType::H => NodeKind::H,
+            VType::ZBox => NodeKind::ZBox,
+            VType::WInput => NodeKind::WInput,
+            VType::WOutput => NodeKind::WOutput,
+            _ => NodeKind::Boundary,
+        }
+    }
+
+    fn node_phase(&self, id: usize) -> Phase {
+        self.phase(id).to_rational()
+    }
+
+    fn wires(&self) -> Vec<(usize, usize, WireKind)> {
+        self.edges()
+            .map(|(a, b, ety)| {
+                let kind = if ety == EType::H { WireKind::Hadamard } else { WireKind::Plain };
+                (a, b, kind)
+            })
+            .collect()
+    }
+
+    fn node_position(&self, id: usize) -> (f64, f64) {
+        (self.qubit(id), self.row(id))
+    }
+
+    fn input_ids(&self) -> Vec<usize> {
+        self.inputs().clone()
+    }
+
+    fn output_ids(&self) -> Vec<usize> {
+        self.outputs().clone()
+    }
+}
+
+impl DiagramBuilder for Graph {
+    fn add_node(&mut self, kind: NodeKind, phase: Phase, qubit: f64, row: f64) -> usize {
+        let ty = match kind {
+            NodeKind::Boundary => VType::B,
+            NodeKind::Z => VType::Z,
+            NodeKind::X => VType::X,
+            NodeKind::H => VType::H,
+            NodeKind::ZBox => VType::ZBox,
+            NodeKind::WInput => VType::WInput,
+            NodeKind::WOutput => VType::WOutput,
+        };
+        self.add_vertex_with_data(VData { ty, phase: phase.into(), qubit, row })
+    }
+
+    fn add_wire(&mut self, a: usize, b: usize, kind: WireKind) {
+        let ety = match kind {
+            WireKind::Plain => EType::N,
+            WireKind::Hadamard => EType::H,
+        };
+        self.add_edge_with_type(a, b, ety);
+    }
+}
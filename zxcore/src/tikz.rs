@@ -0,0 +1,107 @@
+use crate::diagram::{DiagramView, NodeKind};
+use num::Zero;
+use std::io::{Result, Write};
+
+/// Writes a standalone LaTeX document drawing the diagram with TikZ
+///
+/// Nodes are laid out left to right in id order.
+pub fn tikz_to_writer<D: DiagramView, W: Write>(g: &D, file: &mut W) -> Result<()> {
+    tikz_to_writer_styled(g, file, |_, _| None)
+}
+
+/// Extra drawing instructions for one wire
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WireStyle {
+    /// TikZ options such as `"draw=blue, very thick"`, empty for none
+    pub options: String,
+    /// Short text drawn on the middle of the wire
+    pub label: Option<String>,
+}
+
+/// Same as [`tikz_to_writer`], with extra TikZ options and labels per wire
+///
+/// `wire_style` gets each wire as (smaller, larger) id and returns how to draw it,
+/// or `None` for a plain wire.
+pub fn tikz_to_writer_styled<D, W, F>(g: &D, file: &mut W, wire_style: F) -> Result<()>
+where
+    D: DiagramView,
+    W: Write,
+    F: Fn(usize, usize) -> Option<WireStyle>,
+{
+    tikz_to_writer_themed(g, file, &SpiderFills::default(), wire_style)
+}
+
+/// Fill colours of the node styles, as xcolor expressions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpiderFills {
+    pub x: String,
+    pub z: String,
+    pub boundary: String,
+}
+
+impl Default for SpiderFills {
+    fn default() -> Self {
+        SpiderFills { x: "red!20".to_string(), z: "green!20".to_string(), boundary: "black!20".to_string() }
+    }
+}
+
+/// Same as [`tikz_to_writer_styled`], filling the nodes with `fills`
+pub fn tikz_to_writer_themed<D, W, F>(g: &D, file: &mut W, fills: &SpiderFills, wire_style: F) -> Result<()>
+where
+    D: DiagramView,
+    W: Write,
+    F: Fn(usize, usize) -> Option<WireStyle>,
+{
+    writeln!(file, "\\documentclass{{standalone}}")?;
+    writeln!(file, "\\usepackage{{tikz}}")?;
+    writeln!(file, "\\begin{{document}}")?;
+    writeln!(file, "\\begin{{tikzpicture}}[scale=1]")?;
+
+    // Define styles for X, Z and boundary spiders
+    writeln!(file, "\\tikzstyle{{xspider}}=[draw,circle,fill={}]", fills.x)?;
+    writeln!(file, "\\tikzstyle{{zspider}}=[draw,circle,fill={}]", fills.z)?;
+    writeln!(file, "\\tikzstyle{{boundary}}=[draw,circle,fill={}]", fills.boundary)?;
+
+    // Assign simple horizontal positions to each vertex
+    for (i, v) in g.sorted_node_ids().into_iter().enumerate() {
+        let x = i as f64 * 1.5; // horizontal spacing
+        let phase_label = || {
+            let phase = g.node_phase(v);
+            if phase.is_zero() { String::new() } else { format!("{}π", phase) }
+        };
+        let (style, label) = match g.node_kind(v) {
+            NodeKind::X => ("xspider", phase_label()),
+            NodeKind::Z => ("zspider", phase_label()),
+            _ => ("boundary", String::from("B")),
+        };
+
+        writeln!(
+            file,
+            "\\node[{}] (v{}) at ({},0) {{{}}};",
+            style, v, x, label
+        )?;
+    }
+
+    // Draw edges
+    for (v0, v1, _) in g.sorted_wires() {
+        let style = wire_style(v0, v1).unwrap_or_default();
+        let options = if style.options.is_empty() { String::new() } else { format!("[{}]", style.options) };
+        let label = match &style.label {
+            Some(label) => format!("node[midway, fill=white, inner sep=1pt, font=\\small] {{{}}} ", label),
+            None => String::new(),
+        };
+        writeln!(file, "\\draw{} (v{}) -- {}(v{});", options, v0, label, v1)?;
+    }
+
+    writeln!(file, "\\end{{tikzpicture}}")?;
+    writeln!(file, "\\end{{document}}")?;
+
+    Ok(())
+}
+
+/// Same as [`tikz_to_writer`], returning the document as a string
+pub fn tikz_to_string<D: DiagramView>(g: &D) -> String {
+    let mut buffer = Vec::new();
+    tikz_to_writer(g, &mut buffer).expect("writing to a Vec can't fail");
+    String::from_utf8(buffer).expect("TikZ output is valid UTF-8")
+}
@@ -0,0 +1,108 @@
+use crate::diagram::{DiagramView, NodeKind, WireKind};
+use num::Zero;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Version of the .zxg schema written by [`to_zxg`]
+///
+/// Readers migrate files of older versions and refuse newer ones.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// The .zxg `data.type` of a node kind, or `None` for boundaries, which are wire vertices
+pub fn node_type(kind: NodeKind) -> Option<&'static str> {
+    match kind {
+        NodeKind::Boundary => None,
+        NodeKind::Z => Some("Z"),
+        NodeKind::X => Some("X"),
+        NodeKind::H => Some("hadamard"),
+        NodeKind::ZBox => Some("Z_box"),
+        NodeKind::WInput => Some("W_input"),
+        NodeKind::WOutput => Some("W_output"),
+    }
+}
+
+/// Serialises the diagram to .zxg, the JSON schema of ZXLive and PyZX
+///
+/// Boundaries become wire vertices `b<id>` and other nodes node vertices `v<id>`, both at
+/// coordinates `[row, qubit]`. The schema only has plain edges, so each Hadamard wire
+/// gets an H node `h<a>_<b>` halfway along it. Phases are written in units of π as
+/// fractions, e.g. `"1/2"`, and left out when zero. Inputs and outputs, if set, are
+/// listed in order by name, so reloading keeps which boundary is which qubit. The file
+/// is stamped with [`FORMAT_VERSION`].
+pub fn to_zxg<D: DiagramView>(g: &D) -> String {
+    to_zxg_3d(g, &HashMap::new())
+}
+
+/// Same as [`to_zxg`] for a 3D diagram, writing `[row, qubit, t]` coordinates for the
+/// nodes in `times`
+///
+/// An H node gets the mean `t` of its ends if both have one.
+pub fn to_zxg_3d<D: DiagramView>(g: &D, times: &HashMap<usize, f64>) -> String {
+    let name = |id| match g.node_kind(id) {
+        NodeKind::Boundary => format!("b{}", id),
+        _ => format!("v{}", id),
+    };
+    let coord = |id| {
+        let (qubit, row) = g.node_position(id);
+        match times.get(&id) {
+            Some(t) => json!([row, qubit, t]),
+            None => json!([row, qubit]),
+        }
+    };
+
+    let mut wire_vertices = Map::new();
+    let mut node_vertices = Map::new();
+    for id in g.sorted_node_ids() {
+        let Some(ty) = node_type(g.node_kind(id)) else {
+            wire_vertices.insert(name(id), json!({ "annotation": { "boundary": true, "coord": coord(id) } }));
+            continue;
+        };
+        let mut data = json!({ "type": ty });
+        let phase = g.node_phase(id);
+        if !phase.is_zero() {
+            data["value"] = json!(phase.to_string());
+        }
+        node_vertices.insert(name(id), json!({ "annotation": { "coord": coord(id) }, "data": data }));
+    }
+
+    let mut undir_edges = Map::new();
+    let mut add_edge = |src: String, tgt: String| {
+        let key = format!("e{}", undir_edges.len());
+        undir_edges.insert(key, json!({ "src": src, "tgt": tgt }));
+    };
+    for (a, b, kind) in g.sorted_wires() {
+        match kind {
+            WireKind::Plain => add_edge(name(a), name(b)),
+            WireKind::Hadamard => {
+                let h = format!("h{}_{}", a, b);
+                let ((qa, ra), (qb, rb)) = (g.node_position(a), g.node_position(b));
+                let (row, qubit) = ((ra + rb) / 2.0, (qa + qb) / 2.0);
+                let middle = match (times.get(&a), times.get(&b)) {
+                    (Some(ta), Some(tb)) => json!([row, qubit, (ta + tb) / 2.0]),
+                    _ => json!([row, qubit]),
+                };
+                node_vertices.insert(h.clone(), json!({ "annotation": { "coord": middle }, "data": { "type": "hadamard" } }));
+                add_edge(name(a), h.clone());
+                add_edge(h, name(b));
+            }
+        }
+    }
+
+    // PyZX keeps the scalar as a JSON string inside the file
+    let (power2, phase) = g.global_scalar();
+    let scalar = json!({ "power2": power2, "phase": phase.to_string() });
+    let mut zxg = json!({
+        "format_version": FORMAT_VERSION,
+        "wire_vertices": Value::Object(wire_vertices),
+        "node_vertices": Value::Object(node_vertices),
+        "undir_edges": Value::Object(undir_edges),
+        "variable_types": {},
+        "scalar": scalar.to_string(),
+    });
+    let (inputs, outputs) = (g.input_ids(), g.output_ids());
+    if !inputs.is_empty() || !outputs.is_empty() {
+        zxg["inputs"] = inputs.into_iter().map(name).collect();
+        zxg["outputs"] = outputs.into_iter().map(name).collect();
+    }
+    serde_json::to_string_pretty(&zxg).expect("JSON values always serialise")
+}